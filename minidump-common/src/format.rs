@@ -213,6 +213,9 @@ pub enum MINIDUMP_STREAM_TYPE {
     ///
     /// See [`MINIDUMP_HANDLE_DATA_STREAM`]
     HandleDataStream = 12,
+    /// The function tables registered at runtime by the process (e.g. by JIT engines)
+    ///
+    /// See [`MINIDUMP_FUNCTION_TABLE_STREAM`]
     FunctionTable = 13,
     /// The list of executable modules from the process that were unloaded by the time of the crash
     ///
@@ -234,8 +237,17 @@ pub enum MINIDUMP_STREAM_TYPE {
     ///
     /// See ['MINIDUMP_MEMORY_INFO_LIST'].
     MemoryInfoListStream = 16,
+    /// Extra information about the threads from the process
+    ///
+    /// See ['MINIDUMP_THREAD_INFO'].
     ThreadInfoListStream = 17,
+    /// Traces of operations on handles recorded by the process
+    ///
+    /// See [`MINIDUMP_HANDLE_OPERATION_LIST`]
     HandleOperationListStream = 18,
+    /// The access tokens of the process and its threads
+    ///
+    /// See [`MINIDUMP_TOKEN_INFO_LIST`]
     TokenStream = 19,
     JavaScriptDataStream = 20,
     SystemMemoryInfoStream = 21,
@@ -779,8 +791,8 @@ bitflags! {
     /// CPU type values in the `context_flags` member of `CONTEXT_` structs
     ///
     /// This applies to the [`CONTEXT_ARM`], [`CONTEXT_PPC`], [`CONTEXT_MIPS`],
-    /// [`CONTEXT_AMD64`], [`CONTEXT_ARM64`], [`CONTEXT_PPC64`], [`CONTEXT_SPARC`] and
-    /// [`CONTEXT_ARM64_OLD`] structs.
+    /// [`CONTEXT_AMD64`], [`CONTEXT_ARM64`], [`CONTEXT_PPC64`], [`CONTEXT_SPARC`],
+    /// [`CONTEXT_RISCV64`] and [`CONTEXT_ARM64_OLD`] structs.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ContextFlagsCpu: u32 {
         const CONTEXT_IA64 = 0x80000;
@@ -801,6 +813,7 @@ bitflags! {
         const CONTEXT_MIPS64 = 0x80000;
         const CONTEXT_PPC = 0x20000000;
         const CONTEXT_PPC64 = 0x1000000;
+        const CONTEXT_RISCV64 = 0x8000000;
         const CONTEXT_SPARC = 0x10000000;
         const CONTEXT_X86 = 0x10000;
     }
@@ -890,6 +903,18 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags available for use in [`CONTEXT_RISCV64.context_flags`]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ContextFlagsRiscv64: u32 {
+        /// pc, x1-x31
+        const CONTEXT_RISCV64_INTEGER = 0x00000001 | ContextFlagsCpu::CONTEXT_RISCV64.bits();
+        /// f0-f31, fcsr
+        const CONTEXT_RISCV64_FLOATING_POINT = 0x00000004 | ContextFlagsCpu::CONTEXT_RISCV64.bits();
+        const CONTEXT_RISCV64_FULL = Self::CONTEXT_RISCV64_INTEGER.bits() | Self::CONTEXT_RISCV64_FLOATING_POINT.bits();
+    }
+}
+
 bitflags! {
     /// Flags available for use in [`CONTEXT_ARM.context_flags`]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1245,7 +1270,7 @@ impl MipsRegisterNumbers {
 }
 
 /// PPC floating point state
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FLOATING_SAVE_AREA_PPC {
     pub fpregs: [u64; 32],
@@ -1254,7 +1279,7 @@ pub struct FLOATING_SAVE_AREA_PPC {
 }
 
 /// PPC vector state
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct VECTOR_SAVE_AREA_PPC {
     pub save_vr: [u128; 32],
@@ -1267,7 +1292,7 @@ pub struct VECTOR_SAVE_AREA_PPC {
 /// A PPC CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC in WinNT.h.
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CONTEXT_PPC {
     pub context_flags: u32,
@@ -1291,10 +1316,18 @@ pub enum PpcRegisterNumbers {
     StackPointer = 1,
 }
 
+impl PpcRegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::StackPointer => "r1",
+        }
+    }
+}
+
 /// A PPC64 CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC64 in WinNT.h.
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CONTEXT_PPC64 {
     pub context_flags: u64,
@@ -1317,8 +1350,16 @@ pub enum Ppc64RegisterNumbers {
     StackPointer = 1,
 }
 
+impl Ppc64RegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::StackPointer => "r1",
+        }
+    }
+}
+
 /// SPARC floating point state
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FLOATING_SAVE_AREA_SPARC {
     pub regs: [u64; 32],
@@ -1329,7 +1370,7 @@ pub struct FLOATING_SAVE_AREA_SPARC {
 /// A SPARC CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for SPARC in WinNT.h.
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CONTEXT_SPARC {
     pub context_flags: u32,
@@ -1348,7 +1389,73 @@ pub struct CONTEXT_SPARC {
 #[repr(usize)]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum SparcRegisterNumbers {
+    /// `o6`
     StackPointer = 14,
+    /// `o7`, the address of the last `call` made by this function
+    ReturnAddress = 15,
+    /// `i6`, the stack pointer of the caller
+    FramePointer = 30,
+    /// `i7`, the address of the `call` that called this function
+    CalleeReturnAddress = 31,
+}
+
+impl SparcRegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::StackPointer => "g_r14",
+            Self::ReturnAddress => "g_r15",
+            Self::FramePointer => "g_r30",
+            Self::CalleeReturnAddress => "g_r31",
+        }
+    }
+}
+
+/// RISC-V floating point state
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct FLOATING_SAVE_AREA_RISCV {
+    /// `[f0, f1, ..., f31]`
+    pub regs: [u64; 32],
+    pub fpcsr: u32,
+}
+
+/// A RISC-V 64 CPU context
+///
+/// This is a Breakpad extension, as there is no definition of `CONTEXT` for RISC-V in WinNT.h.
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct CONTEXT_RISCV64 {
+    pub context_flags: u32,
+    pub version: u32,
+    pub pc: u64,
+    /// `[x1, x2, ..., x31]`, i.e. `[ra, sp, gp, tp, t0, ..., t6]`. See [Riscv64RegisterNumbers][].
+    ///
+    /// x0 is hardwired to zero, so it isn't saved.
+    pub iregs: [u64; 31],
+    pub float_save: FLOATING_SAVE_AREA_RISCV,
+}
+
+/// Offsets into [`CONTEXT_RISCV64::iregs`] for registers with a dedicated or conventional purpose
+#[repr(usize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Riscv64RegisterNumbers {
+    ReturnAddress = 0,
+    StackPointer = 1,
+    GlobalPointer = 2,
+    ThreadPointer = 3,
+    FramePointer = 7,
+}
+
+impl Riscv64RegisterNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ReturnAddress => "ra",
+            Self::StackPointer => "sp",
+            Self::GlobalPointer => "gp",
+            Self::ThreadPointer => "tp",
+            Self::FramePointer => "s0",
+        }
+    }
 }
 
 /// x86 floating point state
@@ -1507,6 +1614,8 @@ pub enum ProcessorArchitecture {
     PROCESSOR_ARCHITECTURE_ARM64_OLD = 0x8003,
     /// Breakpad-defined value for MIPS64
     PROCESSOR_ARCHITECTURE_MIPS64 = 0x8004,
+    /// Breakpad-defined value for RISC-V 64
+    PROCESSOR_ARCHITECTURE_RISCV64 = 0x8006,
     PROCESSOR_ARCHITECTURE_UNKNOWN = 0xffff,
 }
 
@@ -1769,6 +1878,8 @@ pub enum XstateFeatureIndex {
     AVX512_ZMM_H = 6,
     ACK512_ZMM = 7,
     XSTATE_IPT = 8,
+    XSTATE_CET_U = 11,
+    XSTATE_CET_S = 12,
     XSTATE_LWP = 62,
 }
 
@@ -1785,6 +1896,8 @@ impl XstateFeatureIndex {
             6 => Some(AVX512_ZMM_H),
             7 => Some(ACK512_ZMM),
             8 => Some(XSTATE_IPT),
+            11 => Some(XSTATE_CET_U),
+            12 => Some(XSTATE_CET_S),
             62 => Some(XSTATE_LWP),
             _ => None,
         }
@@ -1921,6 +2034,340 @@ bitflags! {
     }
 }
 
+/// The header of the thread info list stream
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info_list
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO_LIST {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each entry in the list
+    pub size_of_entry: u32,
+    /// The number of entries in the list
+    pub number_of_entries: u32,
+}
+
+/// Extra information about a thread in a minidump
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO {
+    /// The identifier of the thread
+    pub thread_id: u32,
+    /// Flags describing the state of this entry
+    ///
+    /// See [`ThreadInfoDumpFlags`] for valid values.
+    pub dump_flags: u32,
+    /// An HRESULT indicating why information for this thread could not be retrieved
+    pub dump_error: u32,
+    /// The exit status of the thread, if it had already exited
+    pub exit_status: u32,
+    /// The time the thread was created, as a `FILETIME`
+    pub create_time: u64,
+    /// The time the thread exited, as a `FILETIME`
+    pub exit_time: u64,
+    /// The time spent executing in kernel mode, in 100-nanosecond units
+    pub kernel_time: u64,
+    /// The time spent executing in user mode, in 100-nanosecond units
+    pub user_time: u64,
+    /// The address of the thread's start routine
+    pub start_address: u64,
+    /// The processor affinity mask of the thread
+    pub affinity: u64,
+}
+
+bitflags! {
+    /// Potential values for [`MINIDUMP_THREAD_INFO::dump_flags`]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ThreadInfoDumpFlags: u32 {
+        /// A placeholder thread due to an error accessing the thread
+        const MINIDUMP_THREAD_INFO_ERROR_THREAD    = 0x01;
+        /// The thread that wrote the minidump
+        const MINIDUMP_THREAD_INFO_WRITING_THREAD  = 0x02;
+        /// The thread had already exited
+        const MINIDUMP_THREAD_INFO_EXITED_THREAD   = 0x04;
+        /// Thread-level information is not present
+        const MINIDUMP_THREAD_INFO_INVALID_INFO    = 0x08;
+        /// Context-level information is not present
+        const MINIDUMP_THREAD_INFO_INVALID_CONTEXT = 0x10;
+        /// TEB information is not present
+        const MINIDUMP_THREAD_INFO_INVALID_TEB     = 0x20;
+    }
+}
+
+/// Basic information about the system's memory
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_INFORMATION {
+    pub timer_resolution: u32,
+    /// The size of a page, in bytes
+    pub page_size: u32,
+    /// The amount of physical memory, in pages
+    pub number_of_physical_pages: u32,
+    pub lowest_physical_page_number: u32,
+    pub highest_physical_page_number: u32,
+    pub allocation_granularity: u32,
+    pub minimum_user_mode_address: u64,
+    pub maximum_user_mode_address: u64,
+    pub active_processors_affinity_mask: u64,
+    pub number_of_processors: u32,
+}
+
+/// Information about the system's file cache
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_FILECACHE_INFORMATION {
+    pub current_size: u64,
+    pub peak_size: u64,
+    pub page_fault_count: u32,
+    pub minimum_working_set: u64,
+    pub maximum_working_set: u64,
+    pub current_size_including_transition_in_pages: u64,
+    pub peak_size_including_transition_in_pages: u64,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_FILECACHE_TRANSITIONREPURPOSECOUNT_FLAGS`]
+    pub transition_re_purpose_count: u32,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_FILECACHE_TRANSITIONREPURPOSECOUNT_FLAGS`]
+    pub flags: u32,
+}
+
+/// 64-bit versions of the system's commit charge counters
+///
+/// All values are in pages.
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION {
+    /// The amount of physical memory available for use
+    pub available_pages: u64,
+    /// The system-wide commit charge
+    pub committed_pages: u64,
+    /// The maximum commit charge before the page file has to grow
+    pub commit_limit: u64,
+    /// The peak system-wide commit charge
+    pub peak_commitment: u64,
+}
+
+/// Performance counters of the system's memory manager
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION {
+    pub idle_process_time: u64,
+    pub io_read_transfer_count: u64,
+    pub io_write_transfer_count: u64,
+    pub io_other_transfer_count: u64,
+    pub io_read_operation_count: u32,
+    pub io_write_operation_count: u32,
+    pub io_other_operation_count: u32,
+    /// The amount of physical memory available for use, in pages
+    pub available_pages: u32,
+    /// The system-wide commit charge, in pages
+    pub committed_pages: u32,
+    /// The maximum commit charge before the page file has to grow, in pages
+    pub commit_limit: u32,
+    /// The peak system-wide commit charge, in pages
+    pub peak_commitment: u32,
+    pub page_fault_count: u32,
+    pub copy_on_write_count: u32,
+    pub transition_count: u32,
+    pub cache_transition_count: u32,
+    pub demand_zero_count: u32,
+    pub page_read_count: u32,
+    pub page_read_io_count: u32,
+    pub cache_read_count: u32,
+    pub cache_io_count: u32,
+    pub dirty_pages_write_count: u32,
+    pub dirty_write_io_count: u32,
+    pub mapped_pages_write_count: u32,
+    pub mapped_write_io_count: u32,
+    pub paged_pool_pages: u32,
+    pub non_paged_pool_pages: u32,
+    pub paged_pool_allocs: u32,
+    pub paged_pool_frees: u32,
+    pub non_paged_pool_allocs: u32,
+    pub non_paged_pool_frees: u32,
+    pub free_system_ptes: u32,
+    pub resident_system_code_page: u32,
+    pub total_system_driver_pages: u32,
+    pub total_system_code_pages: u32,
+    pub non_paged_pool_lookaside_hits: u32,
+    pub paged_pool_lookaside_hits: u32,
+    pub available_paged_pool_pages: u32,
+    pub resident_system_cache_page: u32,
+    pub resident_paged_pool_page: u32,
+    pub resident_system_driver_page: u32,
+    pub cc_fast_read_no_wait: u32,
+    pub cc_fast_read_wait: u32,
+    pub cc_fast_read_resource_miss: u32,
+    pub cc_fast_read_not_possible: u32,
+    pub cc_fast_mdl_read_no_wait: u32,
+    pub cc_fast_mdl_read_wait: u32,
+    pub cc_fast_mdl_read_resource_miss: u32,
+    pub cc_fast_mdl_read_not_possible: u32,
+    pub cc_map_data_no_wait: u32,
+    pub cc_map_data_wait: u32,
+    pub cc_map_data_no_wait_miss: u32,
+    pub cc_map_data_wait_miss: u32,
+    pub cc_pin_mapped_data_count: u32,
+    pub cc_pin_read_no_wait: u32,
+    pub cc_pin_read_wait: u32,
+    pub cc_pin_read_no_wait_miss: u32,
+    pub cc_pin_read_wait_miss: u32,
+    pub cc_copy_read_no_wait: u32,
+    pub cc_copy_read_wait: u32,
+    pub cc_copy_read_no_wait_miss: u32,
+    pub cc_copy_read_wait_miss: u32,
+    pub cc_mdl_read_no_wait: u32,
+    pub cc_mdl_read_wait: u32,
+    pub cc_mdl_read_no_wait_miss: u32,
+    pub cc_mdl_read_wait_miss: u32,
+    pub cc_read_ahead_ios: u32,
+    pub cc_lazy_write_ios: u32,
+    pub cc_lazy_write_pages: u32,
+    pub cc_data_flushes: u32,
+    pub cc_data_pages: u32,
+    pub context_switches: u32,
+    pub first_level_tb_fills: u32,
+    pub second_level_tb_fills: u32,
+    pub system_calls: u32,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_PERF_CCTOTALDIRTYPAGES_CCDIRTYPAGETHRESHOLD`]
+    pub cc_total_dirty_pages: u64,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_PERF_CCTOTALDIRTYPAGES_CCDIRTYPAGETHRESHOLD`]
+    pub cc_dirty_page_threshold: u64,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_PERF_RESIDENTAVAILABLEPAGES_SHAREDCOMMITPAGES`]
+    pub resident_available_pages: i64,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_PERF_RESIDENTAVAILABLEPAGES_SHAREDCOMMITPAGES`]
+    pub shared_committed_pages: u64,
+}
+
+/// System-wide memory information
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream`].
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_MEMORY_INFO_1 {
+    /// The revision of this struct, currently always 1
+    pub revision: u16,
+    /// Flags describing which optional fields are valid
+    ///
+    /// See [`SystemMemoryInfoFlags`] for valid values.
+    pub flags: u16,
+    pub basic_info: MINIDUMP_SYSTEM_BASIC_INFORMATION,
+    pub file_cache_info: MINIDUMP_SYSTEM_FILECACHE_INFORMATION,
+    /// Guarded by [`SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF`]
+    pub basic_perf_info: MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION,
+    pub perf_info: MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION,
+}
+
+bitflags! {
+    /// Potential values for [`MINIDUMP_SYSTEM_MEMORY_INFO_1::flags`]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SystemMemoryInfoFlags: u16 {
+        const MINIDUMP_SYSMEMINFO1_FILECACHE_TRANSITIONREPURPOSECOUNT_FLAGS = 0x0001;
+        const MINIDUMP_SYSMEMINFO1_BASICPERF                                 = 0x0002;
+        const MINIDUMP_SYSMEMINFO1_PERF_CCTOTALDIRTYPAGES_CCDIRTYPAGETHRESHOLD = 0x0004;
+        const MINIDUMP_SYSMEMINFO1_PERF_RESIDENTAVAILABLEPAGES_SHAREDCOMMITPAGES = 0x0008;
+    }
+}
+
+/// Memory usage counters of the process
+///
+/// This is the original format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`],
+/// see [`MINIDUMP_PROCESS_VM_COUNTERS_2`] for the newer one. All sizes are in bytes.
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_1 {
+    /// The revision of this struct (1)
+    pub revision: u16,
+    pub __align: u16,
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    /// The commit charge of the process (historically misnamed)
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+    /// The memory that can't be shared with other processes ("private bytes")
+    pub private_usage: u64,
+}
+
+/// Memory usage counters of the process
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`] written
+/// by newer versions of Windows. All sizes are in bytes.
+///
+/// This struct matches the struct of the same name from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_2 {
+    /// The revision of this struct (2)
+    pub revision: u16,
+    /// Flags describing which fields are valid
+    ///
+    /// See [`ProcessVmCountersFlags`] for valid values.
+    pub flags: u16,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub page_fault_count: u32,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub peak_working_set_size: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub working_set_size: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub quota_peak_paged_pool_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub quota_paged_pool_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub quota_peak_non_paged_pool_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub quota_non_paged_pool_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub pagefile_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS`]
+    pub peak_pagefile_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE`]
+    pub peak_virtual_size: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE`]
+    pub virtual_size: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX`]
+    pub private_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX2`]
+    pub private_working_set_size: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX2`]
+    pub shared_commit_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB`]
+    pub job_shared_commit_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB`]
+    pub job_private_commit_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB`]
+    pub job_peak_private_commit_usage: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB`]
+    pub job_private_commit_limit: u64,
+    /// Guarded by [`ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB`]
+    pub job_total_commit_limit: u64,
+}
+
+bitflags! {
+    /// Potential values for [`MINIDUMP_PROCESS_VM_COUNTERS_2::flags`]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ProcessVmCountersFlags: u16 {
+        const MINIDUMP_PROCESS_VM_COUNTERS             = 0x0001;
+        const MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE = 0x0002;
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX          = 0x0004;
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX2         = 0x0008;
+        const MINIDUMP_PROCESS_VM_COUNTERS_JOB         = 0x0010;
+    }
+}
+
 /// A Breakpad extension containing some additional process information
 ///
 /// Taken from the definition in Breakpad's [minidump_format.h][fmt].
@@ -2042,6 +2489,76 @@ pub struct DSO_DEBUG_64 {
     pub dynamic: u64,
 }
 
+/// The type of an entry in the auxiliary vector of a Linux process
+///
+/// The `LinuxAuxv` stream holds the contents of `/proc/self/auxv`, which is an array of
+/// pointer-sized `(type, value)` pairs terminated by an `AT_NULL` entry. These values match
+/// the `AT_*` definitions in <elf.h> and <linux/auxvec.h>.
+#[repr(u64)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum AuxvType {
+    /// End of the vector
+    AT_NULL = 0,
+    /// Entry should be ignored
+    AT_IGNORE = 1,
+    /// File descriptor of the program
+    AT_EXECFD = 2,
+    /// Address of the program headers of the program
+    AT_PHDR = 3,
+    /// Size of a program header entry
+    AT_PHENT = 4,
+    /// Number of program headers
+    AT_PHNUM = 5,
+    /// System page size
+    AT_PAGESZ = 6,
+    /// Base address of the program interpreter (usually the dynamic linker)
+    AT_BASE = 7,
+    /// Flags
+    AT_FLAGS = 8,
+    /// Entry point of the program
+    AT_ENTRY = 9,
+    /// Program is not ELF
+    AT_NOTELF = 10,
+    /// Real uid
+    AT_UID = 11,
+    /// Effective uid
+    AT_EUID = 12,
+    /// Real gid
+    AT_GID = 13,
+    /// Effective gid
+    AT_EGID = 14,
+    /// Address of a string identifying the CPU, for optimizations
+    AT_PLATFORM = 15,
+    /// Arch-dependent hints about the CPU's capabilities
+    AT_HWCAP = 16,
+    /// Frequency at which `times()` increments
+    AT_CLKTCK = 17,
+    /// Secure mode boolean (e.g. for setuid programs)
+    AT_SECURE = 23,
+    /// Address of a string identifying the real platform, may differ from `AT_PLATFORM`
+    AT_BASE_PLATFORM = 24,
+    /// Address of 16 random bytes
+    AT_RANDOM = 25,
+    /// Extension of `AT_HWCAP`
+    AT_HWCAP2 = 26,
+    /// rseq supported feature size
+    AT_RSEQ_FEATURE_SIZE = 27,
+    /// rseq allocation alignment
+    AT_RSEQ_ALIGN = 28,
+    /// Extension of `AT_HWCAP`
+    AT_HWCAP3 = 29,
+    /// Extension of `AT_HWCAP`
+    AT_HWCAP4 = 30,
+    /// Address of the filename of the program
+    AT_EXECFN = 31,
+    /// Entry point of the vsyscall page (32-bit x86 only)
+    AT_SYSINFO = 32,
+    /// Base address of the vDSO
+    AT_SYSINFO_EHDR = 33,
+    /// Minimal stack size for signal delivery
+    AT_MINSIGSTKSZ = 51,
+}
+
 /// A variable-length UTF-8-encoded string carried within a minidump file.
 ///
 /// See <https://crashpad.chromium.org/doxygen/structcrashpad_1_1MinidumpUTF8String.html>
@@ -2552,3 +3069,151 @@ pub struct MINIDUMP_HANDLE_DATA_STREAM {
     /// Reserved for future use; must be zero.
     pub reserved: u32,
 }
+
+/// The header of the list of handle operations in a [`MINIDUMP_STREAM_TYPE::HandleOperationListStream`]
+///
+/// The entries are [`AVRF_HANDLE_OPERATION`]s, they are only written if handle
+/// tracing was enabled in the process (e.g. by Application Verifier).
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_OPERATION_LIST {
+    /// The size of this header, in bytes.
+    pub size_of_header: u32,
+    /// The size of each entry in the stream, in bytes.
+    pub size_of_entry: u32,
+    /// The number of entries in the stream.
+    pub number_of_entries: u32,
+    /// Reserved for future use; must be zero.
+    pub reserved: u32,
+}
+
+/// The maximum number of return addresses in an [`AVRF_BACKTRACE_INFORMATION`]
+pub const AVRF_MAX_TRACES: usize = 32;
+
+/// A stack backtrace captured by handle tracing
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_BACKTRACE_INFORMATION {
+    /// The number of valid entries in `return_addresses`.
+    pub depth: u32,
+    /// The index of this backtrace in the trace database.
+    pub index: u32,
+    /// The return addresses of the backtrace, innermost first.
+    pub return_addresses: [u64; 32],
+}
+
+/// A single operation on a handle, recorded by handle tracing
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_HANDLE_OPERATION {
+    /// The handle that was operated on.
+    pub handle: u64,
+    /// The process that performed the operation.
+    pub process_id: u32,
+    /// The thread that performed the operation.
+    pub thread_id: u32,
+    /// What happened to the handle, see [`eHANDLE_TRACE_OPERATIONS`].
+    pub operation_type: u32,
+    /// Reserved for future use; must be zero.
+    pub spare0: u32,
+    /// The stack of the thread at the time of the operation.
+    pub back_trace_information: AVRF_BACKTRACE_INFORMATION,
+}
+
+/// The kind of operation in an [`AVRF_HANDLE_OPERATION`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum eHANDLE_TRACE_OPERATIONS {
+    OperationDbUnused = 0,
+    /// The handle was opened
+    OperationDbOPEN = 1,
+    /// The handle was closed
+    OperationDbCLOSE = 2,
+    /// An invalid handle was used
+    OperationDbBADREF = 3,
+}
+
+/// The header of the list of tokens in a [`MINIDUMP_STREAM_TYPE::TokenStream`]
+///
+/// The entries start with a [`MINIDUMP_TOKEN_INFO_HEADER`], followed by the
+/// token's information.
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_LIST {
+    /// The size of the whole list, in bytes.
+    pub token_list_size: u32,
+    /// The number of entries in the list.
+    pub token_list_entries: u32,
+    /// The size of this header, in bytes.
+    pub list_header_size: u32,
+    /// The size of the header of each entry, in bytes.
+    pub element_header_size: u32,
+}
+
+/// The header of a single token in a [`MINIDUMP_STREAM_TYPE::TokenStream`]
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_HEADER {
+    /// The size of the token, including this header, in bytes.
+    pub token_size: u32,
+    /// The process id (for process tokens) or thread id (for thread tokens) the
+    /// token was opened for.
+    pub token_id: u32,
+    /// The handle of the token in the process.
+    pub token_handle: u64,
+}
+
+/// The header of a [`MINIDUMP_STREAM_TYPE::FunctionTable`] stream
+///
+/// The stream contains the function tables which the process registered at
+/// runtime with `RtlAddFunctionTable` or `RtlInstallFunctionTableCallback`,
+/// which is what JIT engines do to make their generated code unwindable.
+///
+/// This header is followed by `size_of_align_pad` bytes of padding and
+/// `number_of_descriptors` tables, each of which is laid out as:
+///
+/// * a [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`] (`size_of_descriptor` bytes)
+/// * the operating system's own description of the table
+///   (`size_of_native_descriptor` bytes)
+/// * `entry_count` function entries (`size_of_function_entry` bytes each),
+///   which are [`IMAGE_RUNTIME_FUNCTION_ENTRY`]s on x86-64
+/// * `size_of_align_pad` bytes of padding, as given by the table's descriptor
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_STREAM {
+    /// The size of this header, in bytes.
+    pub size_of_header: u32,
+    /// The size of each descriptor in the stream, in bytes.
+    pub size_of_descriptor: u32,
+    /// The size of the native descriptor of each table, in bytes.
+    pub size_of_native_descriptor: u32,
+    /// The size of each function entry, in bytes.
+    pub size_of_function_entry: u32,
+    /// The number of descriptors in the stream.
+    pub number_of_descriptors: u32,
+    /// The size of the padding after this header, in bytes.
+    pub size_of_align_pad: u32,
+}
+
+/// The description of a single function table in a [`MINIDUMP_STREAM_TYPE::FunctionTable`] stream
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_DESCRIPTOR {
+    /// The lowest address covered by the table.
+    pub minimum_address: u64,
+    /// The highest address covered by the table.
+    pub maximum_address: u64,
+    /// The base address the addresses in the function entries are relative to.
+    pub base_address: u64,
+    /// The number of function entries in the table.
+    pub entry_count: u32,
+    /// The size of the padding after the table's entries, in bytes.
+    pub size_of_align_pad: u32,
+}
+
+/// A function entry in an x86-64 function table
+///
+/// All addresses are relative to the base address of the table (or image) the
+/// entry belongs to. This is `RUNTIME_FUNCTION` in the Windows headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct IMAGE_RUNTIME_FUNCTION_ENTRY {
+    /// The address of the start of the function.
+    pub begin_address: u32,
+    /// The address of the end of the function (exclusive).
+    pub end_address: u32,
+    /// The address of the function's `UNWIND_INFO`.
+    pub unwind_info_address: u32,
+}
@@ -1,4 +1,4 @@
-use minidump::{CpuContext, MinidumpRawContext, UnifiedMemory};
+use minidump::{CpuContext, MinidumpAddressSpace, MinidumpRawContext};
 use minidump_unwind::{CallStack, CallingConvention, FunctionArg, FunctionArgs};
 
 // # Recovering x86 function arguments
@@ -67,7 +67,17 @@ use minidump_unwind::{CallStack, CallingConvention, FunctionArg, FunctionArgs};
 // are worth carving out special cases for, but until then: it's all pointers!
 
 /// Try to recover function arguments
-pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: Option<UnifiedMemory>) {
+pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: &MinidumpAddressSpace) {
+    // The stack grows down, so the maximum address in the stack
+    // is actually the base of the stack. Since we're walking down
+    // the stack, the base of the stack is a good upper-bound
+    // (and default value) for any stack/frame pointer.
+    let stack_base = call_stack
+        .frames
+        .first()
+        .and_then(|frame| stack_memory.memory_range_at_address(frame.context.get_stack_pointer()))
+        .map(|range| range.end.saturating_add(1));
+
     // Collect up all the results at once to avoid borrowing issues.
     let args = call_stack
         .frames
@@ -76,8 +86,8 @@ pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: Option<UnifiedMe
         .map(|(frame_idx, frame)| {
             // Only x86 is implemented because it has friendly calling conventions.
             // and we need the function name to make any guesses at what the arguments are.
-            if let (Some(mem), Some(func_name), MinidumpRawContext::X86(ctx)) =
-                (stack_memory, &frame.function_name, &frame.context.raw)
+            if let (Some(stack_base), Some(func_name), MinidumpRawContext::X86(ctx)) =
+                (stack_base, &frame.function_name, &frame.context.raw)
             {
                 const POINTER_WIDTH: u64 = 4;
 
@@ -87,12 +97,6 @@ pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: Option<UnifiedMe
                     // in reverse order (which in fact means we can start at the top
                     // of the frame and read them off *in order*).
 
-                    // The stack grows down, so the maximum address in the stack
-                    // is actually the base of the stack. Since we're walking down
-                    // the stack, the base of the stack is a good upper-bound
-                    // (and default value) for any stack/frame pointer.
-                    let stack_base = mem.base_address().saturating_add(mem.size());
-
                     let caller_stack_pointer = call_stack
                         .frames
                         .get(frame_idx + 1)
@@ -107,7 +111,7 @@ pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: Option<UnifiedMe
                     let mut read_head = caller_stack_pointer;
                     let mut pop_value = || {
                         if read_head < caller_frame_pointer {
-                            let val = mem.get_memory_at_address::<u32>(read_head);
+                            let val = stack_memory.read_u32(read_head);
                             read_head += POINTER_WIDTH;
                             val.map(|val| val as u64)
                         } else {
@@ -0,0 +1,369 @@
+use std::convert::{TryFrom, TryInto};
+
+use minidump::system_info::Cpu;
+use minidump::{MinidumpAddressSpace, MinidumpModuleList, Module, UnifiedMemoryList};
+use minidump_common::utils::basename;
+use minidump_unwind::{CodeSection, ModuleCodeCache, SymbolProvider};
+
+use crate::process_state::{CodeIntegrity, CodeModification, CodeModificationKind};
+
+// # Checking the integrity of code
+//
+// Code pages are read-only, so the code of a module in a minidump should be
+// exactly what's in its binary (once it's been relocated, see `ModuleCode`).
+// When it isn't, something went out of its way to change it:
+//
+// * Software that injects itself into other processes (antiviruses, overlays,
+//   malware...) usually hooks functions by overwriting their first few
+//   instructions with a jump to its own code.
+// * Some software patches individual instructions in place.
+// * Faulty hardware flips single bits.
+//
+// All of these make the process crash in ways that make no sense from the
+// source code, so we diff the code in the minidump against the binaries and
+// report whatever differs. Most minidumps only contain a little code (around
+// the crashing instruction), but full-memory dumps contain all of it.
+
+/// Differences separated by fewer identical bytes than this are reported as
+/// one modification, since hooks usually overwrite whole instructions.
+const MAX_GAP: usize = 8;
+
+/// How many of the original and modified bytes of each modification to report.
+const MAX_REPORTED_BYTES: usize = 64;
+
+/// Compare the code of the modules in `memory_list` with the code in their binaries.
+///
+/// Only the binaries of the modules that have code in `memory_list` are read.
+pub(crate) async fn check<P>(
+    module_code: &ModuleCodeCache,
+    memory_list: &UnifiedMemoryList<'_>,
+    modules: &MinidumpModuleList,
+    cpu: Cpu,
+    symbol_provider: &P,
+) -> CodeIntegrity
+where
+    P: SymbolProvider + Sync,
+{
+    let memory = MinidumpAddressSpace::from(memory_list);
+    let mut integrity = CodeIntegrity::default();
+    for module in modules.iter() {
+        let module_end = module.base_address().saturating_add(module.size());
+        let in_memory = memory_list.by_addr().any(|region| {
+            region.base_address() < module_end
+                && region.base_address().saturating_add(region.size()) > module.base_address()
+        });
+        if !in_memory {
+            continue;
+        }
+        let code = match module_code.get(module, symbol_provider).await {
+            Some(code) => code,
+            None => continue,
+        };
+        let module_name = basename(&module.code_file()).to_owned();
+
+        let mut checked = false;
+        for section in &code.sections {
+            for region in memory_list.by_addr() {
+                let (start, original, modified) = match overlap(
+                    section,
+                    region.base_address(),
+                    region.size(),
+                    region.bytes(),
+                ) {
+                    Some(overlap) => overlap,
+                    None => continue,
+                };
+                checked = true;
+
+                for run in differences(original, modified) {
+                    let original = &original[run.clone()];
+                    let modified = &modified[run.clone()];
+                    let address = start + run.start as u64;
+                    let kind = classify(address, original, modified, cpu, &memory, modules);
+                    let mut modification = CodeModification {
+                        module: module_name.clone(),
+                        section: section.name.clone(),
+                        address,
+                        size: run.len() as u64,
+                        original: original[..run.len().min(MAX_REPORTED_BYTES)].to_vec(),
+                        modified: modified[..run.len().min(MAX_REPORTED_BYTES)].to_vec(),
+                        kind,
+                        function_name: None,
+                        function_base: None,
+                    };
+                    // Missing symbols are fine, the address is still useful without them.
+                    let _ = symbol_provider.fill_symbol(module, &mut modification).await;
+                    integrity.modifications.push(modification);
+                }
+            }
+        }
+        if checked {
+            integrity.checked_modules.push(module_name);
+        }
+    }
+    integrity
+}
+
+/// The address and contents of `section` and of the memory region at `base_address`
+/// where they overlap.
+///
+/// `bytes` can be shorter than `size` (or empty) if the minidump is truncated or the
+/// region couldn't be read, so only what's actually there is compared.
+fn overlap<'b>(
+    section: &'b CodeSection,
+    base_address: u64,
+    size: u64,
+    bytes: &'b [u8],
+) -> Option<(u64, &'b [u8], &'b [u8])> {
+    let section_end = section.address.saturating_add(section.bytes.len() as u64);
+    let start = base_address.max(section.address);
+    let end = base_address.saturating_add(size).min(section_end);
+    if start >= end {
+        return None;
+    }
+    let original = section.bytes.get((start - section.address) as usize..)?;
+    let modified = bytes.get(usize::try_from(start - base_address).ok()?..)?;
+    let len = ((end - start) as usize).min(modified.len());
+    if len == 0 {
+        return None;
+    }
+    Some((start, &original[..len], &modified[..len]))
+}
+
+/// The ranges of indices where `original` and `modified` differ.
+fn differences<'b>(
+    original: &'b [u8],
+    modified: &'b [u8],
+) -> impl Iterator<Item = std::ops::Range<usize>> + 'b {
+    let mut differing = original
+        .iter()
+        .zip(modified)
+        .enumerate()
+        .filter(|(_, (original, modified))| original != modified)
+        .map(|(index, _)| index)
+        .peekable();
+    std::iter::from_fn(move || {
+        let start = differing.next()?;
+        let mut end = start + 1;
+        while let Some(&next) = differing.peek() {
+            if next - end >= MAX_GAP {
+                break;
+            }
+            end = next + 1;
+            differing.next();
+        }
+        Some(start..end)
+    })
+}
+
+fn classify(
+    address: u64,
+    original: &[u8],
+    modified: &[u8],
+    cpu: Cpu,
+    memory: &MinidumpAddressSpace<'_, '_>,
+    modules: &MinidumpModuleList,
+) -> CodeModificationKind {
+    if let ([original], [modified]) = (original, modified) {
+        if (original ^ modified).count_ones() == 1 {
+            return CodeModificationKind::BitFlip;
+        }
+    }
+
+    let target = match cpu {
+        Cpu::X86 => x86_jump_target(address, modified, false, memory),
+        Cpu::X86_64 => x86_jump_target(address, modified, true, memory),
+        Cpu::Arm64 => arm64_jump_target(address, modified),
+        _ => None,
+    };
+    match target {
+        Some(target) => CodeModificationKind::InlineHook {
+            target,
+            target_module: modules
+                .module_at_address(target)
+                .map(|module| basename(&module.code_file()).to_owned()),
+        },
+        None => CodeModificationKind::Patch,
+    }
+}
+
+/// If `code` (at `address`) starts with an unconditional jump, return where it jumps to.
+///
+/// These are the jumps that hooking libraries (Detours, MinHook...) write.
+fn x86_jump_target(
+    address: u64,
+    code: &[u8],
+    is_64_bit: bool,
+    memory: &MinidumpAddressSpace<'_, '_>,
+) -> Option<u64> {
+    let truncate = |target: u64| {
+        if is_64_bit {
+            target
+        } else {
+            target & 0xffff_ffff
+        }
+    };
+    let u32_at = |offset: usize| {
+        Some(u32::from_le_bytes(
+            code.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let u64_at = |offset: usize| {
+        Some(u64::from_le_bytes(
+            code.get(offset..offset + 8)?.try_into().ok()?,
+        ))
+    };
+    match code {
+        // jmp rel32
+        [0xe9, ..] => Some(truncate(
+            address
+                .wrapping_add(5)
+                .wrapping_add(u32_at(1)? as i32 as u64),
+        )),
+        // jmp rel8
+        [0xeb, rel, ..] => Some(truncate(
+            address.wrapping_add(2).wrapping_add(*rel as i8 as u64),
+        )),
+        // jmp [rip+disp32] (64-bit) or jmp [disp32] (32-bit)
+        [0xff, 0x25, ..] => {
+            let disp = u32_at(2)?;
+            if is_64_bit {
+                let pointer = address.wrapping_add(6).wrapping_add(disp as i32 as u64);
+                // The pointer often immediately follows the jump.
+                u64_at(6)
+                    .filter(|_| disp == 0)
+                    .or_else(|| memory.read_u64(pointer))
+            } else {
+                memory.read_u32(disp as u64).map(u64::from)
+            }
+        }
+        // push imm32; ret
+        [0x68, _, _, _, _, 0xc3, ..] => Some(u32_at(1)? as u64),
+        // mov rax, imm64; jmp rax
+        [0x48, 0xb8, _, _, _, _, _, _, _, _, 0xff, 0xe0, ..] if is_64_bit => u64_at(2),
+        // mov r11, imm64; jmp r11
+        [0x49, 0xbb, _, _, _, _, _, _, _, _, 0x41, 0xff, 0xe3, ..] if is_64_bit => u64_at(2),
+        _ => None,
+    }
+}
+
+/// If `code` (at `address`) starts with an unconditional jump, return where it jumps to.
+fn arm64_jump_target(address: u64, code: &[u8]) -> Option<u64> {
+    let instruction = |index: usize| {
+        Some(u32::from_le_bytes(
+            code.get(index * 4..index * 4 + 4)?.try_into().ok()?,
+        ))
+    };
+    let first = instruction(0)?;
+    // b imm26
+    if first & 0xfc00_0000 == 0x1400_0000 {
+        let offset = (((first & 0x03ff_ffff) << 6) as i32 >> 4) as i64;
+        return Some(address.wrapping_add(offset as u64));
+    }
+    // ldr xN, #8; br xN; .quad target
+    let register = first & 0x1f;
+    if first == 0x5800_0040 | register && instruction(1)? == 0xd61f_0000 | (register << 5) {
+        return Some(u64::from_le_bytes(code.get(8..16)?.try_into().ok()?));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differences() {
+        let original = [0u8; 32];
+        let mut modified = [0u8; 32];
+        modified[1] = 1;
+        modified[4] = 1;
+        modified[20] = 1;
+        assert_eq!(
+            differences(&original, &modified).collect::<Vec<_>>(),
+            vec![1..5, 20..21]
+        );
+        assert_eq!(differences(&original, &original).count(), 0);
+    }
+
+    #[test]
+    fn test_overlap() {
+        let section = CodeSection {
+            name: String::from(".text"),
+            address: 0x1000,
+            bytes: vec![0xcc; 0x100],
+        };
+        let bytes = [0x90; 0x20];
+        assert_eq!(
+            overlap(&section, 0x10f0, 0x20, &bytes),
+            Some((0x10f0, &section.bytes[..0x10], &bytes[..0x10]))
+        );
+        // A truncated region is only compared as far as it goes.
+        assert_eq!(
+            overlap(&section, 0x1010, 0x80, &bytes),
+            Some((0x1010, &section.bytes[..0x20], &bytes[..]))
+        );
+        // A region that couldn't be read is skipped.
+        assert_eq!(overlap(&section, 0x1010, 0x80, &[]), None);
+        assert_eq!(overlap(&section, 0x1100, 0x20, &bytes), None);
+    }
+
+    #[test]
+    fn test_x86_jump_target() {
+        let memory = MinidumpAddressSpace::new();
+        let jump = |code: &[u8], is_64_bit| x86_jump_target(0x1000, code, is_64_bit, &memory);
+        assert_eq!(jump(&[0xe9, 0xfb, 0x0f, 0x00, 0x00], false), Some(0x2000));
+        assert_eq!(jump(&[0xe9, 0xfb, 0xef, 0xff, 0xff], false), Some(0));
+        assert_eq!(jump(&[0xeb, 0xfe], true), Some(0x1000));
+        assert_eq!(
+            jump(
+                &[0xff, 0x25, 0, 0, 0, 0, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11],
+                true
+            ),
+            Some(0x1122_3344_5566_7788)
+        );
+        assert_eq!(
+            jump(&[0x68, 0x78, 0x56, 0x34, 0x12, 0xc3], false),
+            Some(0x1234_5678)
+        );
+        assert_eq!(
+            jump(
+                &[0x48, 0xb8, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0xff, 0xe0],
+                true
+            ),
+            Some(0x1122_3344_5566_7788)
+        );
+        // mov rax, imm64 isn't a thing on 32-bit
+        assert_eq!(
+            jump(
+                &[0x48, 0xb8, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0xff, 0xe0],
+                false
+            ),
+            None
+        );
+        // push rbp; mov rbp, rsp
+        assert_eq!(jump(&[0x55, 0x48, 0x89, 0xe5], true), None);
+    }
+
+    #[test]
+    fn test_arm64_jump_target() {
+        // b #-0x10
+        assert_eq!(
+            arm64_jump_target(0x1000, &[0xfc, 0xff, 0xff, 0x17]),
+            Some(0xff0)
+        );
+        // ldr x16, #8; br x16; .quad 0x1122334455667788
+        assert_eq!(
+            arm64_jump_target(
+                0x1000,
+                &[
+                    0x50, 0x00, 0x00, 0x58, 0x00, 0x02, 0x1f, 0xd6, 0x88, 0x77, 0x66, 0x55, 0x44,
+                    0x33, 0x22, 0x11
+                ]
+            ),
+            Some(0x1122_3344_5566_7788)
+        );
+        // stp x29, x30, [sp, #-0x10]!
+        assert_eq!(arm64_jump_target(0x1000, &[0xfd, 0x7b, 0xbf, 0xa9]), None);
+    }
+}
@@ -83,7 +83,9 @@ async fn main() -> Result<(), ()> {
 doc_comment::doctest!("../README.md");
 
 mod arg_recovery;
+mod code_integrity;
 mod evil;
+mod oom;
 mod op_analysis;
 mod process_state;
 mod processor;
@@ -0,0 +1,296 @@
+use std::time::SystemTime;
+
+use minidump::system_info::PointerWidth;
+use minidump::{format as md, CrashReason, MinidumpMiscInfo, UnifiedMemoryInfoList};
+use minidump_common::errors::NtStatusWindows;
+
+use crate::process_state::{Limit, LinuxProcStatus, OomAssessment, OomEvidence, ProcessState};
+
+// # Classifying out-of-memory crashes
+//
+// Running out of memory rarely produces a distinctive crash. Allocation
+// failures usually end up in some handler which deliberately aborts (or
+// throws `std::bad_alloc`, which nobody catches), so the crash itself looks
+// like any other abort. What we can do is collect the circumstantial evidence
+// that's lying around in the minidump and draw a conclusion from it:
+//
+// * The crash reason may say so outright (`STATUS_NO_MEMORY`).
+// * The crashing thread may be inside a function that handles allocation
+//   failures (`mozalloc_handle_oom`, `alloc::alloc::handle_alloc_error`...),
+//   which is about as good as the crash reason, or inside an allocator
+//   (`operator new`, `malloc`...), which is a lot weaker since allocators
+//   crash for all sorts of reasons (mostly heap corruption).
+// * A 32-bit process may simply have run out of address space, which we can
+//   see from the memory info list or the linux maps.
+// * The process may have been close to its `RLIMIT_AS`.
+// * The system may have been close to its commit limit.
+// * The process may have been running for long enough to leak its way out of
+//   memory, which we can see from its creation time in the misc info.
+//
+// See `OomAssessment::from_evidence` for how these are weighed.
+
+/// How many frames from the top of the crashing thread to look at.
+const MAX_FRAMES_TO_SCAN: usize = 10;
+
+/// A 32-bit process whose largest free block of address space is smaller
+/// than this is considered to have run out of address space.
+const MIN_LARGEST_FREE_BLOCK: u64 = 16 * 1024 * 1024;
+
+/// A process that has been running for at least this long (in seconds) may
+/// have slowly leaked its way out of memory.
+const MIN_LONG_UPTIME: u64 = 7 * 24 * 60 * 60;
+
+/// Functions which are only ever called when an allocation failed.
+///
+/// These are matched as substrings, so they also catch mangled or decorated
+/// variants of the names.
+const OOM_HANDLERS: &[&str] = &[
+    // C++
+    "std::__throw_bad_alloc",
+    "__scrt_throw_std_bad_alloc",
+    "std::_Xbad_alloc",
+    // Firefox
+    "mozalloc_handle_oom",
+    "NS_ABORT_OOM",
+    // Rust
+    "alloc::alloc::handle_alloc_error",
+    "std::alloc::rust_oom",
+    "std::alloc::default_alloc_error_hook",
+    "__rg_oom",
+    "__rust_alloc_error_handler",
+    // Chromium
+    "TerminateBecauseOutOfMemory",
+    "OnNoMemory",
+    "FatalProcessOutOfMemory",
+];
+
+/// Allocation functions.
+///
+/// These are matched exactly, after removing the argument list and any Rust
+/// symbol hash from the function name.
+const ALLOCATORS: &[&str] = &[
+    // C++
+    "operator new",
+    "operator new[]",
+    // C
+    "malloc",
+    "calloc",
+    "realloc",
+    "memalign",
+    "posix_memalign",
+    "aligned_alloc",
+    // jemalloc
+    "je_malloc",
+    "je_calloc",
+    "je_realloc",
+    "je_memalign",
+    // Firefox
+    "moz_xmalloc",
+    "moz_xcalloc",
+    "moz_xrealloc",
+    "moz_xmemalign",
+    // Rust
+    "alloc::alloc::alloc",
+    "alloc::alloc::alloc_zeroed",
+    "alloc::alloc::realloc",
+    "alloc::alloc::exchange_malloc",
+    "__rust_alloc",
+    "__rust_alloc_zeroed",
+    "__rust_realloc",
+    "__rdl_alloc",
+    "__rdl_alloc_zeroed",
+    "__rdl_realloc",
+    // Windows
+    "HeapAlloc",
+    "HeapReAlloc",
+    "RtlAllocateHeap",
+    "RtlReAllocateHeap",
+    "VirtualAlloc",
+    "VirtualAllocEx",
+];
+
+/// Assess whether the crash described by `state` was caused by running out of memory.
+///
+/// This must be called after the threads have been walked, as it looks at
+/// the symbolicated frames of the crashing thread.
+pub(crate) fn assess(
+    state: &ProcessState,
+    memory_info: &UnifiedMemoryInfoList,
+    proc_status: Option<&LinuxProcStatus>,
+    misc_info: Option<&MinidumpMiscInfo>,
+) -> OomAssessment {
+    let mut evidence = vec![];
+
+    if let Some(exception_info) = &state.exception_info {
+        if let CrashReason::WindowsNtStatus(
+            NtStatusWindows::STATUS_NO_MEMORY | NtStatusWindows::STATUS_COMMITMENT_LIMIT,
+        ) = exception_info.reason
+        {
+            evidence.push(OomEvidence::CrashReason(exception_info.reason.to_string()));
+        }
+    }
+
+    evidence.extend(frame_evidence(state));
+
+    if state.system_info.cpu.pointer_width() == PointerWidth::Bits32 {
+        evidence.extend(address_space_evidence(memory_info));
+    }
+
+    evidence.extend(address_space_limit_evidence(state, proc_status));
+
+    if let Some(system) = state
+        .memory_stats
+        .as_ref()
+        .and_then(|stats| stats.system.as_ref())
+    {
+        // Windows starts failing allocations a bit before the commit limit is
+        // actually reached, so don't require it to be completely exhausted.
+        if system.commit_limit != 0 && system.commit_charge >= system.commit_limit / 100 * 95 {
+            evidence.push(OomEvidence::CommitLimit {
+                commit_charge: system.commit_charge,
+                commit_limit: system.commit_limit,
+            });
+        }
+    }
+
+    evidence.extend(uptime_evidence(state.time, misc_info));
+
+    OomAssessment::from_evidence(evidence)
+}
+
+/// Look for OOM handlers and allocators at the top of the crashing thread.
+fn frame_evidence(state: &ProcessState) -> Vec<OomEvidence> {
+    let mut evidence = vec![];
+    let Some(thread) = state
+        .requesting_thread
+        .and_then(|idx| state.threads.get(idx))
+    else {
+        return evidence;
+    };
+
+    let mut found_handler = false;
+    let mut found_allocator = false;
+    for (frame_idx, frame) in thread.frames.iter().enumerate().take(MAX_FRAMES_TO_SCAN) {
+        let Some(function) = frame.function_name.as_deref() else {
+            continue;
+        };
+
+        if !found_handler && OOM_HANDLERS.iter().any(|name| function.contains(name)) {
+            found_handler = true;
+            evidence.push(OomEvidence::OomHandlerFrame {
+                frame: frame_idx,
+                function: function.to_owned(),
+            });
+        } else if !found_allocator && ALLOCATORS.contains(&base_function_name(function)) {
+            found_allocator = true;
+            evidence.push(OomEvidence::AllocatorFrame {
+                frame: frame_idx,
+                function: function.to_owned(),
+            });
+        }
+    }
+
+    evidence
+}
+
+/// Strip the argument list and the Rust symbol hash (`::h0123456789abcdef`)
+/// from a function name.
+fn base_function_name(function: &str) -> &str {
+    let function = function.split('(').next().unwrap_or(function).trim();
+    match function.rsplit_once("::h") {
+        Some((base, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            base
+        }
+        _ => function,
+    }
+}
+
+/// Check whether a 32-bit process ran out of address space.
+fn address_space_evidence(memory_info: &UnifiedMemoryInfoList) -> Option<OomEvidence> {
+    let free_blocks: Vec<u64> = match memory_info {
+        UnifiedMemoryInfoList::Info(info) => info
+            .iter()
+            .filter(|region| region.state.contains(md::MemoryState::MEM_FREE))
+            .map(|region| region.raw.region_size)
+            .collect(),
+        // The maps only list what is mapped, the free space is in the gaps.
+        UnifiedMemoryInfoList::Maps(maps) => {
+            let ranges: Vec<_> = maps
+                .by_addr()
+                .filter_map(|map| map.memory_range())
+                .collect();
+            ranges
+                .windows(2)
+                .map(|pair| pair[1].start.saturating_sub(pair[0].end))
+                .filter(|&gap| gap > 0)
+                .collect()
+        }
+    };
+
+    // If there's no free space at all, this list most likely just doesn't
+    // describe free memory, so don't draw any conclusions from it.
+    let largest_free_block = free_blocks.iter().copied().max()?;
+    let total_free = free_blocks.iter().sum();
+    (largest_free_block < MIN_LARGEST_FREE_BLOCK).then_some(OomEvidence::AddressSpaceExhausted {
+        largest_free_block,
+        total_free,
+    })
+}
+
+/// Check whether the process was close to its `RLIMIT_AS`.
+fn address_space_limit_evidence(
+    state: &ProcessState,
+    proc_status: Option<&LinuxProcStatus>,
+) -> Option<OomEvidence> {
+    let proc_status = proc_status?;
+    let limit = match state
+        .linux_proc_limits
+        .as_ref()?
+        .limits
+        .get("Max address space")?
+        .soft
+    {
+        Limit::Limited(limit) if limit != 0 => limit,
+        _ => return None,
+    };
+
+    let threshold = limit / 10 * 9;
+    let near_limit = |val: Option<u64>| val.is_some_and(|val| val >= threshold);
+    (near_limit(proc_status.vm_size) || near_limit(proc_status.vm_rss)).then_some(
+        OomEvidence::AddressSpaceLimit {
+            vm_size: proc_status.vm_size,
+            vm_rss: proc_status.vm_rss,
+            limit,
+        },
+    )
+}
+
+/// Check whether the process had been running for a long time when it crashed.
+fn uptime_evidence(
+    crash_time: SystemTime,
+    misc_info: Option<&MinidumpMiscInfo>,
+) -> Option<OomEvidence> {
+    let create_time = misc_info?.process_create_time()?;
+    let uptime = crash_time.duration_since(create_time).ok()?.as_secs();
+    (uptime >= MIN_LONG_UPTIME).then_some(OomEvidence::LongUptime { uptime })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_function_name() {
+        assert_eq!(
+            base_function_name("operator new(unsigned long)"),
+            "operator new"
+        );
+        assert_eq!(base_function_name("moz_xmalloc"), "moz_xmalloc");
+        assert_eq!(
+            base_function_name("alloc::alloc::exchange_malloc::h0123456789abcdef"),
+            "alloc::alloc::exchange_malloc"
+        );
+        // Not a symbol hash
+        assert_eq!(base_function_name("foo::hello"), "foo::hello");
+    }
+}
@@ -16,9 +16,13 @@
 
 #![deny(missing_docs)]
 
-use minidump::{MinidumpContext, MinidumpRawContext, UnifiedMemory};
+use minidump::{MinidumpAddressSpace, MinidumpContext, MinidumpRawContext};
+use std::borrow::Cow;
 use std::collections::BTreeSet;
 
+/// The length of the longest instruction of any supported architecture (15 bytes on x86).
+const MAX_INSTRUCTION_LENGTH: usize = 15;
+
 /// Error type for the functions in this module
 #[derive(Debug, thiserror::Error)]
 pub enum OpAnalysisError {
@@ -77,7 +81,7 @@ pub struct MemoryAccess {
 /// Analyze the instructions being run by the given thread
 ///
 /// Using the passed-in `context` of the thread's execution and the memory contained in
-/// `memory_list`, this function will use a disassembler to analyze the instructions the thread
+/// `memory`, this function will use a disassembler to analyze the instructions the thread
 /// was running and determine information that may be useful for people who need to analyze crash
 /// dumps.
 ///
@@ -94,19 +98,15 @@ pub struct MemoryAccess {
 /// may still be missing from the returned `OpAnalysis` structure.
 pub fn analyze_thread_context(
     context: &MinidumpContext,
-    memory_list: &minidump::UnifiedMemoryList,
-    stack_memory: Option<UnifiedMemory>,
+    memory: &MinidumpAddressSpace,
 ) -> Result<OpAnalysis, OpAnalysisError> {
-    let instruction_bytes = get_thread_instruction_bytes(context, memory_list)?;
+    let instruction_bytes = get_thread_instruction_bytes(context, memory)?;
 
     match context.raw {
         #[cfg(feature = "disasm_amd64")]
-        MinidumpRawContext::Amd64(_) => self::amd64::analyze_instruction(
-            context,
-            instruction_bytes,
-            Some(memory_list),
-            stack_memory,
-        ),
+        MinidumpRawContext::Amd64(_) => {
+            self::amd64::analyze_instruction(context, &instruction_bytes, Some(memory))
+        }
         _ => Err(OpAnalysisError::UnsupportedCpuArch),
     }
 }
@@ -114,23 +114,19 @@ pub fn analyze_thread_context(
 /// Helper to read the instruction bytes that were being run by the given thread
 ///
 /// Use the given `context` to attempt to read `1 <= n <= MAX_INSTRUCTION_LENGTH`
-/// bytes at the instruction pointer from the given memory list.
+/// bytes at the instruction pointer from the given memory.
 ///
 /// # Errors
 ///
 /// This may fail if there are no bytes at the instruction pointer.
 fn get_thread_instruction_bytes<'a>(
     context: &MinidumpContext,
-    memory_list: &'a minidump::UnifiedMemoryList<'a>,
-) -> Result<&'a [u8], OpAnalysisError> {
+    memory: &MinidumpAddressSpace<'a, '_>,
+) -> Result<Cow<'a, [u8]>, OpAnalysisError> {
     let instruction_pointer = context.get_instruction_pointer();
 
-    memory_list
-        .memory_at_address(instruction_pointer)
-        .map(|memory| {
-            let offset = (instruction_pointer - memory.base_address()) as usize;
-            &memory.bytes()[offset..]
-        })
+    memory
+        .read_bytes_up_to(instruction_pointer, MAX_INSTRUCTION_LENGTH)
         .ok_or(OpAnalysisError::ReadThreadInstructionFailed)
 }
 
@@ -147,14 +143,13 @@ mod amd64 {
     pub fn analyze_instruction(
         context: &MinidumpContext,
         instruction_bytes: &[u8],
-        memory_list: Option<&minidump::UnifiedMemoryList>,
-        stack_memory: Option<minidump::UnifiedMemory>,
+        memory: Option<&MinidumpAddressSpace>,
     ) -> Result<OpAnalysis, OpAnalysisError> {
         let decoded_instruction = decode_instruction(instruction_bytes)?;
 
         let instruction_str = decoded_instruction.to_string();
 
-        let memory_accesses = GetMemoryAccess::new(context, memory_list, stack_memory)
+        let memory_accesses = GetMemoryAccess::new(context, memory)
             .get_instruction_memory_access(decoded_instruction)
             .map_err(|e| tracing::warn!("failed to determine instruction memory access: {}", e))
             .ok();
@@ -185,8 +180,7 @@ mod amd64 {
 
     struct GetMemoryAccess<'a> {
         context: &'a MinidumpContext,
-        memory_list: Option<&'a minidump::UnifiedMemoryList<'a>>,
-        stack_memory: Option<minidump::UnifiedMemory<'a, 'a>>,
+        memory: Option<&'a MinidumpAddressSpace<'a, 'a>>,
     }
 
     #[derive(Default)]
@@ -257,14 +251,9 @@ mod amd64 {
     impl<'a> GetMemoryAccess<'a> {
         pub fn new(
             context: &'a MinidumpContext,
-            memory_list: Option<&'a minidump::UnifiedMemoryList<'a>>,
-            stack_memory: Option<minidump::UnifiedMemory<'a, 'a>>,
+            memory: Option<&'a MinidumpAddressSpace<'a, 'a>>,
         ) -> Self {
-            GetMemoryAccess {
-                context,
-                memory_list,
-                stack_memory,
-            }
+            GetMemoryAccess { context, memory }
         }
 
         /// Determine the memory accesses implied by the given instruction and context
@@ -359,15 +348,11 @@ mod amd64 {
                         }
                         other_operand => {
                             // If the operand was some sort of register dereference, try to get the
-                            // _actual_ address from the memory list.
+                            // _actual_ address from the memory.
                             if let Some(op_info) = RegOperandInfo::try_from_operand(other_operand) {
                                 let memory_address = self.calculate_address(None, op_info)?.address;
-                                if let Some(address) = self
-                                    .memory_list
-                                    .and_then(|ml| ml.memory_at_address(memory_address))
-                                    .and_then(|mem| {
-                                        mem.get_memory_at_address::<u64>(memory_address)
-                                    })
+                                if let Some(address) =
+                                    self.memory.and_then(|mem| mem.read_u64(memory_address))
                                 {
                                     push_indirect_access(address);
                                 }
@@ -378,9 +363,9 @@ mod amd64 {
                 Opcode::RETURN | Opcode::RETF | Opcode::IRET | Opcode::IRETD | Opcode::IRETQ => {
                     // Use the return address (from the stack)
                     if let (Ok(rsp), Some(stack)) =
-                        (self.context.get_regspec(RegSpec::rsp()), &self.stack_memory)
+                        (self.context.get_regspec(RegSpec::rsp()), self.memory)
                     {
-                        if let Some(address) = stack.get_memory_at_address::<u64>(rsp) {
+                        if let Some(address) = stack.read_u64(rsp) {
                             push_indirect_access(address);
                         }
                     }
@@ -468,8 +453,7 @@ mod tests {
             let context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(context_raw));
 
             let op_analysis =
-                crate::op_analysis::amd64::analyze_instruction(&context, data.bytes, None, None)
-                    .unwrap();
+                crate::op_analysis::amd64::analyze_instruction(&context, data.bytes, None).unwrap();
 
             let memory_accesses = op_analysis.memory_accesses.unwrap();
 
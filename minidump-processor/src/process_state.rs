@@ -108,6 +108,10 @@ impl From<MinidumpLinuxLsbRelease<'_>> for LinuxStandardBase {
 #[derive(Debug, Clone)]
 pub struct LinuxProcStatus {
     pub pid: u32,
+    /// The virtual memory size of the process (`VmSize`), in bytes
+    pub vm_size: Option<u64>,
+    /// The resident set size of the process (`VmRSS`), in bytes
+    pub vm_rss: Option<u64>,
 }
 
 impl From<MinidumpLinuxProcStatus<'_>> for LinuxProcStatus {
@@ -118,7 +122,22 @@ impl From<MinidumpLinuxProcStatus<'_>> for LinuxProcStatus {
             .map_or(0, |key_val| {
                 key_val.1.to_string_lossy().parse::<u32>().unwrap_or(0)
             });
-        LinuxProcStatus { pid }
+        // Memory values look like `VmRSS:     1234 kB`
+        let kilobytes = |key: &[u8]| {
+            status
+                .iter()
+                .find(|entry| entry.0.as_bytes() == key)
+                .and_then(|key_val| {
+                    let val = key_val.1.to_string_lossy();
+                    let kb = val.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+                    kb.checked_mul(1024)
+                })
+        };
+        LinuxProcStatus {
+            pid,
+            vm_size: kilobytes(b"VmSize"),
+            vm_rss: kilobytes(b"VmRSS"),
+        }
     }
 }
 
@@ -196,6 +215,465 @@ impl From<MinidumpLinuxProcLimits<'_>> for LinuxProcLimits {
     }
 }
 
+/// Interesting values extracted from the auxiliary vector of a Linux process
+#[derive(Debug, Clone, Default)]
+pub struct LinuxAuxv {
+    /// Arch-specific CPU capability bits (`AT_HWCAP`)
+    pub hwcap: Option<u64>,
+    /// More arch-specific CPU capability bits (`AT_HWCAP2`)
+    pub hwcap2: Option<u64>,
+    /// The platform string (`AT_PLATFORM`), if its memory was captured
+    pub platform: Option<String>,
+    /// The filename used to execute the program (`AT_EXECFN`), if its memory was captured
+    pub execfn: Option<String>,
+    /// The base address of the vDSO (`AT_SYSINFO_EHDR`)
+    pub vdso_base: Option<u64>,
+    /// The base address of the program interpreter (`AT_BASE`)
+    pub interpreter_base: Option<u64>,
+}
+
+impl LinuxAuxv {
+    pub fn new(auxv: &MinidumpLinuxAuxv, memory: &UnifiedMemoryList) -> Self {
+        LinuxAuxv {
+            hwcap: auxv.hwcap(),
+            hwcap2: auxv.hwcap2(),
+            platform: auxv.platform(memory),
+            execfn: auxv.execfn(memory),
+            vdso_base: auxv.sysinfo_ehdr(),
+            interpreter_base: auxv.base(),
+        }
+    }
+}
+
+/// Memory usage statistics from the time the minidump was written
+///
+/// Currently only available for Windows minidumps. All values are in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// System-wide statistics (from the `SystemMemoryInfoStream`)
+    pub system: Option<SystemMemoryStats>,
+    /// Statistics of the crashed process (from the `ProcessVmCountersStream`)
+    pub process: Option<ProcessMemoryStats>,
+}
+
+/// System-wide memory usage statistics, in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemMemoryStats {
+    /// The system-wide commit charge
+    pub commit_charge: u64,
+    /// The system-wide commit limit, allocations start failing beyond it
+    pub commit_limit: u64,
+    /// The peak system-wide commit charge
+    pub peak_commit_charge: u64,
+    /// The amount of physical memory in the system
+    pub total_physical_memory: u64,
+    /// The amount of physical memory that was available for use
+    pub available_physical_memory: u64,
+}
+
+/// Memory usage statistics of the crashed process, in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessMemoryStats {
+    /// The memory committed by the process that can't be shared with others
+    pub private_bytes: Option<u64>,
+    /// The commit charge of the process (Windows' `PagefileUsage`)
+    pub page_file_usage: Option<u64>,
+    /// The peak commit charge of the process
+    pub peak_page_file_usage: Option<u64>,
+    /// The amount of physical memory used by the process
+    pub working_set_size: Option<u64>,
+    /// The peak amount of physical memory used by the process
+    pub peak_working_set_size: Option<u64>,
+    /// The amount of address space reserved by the process
+    pub virtual_size: Option<u64>,
+}
+
+impl MemoryStats {
+    pub fn new(
+        system_memory_info: Option<&MinidumpSystemMemoryInfo>,
+        vm_counters: Option<&MinidumpProcessVmCounters>,
+    ) -> Option<Self> {
+        if system_memory_info.is_none() && vm_counters.is_none() {
+            return None;
+        }
+        let system = system_memory_info.map(|info| SystemMemoryStats {
+            commit_charge: info.commit_charge(),
+            commit_limit: info.commit_limit(),
+            peak_commit_charge: info.peak_commit_charge(),
+            total_physical_memory: info.total_physical_memory(),
+            available_physical_memory: info.available_physical_memory(),
+        });
+        let process = vm_counters.map(|counters| ProcessMemoryStats {
+            private_bytes: counters.raw.private_usage().copied(),
+            page_file_usage: counters.raw.pagefile_usage().copied(),
+            peak_page_file_usage: counters.raw.peak_pagefile_usage().copied(),
+            working_set_size: counters.raw.working_set_size().copied(),
+            peak_working_set_size: counters.raw.peak_working_set_size().copied(),
+            virtual_size: counters.raw.virtual_size().copied(),
+        });
+        Some(MemoryStats { system, process })
+    }
+}
+
+/// A frame of the backtrace of a [`HandleOperation`]
+#[derive(Debug, Clone, Default)]
+pub struct HandleTraceFrame {
+    /// The return address recorded in the backtrace
+    pub instruction: u64,
+    /// The module containing `instruction`, if any
+    pub module: Option<MinidumpModule>,
+    pub function_name: Option<String>,
+    pub function_base: Option<u64>,
+    pub source_file_name: Option<String>,
+    pub source_line: Option<u32>,
+}
+
+impl HandleTraceFrame {
+    pub fn new(instruction: u64) -> Self {
+        HandleTraceFrame {
+            instruction,
+            ..Default::default()
+        }
+    }
+}
+
+impl minidump_unwind::FrameSymbolizer for HandleTraceFrame {
+    fn get_instruction(&self) -> u64 {
+        // These are return addresses, look up the call instruction instead
+        // of whatever comes after it.
+        self.instruction.saturating_sub(1)
+    }
+    fn set_function(&mut self, name: &str, base: u64, _parameter_size: u32) {
+        self.function_name = Some(String::from(name));
+        self.function_base = Some(base);
+    }
+    fn set_source_file(&mut self, file: &str, line: u32, _base: u64) {
+        self.source_file_name = Some(String::from(file));
+        self.source_line = Some(line);
+    }
+}
+
+/// An operation on a handle recorded by handle tracing, from the
+/// `HandleOperationListStream`
+#[derive(Debug, Clone)]
+pub struct HandleOperation {
+    pub handle: u64,
+    /// The thread that performed the operation
+    pub thread_id: u32,
+    /// What happened to the handle, if known
+    pub operation: Option<minidump_common::format::eHANDLE_TRACE_OPERATIONS>,
+    /// The symbolicated backtrace of the operation, innermost frame first
+    pub backtrace: Vec<HandleTraceFrame>,
+}
+
+impl HandleOperation {
+    /// A short name for the operation (`open`, `close`, `bad_ref` or `unknown`)
+    pub fn operation_str(&self) -> &'static str {
+        use minidump_common::format::eHANDLE_TRACE_OPERATIONS::*;
+        match self.operation {
+            Some(OperationDbOPEN) => "open",
+            Some(OperationDbCLOSE) => "close",
+            Some(OperationDbBADREF) => "bad_ref",
+            Some(OperationDbUnused) | None => "unknown",
+        }
+    }
+}
+
+/// How likely it is that a crash was caused by running out of memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OomLikelihood {
+    /// Nothing suggests the process ran out of memory
+    #[default]
+    Unlikely,
+    /// Some circumstantial evidence suggests the process was running out of memory
+    Possible,
+    /// The process very likely crashed because it ran out of memory
+    Likely,
+}
+
+impl OomLikelihood {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OomLikelihood::Unlikely => "unlikely",
+            OomLikelihood::Possible => "possible",
+            OomLikelihood::Likely => "likely",
+        }
+    }
+}
+
+/// A piece of evidence that a crash was caused by running out of memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OomEvidence {
+    /// The crash reason itself says the process ran out of memory (e.g. `STATUS_NO_MEMORY`)
+    CrashReason(String),
+    /// A function that handles allocation failures (e.g. `mozalloc_handle_oom` or
+    /// `alloc::alloc::handle_alloc_error`) is at the top of the crashing thread's stack
+    OomHandlerFrame { frame: usize, function: String },
+    /// An allocation function (e.g. `operator new` or `moz_xmalloc`) is at the top
+    /// of the crashing thread's stack
+    AllocatorFrame { frame: usize, function: String },
+    /// Almost nothing of the 32-bit address space was left (in bytes)
+    AddressSpaceExhausted {
+        largest_free_block: u64,
+        total_free: u64,
+    },
+    /// The process was close to its address space limit (`RLIMIT_AS`, in bytes)
+    AddressSpaceLimit {
+        vm_size: Option<u64>,
+        vm_rss: Option<u64>,
+        limit: u64,
+    },
+    /// The system's commit charge was close to the commit limit (in bytes)
+    CommitLimit {
+        commit_charge: u64,
+        commit_limit: u64,
+    },
+    /// The process had been running for a long time (in seconds), long enough to
+    /// leak its way out of memory
+    LongUptime { uptime: u64 },
+}
+
+impl OomEvidence {
+    /// Whether this evidence is enough to conclude that the process ran out of
+    /// memory on its own, as opposed to just being circumstantial.
+    pub fn is_conclusive(&self) -> bool {
+        match self {
+            OomEvidence::CrashReason(_) | OomEvidence::OomHandlerFrame { .. } => true,
+            OomEvidence::AllocatorFrame { .. }
+            | OomEvidence::AddressSpaceExhausted { .. }
+            | OomEvidence::AddressSpaceLimit { .. }
+            | OomEvidence::CommitLimit { .. }
+            | OomEvidence::LongUptime { .. } => false,
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut json = match self {
+            OomEvidence::CrashReason(reason) => json!({ "reason": reason }),
+            OomEvidence::OomHandlerFrame { frame, function }
+            | OomEvidence::AllocatorFrame { frame, function } => json!({
+                "frame": frame,
+                "function": function,
+            }),
+            OomEvidence::AddressSpaceExhausted {
+                largest_free_block,
+                total_free,
+            } => json!({
+                "largest_free_block": largest_free_block,
+                "total_free": total_free,
+            }),
+            OomEvidence::AddressSpaceLimit {
+                vm_size,
+                vm_rss,
+                limit,
+            } => json!({
+                "vm_size": vm_size,
+                "vm_rss": vm_rss,
+                "limit": limit,
+            }),
+            OomEvidence::CommitLimit {
+                commit_charge,
+                commit_limit,
+            } => json!({
+                "commit_charge": commit_charge,
+                "commit_limit": commit_limit,
+            }),
+            OomEvidence::LongUptime { uptime } => json!({ "uptime": uptime }),
+        };
+        json["type"] = self.kind().into();
+        json
+    }
+
+    /// A short, stable name for this kind of evidence.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OomEvidence::CrashReason(_) => "crash_reason",
+            OomEvidence::OomHandlerFrame { .. } => "oom_handler_frame",
+            OomEvidence::AllocatorFrame { .. } => "allocator_frame",
+            OomEvidence::AddressSpaceExhausted { .. } => "address_space_exhausted",
+            OomEvidence::AddressSpaceLimit { .. } => "address_space_limit",
+            OomEvidence::CommitLimit { .. } => "commit_limit",
+            OomEvidence::LongUptime { .. } => "long_uptime",
+        }
+    }
+}
+
+impl std::fmt::Display for OomEvidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OomEvidence::CrashReason(reason) => write!(f, "crash reason is {reason}"),
+            OomEvidence::OomHandlerFrame { frame, function } => {
+                write!(f, "out-of-memory handler {function} in frame {frame}")
+            }
+            OomEvidence::AllocatorFrame { frame, function } => {
+                write!(f, "allocator {function} in frame {frame}")
+            }
+            OomEvidence::AddressSpaceExhausted {
+                largest_free_block,
+                total_free,
+            } => write!(
+                f,
+                "address space exhausted ({total_free} bytes free, largest free block {largest_free_block} bytes)"
+            ),
+            OomEvidence::AddressSpaceLimit {
+                vm_size,
+                vm_rss,
+                limit,
+            } => {
+                write!(f, "close to the address space limit of {limit} bytes (")?;
+                if let Some(vm_size) = vm_size {
+                    write!(f, "VmSize {vm_size} bytes")?;
+                }
+                if vm_size.is_some() && vm_rss.is_some() {
+                    write!(f, ", ")?;
+                }
+                if let Some(vm_rss) = vm_rss {
+                    write!(f, "VmRSS {vm_rss} bytes")?;
+                }
+                write!(f, ")")
+            }
+            OomEvidence::CommitLimit {
+                commit_charge,
+                commit_limit,
+            } => write!(
+                f,
+                "system commit charge {commit_charge} bytes of {commit_limit} bytes"
+            ),
+            OomEvidence::LongUptime { uptime } => {
+                write!(f, "process had been running for {uptime} seconds")
+            }
+        }
+    }
+}
+
+/// An assessment of whether a crash was caused by running out of memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OomAssessment {
+    /// The overall conclusion drawn from the evidence
+    pub likelihood: OomLikelihood,
+    /// Everything that suggests the process ran out of memory
+    pub evidence: Vec<OomEvidence>,
+}
+
+impl OomAssessment {
+    /// Draw a conclusion from the given evidence.
+    ///
+    /// Any conclusive evidence makes an OOM likely, otherwise it's possible if
+    /// there is any circumstantial evidence, and likely if there are multiple
+    /// different kinds of it.
+    pub fn from_evidence(evidence: Vec<OomEvidence>) -> Self {
+        let mut kinds = evidence.iter().map(OomEvidence::kind).collect::<Vec<_>>();
+        kinds.dedup();
+        let likelihood = if evidence.iter().any(OomEvidence::is_conclusive) || kinds.len() > 1 {
+            OomLikelihood::Likely
+        } else if !evidence.is_empty() {
+            OomLikelihood::Possible
+        } else {
+            OomLikelihood::Unlikely
+        };
+        OomAssessment {
+            likelihood,
+            evidence,
+        }
+    }
+}
+
+/// How the code of a module in the minidump differs from the code in its binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeModificationKind {
+    /// A single bit differs, which suggests faulty hardware
+    BitFlip,
+    /// The code was overwritten with a jump elsewhere, which is how injected software
+    /// usually hooks functions
+    InlineHook {
+        /// Where the jump goes
+        target: u64,
+        /// The module containing `target`, if any
+        target_module: Option<String>,
+    },
+    /// Any other change to the code
+    Patch,
+}
+
+impl CodeModificationKind {
+    /// A short, stable name for this kind of modification.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeModificationKind::BitFlip => "bit_flip",
+            CodeModificationKind::InlineHook { .. } => "inline_hook",
+            CodeModificationKind::Patch => "patch",
+        }
+    }
+}
+
+/// A range of code in the minidump that differs from the code in the module's binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeModification {
+    /// The name of the module whose code was modified
+    pub module: String,
+    /// The section of the binary containing the modification (e.g. `.text`)
+    pub section: String,
+    /// The address of the first modified byte
+    pub address: u64,
+    /// The number of bytes from the first to the last modified byte
+    pub size: u64,
+    /// The bytes in the binary (the first 64 at most)
+    pub original: Vec<u8>,
+    /// The bytes in the minidump (the first 64 at most)
+    pub modified: Vec<u8>,
+    pub kind: CodeModificationKind,
+    pub function_name: Option<String>,
+    pub function_base: Option<u64>,
+}
+
+impl minidump_unwind::FrameSymbolizer for CodeModification {
+    fn get_instruction(&self) -> u64 {
+        self.address
+    }
+    fn set_function(&mut self, name: &str, base: u64, _parameter_size: u32) {
+        self.function_name = Some(String::from(name));
+        self.function_base = Some(base);
+    }
+    fn set_source_file(&mut self, _file: &str, _line: u32, _base: u64) {}
+}
+
+impl std::fmt::Display for CodeModification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.kind {
+            CodeModificationKind::BitFlip => write!(f, "bit flip")?,
+            CodeModificationKind::InlineHook {
+                target,
+                target_module,
+            } => {
+                write!(f, "inline hook jumping to {target:#x}")?;
+                if let Some(target_module) = target_module {
+                    write!(f, " ({target_module})")?;
+                }
+            }
+            CodeModificationKind::Patch => write!(f, "{} bytes patched", self.size)?,
+        }
+        write!(
+            f,
+            " at {:#x} in {} {}",
+            self.address, self.module, self.section
+        )?;
+        if let (Some(name), Some(base)) = (&self.function_name, self.function_base) {
+            write!(f, ", {name} + {:#x}", self.address - base)?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of comparing the code of the modules in the minidump with their binaries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodeIntegrity {
+    /// The modules whose binaries were found and whose code was (at least partly) in
+    /// the minidump
+    pub checked_modules: Vec<String>,
+    /// Everything that differs from the binaries
+    pub modifications: Vec<CodeModification>,
+}
+
 /// Info about an exception that may have occurred
 ///
 /// May not be available if the minidump wasn't triggered by an exception, or if required
@@ -451,8 +929,25 @@ pub struct ProcessState {
     pub linux_standard_base: Option<LinuxStandardBase>,
     /// Linux Proc Limits
     pub linux_proc_limits: Option<LinuxProcLimits>,
+    /// Linux auxiliary vector
+    pub linux_auxv: Option<LinuxAuxv>,
     pub mac_crash_info: Option<Vec<RawMacCrashInfo>>,
     pub mac_boot_args: Option<MinidumpMacBootargs>,
+    /// A free-form comment written into the dump by the process that wrote it.
+    pub comment: Option<MinidumpComment>,
+    /// Memory usage of the system and the process when the minidump was written
+    pub memory_stats: Option<MemoryStats>,
+    /// Whether the crash looks like the process ran out of memory, and why
+    pub oom_assessment: OomAssessment,
+    /// The most recent operations on handles, if handle tracing was enabled
+    pub handle_operations: Option<Vec<HandleOperation>>,
+    /// How the code of the modules in the minidump differs from their binaries, if
+    /// requested with
+    /// [`ProcessorOptions::check_code_integrity`][crate::ProcessorOptions::check_code_integrity].
+    pub code_integrity: Option<CodeIntegrity>,
+    /// The command line of the process, if it was requested with
+    /// [`ProcessorOptions::command_line_redactor`][crate::ProcessorOptions::command_line_redactor].
+    pub command_line: Option<Vec<String>>,
     /// The modules that were loaded into the process represented by the
     /// `ProcessState`.
     pub modules: MinidumpModuleList,
@@ -541,6 +1036,13 @@ impl ProcessState {
                 lsb.id, lsb.release, lsb.codename, lsb.description
             )?;
         }
+        if let Some(hwcap) = self.linux_auxv.as_ref().and_then(|auxv| auxv.hwcap) {
+            write!(f, "CPU hwcap: {hwcap:#x}")?;
+            if let Some(hwcap2) = self.linux_auxv.as_ref().and_then(|auxv| auxv.hwcap2) {
+                write!(f, " hwcap2: {hwcap2:#x}")?;
+            }
+            writeln!(f)?;
+        }
         writeln!(f)?;
 
         if let Some(ref crash_info) = self.exception_info {
@@ -616,6 +1118,26 @@ impl ProcessState {
         if let Some(ref assertion) = self.assertion {
             writeln!(f, "Assertion: {assertion}")?;
         }
+        if self.oom_assessment.likelihood != OomLikelihood::Unlikely {
+            writeln!(
+                f,
+                "Out of memory: {}",
+                self.oom_assessment.likelihood.as_str()
+            )?;
+            for evidence in &self.oom_assessment.evidence {
+                writeln!(f, "  {evidence}")?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(integrity) = &self.code_integrity {
+            if !integrity.modifications.is_empty() {
+                writeln!(f, "Modified code:")?;
+                for modification in &integrity.modifications {
+                    writeln!(f, "  {modification}")?;
+                }
+                writeln!(f)?;
+            }
+        }
         if let Some(ref info) = self.mac_crash_info {
             writeln!(f, "Mac Crash Info:")?;
             for (idx, record) in info.iter().enumerate() {
@@ -656,6 +1178,14 @@ impl ProcessState {
             )?;
             writeln!(f)?;
         }
+        if let Some(ref comment) = self.comment {
+            writeln!(f, "Comment: {}", comment.comment())?;
+            writeln!(f)?;
+        }
+        if let Some(ref command_line) = self.command_line {
+            writeln!(f, "Command line: {}", command_line.join(" "))?;
+            writeln!(f)?;
+        }
         if let Some(ref time) = self.process_create_time {
             let uptime = self.time.duration_since(*time).unwrap_or_default();
             writeln!(f, "Process uptime: {} seconds", uptime.as_secs())?;
@@ -664,6 +1194,40 @@ impl ProcessState {
         }
         writeln!(f)?;
 
+        if let Some(ref memory_stats) = self.memory_stats {
+            writeln!(f, "Memory stats:")?;
+            if let Some(ref system) = memory_stats.system {
+                writeln!(
+                    f,
+                    "  system commit charge: {} of {} bytes (peak {})",
+                    system.commit_charge, system.commit_limit, system.peak_commit_charge
+                )?;
+                writeln!(
+                    f,
+                    "  system physical memory: {} of {} bytes available",
+                    system.available_physical_memory, system.total_physical_memory
+                )?;
+            }
+            if let Some(ref process) = memory_stats.process {
+                let bytes = |val: Option<u64>| {
+                    val.map(|val| format!("{val} bytes"))
+                        .unwrap_or_else(|| String::from("unknown"))
+                };
+                writeln!(
+                    f,
+                    "  process private bytes: {}",
+                    bytes(process.private_bytes)
+                )?;
+                writeln!(
+                    f,
+                    "  process page file usage: {} (peak {})",
+                    bytes(process.page_file_usage),
+                    bytes(process.peak_page_file_usage)
+                )?;
+            }
+            writeln!(f)?;
+        }
+
         if let Some(requesting_thread) = self.requesting_thread {
             let stack = &self.threads[requesting_thread];
             writeln!(
@@ -790,6 +1354,28 @@ Unknown streams encountered:
     ///
     /// See the top level documentation of this library for the stable JSON schema.
     pub fn print_json<T: Write>(&self, f: &mut T, pretty: bool) -> Result<(), serde_json::Error> {
+        self.print_json_internal(f, pretty, false)
+    }
+
+    /// **\[UNSTABLE\]** Like [`ProcessState::print_json`], but also reports the
+    /// floating-point and SIMD registers of the crashing frame.
+    ///
+    /// These are left out by default because they're bulky (x87, SSE and NEON
+    /// registers), see [`MinidumpContext::float_registers`].
+    pub fn print_json_with_float_registers<T: Write>(
+        &self,
+        f: &mut T,
+        pretty: bool,
+    ) -> Result<(), serde_json::Error> {
+        self.print_json_internal(f, pretty, true)
+    }
+
+    fn print_json_internal<T: Write>(
+        &self,
+        f: &mut T,
+        pretty: bool,
+        float_registers: bool,
+    ) -> Result<(), serde_json::Error> {
         // See ../json-schema.md for details on this format.
 
         self.set_print_context();
@@ -800,6 +1386,16 @@ Unknown streams encountered:
             Address(address).to_string()
         }
 
+        fn hex_bytes(bytes: &[u8]) -> String {
+            bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+        }
+
+        fn unix_secs(time: SystemTime) -> u64 {
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        }
+
         let mut output = json!({
             // Currently unused, we either produce no output or successful output.
             // OK | ERROR_* | SYMBOL_SUPPLIER_INTERRUPTED
@@ -870,6 +1466,21 @@ Unknown streams encountered:
                 })).collect::<Vec<_>>()
             })),
             // optional
+            "linux_auxv": self.linux_auxv.as_ref().map(|auxv| json!({
+                // optional, print as hex string
+                "hwcap": auxv.hwcap.map(|num| format!("{num:#x}")),
+                // optional, print as hex string
+                "hwcap2": auxv.hwcap2.map(|num| format!("{num:#x}")),
+                // optional
+                "platform": auxv.platform,
+                // optional
+                "execfn": auxv.execfn,
+                // optional
+                "vdso_base": auxv.vdso_base.map(json_hex),
+                // optional
+                "interpreter_base": auxv.interpreter_base.map(json_hex),
+            })),
+            // optional
             "mac_crash_info": self.mac_crash_info.as_ref().map(|info| json!({
                 "num_records": info.len(),
                 // All of these fields are optional
@@ -887,6 +1498,40 @@ Unknown streams encountered:
             })),
             // optional
             "mac_boot_args": self.mac_boot_args.as_ref().map(|info| info.bootargs.as_ref()),
+            // optional
+            "comment": self.comment.as_ref().map(|comment| comment.comment()),
+            // optional
+            "command_line": self.command_line,
+            // optional
+            "memory_stats": self.memory_stats.as_ref().map(|stats| json!({
+                // optional
+                "system": stats.system.as_ref().map(|system| json!({
+                    "commit_charge": system.commit_charge,
+                    "commit_limit": system.commit_limit,
+                    "peak_commit_charge": system.peak_commit_charge,
+                    "total_physical_memory": system.total_physical_memory,
+                    "available_physical_memory": system.available_physical_memory,
+                })),
+                // optional
+                "process": stats.process.as_ref().map(|process| json!({
+                    // optional
+                    "private_bytes": process.private_bytes,
+                    // optional
+                    "page_file_usage": process.page_file_usage,
+                    // optional
+                    "peak_page_file_usage": process.peak_page_file_usage,
+                    // optional
+                    "working_set_size": process.working_set_size,
+                    // optional
+                    "peak_working_set_size": process.peak_working_set_size,
+                    // optional
+                    "virtual_size": process.virtual_size,
+                })),
+            })),
+            "oom": json!({
+                "likelihood": self.oom_assessment.likelihood.as_str(),
+                "evidence": self.oom_assessment.evidence.iter().map(OomEvidence::to_json).collect::<Vec<_>>(),
+            }),
 
             // the first module is always the main one
             "main_module": 0,
@@ -938,6 +1583,19 @@ Unknown streams encountered:
                 "last_error_value": thread.last_error_value.map(|error| error.to_string()),
                 // optional
                 "thread_name": thread.thread_name,
+                // optional, from the ThreadInfoListStream
+                "thread_info": thread.thread_info.as_ref().map(|info| json!({
+                    // optional, seconds since the unix epoch
+                    "create_time": info.create_time().map(unix_secs),
+                    // optional, seconds since the unix epoch
+                    "exit_time": info.exit_time().map(unix_secs),
+                    "exited": info.has_exited(),
+                    "exit_status": json_hex(info.raw.exit_status as u64),
+                    "kernel_time_ms": info.kernel_time().as_millis() as u64,
+                    "user_time_ms": info.user_time().as_millis() as u64,
+                    "start_address": json_hex(info.raw.start_address),
+                    "affinity": json_hex(info.raw.affinity),
+                })),
                 "frames": thread.frames.iter().enumerate().map(|(idx, frame)| json!({
                     "frame": idx,
                     // optional
@@ -999,6 +1657,65 @@ Unknown streams encountered:
                 "type_name": handle.type_name,
                 "object_name": handle.object_name
             })).collect::<Vec<_>>()),
+            // optional, from the HandleOperationListStream
+            "handle_operations": self.handle_operations.as_ref().map(|operations| operations.iter().map(|operation| json!({
+                "handle": operation.handle,
+                "thread_id": json_hex(operation.thread_id as u64),
+                // open | close | bad_ref | unknown
+                "operation": operation.operation_str(),
+                "frames": operation.backtrace.iter().enumerate().map(|(idx, frame)| json!({
+                    "frame": idx,
+                    // optional
+                    "module": frame.module.as_ref().map(|module| basename(&module.name)),
+                    // optional
+                    "function": frame.function_name,
+                    // optional
+                    "file": frame.source_file_name,
+                    // optional
+                    "line": frame.source_line,
+                    "offset": json_hex(frame.instruction),
+                    // optional
+                    "module_offset": frame
+                        .module
+                        .as_ref()
+                        .map(|module| frame.instruction - module.raw.base_of_image)
+                        .map(json_hex),
+                    // optional
+                    "function_offset": frame
+                        .function_base
+                        .map(|func_base| frame.instruction - func_base)
+                        .map(json_hex),
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>()),
+            // optional, if the code integrity check was requested
+            "code_integrity": self.code_integrity.as_ref().map(|integrity| json!({
+                "checked_modules": integrity.checked_modules,
+                "modifications": integrity.modifications.iter().map(|modification| {
+                    let mut json = json!({
+                        // bit_flip | inline_hook | patch
+                        "type": modification.kind.as_str(),
+                        "module": modification.module,
+                        "section": modification.section,
+                        "address": json_hex(modification.address),
+                        "size": modification.size,
+                        "original": hex_bytes(&modification.original),
+                        "modified": hex_bytes(&modification.modified),
+                        // optional
+                        "function": modification.function_name,
+                        // optional
+                        "function_offset": modification
+                            .function_base
+                            .map(|base| modification.address - base)
+                            .map(json_hex),
+                    });
+                    if let CodeModificationKind::InlineHook { target, target_module } = &modification.kind {
+                        json["target"] = json_hex(*target).into();
+                        // optional
+                        json["target_module"] = target_module.clone().into();
+                    }
+                    json
+                }).collect::<Vec<_>>(),
+            })),
         });
 
         if let Some(requesting_thread) = self.requesting_thread {
@@ -1027,6 +1744,24 @@ Unknown streams encountered:
                 let frame = frames[0].as_object_mut().unwrap();
 
                 frame.insert(String::from("registers"), registers);
+                if let Some(xstate) = &f.context.xstate {
+                    let extended_registers: serde_json::Map<_, _> = xstate
+                        .registers()
+                        .map(|(name, value)| (name, json!(value)))
+                        .collect();
+                    frame.insert(
+                        String::from("extended_registers"),
+                        json!(extended_registers),
+                    );
+                }
+                if float_registers {
+                    let float_registers: serde_json::Map<_, _> = f
+                        .context
+                        .float_registers()
+                        .map(|reg| (String::from(reg.name), json!(reg.format())))
+                        .collect();
+                    frame.insert(String::from("float_registers"), json!(float_registers));
+                }
                 thread_obj.insert(String::from("threads_index"), json!(requesting_thread));
 
                 output
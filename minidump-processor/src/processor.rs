@@ -2,6 +2,8 @@
 // file at the top-level directory of this distribution.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt::Debug;
 use std::ops::{Deref, RangeInclusive};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -10,12 +12,18 @@ use std::time::{Duration, SystemTime};
 use minidump::system_info::PointerWidth;
 use minidump::{self, *};
 use minidump_unwind::{
-    walk_stack, CallStack, CallStackInfo, FrameTrust, StackFrame, SymbolProvider, SystemInfo,
+    code_memory_list, walk_stack, CallStack, CallStackInfo, DynamicFunctionTables, FrameTrust,
+    ModuleCode, ModuleCodeCache, StackFrame, SymbolProvider, SystemInfo, WalkStackOptions,
 };
 
 use crate::op_analysis::MemoryAccess;
-use crate::process_state::{LinuxStandardBase, ProcessState};
-use crate::{arg_recovery, evil, AdjustedAddress, LinuxProcLimits, LinuxProcStatus};
+use crate::process_state::{
+    HandleOperation, HandleTraceFrame, LinuxAuxv, LinuxStandardBase, MemoryStats, OomAssessment,
+    ProcessState,
+};
+use crate::{
+    arg_recovery, code_integrity, evil, oom, AdjustedAddress, LinuxProcLimits, LinuxProcStatus,
+};
 
 /// Configuration of the processor's exact behaviour.
 ///
@@ -69,6 +77,108 @@ pub struct ProcessorOptions<'a> {
     ///
     /// See [`PendingProcessorStats`] and [`PendingProcessorStatSubscriptions`].
     pub stat_reporter: Option<&'a PendingProcessorStats>,
+
+    /// **\[UNSTABLE\]** Opt-in hook for reporting the command line of the crashed process.
+    ///
+    /// Command lines routinely contain things like urls, file paths, and tokens, so
+    /// they're only included in the [`ProcessState`] when this is set. The hook gets
+    /// the raw arguments and decides what actually gets reported, see
+    /// [`CommandLineRedactor`].
+    ///
+    /// Currently this only works for Linux minidumps (the `LinuxCmdLine` stream).
+    pub command_line_redactor: Option<&'a dyn CommandLineRedactor>,
+
+    /// **\[UNSTABLE\]** Whether to read the code of modules from their binaries.
+    ///
+    /// Most minidumps don't contain the code of the modules, so the crashing instruction
+    /// can't be disassembled, and return addresses found by stack scanning can't be checked
+    /// against the calls in front of them. With this set, the binaries are fetched with
+    /// [`SymbolProvider::get_file_path`] and their code is used wherever the minidump
+    /// doesn't have any, see [`ModuleCode`]. Only the binaries of the crashing module and
+    /// the modules stack scanning lands in are read.
+    pub code_from_binaries: bool,
+
+    /// **\[UNSTABLE\]** Whether to compare the code of modules in the minidump with their
+    /// binaries.
+    ///
+    /// Differences are reported in [`ProcessState::code_integrity`]: inline hooks (usually
+    /// from software injected into the process), other patches, and bit flips. The binaries
+    /// are fetched like for [`code_from_binaries`][Self::code_from_binaries]. Only the code
+    /// that's in the minidump can be checked, which is all of it in full-memory dumps but
+    /// usually very little otherwise.
+    pub check_code_integrity: bool,
+}
+
+/// A hook for redacting the command line of the crashed process.
+///
+/// Pass this into [`ProcessorOptions::command_line_redactor`] to use it.
+///
+/// Implementations are free to drop, rewrite, or add arguments. Whatever
+/// [`CommandLineRedactor::redact`] returns is what ends up in
+/// [`ProcessState::command_line`].
+pub trait CommandLineRedactor: Debug + Sync {
+    /// Redact the given arguments. The first argument is usually the path
+    /// of the executable (argv\[0\]).
+    fn redact(&self, args: Vec<String>) -> Vec<String>;
+}
+
+/// A [`CommandLineRedactor`] that reports the command line as-is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRedaction;
+
+impl CommandLineRedactor for NoRedaction {
+    fn redact(&self, args: Vec<String>) -> Vec<String> {
+        args
+    }
+}
+
+/// A [`CommandLineRedactor`] that only keeps the "shape" of the command line.
+///
+/// * argv\[0\] is kept as-is
+/// * switches (`-x`, `--switch`) are kept, but their values (`--switch=value`)
+///   are replaced with `<redacted>`, unless the switch is listed in `keep_values`
+/// * everything else is replaced with `<redacted>`
+///
+/// The default keeps the value of `--type`, which multi-process applications like
+/// Chromium and Electron use to distinguish child processes (`--type=renderer`)
+/// from the main process.
+#[derive(Debug, Clone)]
+pub struct RedactArguments {
+    /// The names of the switches (without leading dashes) whose values are kept.
+    pub keep_values: Vec<String>,
+}
+
+impl Default for RedactArguments {
+    fn default() -> Self {
+        Self {
+            keep_values: vec![String::from("type")],
+        }
+    }
+}
+
+impl CommandLineRedactor for RedactArguments {
+    fn redact(&self, args: Vec<String>) -> Vec<String> {
+        const REDACTED: &str = "<redacted>";
+        let mut args = args.into_iter();
+        let program = args.next();
+        let rest = args.map(|arg| {
+            if !arg.starts_with('-') {
+                return String::from(REDACTED);
+            }
+            match arg.split_once('=') {
+                Some((switch, _))
+                    if !self
+                        .keep_values
+                        .iter()
+                        .any(|keep| keep == switch.trim_start_matches('-')) =>
+                {
+                    format!("{switch}={REDACTED}")
+                }
+                _ => arg,
+            }
+        });
+        program.into_iter().chain(rest).collect()
+    }
 }
 
 /// A subscription to various live updates during minidump processing.
@@ -277,6 +387,9 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: false`
+    /// * `command_line_redactor: None`
+    /// * `code_from_binaries: false`
+    /// * `check_code_integrity: false`
     ///
     /// Unlike stable_all, you shouldn't expect this to change its results much.
     ///
@@ -291,6 +404,9 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: false,
             stat_reporter: None,
+            command_line_redactor: None,
+            code_from_binaries: false,
+            check_code_integrity: false,
         }
     }
 
@@ -298,6 +414,9 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: false`
+    /// * `command_line_redactor: None`
+    /// * `code_from_binaries: false`
+    /// * `check_code_integrity: false`
     ///
     /// (At this precise moment this is identical to stable_basic, but may diverge
     /// as we introduce more features.)
@@ -310,6 +429,9 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: false,
             stat_reporter: None,
+            command_line_redactor: None,
+            code_from_binaries: false,
+            check_code_integrity: false,
         }
     }
 
@@ -317,8 +439,12 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: true`
+    /// * `command_line_redactor: None`
+    /// * `code_from_binaries: true`
+    /// * `check_code_integrity: true`
     ///
-    /// (evil_json is still "disabled" because you need to give it needs a path.)
+    /// (evil_json is still "disabled" because you need to give it needs a path,
+    /// and the command line is never reported unless you pick how to redact it.)
     ///
     /// Some of this stuff can be really jank, use at your own risk!
     pub fn unstable_all() -> Self {
@@ -326,6 +452,9 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: true,
             stat_reporter: None,
+            command_line_redactor: None,
+            code_from_binaries: true,
+            check_code_integrity: true,
         }
     }
 
@@ -433,6 +562,75 @@ fn get_microcode_version(linux_cpu_info: &MinidumpLinuxCpuInfo, evil: &evil::Evi
         .and_then(|val| u64::from_str_radix(val, 16).ok())
 }
 
+/// Add the shared objects from the dynamic linker's `link_map` that are missing from `modules`.
+///
+/// The extent of a missing module is recovered from the file mappings with the same path
+/// in `maps`, so nothing can be added without them. Modules that overlap an existing
+/// module are never added, but a warning is emitted if their names don't match.
+fn add_link_map_modules<'a>(
+    modules: MinidumpModuleList,
+    link_map: impl Iterator<Item = &'a MinidumpLinkMapEntry>,
+    maps: Option<&MinidumpLinuxMaps>,
+) -> MinidumpModuleList {
+    let mut recovered = vec![];
+    for entry in link_map {
+        // The main executable has no name (and is always in the module list).
+        if entry.name.is_empty() {
+            continue;
+        }
+        if let Some(module) = modules.module_at_address(entry.addr) {
+            if module.name != entry.name {
+                tracing::warn!(
+                    "link_map entry {} at {:#x} overlaps module {}",
+                    entry.name,
+                    entry.addr,
+                    module.name
+                );
+            }
+            continue;
+        }
+
+        let range = maps.and_then(|maps| {
+            maps.iter()
+                .filter(|map| match &map.kind {
+                    MinidumpLinuxMapKind::File(path) | MinidumpLinuxMapKind::DeletedFile(path) => {
+                        path.as_bytes() == entry.name.as_bytes()
+                    }
+                    _ => false,
+                })
+                .fold(None, |range: Option<(u64, u64)>, map| {
+                    let (start, end) = range.unwrap_or((map.base_address, map.final_address));
+                    Some((start.min(map.base_address), end.max(map.final_address)))
+                })
+        });
+        let Some((start, end)) = range else {
+            continue;
+        };
+        // `final_address` is the (exclusive) end address from /proc/self/maps.
+        let Ok(size) = u32::try_from(end - start) else {
+            continue;
+        };
+        let overlaps = |module: &MinidumpModule| {
+            module.base_address() < end && start < module.base_address() + module.size()
+        };
+        if modules.iter().chain(recovered.iter()).any(overlaps) {
+            continue;
+        }
+        tracing::debug!(
+            "recovered module {} at {:#x} from the link_map",
+            entry.name,
+            start
+        );
+        recovered.push(MinidumpModule::new(start, size, &entry.name));
+    }
+
+    if recovered.is_empty() {
+        modules
+    } else {
+        MinidumpModuleList::from_modules(modules.iter().cloned().chain(recovered).collect())
+    }
+}
+
 /// Process `dump` with the given options and return a report as a `ProcessState`.
 ///
 /// See [`ProcessorOptions`][] for details on the specific features that can be
@@ -448,13 +646,21 @@ where
 {
     let info = MinidumpInfo::new(dump, options)?;
 
-    let mut exception_details = info.get_exception_details();
+    let module_code = ModuleCodeCache::default();
+    let crashing_module_code = if info.options.code_from_binaries {
+        info.load_crashing_module_code(&module_code, symbol_provider)
+            .await
+    } else {
+        None
+    };
+    let mut exception_details =
+        info.get_exception_details(&code_memory_list(crashing_module_code.as_deref()));
 
     if let Some(details) = &mut exception_details {
         info.check_for_bitflips(details);
         info.check_for_guard_pages(details);
     }
-    info.into_process_state(dump, symbol_provider, exception_details)
+    info.into_process_state(dump, symbol_provider, exception_details, &module_code)
         .await
 }
 
@@ -463,25 +669,32 @@ struct MinidumpInfo<'a> {
     evil: crate::evil::Evil,
     thread_list: MinidumpThreadList<'a>,
     thread_names: MinidumpThreadNames,
+    thread_infos: MinidumpThreadInfoList,
     dump_system_info: MinidumpSystemInfo,
     linux_standard_base: Option<LinuxStandardBase>,
     linux_proc_status: Option<LinuxProcStatus>,
     linux_proc_limits: Option<LinuxProcLimits>,
+    linux_auxv: Option<LinuxAuxv>,
     system_info: SystemInfo,
     mac_crash_info: Option<Vec<RawMacCrashInfo>>,
     mac_boot_args: Option<MinidumpMacBootargs>,
+    comment: Option<MinidumpComment>,
+    command_line: Option<Vec<String>>,
+    memory_stats: Option<MemoryStats>,
     misc_info: Option<MinidumpMiscInfo>,
     dump_thread_id: Option<u32>,
     requesting_thread_id: Option<u32>,
     modules: MinidumpModuleList,
     unloaded_modules: MinidumpUnloadedModuleList,
     memory_list: UnifiedMemoryList<'a>,
+    function_tables: Option<MinidumpFunctionTableList<'a>>,
     /*
     memory_info_list: Option<MinidumpMemoryInfoList<'a>>,
     linux_maps: Option<MinidumpLinuxMaps<'a>>,
     */
     memory_info: UnifiedMemoryInfoList<'a>,
     handle_data_stream: Option<MinidumpHandleDataStream>,
+    handle_operations: Option<MinidumpHandleOperationList>,
     exception: Option<MinidumpException<'a>>,
     //exception_details: Option<ExceptionDetails<'a>>,
 }
@@ -514,6 +727,11 @@ impl<'a> MinidumpInfo<'a> {
             .get_stream::<MinidumpThreadNames>()
             .unwrap_or_else(|_| MinidumpThreadNames::default());
 
+        // Extra thread info (creation times, start addresses...) is also optional.
+        let thread_infos = dump
+            .get_stream::<MinidumpThreadInfoList>()
+            .unwrap_or_default();
+
         // System info is required for processing.
         let dump_system_info = dump
             .get_stream::<MinidumpSystemInfo>()
@@ -562,7 +780,23 @@ impl<'a> MinidumpInfo<'a> {
 
         let mac_boot_args = dump.get_stream::<MinidumpMacBootargs>().ok();
 
+        let comment = dump.get_comment();
+
+        // The command line is only reported if the user opted in (and redacted it).
+        let command_line = options.command_line_redactor.and_then(|redactor| {
+            let cmd_line = dump.get_stream::<MinidumpLinuxCmdLine>().ok()?;
+            let args = cmd_line
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            Some(redactor.redact(args))
+        });
+
         let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+
+        let system_memory_info = dump.get_stream::<MinidumpSystemMemoryInfo>().ok();
+        let vm_counters = dump.get_stream::<MinidumpProcessVmCounters>().ok();
+        let memory_stats = MemoryStats::new(system_memory_info.as_ref(), vm_counters.as_ref());
         // If Breakpad info exists in dump, get dump and requesting thread ids.
         let breakpad_info = dump.get_stream::<MinidumpBreakpadInfo>();
         let (dump_thread_id, requesting_thread_id) = if let Ok(info) = breakpad_info {
@@ -582,11 +816,27 @@ impl<'a> MinidumpInfo<'a> {
             Err(_) => MinidumpUnloadedModuleList::new(),
         };
         let memory_list = dump.get_memory().unwrap_or_default();
+        let linux_auxv = dump
+            .get_stream::<MinidumpLinuxAuxv>()
+            .ok()
+            .map(|auxv| LinuxAuxv::new(&auxv, &memory_list));
         let memory_info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
         let linux_maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
         let memory_info =
             UnifiedMemoryInfoList::new(memory_info_list, linux_maps).unwrap_or_default();
+        // The module list of Linux minidumps is built from /proc/self/maps heuristics,
+        // which can miss libraries. Fill in the gaps with what the dynamic linker knew.
+        let modules = match dump.get_stream::<MinidumpLinuxDsoDebug>() {
+            Ok(dso_debug) => {
+                let live_link_map = dso_debug.walk_link_map(&memory_list).unwrap_or_default();
+                let link_map = dso_debug.link_map.iter().chain(live_link_map.iter());
+                add_link_map_modules(modules, link_map, memory_info.maps())
+            }
+            Err(_) => modules,
+        };
         let handle_data_stream = dump.get_stream::<MinidumpHandleDataStream>().ok();
+        let handle_operations = dump.get_stream::<MinidumpHandleOperationList>().ok();
+        let function_tables = dump.get_stream::<MinidumpFunctionTableList>().ok();
 
         // Get exception info if it exists.
         let exception = dump.get_stream::<MinidumpException>().ok();
@@ -596,41 +846,77 @@ impl<'a> MinidumpInfo<'a> {
             evil,
             thread_list,
             thread_names,
+            thread_infos,
             dump_system_info,
             linux_standard_base,
             linux_proc_status,
             linux_proc_limits,
+            linux_auxv,
             system_info,
             mac_crash_info,
             mac_boot_args,
+            comment,
+            command_line,
+            memory_stats,
             misc_info,
             dump_thread_id,
             requesting_thread_id,
             modules,
             unloaded_modules,
             memory_list,
+            function_tables,
             /*
             memory_info_list: Option<MinidumpMemoryInfoList<'a>>,
             linux_maps: Option<MinidumpLinuxMaps<'a>>,
             */
             memory_info,
             handle_data_stream,
+            handle_operations,
             exception,
             //exception_details: None,
         })
     }
 
+    /// Read the code of the module with the crashing instruction from its binary, see
+    /// [`ModuleCode`].
+    async fn load_crashing_module_code<P>(
+        &self,
+        module_code: &ModuleCodeCache,
+        symbol_provider: &P,
+    ) -> Option<Arc<ModuleCode>>
+    where
+        P: SymbolProvider + Sync,
+    {
+        let context = self
+            .exception
+            .as_ref()?
+            .context(&self.dump_system_info, self.misc_info.as_ref())?;
+        let module = self
+            .modules
+            .module_at_address(context.get_instruction_pointer())?;
+        module_code.get(module, symbol_provider).await
+    }
+
     /// Get details about the minidump exception, if available.
-    pub fn get_exception_details(&self) -> Option<ExceptionDetails<'a>> {
+    ///
+    /// `code` fills in the code the minidump doesn't contain.
+    pub fn get_exception_details(
+        &self,
+        code: &UnifiedMemoryList<'_>,
+    ) -> Option<ExceptionDetails<'a>> {
         let exception = self.exception.as_ref()?;
 
         let reason = exception.get_crash_reason(self.system_info.os, self.system_info.cpu);
         let address = exception.get_crash_address(self.system_info.os, self.system_info.cpu);
 
-        let stack_memory_ref = self
+        let memory = match self
             .thread_list
             .get_thread(exception.get_crashing_thread_id())
-            .and_then(|thread| thread.stack_memory(&self.memory_list));
+        {
+            Some(thread) => thread.stack_address_space(&self.memory_list),
+            None => MinidumpAddressSpace::from(&self.memory_list),
+        }
+        .with_memory_list(code);
 
         let context = exception.context(&self.dump_system_info, self.misc_info.as_ref());
 
@@ -639,11 +925,7 @@ impl<'a> MinidumpInfo<'a> {
 
         // If we have a context, we can attempt to analyze the crashing thread's instructions
         if let Some(context) = context.as_ref() {
-            match crate::op_analysis::analyze_thread_context(
-                context,
-                &self.memory_list,
-                stack_memory_ref,
-            ) {
+            match crate::op_analysis::analyze_thread_context(context, &memory) {
                 Ok(op_analysis) => {
                     let memory_accesses = op_analysis.memory_accesses.as_deref();
 
@@ -814,6 +1096,7 @@ impl<'a> MinidumpInfo<'a> {
         dump: &Minidump<'a, T>,
         symbol_provider: &P,
         exception_details: Option<ExceptionDetails<'a>>,
+        module_code: &ModuleCodeCache,
     ) -> Result<ProcessState, ProcessError>
     where
         T: Deref<Target = [u8]> + 'a,
@@ -874,6 +1157,7 @@ impl<'a> MinidumpInfo<'a> {
                     thread_id: id,
                     thread_name: name,
                     last_error_value: thread.last_error(self.system_info.cpu, &self.memory_list),
+                    thread_info: self.thread_infos.get_thread_info(id).cloned(),
                 }
             })
             .collect();
@@ -890,6 +1174,7 @@ impl<'a> MinidumpInfo<'a> {
             misc_info.raw.process_id().cloned()
         } else {
             self.linux_proc_status
+                .as_ref()
                 .map(|linux_proc_status| linux_proc_status.pid)
         };
 
@@ -910,8 +1195,15 @@ impl<'a> MinidumpInfo<'a> {
             system_info: self.system_info,
             linux_standard_base: self.linux_standard_base,
             linux_proc_limits: self.linux_proc_limits,
+            linux_auxv: self.linux_auxv,
             mac_crash_info: self.mac_crash_info,
             mac_boot_args: self.mac_boot_args,
+            comment: self.comment,
+            command_line: self.command_line,
+            memory_stats: self.memory_stats,
+            oom_assessment: OomAssessment::default(),
+            handle_operations: None,
+            code_integrity: None,
             threads,
             modules: self.modules,
             unloaded_modules: self.unloaded_modules,
@@ -928,10 +1220,18 @@ impl<'a> MinidumpInfo<'a> {
 
         {
             let memory_list = &self.memory_list;
+            let memory = MinidumpAddressSpace::from(memory_list);
+            let memory = &memory;
+            let function_tables = self
+                .function_tables
+                .as_ref()
+                .map(|tables| DynamicFunctionTables { tables, memory });
             let modules = &state.modules;
             let system_info = &state.system_info;
             let unloaded_modules = &state.unloaded_modules;
             let options = &self.options;
+            // The code of the modules is only used where the minidump doesn't have any.
+            let module_code = Some(module_code).filter(|_| options.code_from_binaries);
 
             futures_util::future::join_all(
                 state
@@ -940,24 +1240,15 @@ impl<'a> MinidumpInfo<'a> {
                     .zip(self.thread_list.threads.iter())
                     .enumerate()
                     .map(|(i, (stack, thread))| async move {
-                        let mut stack_memory = thread.stack_memory(memory_list);
-                        // Always choose the memory region that is referenced by the context,
-                        // as the `exception_context` may refer to a different memory region than
-                        // the `thread_context`, which in turn would fail to stack walk.
-                        let stack_ptr = stack
-                            .frames
-                            .get(0)
-                            .map(|ctx_frame| ctx_frame.context.get_stack_pointer());
-                        if let Some(stack_ptr) = stack_ptr {
-                            let contains_stack_ptr = stack_memory
-                                .as_ref()
-                                .and_then(|memory| memory.get_memory_at_address::<u64>(stack_ptr))
-                                .is_some();
-                            if !contains_stack_ptr {
-                                stack_memory =
-                                    memory_list.memory_at_address(stack_ptr).or(stack_memory);
-                            }
-                        }
+                        // This includes the whole memory list, so that the stack can be read
+                        // even when the context refers to a different memory region than the
+                        // thread's stack (as the `exception_context` may), or when the stack
+                        // spans several regions.
+                        let stack_memory = thread.stack_address_space(memory_list);
+                        let mut walk_options = WalkStackOptions::default();
+                        walk_options.stack_memory = Some(&stack_memory);
+                        walk_options.function_tables = function_tables;
+                        walk_options.module_code = module_code;
 
                         walk_stack(
                             |frame_idx: usize, frame: &StackFrame| {
@@ -966,7 +1257,7 @@ impl<'a> MinidumpInfo<'a> {
                                 }
                             },
                             stack,
-                            stack_memory,
+                            walk_options,
                             modules,
                             system_info,
                             symbol_provider,
@@ -994,7 +1285,7 @@ impl<'a> MinidumpInfo<'a> {
                         }
 
                         if options.recover_function_args {
-                            arg_recovery::fill_arguments(stack, stack_memory);
+                            arg_recovery::fill_arguments(stack, &stack_memory);
                         }
 
                         // Report the unwalked result
@@ -1008,6 +1299,33 @@ impl<'a> MinidumpInfo<'a> {
             .await
         };
 
+        if let Some(operations) = &self.handle_operations {
+            state.handle_operations = Some(
+                symbolicate_handle_operations(operations, &state.modules, symbol_provider).await,
+            );
+        }
+
+        if self.options.check_code_integrity {
+            state.code_integrity = Some(
+                code_integrity::check(
+                    module_code,
+                    &self.memory_list,
+                    &state.modules,
+                    state.system_info.cpu,
+                    symbol_provider,
+                )
+                .await,
+            );
+        }
+
+        // This needs the symbolicated frames of the crashing thread
+        state.oom_assessment = oom::assess(
+            &state,
+            &self.memory_info,
+            self.linux_proc_status.as_ref(),
+            self.misc_info.as_ref(),
+        );
+
         let symbol_stats = symbol_provider.stats();
         state.symbol_stats = symbol_stats;
 
@@ -1015,6 +1333,37 @@ impl<'a> MinidumpInfo<'a> {
     }
 }
 
+/// Symbolicate the backtraces of handle operations.
+async fn symbolicate_handle_operations<P>(
+    operations: &MinidumpHandleOperationList,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Vec<HandleOperation>
+where
+    P: SymbolProvider + Sync,
+{
+    let mut result = Vec::with_capacity(operations.operations.len());
+    for operation in operations.iter() {
+        let mut backtrace = Vec::with_capacity(operation.backtrace().len());
+        for &address in operation.backtrace() {
+            let mut frame = HandleTraceFrame::new(address);
+            if let Some(module) = modules.module_at_address(address) {
+                // Missing symbols are fine, the frame is still useful without them.
+                let _ = symbol_provider.fill_symbol(module, &mut frame).await;
+                frame.module = Some(module.clone());
+            }
+            backtrace.push(frame);
+        }
+        result.push(HandleOperation {
+            handle: operation.raw.handle,
+            thread_id: operation.raw.thread_id,
+            operation: operation.operation,
+            backtrace,
+        });
+    }
+    result
+}
+
 struct ExceptionDetails<'a> {
     info: crate::ExceptionInfo,
     context: Option<std::borrow::Cow<'a, MinidumpContext>>,
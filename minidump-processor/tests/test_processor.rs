@@ -6,9 +6,17 @@ use minidump::{
     Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpRawContext, Module,
 };
 use minidump_common::format::MemoryProtection;
-use minidump_processor::{Limit, LinuxStandardBase, ProcessState};
-use minidump_unwind::{simple_symbol_supplier, CallStackInfo, FrameTrust, Symbolizer};
+use minidump_processor::{
+    CodeModificationKind, Limit, LinuxStandardBase, NoRedaction, OomEvidence, OomLikelihood,
+    ProcessState, ProcessorOptions, RedactArguments,
+};
+use minidump_unwind::{
+    simple_symbol_supplier, string_symbol_supplier, CallStackInfo, FileError, FileKind,
+    FillSymbolError, FrameSymbolizer, FrameTrust, FrameWalker, SymbolProvider, Symbolizer,
+};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use minidump_synth::*;
 use test_assembler::*;
@@ -77,6 +85,7 @@ async fn test_processor() {
     if let MinidumpContext {
         raw: MinidumpRawContext::X86(ref raw),
         ref valid,
+        ..
     } = f0.context
     {
         assert_eq!(raw.eip, 0x0040429e);
@@ -95,6 +104,7 @@ async fn test_processor() {
     if let MinidumpContext {
         raw: MinidumpRawContext::X86(ref raw),
         ref valid,
+        ..
     } = f3.context
     {
         assert_eq!(raw.eip, 0x7c816fd7);
@@ -242,6 +252,49 @@ async fn test_linux_environ() {
     let _state = read_synth_dump(dump).await;
 }
 
+#[tokio::test]
+async fn test_linux_cmd_line() {
+    let input = b"/opt/app/app\0--type=renderer\0--token=hunter2\0--no-sandbox\0file.txt\0";
+    let dump = minimal_minidump().set_linux_cmd_line(input);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+    let process =
+        |options| minidump_processor::process_minidump_with_options(&dump, &symbolizer, options);
+
+    // The command line is opt-in.
+    let state = process(ProcessorOptions::default()).await.unwrap();
+    assert_eq!(state.command_line, None);
+
+    let mut options = ProcessorOptions::default();
+    options.command_line_redactor = Some(&NoRedaction);
+    let state = process(options).await.unwrap();
+    assert_eq!(
+        state.command_line.unwrap(),
+        [
+            "/opt/app/app",
+            "--type=renderer",
+            "--token=hunter2",
+            "--no-sandbox",
+            "file.txt"
+        ]
+    );
+
+    let redactor = RedactArguments::default();
+    let mut options = ProcessorOptions::default();
+    options.command_line_redactor = Some(&redactor);
+    let state = process(options).await.unwrap();
+    assert_eq!(
+        state.command_line.unwrap(),
+        [
+            "/opt/app/app",
+            "--type=renderer",
+            "--token=<redacted>",
+            "--no-sandbox",
+            "<redacted>"
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_linux_proc_status() {
     // Whitespace intentionally wonky to test robustness
@@ -306,6 +359,386 @@ Max realtime timeout      unlimited            unlimited            us
     }
 }
 
+#[tokio::test]
+async fn test_thread_info() {
+    let info = ThreadInfo::new(
+        Endian::Little,
+        0x1234,
+        0,
+        // 2021-01-01T00:00:00Z as a FILETIME.
+        132_539_328_000_000_000,
+        0,
+        20_000_000,
+        30_000_000,
+        0xabcd0000,
+    );
+    let dump = minimal_minidump().add_thread_info(info);
+    let state = read_synth_dump(dump).await;
+    let info = state.threads[0]
+        .thread_info
+        .as_ref()
+        .expect("thread info should be present");
+    assert_eq!(info.raw.start_address, 0xabcd0000);
+    assert_eq!(info.kernel_time().as_secs(), 2);
+    assert_eq!(info.user_time().as_secs(), 3);
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let thread_info = &json["threads"][0]["thread_info"];
+    assert_eq!(thread_info["create_time"], 1_609_459_200);
+    assert_eq!(thread_info["exit_time"], serde_json::Value::Null);
+    assert_eq!(thread_info["kernel_time_ms"], 2000);
+    assert_eq!(thread_info["start_address"], "0xabcd0000");
+}
+
+#[tokio::test]
+async fn test_comment() {
+    let dump = minimal_minidump().add_stream(SimpleStream {
+        stream_type: minidump_common::format::MINIDUMP_STREAM_TYPE::CommentStreamA as u32,
+        section: Section::new().append_bytes(b"written by the test suite\0"),
+    });
+    let state = read_synth_dump(dump).await;
+    assert_eq!(
+        state.comment.as_ref().map(|comment| comment.comment()),
+        Some("written by the test suite")
+    );
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["comment"], "written by the test suite");
+}
+
+#[tokio::test]
+async fn test_memory_stats() {
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.memory_stats, None);
+
+    let mut system_memory_info = SystemMemoryInfo::new(Endian::Little);
+    system_memory_info.number_of_physical_pages = 0x1000;
+    system_memory_info.basic_perf_info = Some(SystemMemoryFieldsCommit {
+        available_pages: 0x10,
+        committed_pages: 0x1800,
+        commit_limit: 0x2000,
+        peak_commitment: 0x1900,
+    });
+    let mut vm_counters = ProcessVmCounters::new(Endian::Little);
+    vm_counters.counters = Some(VmCountersFields {
+        pagefile_usage: 0x100_0000,
+        peak_pagefile_usage: 0x200_0000,
+        ..Default::default()
+    });
+    vm_counters.private_usage = Some(0x80_0000);
+    let dump = minimal_minidump()
+        .add_stream(system_memory_info)
+        .add_stream(vm_counters);
+    let state = read_synth_dump(dump).await;
+
+    let stats = state.memory_stats.as_ref().unwrap();
+    let system = stats.system.as_ref().unwrap();
+    assert_eq!(system.commit_charge, 0x180_0000);
+    assert_eq!(system.commit_limit, 0x200_0000);
+    assert_eq!(system.peak_commit_charge, 0x190_0000);
+    assert_eq!(system.total_physical_memory, 0x100_0000);
+    assert_eq!(system.available_physical_memory, 0x1_0000);
+    let process = stats.process.as_ref().unwrap();
+    assert_eq!(process.private_bytes, Some(0x80_0000));
+    assert_eq!(process.page_file_usage, Some(0x100_0000));
+    assert_eq!(process.peak_page_file_usage, Some(0x200_0000));
+    assert_eq!(process.virtual_size, None);
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["memory_stats"]["system"]["commit_charge"], 0x180_0000);
+    assert_eq!(json["memory_stats"]["process"]["private_bytes"], 0x80_0000);
+    assert_eq!(
+        json["memory_stats"]["process"]["page_file_usage"],
+        0x100_0000
+    );
+    assert!(json["memory_stats"]["process"]["virtual_size"].is_null());
+}
+
+#[tokio::test]
+async fn test_oom_assessment() {
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Unlikely);
+    assert!(state.oom_assessment.evidence.is_empty());
+
+    // The minimal minidump is for a 32-bit x86 process, which only has 1MB
+    // of address space left here.
+    let maps = b"\
+00001000-00100000 r-xp 00000000 08:01 1 /usr/bin/app
+00200000-fff00000 rw-p 00000000 00:00 0
+";
+    let dump = minimal_minidump().set_linux_maps(maps);
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Possible);
+    assert_eq!(
+        state.oom_assessment.evidence,
+        vec![OomEvidence::AddressSpaceExhausted {
+            largest_free_block: 0x10_0000,
+            total_free: 0x10_0000,
+        }]
+    );
+
+    // Being close to RLIMIT_AS on top of that makes it likely.
+    let proc_status = b"Pid:\t1234\nVmSize:\t 3900 kB\nVmRSS:\t 1000 kB\n";
+    let proc_limits = b"\
+Limit                     Soft Limit           Hard Limit           Units
+Max address space         4096000              unlimited            bytes
+";
+    let dump = minimal_minidump()
+        .set_linux_maps(maps)
+        .set_linux_proc_status(proc_status)
+        .set_linux_proc_limits(proc_limits);
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Likely);
+    assert_eq!(
+        state.oom_assessment.evidence[1],
+        OomEvidence::AddressSpaceLimit {
+            vm_size: Some(3900 * 1024),
+            vm_rss: Some(1000 * 1024),
+            limit: 4096000,
+        }
+    );
+
+    // A nearly exhausted commit limit is only circumstantial, another process
+    // may well be the one using up the memory.
+    let mut system_memory_info = SystemMemoryInfo::new(Endian::Little);
+    system_memory_info.basic_perf_info = Some(SystemMemoryFieldsCommit {
+        available_pages: 0x10,
+        committed_pages: 0x1ff0,
+        commit_limit: 0x2000,
+        peak_commitment: 0x1ff0,
+    });
+    let dump = minimal_minidump().add_stream(system_memory_info);
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Possible);
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["oom"]["likelihood"], "possible");
+    assert_eq!(json["oom"]["evidence"][0]["type"], "commit_limit");
+    assert_eq!(json["oom"]["evidence"][0]["commit_charge"], 0x1ff_0000);
+
+    // So is a long uptime, which is when leaks add up. The synthetic minidump
+    // was written at 1262805309.
+    let uptime_dump = |uptime: u32| {
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.process_times = Some(MiscFieldsProcessTimes {
+            process_create_time: 1262805309 - uptime,
+            process_user_time: 0,
+            process_kernel_time: 0,
+        });
+        minimal_minidump().add_stream(misc)
+    };
+    let state = read_synth_dump(uptime_dump(60 * 60)).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Unlikely);
+    let state = read_synth_dump(uptime_dump(8 * 24 * 60 * 60)).await;
+    assert_eq!(state.oom_assessment.likelihood, OomLikelihood::Possible);
+    assert_eq!(
+        state.oom_assessment.evidence,
+        vec![OomEvidence::LongUptime {
+            uptime: 8 * 24 * 60 * 60
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_handle_operations() {
+    use minidump_common::format::eHANDLE_TRACE_OPERATIONS;
+
+    let name = DumpString::new("C:\\handles.dll", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x1000_0000, 0x1_0000, &name, 0, 0, None);
+    let open = HandleOperation::new(
+        Endian::Little,
+        0x44,
+        0x1234,
+        eHANDLE_TRACE_OPERATIONS::OperationDbOPEN,
+        &[0x1000_1005, 0x2000_0000],
+    );
+    let dump = minimal_minidump()
+        .add_module(module)
+        .add(name)
+        .add_handle_operation(open);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let mut symbols = std::collections::HashMap::new();
+    symbols.insert(
+        String::from("C:\\handles.dll"),
+        String::from(
+            "MODULE windows x86 0123456789ABCDEF0123456789ABCDEF0 handles.pdb\n\
+             FILE 0 handles.cpp\n\
+             FUNC 1000 10 0 OpenTheHandle\n\
+             1000 10 42 0\n",
+        ),
+    );
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+
+    let operations = state.handle_operations.as_ref().unwrap();
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0].handle, 0x44);
+    assert_eq!(operations[0].thread_id, 0x1234);
+    assert_eq!(operations[0].operation_str(), "open");
+    let frames = &operations[0].backtrace;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].instruction, 0x1000_1005);
+    assert_eq!(frames[0].function_name.as_deref(), Some("OpenTheHandle"));
+    assert_eq!(frames[0].source_file_name.as_deref(), Some("handles.cpp"));
+    assert_eq!(frames[0].source_line, Some(42));
+    // Not in any module
+    assert!(frames[1].module.is_none());
+    assert!(frames[1].function_name.is_none());
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let frame = &json["handle_operations"][0]["frames"][0];
+    assert_eq!(json["handle_operations"][0]["operation"], "open");
+    assert_eq!(frame["module"], "handles.dll");
+    assert_eq!(frame["function"], "OpenTheHandle");
+    assert_eq!(frame["module_offset"], "0x00001005");
+    assert_eq!(frame["function_offset"], "0x00000005");
+}
+
+#[tokio::test]
+async fn test_linux_auxv() {
+    use minidump_common::format::{AuxvType, MINIDUMP_STREAM_TYPE};
+
+    // The minimal minidump is for a 32-bit x86 process.
+    let auxv = Section::with_endian(Endian::Little)
+        .D32(AuxvType::AT_HWCAP as u32)
+        .D32(0xbfebfbff)
+        .D32(AuxvType::AT_SYSINFO_EHDR as u32)
+        .D32(0xf7fc_1000)
+        .D32(AuxvType::AT_NULL as u32)
+        .D32(0);
+    let dump = minimal_minidump().add_stream(SimpleStream {
+        stream_type: MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+        section: auxv,
+    });
+    let state = read_synth_dump(dump).await;
+    let auxv = state.linux_auxv.as_ref().expect("auxv should be present");
+    assert_eq!(auxv.hwcap, Some(0xbfebfbff));
+    assert_eq!(auxv.hwcap2, None);
+    assert_eq!(auxv.vdso_base, Some(0xf7fc_1000));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["linux_auxv"]["hwcap"], "0xbfebfbff");
+    assert_eq!(json["linux_auxv"]["vdso_base"], "0xf7fc1000");
+}
+
+#[tokio::test]
+async fn test_linux_dso_debug_modules() {
+    use minidump_common::format::MINIDUMP_STREAM_TYPE;
+
+    let maps = b"\
+b7000000-b7001000 r--p 00000000 08:01 1234 /usr/lib/libplugin.so
+b7001000-b7004000 r-xp 00001000 08:01 1234 /usr/lib/libplugin.so
+b7004000-b7005000 rw-p 00004000 08:01 1234 /usr/lib/libplugin.so
+b7100000-b7101000 r-xp 00000000 08:01 5678 /usr/lib/libother.so
+";
+    let plugin = DumpString::new("/usr/lib/libplugin.so", Endian::Little);
+    // Not in the maps, so its extent can't be recovered.
+    let unmapped = DumpString::new("/usr/lib/libunmapped.so", Endian::Little);
+    let link_map = Section::with_endian(Endian::Little)
+        .D32(0)
+        .D32(0)
+        .D32(0x0804_f000)
+        .D32(0xb700_0000)
+        .D32(plugin.file_offset())
+        .D32(0xb700_4000)
+        .D32(0xb720_0000)
+        .D32(unmapped.file_offset())
+        .D32(0xb720_4000);
+    let dso_debug = Section::with_endian(Endian::Little)
+        .D32(1)
+        .D32(link_map.file_offset())
+        .D32(3)
+        .D32(0)
+        .D32(0)
+        .D32(0);
+
+    let dump = minimal_minidump()
+        .set_linux_maps(maps)
+        .add(link_map)
+        .add(plugin)
+        .add(unmapped)
+        .add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+            section: dso_debug,
+        });
+    let state = read_synth_dump(dump).await;
+    let modules = state.modules.iter().collect::<Vec<_>>();
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules[0].code_file(), "/usr/lib/libplugin.so");
+    assert_eq!(modules[0].base_address(), 0xb700_0000);
+    assert_eq!(modules[0].size(), 0x5000);
+    assert_eq!(
+        state
+            .modules
+            .module_at_address(0xb700_2345)
+            .map(|module| module.code_file()),
+        Some("/usr/lib/libplugin.so".into())
+    );
+}
+
+#[tokio::test]
+async fn test_elf_core() {
+    use minidump::elf_core::ElfCore;
+
+    let stack = Section::with_endian(Endian::Little);
+    stack.start().set_const(0x7ffe_0000);
+    let stack = stack.D64(0).D64(0x4000_0900).append_repeated(0, 0x100);
+    let core = SynthElfCore::new(EM_X86_64)
+        .add_thread(1234, 11, amd64_user_regs(0x4000_0810, 0x7ffe_0008, 0))
+        .add_psinfo(1234, "crasher")
+        .add_siginfo(11, 1, 0xdead_beef)
+        .add_files(&[(0x4000_0000, 0x4000_2000, 0, "/usr/lib/libfoo.so")])
+        .add_memory(
+            0x2000,
+            PF_R | PF_X,
+            elf_headers_with_build_id(0x4000_0000, &[1, 2, 3, 4]),
+        )
+        .add_memory(0x1000, PF_R | PF_W, stack)
+        .finish()
+        .unwrap();
+
+    let core = ElfCore::read(&core[..]).unwrap();
+    let dump = core.to_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(state.system_info.os, Os::Linux);
+    assert_eq!(state.system_info.cpu, Cpu::X86_64);
+    assert_eq!(state.process_id, Some(1234));
+    let exception_info = state.exception_info.as_ref().unwrap();
+    assert_eq!(exception_info.address.0, 0xdead_beef);
+    assert_eq!(state.requesting_thread, Some(0));
+
+    let thread = &state.threads[0];
+    assert_eq!(thread.thread_id, 1234);
+    assert_eq!(thread.frames[0].instruction, 0x4000_0810);
+    assert_eq!(
+        thread.frames[0].module.as_ref().unwrap().code_file(),
+        "/usr/lib/libfoo.so"
+    );
+    assert_eq!(thread.frames[1].instruction, 0x4000_08ff);
+}
+
 #[tokio::test]
 async fn test_no_frames() {
     let context = minidump_synth::x86_context(Endian::Little, 0, 0);
@@ -334,6 +767,110 @@ async fn test_no_frames() {
     state.print_json(&mut std::io::sink(), true).unwrap();
 }
 
+#[tokio::test]
+async fn test_read_lazy() {
+    let dump = read_test_minidump().unwrap();
+    let file = std::fs::File::open(locate_testdata().join("test.dmp")).unwrap();
+    let lazy_dump = Minidump::read_lazy(std::io::BufReader::new(file)).unwrap();
+
+    let mut expected = vec![];
+    let mut actual = vec![];
+    let provider = Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()]));
+    minidump_processor::process_minidump(&dump, &provider)
+        .await
+        .unwrap()
+        .print_json(&mut expected, true)
+        .unwrap();
+    minidump_processor::process_minidump(&lazy_dump, &provider)
+        .await
+        .unwrap()
+        .print_json(&mut actual, true)
+        .unwrap();
+    assert_eq!(String::from_utf8(actual), String::from_utf8(expected));
+}
+
+#[tokio::test]
+async fn test_read_lazy_full_memory() {
+    // Like in full-memory dumps, the thread's stack descriptor is empty and the stack is
+    // only stored with the rest of the memory at the end of the file, which is read as
+    // it's used.
+    let context = minidump_synth::x86_context(Endian::Little, 0x4000_1000, 0x7000);
+    let stack_descriptor = Memory::with_section(Section::with_endian(Endian::Little), 0x7000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x7000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack_descriptor, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add(stack_descriptor)
+        .add_memory64(stack);
+    let bytes = dump.finish().unwrap();
+    let dump = Minidump::read(bytes.clone()).unwrap();
+    let lazy_dump = Minidump::read_lazy(std::io::Cursor::new(bytes)).unwrap();
+
+    let provider = Symbolizer::new(simple_symbol_supplier(vec![]));
+    let expected = minidump_processor::process_minidump(&dump, &provider)
+        .await
+        .unwrap();
+    let actual = minidump_processor::process_minidump(&lazy_dump, &provider)
+        .await
+        .unwrap();
+    assert_eq!(actual.threads.len(), 1);
+    assert_eq!(
+        actual.threads[0].frames[0]
+            .context
+            .get_instruction_pointer(),
+        0x4000_1000
+    );
+    let (mut expected_json, mut actual_json) = (vec![], vec![]);
+    expected.print_json(&mut expected_json, true).unwrap();
+    actual.print_json(&mut actual_json, true).unwrap();
+    assert_eq!(
+        String::from_utf8(actual_json),
+        String::from_utf8(expected_json)
+    );
+}
+
+#[tokio::test]
+async fn test_stack_spanning_memory_regions() {
+    // Full-memory dumps split the stack into several adjacent regions, and the return
+    // address that stack scanning finds is in the second one.
+    let context = minidump_synth::x86_context(Endian::Little, 0x4000_1000, 0x7000);
+    let stack_descriptor = Memory::with_section(Section::with_endian(Endian::Little), 0x7000);
+    let stack_start = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x10),
+        0x7000,
+    );
+    let stack_end = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0x4000_2000)
+            .append_repeated(0, 0x10),
+        0x7010,
+    );
+    let name = DumpString::new("C:\\module.dll", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x4000_0000, 0x1_0000, &name, 0, 0, None);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack_descriptor, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add(stack_descriptor)
+        .add_memory64(stack_start)
+        .add_memory64(stack_end);
+
+    let state = read_synth_dump(dump).await;
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].instruction, 0x4000_1fff);
+    assert_eq!(frames[1].trust, FrameTrust::Scan);
+}
+
 #[tokio::test]
 async fn test_bit_flip() {
     let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
@@ -434,6 +971,207 @@ async fn test_bit_flip_arm64() {
         .is_empty());
 }
 
+#[tokio::test]
+async fn test_function_table_unwinding() {
+    // A thread crashed in JIT code, which can only be unwound using the
+    // function table that the JIT registered for it.
+    let jit_base = 0x7ff0_0000_0000;
+    let context = minidump_synth::amd64_context(Endian::Little, jit_base + 0x1050, 0x80000);
+    let unwind_info = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D8(1) // version 1, no flags
+            .D8(6) // size of prolog
+            .D8(3) // count of codes
+            .D8(0) // no frame register
+            .D16(0x3206) // UWOP_ALLOC_SMALL 0x20
+            .D16(0x3002) // UWOP_PUSH_NONVOL rbx
+            .D16(0x5001) // UWOP_PUSH_NONVOL rbp
+            .D16(0), // padding
+        jit_base,
+    );
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x20) // locals
+            .D64(0x1111) // saved rbx
+            .D64(0x2222) // saved rbp
+            .D64(0x1000_0105), // return address
+        0x80000,
+    );
+    let function_tables = FunctionTableList::new(Endian::Little).add_table(FunctionTable {
+        minimum_address: jit_base + 0x1000,
+        maximum_address: jit_base + 0x1100,
+        base_address: jit_base,
+        functions: vec![(0x1000, 0x1100, 0)],
+    });
+    let name = DumpString::new("C:\\host.dll", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x1000_0000, 0x1_0000, &name, 0, 0, None);
+
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add_memory(stack)
+        .add_memory(unwind_info)
+        .add_stream(function_tables);
+
+    let state = read_synth_dump(dump).await;
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 2);
+    assert!(frames[0].module.is_none());
+    assert_eq!(frames[1].trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frames[1].instruction, 0x1000_0104);
+    assert_eq!(
+        frames[1].module.as_ref().unwrap().code_file(),
+        "C:\\host.dll"
+    );
+    assert_eq!(frames[1].context.get_register("rbx"), Some(0x1111));
+    assert_eq!(frames[1].context.get_register("rbp"), Some(0x2222));
+}
+
+#[tokio::test]
+async fn test_riscv64_stackwalk() {
+    let context = minidump_synth::riscv64_context(Endian::Little, 0x1000_0100, 0x80000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x10) // locals
+            .D64(0x1000_0206) // return address
+            .append_repeated(0, 0x10),
+        0x80000,
+    );
+    let name = DumpString::new("/usr/lib/libfoo.so", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x1000_0000, 0x1_0000, &name, 0, 0, None);
+
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_RISCV64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add_memory(stack);
+
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.system_info.cpu, Cpu::Riscv64);
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].trust, FrameTrust::Context);
+    assert_eq!(frames[1].trust, FrameTrust::Scan);
+    assert_eq!(frames[1].instruction, 0x1000_0204);
+    assert_eq!(frames[1].context.get_stack_pointer(), 0x80018);
+}
+
+#[tokio::test]
+async fn test_extended_registers() {
+    let (context, xstate_data) = minidump_synth::amd64_context_with_xstate(
+        Endian::Little,
+        0x1000,
+        0x80000,
+        0x7ff0_1234_5000,
+    );
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.misc_5 = Some(MiscInfo5Fields {
+        xstate_data,
+        process_cookie: None,
+    });
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add(context)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add_memory(stack)
+        .add_stream(misc);
+    let state = read_synth_dump(dump).await;
+
+    let xstate = state.threads[0].frames[0].context.xstate.as_ref().unwrap();
+    assert_eq!(xstate.shadow_stack_pointer, Some(0x7ff0_1234_5000));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let frame = &json["crashing_thread"]["frames"][0];
+    assert_eq!(frame["extended_registers"]["ssp"], "0x00007ff012345000");
+    assert_eq!(frame["extended_registers"]["k2"], "0x0000000000000002");
+    assert_eq!(
+        frame["extended_registers"]["ymm0"],
+        format!("0x{}{}", "20".repeat(16), "10".repeat(16))
+    );
+    assert_eq!(
+        frame["extended_registers"]["zmm31"],
+        format!("0x{}", "4f".repeat(64))
+    );
+    // Only the crashing frame has registers
+    assert!(json["threads"][0]["frames"][0]
+        .get("extended_registers")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_float_registers() {
+    let context = minidump_synth::amd64_context(Endian::Little, 0x1000, 0x80000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add(context)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    let to_json = |json: Vec<u8>| serde_json::from_slice::<serde_json::Value>(&json).unwrap();
+
+    // The float registers are opt-in.
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json = to_json(json);
+    let frame = &json["crashing_thread"]["frames"][0];
+    assert!(frame.get("registers").is_some());
+    assert!(frame.get("float_registers").is_none());
+
+    let mut json = Vec::new();
+    state
+        .print_json_with_float_registers(&mut json, false)
+        .unwrap();
+    let json = to_json(json);
+    let float_registers = &json["crashing_thread"]["frames"][0]["float_registers"];
+    assert_eq!(float_registers["mxcsr"], "0x00000000");
+    assert_eq!(float_registers["st7"], "0x00000000000000000000");
+    assert_eq!(
+        float_registers["xmm15"],
+        "0x00000000000000000000000000000000"
+    );
+    assert_eq!(float_registers.as_object().unwrap().len(), 4 + 8 + 16);
+}
+
 #[tokio::test]
 async fn test_guard_pages() {
     let context = minidump_synth::amd64_context(Endian::Little, 0x2000, 0x81000);
@@ -507,3 +1245,322 @@ async fn test_guard_pages() {
     assert_eq!(accesses[0].address, 0x81000);
     assert!(accesses[0].is_likely_guard_page);
 }
+
+/// A symbol provider without any symbols, that finds the binaries of modules in `binaries`
+/// (by code file).
+#[derive(Default)]
+struct BinarySymbolProvider {
+    binaries: HashMap<String, PathBuf>,
+    /// The code files of the modules whose binaries were looked up.
+    requested: Mutex<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl SymbolProvider for BinarySymbolProvider {
+    async fn fill_symbol(
+        &self,
+        _module: &(dyn Module + Sync),
+        _frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        Err(FillSymbolError {})
+    }
+
+    async fn walk_frame(
+        &self,
+        _module: &(dyn Module + Sync),
+        _walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        None
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        match file_kind {
+            FileKind::Binary => {
+                self.requested
+                    .lock()
+                    .unwrap()
+                    .push(module.code_file().into_owned());
+                self.binaries
+                    .get(module.code_file().as_ref())
+                    .cloned()
+                    .ok_or(FileError::NotFound)
+            }
+            _ => Err(FileError::NotFound),
+        }
+    }
+}
+
+/// Write a minimal x86-64 ELF binary whose only segment is executable, and is `0x1100`
+/// bytes of `int3` except for `code` at `0x1000`. Return its path.
+fn write_test_binary(name: &str, code: &[u8]) -> PathBuf {
+    let binary = Section::with_endian(Endian::Little)
+        .append_bytes(b"\x7fELF\x02\x01\x01") // magic, 64-bit, little-endian, version
+        .append_repeated(0, 9) // padding
+        .D16(3) // e_type: ET_DYN
+        .D16(0x3e) // e_machine: EM_X86_64
+        .D32(1) // e_version
+        .D64(0) // e_entry
+        .D64(64) // e_phoff
+        .D64(0) // e_shoff
+        .D32(0) // e_flags
+        .D16(64) // e_ehsize
+        .D16(56) // e_phentsize
+        .D16(1) // e_phnum
+        .D16(64) // e_shentsize
+        .D16(0) // e_shnum
+        .D16(0) // e_shstrndx
+        .D32(1) // p_type: PT_LOAD
+        .D32(5) // p_flags: PF_R | PF_X
+        .D64(0) // p_offset
+        .D64(0) // p_vaddr
+        .D64(0) // p_paddr
+        .D64(0x1100) // p_filesz
+        .D64(0x1100) // p_memsz
+        .D64(0x1000); // p_align
+    let mut binary = binary.get_contents().unwrap();
+    binary.resize(0x1100, 0xcc);
+    binary[0x1000..0x1000 + code.len()].copy_from_slice(code);
+
+    let path = std::env::temp_dir().join(format!(
+        "minidump-processor-test-{}-{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::write(&path, binary).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_code_from_binaries() {
+    // `mov al, [rsp]`
+    let binary_path = write_test_binary("code-from-binaries", &[0x8a, 0x04, 0x24]);
+
+    let context = minidump_synth::amd64_context(Endian::Little, 0x40_1000, 0x81000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x81000);
+    let name = DumpString::new("/usr/lib/libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(Endian::Little, 0x40_0000, 0x2000, &name, 0, 0, None);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+
+    let context_label = context.file_offset();
+    let context_size = context.file_size();
+
+    let dump = SynthMinidump::with_endian(Endian::Little).add(context);
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_address = 0x40_1000;
+    ex.thread_context = (
+        context_size.value().unwrap() as u32,
+        context_label.value().unwrap() as u32,
+    );
+
+    let dump = dump
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(name)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let provider = BinarySymbolProvider {
+        binaries: HashMap::from([(String::from("/usr/lib/libtest.so"), binary_path.clone())]),
+        ..Default::default()
+    };
+    let process = |options| async {
+        minidump_processor::process_minidump_with_options(&dump, &provider, options)
+            .await
+            .unwrap()
+            .exception_info
+            .expect("missing exception info")
+    };
+
+    // The minidump doesn't contain the crashing instruction.
+    let info = process(ProcessorOptions::default()).await;
+    assert_eq!(info.instruction_str, None);
+
+    let mut options = ProcessorOptions::default();
+    options.code_from_binaries = true;
+    let info = process(options).await;
+    std::fs::remove_file(&binary_path).unwrap();
+
+    assert_eq!(info.instruction_str.as_deref(), Some("mov al, byte [rsp]"));
+    let accesses = info.memory_accesses.expect("no memory accesses");
+    assert_eq!(accesses.len(), 1);
+    assert_eq!(accesses[0].address, 0x81000);
+}
+
+#[tokio::test]
+async fn test_code_from_binaries_when_scanning() {
+    // `call rax`, so 0x1002 is a return address and 0x1010 isn't.
+    let binary_path = write_test_binary("code-from-binaries-when-scanning", &[0xff, 0xd0]);
+
+    let context = minidump_synth::amd64_context(Endian::Little, 0x60_0000, 0x81000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D64(0x40_1010)
+            .D64(0x40_1002)
+            .append_repeated(0, 16),
+        0x81000,
+    );
+    let name = DumpString::new("/usr/lib/libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(Endian::Little, 0x40_0000, 0x2000, &name, 0, 0, None);
+    let unused_name = DumpString::new("/usr/lib/libunused.so", Endian::Little);
+    let unused =
+        minidump_synth::Module::new(Endian::Little, 0x50_0000, 0x2000, &unused_name, 0, 0, None);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add(context)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add_module(unused)
+        .add(name)
+        .add(unused_name)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let provider = BinarySymbolProvider {
+        binaries: HashMap::from([
+            (String::from("/usr/lib/libtest.so"), binary_path.clone()),
+            (String::from("/usr/lib/libunused.so"), binary_path.clone()),
+        ]),
+        ..Default::default()
+    };
+    let process = |options| async {
+        minidump_processor::process_minidump_with_options(&dump, &provider, options)
+            .await
+            .unwrap()
+    };
+
+    // Without the code, the first value that points into a module is taken.
+    let state = process(ProcessorOptions::default()).await;
+    assert_eq!(state.threads[0].frames[1].instruction, 0x40_100f);
+    assert!(provider.requested.lock().unwrap().is_empty());
+
+    let mut options = ProcessorOptions::default();
+    options.code_from_binaries = true;
+    let state = process(options).await;
+    std::fs::remove_file(&binary_path).unwrap();
+
+    let frame = &state.threads[0].frames[1];
+    assert_eq!(frame.trust, FrameTrust::Scan);
+    assert_eq!(frame.instruction, 0x40_1001);
+    // Only the binary of the module that scanning landed in was read.
+    assert_eq!(
+        *provider.requested.lock().unwrap(),
+        vec![String::from("/usr/lib/libtest.so")]
+    );
+}
+
+#[tokio::test]
+async fn test_code_integrity() {
+    // `push rbp; mov rbp, rsp; xor eax, eax; pop rbp; ret`
+    let original = [0x55, 0x48, 0x89, 0xe5, 0x31, 0xc0, 0x5d, 0xc3];
+    let binary_path = write_test_binary("code-integrity", &original);
+
+    // The code in the minidump has a hook at the start of the function, which jumps into
+    // another module, and a bit flip further on.
+    let mut code = vec![0xcc; 0x100];
+    code[..original.len()].copy_from_slice(&original);
+    code[..5].copy_from_slice(&[0xe9, 0xfb, 0xef, 0x0f, 0x00]);
+    code[0x80] ^= 0x10;
+    let code = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(&code),
+        0x40_1000,
+    );
+
+    let context = minidump_synth::amd64_context(Endian::Little, 0x40_1000, 0x81000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x81000);
+    let name = DumpString::new("/usr/lib/libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(Endian::Little, 0x40_0000, 0x2000, &name, 0, 0, None);
+    let injector_name = DumpString::new("/tmp/injector.so", Endian::Little);
+    let injector = minidump_synth::Module::new(
+        Endian::Little,
+        0x50_0000,
+        0x1000,
+        &injector_name,
+        0,
+        0,
+        None,
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add(context)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add_module(injector)
+        .add(name)
+        .add(injector_name)
+        .add_memory(stack)
+        .add_memory(code);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let provider = BinarySymbolProvider {
+        binaries: HashMap::from([(String::from("/usr/lib/libtest.so"), binary_path.clone())]),
+        ..Default::default()
+    };
+
+    // The check is opt-in.
+    let state =
+        minidump_processor::process_minidump_with_options(&dump, &provider, Default::default())
+            .await
+            .unwrap();
+    assert_eq!(state.code_integrity, None);
+
+    let mut options = ProcessorOptions::default();
+    options.check_code_integrity = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &provider, options)
+        .await
+        .unwrap();
+    std::fs::remove_file(&binary_path).unwrap();
+
+    let integrity = state.code_integrity.as_ref().unwrap();
+    assert_eq!(integrity.checked_modules, vec![String::from("libtest.so")]);
+    assert_eq!(integrity.modifications.len(), 2);
+
+    let hook = &integrity.modifications[0];
+    assert_eq!(hook.address, 0x40_1000);
+    assert_eq!(hook.size, 5);
+    assert_eq!(hook.original, &original[..5]);
+    assert_eq!(
+        hook.kind,
+        CodeModificationKind::InlineHook {
+            target: 0x50_0000,
+            target_module: Some(String::from("injector.so")),
+        }
+    );
+
+    let bit_flip = &integrity.modifications[1];
+    assert_eq!(bit_flip.address, 0x40_1080);
+    assert_eq!(bit_flip.original, vec![0xcc]);
+    assert_eq!(bit_flip.modified, vec![0xdc]);
+    assert_eq!(bit_flip.kind, CodeModificationKind::BitFlip);
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let hook = &json["code_integrity"]["modifications"][0];
+    assert_eq!(hook["type"], "inline_hook");
+    assert_eq!(hook["module"], "libtest.so");
+    assert_eq!(hook["address"], "0x0000000000401000");
+    assert_eq!(hook["original"], "554889e531");
+    assert_eq!(hook["modified"], "e9fbef0f00");
+    assert_eq!(hook["target"], "0x0000000000500000");
+    assert_eq!(hook["target_module"], "injector.so");
+    assert_eq!(
+        json["code_integrity"]["modifications"][1]["type"],
+        "bit_flip"
+    );
+}
@@ -0,0 +1,860 @@
+//! A GDB remote serial protocol server backed by a minidump.
+//!
+//! This lets gdb or lldb connect to a minidump (with `target remote` or `gdb-remote`)
+//! as if it were a process stopped at the time of the crash, so that their own
+//! symbolication and scripting can be used on it. Only what a minidump can answer
+//! is supported:
+//!
+//! * registers (`g` and `p`), from the contexts of the threads
+//! * memory (`m`), from the memory lists and the stacks of the threads
+//! * threads (`qfThreadInfo`, `qXfer:threads:read`), from the thread list
+//! * libraries (`qXfer:libraries:read`), from the module list
+//!
+//! Anything that would resume or modify the process is rejected.
+//!
+//! See <https://sourceware.org/gdb/onlinedocs/gdb/Remote-Protocol.html>.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::ops::Deref;
+
+use minidump::system_info::{Cpu, Os};
+use minidump::*;
+use tracing::info;
+
+/// The largest packet we accept, advertised in `qSupported`.
+const PACKET_SIZE: usize = 0x4000;
+
+/// The error returned by `qXfer` packets for unknown objects, and memory reads
+/// of memory that isn't in the minidump (`EFAULT`).
+const ERROR_FAULT: &[u8] = b"E0e";
+/// The error returned for malformed packets and things we can't do (`EINVAL`).
+const ERROR_INVALID: &[u8] = b"E16";
+
+/// Why the server couldn't be started.
+#[derive(Debug)]
+pub enum Error {
+    /// The minidump lacks a stream the server needs.
+    Minidump(minidump::Error),
+    /// The registers of this CPU aren't supported.
+    UnsupportedCpu(Cpu),
+}
+
+impl Error {
+    /// Returns just the name of the error, as a more human-friendly version of
+    /// an error-code for error logging.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Error::Minidump(err) => err.name(),
+            Error::UnsupportedCpu(_) => "UnsupportedCpu",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Minidump(err) => err.fmt(f),
+            Error::UnsupportedCpu(cpu) => write!(
+                f,
+                "The gdb server doesn't support {cpu} minidumps (only amd64 and arm64)"
+            ),
+        }
+    }
+}
+
+impl From<minidump::Error> for Error {
+    fn from(err: minidump::Error) -> Self {
+        Error::Minidump(err)
+    }
+}
+
+/// The architectures whose registers we know how to describe to gdb.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Arch {
+    Amd64,
+    Arm64,
+}
+
+/// A register in the target description.
+struct Register {
+    name: Cow<'static, str>,
+    bits: usize,
+    ty: &'static str,
+}
+
+impl Register {
+    fn new(name: impl Into<Cow<'static, str>>, bits: usize, ty: &'static str) -> Self {
+        Register {
+            name: name.into(),
+            bits,
+            ty,
+        }
+    }
+}
+
+impl Arch {
+    /// The name of the architecture in gdb's target descriptions.
+    fn gdb_name(self) -> &'static str {
+        match self {
+            Arch::Amd64 => "i386:x86-64",
+            Arch::Arm64 => "aarch64",
+        }
+    }
+
+    /// The architecture part of an LLVM target triple.
+    fn triple_name(self) -> &'static str {
+        match self {
+            Arch::Amd64 => "x86_64",
+            Arch::Arm64 => "aarch64",
+        }
+    }
+
+    /// The registers of the architecture, grouped by gdb feature, in the order gdb
+    /// expects them in `g` packets.
+    fn features(self) -> Vec<(&'static str, Vec<Register>)> {
+        match self {
+            Arch::Amd64 => {
+                let mut core = [
+                    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10",
+                    "r11", "r12", "r13", "r14", "r15",
+                ]
+                .iter()
+                .map(|&name| {
+                    let ty = if name == "rbp" || name == "rsp" {
+                        "data_ptr"
+                    } else {
+                        "int64"
+                    };
+                    Register::new(name, 64, ty)
+                })
+                .collect::<Vec<_>>();
+                core.push(Register::new("rip", 64, "code_ptr"));
+                for name in ["eflags", "cs", "ss", "ds", "es", "fs", "gs"] {
+                    core.push(Register::new(name, 32, "int32"));
+                }
+                for i in 0..8 {
+                    core.push(Register::new(format!("st{i}"), 80, "i387_ext"));
+                }
+                for name in [
+                    "fctrl", "fstat", "ftag", "fiseg", "fioff", "foseg", "fooff", "fop",
+                ] {
+                    core.push(Register::new(name, 32, "int"));
+                }
+                let mut sse = (0..16)
+                    .map(|i| Register::new(format!("xmm{i}"), 128, "uint128"))
+                    .collect::<Vec<_>>();
+                sse.push(Register::new("mxcsr", 32, "int"));
+                vec![
+                    ("org.gnu.gdb.i386.core", core),
+                    ("org.gnu.gdb.i386.sse", sse),
+                ]
+            }
+            Arch::Arm64 => {
+                let mut core = (0..31)
+                    .map(|i| Register::new(format!("x{i}"), 64, "int"))
+                    .collect::<Vec<_>>();
+                core.push(Register::new("sp", 64, "data_ptr"));
+                core.push(Register::new("pc", 64, "code_ptr"));
+                core.push(Register::new("cpsr", 32, "int"));
+                let mut fpu = (0..32)
+                    .map(|i| Register::new(format!("v{i}"), 128, "uint128"))
+                    .collect::<Vec<_>>();
+                fpu.push(Register::new("fpsr", 32, "int"));
+                fpu.push(Register::new("fpcr", 32, "int"));
+                vec![
+                    ("org.gnu.gdb.aarch64.core", core),
+                    ("org.gnu.gdb.aarch64.fpu", fpu),
+                ]
+            }
+        }
+    }
+
+    /// The `target.xml` document describing the registers.
+    fn target_xml(self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n<target version=\"1.0\">\n",
+        );
+        xml.push_str(&format!(
+            "  <architecture>{}</architecture>\n",
+            self.gdb_name()
+        ));
+        let mut regnum = 0;
+        for (feature, registers) in self.features() {
+            xml.push_str(&format!("  <feature name=\"{feature}\">\n"));
+            for reg in registers {
+                xml.push_str(&format!(
+                    "    <reg name=\"{}\" bitsize=\"{}\" type=\"{}\" regnum=\"{regnum}\"/>\n",
+                    reg.name, reg.bits, reg.ty
+                ));
+                regnum += 1;
+            }
+            xml.push_str("  </feature>\n");
+        }
+        xml.push_str("</target>\n");
+        xml
+    }
+}
+
+/// The values of the registers of a context, in the order of [`Arch::features`].
+///
+/// Registers the context doesn't have are `None`.
+fn register_values(context: &MinidumpContext) -> Vec<Option<Vec<u8>>> {
+    let int = |val: u64, bytes: usize| Some(val.to_le_bytes()[..bytes].to_vec());
+    let float_registers = context.float_registers().collect::<Vec<_>>();
+    let float = |name: &str, bytes: usize| {
+        float_registers
+            .iter()
+            .find(|reg| reg.name == name)
+            .map(|reg| reg.value.to_le_bytes()[..bytes].to_vec())
+    };
+    let mut values = vec![];
+    match &context.raw {
+        MinidumpRawContext::Amd64(ctx) => {
+            for val in [
+                ctx.rax, ctx.rbx, ctx.rcx, ctx.rdx, ctx.rsi, ctx.rdi, ctx.rbp, ctx.rsp, ctx.r8,
+                ctx.r9, ctx.r10, ctx.r11, ctx.r12, ctx.r13, ctx.r14, ctx.r15, ctx.rip,
+            ] {
+                values.push(int(val, 8));
+            }
+            values.push(int(ctx.eflags as u64, 4));
+            for val in [ctx.cs, ctx.ss, ctx.ds, ctx.es, ctx.fs, ctx.gs] {
+                values.push(int(val as u64, 4));
+            }
+            for i in 0..8 {
+                values.push(float(&format!("st{i}"), 10));
+            }
+            values.push(float("fcw", 4));
+            values.push(float("fsw", 4));
+            values.push(x87_tag_word(&float_registers).and_then(|tags| int(tags as u64, 4)));
+            // The last instruction and operand pointers (fiseg, fioff, foseg, fooff
+            // and fop) aren't available.
+            values.resize(values.len() + 5, None);
+            for i in 0..16 {
+                values.push(float(&format!("xmm{i}"), 16));
+            }
+            values.push(float("mxcsr", 4));
+        }
+        MinidumpRawContext::Arm64(ctx) => {
+            arm64_register_values(&mut values, (&ctx.iregs, ctx.sp, ctx.pc, ctx.cpsr), float);
+        }
+        MinidumpRawContext::OldArm64(ctx) => {
+            arm64_register_values(&mut values, (&ctx.iregs, ctx.sp, ctx.pc, ctx.cpsr), float);
+        }
+        _ => {}
+    }
+    values
+}
+
+type Arm64Registers<'a> = (&'a [u64; 31], u64, u64, u32);
+
+fn arm64_register_values(
+    values: &mut Vec<Option<Vec<u8>>>,
+    (iregs, sp, pc, cpsr): Arm64Registers,
+    float: impl Fn(&str, usize) -> Option<Vec<u8>>,
+) {
+    for &val in iregs.iter().chain([&sp, &pc]) {
+        values.push(Some(val.to_le_bytes().to_vec()));
+    }
+    values.push(Some(cpsr.to_le_bytes().to_vec()));
+    for i in 0..32 {
+        values.push(float(&format!("v{i}"), 16));
+    }
+    values.push(float("fpsr", 4));
+    values.push(float("fpcr", 4));
+}
+
+/// Expand the abridged tag byte of FXSAVE (`ftw`) to the full x87 tag word gdb
+/// expects, which distinguishes zero and special values from valid ones.
+fn x87_tag_word(float_registers: &[FloatRegister]) -> Option<u16> {
+    let value = |name: &str| {
+        float_registers
+            .iter()
+            .find(|reg| reg.name == name)
+            .map(|reg| reg.value)
+    };
+    let status = value("fsw")? as u16;
+    let top = ((status >> 11) & 7) as usize;
+    let abridged = value("ftw")? as u8;
+    let mut tags = 0;
+    for physical in 0..8 {
+        let tag = if abridged & (1 << physical) == 0 {
+            // Empty
+            3
+        } else {
+            // The registers are in stack order.
+            let st = (physical + 8 - top) % 8;
+            let reg = value(&format!("st{st}"))?;
+            let exponent = (reg >> 64) as u16 & 0x7fff;
+            let mantissa = reg as u64;
+            let integer_bit = mantissa >> 63 != 0;
+            match exponent {
+                0 if mantissa == 0 => 1,
+                0 | 0x7fff => 2,
+                _ if !integer_bit => 2,
+                _ => 0,
+            }
+        };
+        tags |= tag << (physical * 2);
+    }
+    Some(tags)
+}
+
+/// Map a Linux signal number to the signal numbers of the remote protocol.
+fn gdb_signal(signal: u32) -> u32 {
+    const SIGBUS: u32 = 7;
+    const SIGSYS: u32 = 31;
+    match signal {
+        SIGBUS => 10,
+        SIGSYS => 12,
+        _ => signal,
+    }
+}
+
+/// A thread of the process, as presented to gdb.
+struct Thread {
+    id: u32,
+    name: Option<String>,
+    registers: Vec<Option<Vec<u8>>>,
+}
+
+/// Serves a [`Minidump`] to gdb or lldb.
+pub struct GdbServer<'a> {
+    arch: Arch,
+    os: Os,
+    process_id: Option<u32>,
+    threads: Vec<Thread>,
+    /// The thread selected by `Hg`, as an index into `threads`.
+    current_thread: usize,
+    stop_signal: u32,
+    memory_list: UnifiedMemoryList<'a>,
+    thread_list: MinidumpThreadList<'a>,
+    module_list: MinidumpModuleList,
+}
+
+impl<'a> GdbServer<'a> {
+    /// Prepare to serve `dump`.
+    pub fn new<T>(dump: &'a Minidump<'a, T>) -> Result<GdbServer<'a>, Error>
+    where
+        T: Deref<Target = [u8]> + 'a,
+    {
+        let system_info = dump.get_stream::<MinidumpSystemInfo>()?;
+        let arch = match system_info.cpu {
+            Cpu::X86_64 => Arch::Amd64,
+            Cpu::Arm64 => Arch::Arm64,
+            cpu => return Err(Error::UnsupportedCpu(cpu)),
+        };
+        let thread_list = dump.get_stream::<MinidumpThreadList>()?;
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        let exception = dump.get_stream::<MinidumpException>().ok();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().ok();
+
+        // Present the crashing thread with the context of the crash, which is
+        // usually in the exception stream rather than the thread list.
+        let crashing_thread_id = exception.as_ref().map(|e| e.get_crashing_thread_id());
+        let mut threads = vec![];
+        let mut current_thread = 0;
+        for thread in &thread_list.threads {
+            let thread_id = thread.raw.thread_id;
+            let exception_context = exception
+                .as_ref()
+                .filter(|_| Some(thread_id) == crashing_thread_id)
+                .and_then(|e| e.context(&system_info, misc_info.as_ref()));
+            let context =
+                exception_context.or_else(|| thread.context(&system_info, misc_info.as_ref()));
+            let registers = context
+                .map(|context| register_values(&context))
+                .unwrap_or_default();
+            if Some(thread_id) == crashing_thread_id {
+                current_thread = threads.len();
+            }
+            threads.push(Thread {
+                id: thread_id,
+                name: thread_names
+                    .as_ref()
+                    .and_then(|names| names.get_name(thread_id))
+                    .map(String::from),
+                registers,
+            });
+        }
+
+        let stop_signal = match &exception {
+            Some(exception) if matches!(system_info.os, Os::Linux | Os::Android) => {
+                gdb_signal(exception.raw.exception_record.exception_code)
+            }
+            // We can't translate the exceptions of other systems, but it's
+            // still a crash.
+            Some(_) => 11,
+            // SIGTRAP, as if the process had been stopped by the debugger.
+            None => 5,
+        };
+
+        Ok(GdbServer {
+            arch,
+            os: system_info.os,
+            process_id: misc_info
+                .as_ref()
+                .and_then(|info| info.raw.process_id())
+                .copied(),
+            threads,
+            current_thread,
+            stop_signal,
+            memory_list: dump.get_memory().unwrap_or_default(),
+            thread_list,
+            module_list: dump.get_stream::<MinidumpModuleList>().unwrap_or_default(),
+        })
+    }
+
+    /// Listen for connections on `addr`, and serve them one at a time until a
+    /// client kills the "process".
+    pub fn listen<A: ToSocketAddrs>(mut self, addr: A) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        info!(
+            "Listening for gdb connections on {}",
+            listener.local_addr()?
+        );
+        for stream in listener.incoming() {
+            if !self.serve(stream?)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve a single connection until the client disconnects or detaches.
+    ///
+    /// Returns false if the client asked to kill the process.
+    pub fn serve<S: Read + Write>(&mut self, stream: S) -> io::Result<bool> {
+        let mut conn = Connection {
+            stream,
+            ack: true,
+            buffer: vec![],
+        };
+        loop {
+            let packet = match conn.read_packet()? {
+                Some(packet) => packet,
+                None => return Ok(true),
+            };
+            match self.handle_packet(&packet) {
+                Action::Reply(reply) => conn.write_packet(&reply)?,
+                Action::StartNoAckMode => {
+                    conn.write_packet(b"OK")?;
+                    conn.ack = false;
+                }
+                Action::Detach => {
+                    conn.write_packet(b"OK")?;
+                    return Ok(true);
+                }
+                Action::Kill => return Ok(false),
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, packet: &[u8]) -> Action {
+        let reply = |reply: &[u8]| Action::Reply(reply.to_vec());
+        if packet == [INTERRUPT] {
+            return Action::Reply(self.stop_reply());
+        }
+        let packet = String::from_utf8_lossy(packet);
+        let (command, args) = match packet.find(|c: char| !c.is_ascii_alphabetic()) {
+            // Single-letter packets have their arguments right after them.
+            _ if !packet.starts_with(['q', 'Q', 'v']) => packet.split_at(packet.len().min(1)),
+            Some(i) => packet.split_at(i),
+            None => (&*packet, ""),
+        };
+        match command {
+            "?" => Action::Reply(self.stop_reply()),
+            "g" => Action::Reply(self.read_registers(None)),
+            "p" => match u64::from_str_radix(args, 16) {
+                Ok(regnum) => Action::Reply(self.read_registers(Some(regnum as usize))),
+                Err(_) => reply(ERROR_INVALID),
+            },
+            "m" => match parse_address_and_length(args) {
+                Some((address, length)) => {
+                    let bytes = self.read_memory(address, length.min(PACKET_SIZE / 2));
+                    if bytes.is_empty() && length != 0 {
+                        reply(ERROR_FAULT)
+                    } else {
+                        Action::Reply(hex(&bytes).into_bytes())
+                    }
+                }
+                None => reply(ERROR_INVALID),
+            },
+            "H" => {
+                let (op, thread) = args.split_at(args.len().min(1));
+                if op == "g" {
+                    if let Some(index) = self.find_thread(thread) {
+                        self.current_thread = index;
+                    } else if !matches!(thread, "0" | "-1") {
+                        return reply(ERROR_INVALID);
+                    }
+                }
+                reply(b"OK")
+            }
+            "T" => match self.find_thread(args) {
+                Some(_) => reply(b"OK"),
+                None => reply(ERROR_INVALID),
+            },
+            "D" => Action::Detach,
+            "k" => Action::Kill,
+            // Nothing can be resumed, written or stepped.
+            "c" | "C" | "s" | "S" | "G" | "P" | "M" | "X" | "Z" | "z" | "vCont" | "vRun"
+            | "vAttach" | "vKill" => reply(ERROR_INVALID),
+            "qSupported" => Action::Reply(
+                format!(
+                    "PacketSize={PACKET_SIZE:x};QStartNoAckMode+;qXfer:features:read+;\
+                     qXfer:libraries:read+;qXfer:threads:read+"
+                )
+                .into_bytes(),
+            ),
+            "QStartNoAckMode" => Action::StartNoAckMode,
+            "qAttached" => reply(b"1"),
+            "qC" => Action::Reply(
+                self.threads
+                    .get(self.current_thread)
+                    .map(|thread| format!("QC{:x}", thread.id).into_bytes())
+                    .unwrap_or_default(),
+            ),
+            "qfThreadInfo" => {
+                let ids = self
+                    .threads
+                    .iter()
+                    .map(|thread| format!("{:x}", thread.id))
+                    .collect::<Vec<_>>();
+                Action::Reply(format!("m{}", ids.join(",")).into_bytes())
+            }
+            "qsThreadInfo" => reply(b"l"),
+            "qThreadExtraInfo" => {
+                let name = args
+                    .strip_prefix(',')
+                    .and_then(|thread| self.find_thread(thread))
+                    .and_then(|index| self.threads[index].name.as_deref());
+                match name {
+                    Some(name) => Action::Reply(hex(name.as_bytes()).into_bytes()),
+                    None => reply(b""),
+                }
+            }
+            "qHostInfo" => Action::Reply(
+                format!(
+                    "triple:{};ptrsize:8;endian:little;",
+                    hex(self.triple().as_bytes())
+                )
+                .into_bytes(),
+            ),
+            "qProcessInfo" => Action::Reply(
+                format!(
+                    "pid:{:x};triple:{};ptrsize:8;endian:little;",
+                    self.process_id.unwrap_or(1),
+                    hex(self.triple().as_bytes())
+                )
+                .into_bytes(),
+            ),
+            "qSymbol" => reply(b"OK"),
+            "qXfer" => self.read_object(args),
+            _ => reply(b""),
+        }
+    }
+
+    fn stop_reply(&self) -> Vec<u8> {
+        let mut reply = format!("T{:02x}", self.stop_signal);
+        if let Some(thread) = self.threads.get(self.current_thread) {
+            reply.push_str(&format!("thread:{:x};", thread.id));
+        }
+        reply.into_bytes()
+    }
+
+    /// Encode the registers of the current thread, either all of them or just `regnum`.
+    fn read_registers(&self, regnum: Option<usize>) -> Vec<u8> {
+        let thread = match self.threads.get(self.current_thread) {
+            Some(thread) => thread,
+            None => return ERROR_INVALID.to_vec(),
+        };
+        let registers = self
+            .arch
+            .features()
+            .into_iter()
+            .flat_map(|(_, registers)| registers)
+            .enumerate()
+            .filter(|(i, _)| regnum.is_none_or(|regnum| regnum == *i))
+            .collect::<Vec<_>>();
+        if registers.is_empty() {
+            return ERROR_INVALID.to_vec();
+        }
+        let mut reply = String::new();
+        for (i, register) in registers {
+            match thread.registers.get(i).and_then(|value| value.as_ref()) {
+                Some(value) => reply.push_str(&hex(value)),
+                // Unavailable
+                None => reply.push_str(&"xx".repeat(register.bits / 8)),
+            }
+        }
+        reply.into_bytes()
+    }
+
+    /// Read as much of the `length` bytes at `address` as the minidump has.
+    fn read_memory(&self, address: u64, length: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(length);
+        while bytes.len() < length {
+            let address = match address.checked_add(bytes.len() as u64) {
+                Some(address) => address,
+                None => break,
+            };
+            let region = self.memory_list.memory_at_address(address).or_else(|| {
+                self.thread_list
+                    .threads
+                    .iter()
+                    .filter_map(|thread| thread.stack_memory(&self.memory_list))
+                    .find(|stack| {
+                        stack
+                            .memory_range()
+                            .is_some_and(|range| range.contains(address))
+                    })
+            });
+            let available = region.and_then(|region| {
+                let offset = (address - region.base_address()) as usize;
+                region.bytes().get(offset..)
+            });
+            match available {
+                Some(available) if !available.is_empty() => {
+                    let count = available.len().min(length - bytes.len());
+                    bytes.extend_from_slice(&available[..count]);
+                }
+                _ => break,
+            }
+        }
+        bytes
+    }
+
+    /// Handle `qXfer:object:read:annex:offset,length`.
+    fn read_object(&self, args: &str) -> Action {
+        let mut parts = args.trim_start_matches(':').splitn(4, ':');
+        let (object, op, annex, range) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(object), Some(op), Some(annex), Some(range)) => (object, op, annex, range),
+                _ => return Action::Reply(ERROR_INVALID.to_vec()),
+            };
+        let (offset, length) = match parse_address_and_length(range) {
+            Some((offset, length)) if op == "read" => (offset as usize, length),
+            _ => return Action::Reply(ERROR_INVALID.to_vec()),
+        };
+        let document = match (object, annex) {
+            ("features", "target.xml") => self.arch.target_xml(),
+            ("libraries", "") => self.libraries_xml(),
+            ("threads", "") => self.threads_xml(),
+            _ => return Action::Reply(ERROR_FAULT.to_vec()),
+        };
+        let document = document.as_bytes();
+        let chunk = document.get(offset..).unwrap_or_default();
+        let length = length.min(PACKET_SIZE / 2);
+        let mut reply = if chunk.len() > length {
+            vec![b'm']
+        } else {
+            vec![b'l']
+        };
+        reply.extend_from_slice(&chunk[..chunk.len().min(length)]);
+        Action::Reply(reply)
+    }
+
+    fn libraries_xml(&self) -> String {
+        let mut xml = String::from("<library-list>\n");
+        for module in self.module_list.by_addr() {
+            xml.push_str(&format!(
+                "  <library name=\"{}\"><segment address=\"{:#x}\"/></library>\n",
+                xml_escape(&module.code_file()),
+                module.base_address()
+            ));
+        }
+        xml.push_str("</library-list>\n");
+        xml
+    }
+
+    fn threads_xml(&self) -> String {
+        let mut xml = String::from("<threads>\n");
+        for thread in &self.threads {
+            xml.push_str(&format!("  <thread id=\"{:x}\"", thread.id));
+            if let Some(name) = &thread.name {
+                xml.push_str(&format!(" name=\"{}\"", xml_escape(name)));
+            }
+            xml.push_str("/>\n");
+        }
+        xml.push_str("</threads>\n");
+        xml
+    }
+
+    fn find_thread(&self, id: &str) -> Option<usize> {
+        let id = u32::from_str_radix(id, 16).ok()?;
+        self.threads.iter().position(|thread| thread.id == id)
+    }
+
+    /// The LLVM target triple of the process, for lldb.
+    fn triple(&self) -> String {
+        let rest = match self.os {
+            Os::Windows => "pc-windows-msvc",
+            Os::MacOs => "apple-macosx",
+            Os::Ios => "apple-ios",
+            Os::Linux => "unknown-linux-gnu",
+            Os::Android => "unknown-linux-android",
+            _ => "unknown-unknown",
+        };
+        format!("{}-{rest}", self.arch.triple_name())
+    }
+}
+
+/// What to do after handling a packet.
+enum Action {
+    Reply(Vec<u8>),
+    /// Reply "OK" and stop acknowledging packets.
+    StartNoAckMode,
+    /// Reply "OK" and close the connection.
+    Detach,
+    /// Close the connection and stop serving.
+    Kill,
+}
+
+/// The byte gdb sends outside of packets to interrupt the process.
+const INTERRUPT: u8 = 0x03;
+
+/// The framing of the remote protocol.
+struct Connection<S> {
+    stream: S,
+    /// Whether packets are acknowledged, until `QStartNoAckMode`.
+    ack: bool,
+    buffer: Vec<u8>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.stream.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Read the next packet (without its framing), or `None` at the end of the stream.
+    fn read_packet(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            // Skip acknowledgements, we never resend anything.
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(INTERRUPT) => return Ok(Some(vec![INTERRUPT])),
+                Some(b'$') => {}
+                Some(_) => continue,
+            }
+            self.buffer.clear();
+            let mut checksum = 0u8;
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => {
+                        checksum = checksum.wrapping_add(byte);
+                        self.buffer.push(byte);
+                    }
+                }
+            }
+            let mut expected = [0; 2];
+            for digit in &mut expected {
+                *digit = match self.read_byte()? {
+                    Some(byte) => byte,
+                    None => return Ok(None),
+                };
+            }
+            let expected = std::str::from_utf8(&expected)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+            if self.ack {
+                if expected != Some(checksum) {
+                    self.stream.write_all(b"-")?;
+                    continue;
+                }
+                self.stream.write_all(b"+")?;
+            }
+            return Ok(Some(unescape(&self.buffer)));
+        }
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> io::Result<()> {
+        let data = escape(data);
+        let checksum = data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        packet.extend_from_slice(&data);
+        packet.extend_from_slice(format!("#{checksum:02x}").as_bytes());
+        self.stream.write_all(&packet)?;
+        self.stream.flush()?;
+        if self.ack {
+            // Wait for the acknowledgement, resending on '-'.
+            loop {
+                match self.read_byte()? {
+                    Some(b'+') | None => break,
+                    Some(b'-') => self.stream.write_all(&packet)?,
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escape the bytes that can't appear in a packet as-is.
+fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len());
+    for &byte in data {
+        if matches!(byte, b'$' | b'#' | b'}' | b'*') {
+            escaped.push(b'}');
+            escaped.push(byte ^ 0x20);
+        } else {
+            escaped.push(byte);
+        }
+    }
+    escaped
+}
+
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'}' {
+            if let Some(&next) = bytes.next() {
+                unescaped.push(next ^ 0x20);
+            }
+        } else {
+            unescaped.push(byte);
+        }
+    }
+    unescaped
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parse the `addr,length` arguments of `m` and `qXfer` packets.
+fn parse_address_and_length(args: &str) -> Option<(u64, usize)> {
+    let (address, length) = args.split_once(',')?;
+    let address = u64::from_str_radix(address, 16).ok()?;
+    let length = usize::from_str_radix(length, 16).ok()?;
+    Some((address, length))
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -8,12 +8,17 @@ use std::panic;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use std::{boxed::Box, path::PathBuf};
+use std::{
+    boxed::Box,
+    path::{Path, PathBuf},
+};
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use memmap2::Mmap;
 use minidump::*;
 use minidump_processor::{
-    PendingProcessorStatSubscriptions, PendingProcessorStats, ProcessorOptions,
+    NoRedaction, PendingProcessorStatSubscriptions, PendingProcessorStats, ProcessorOptions,
+    RedactArguments,
 };
 use minidump_unwind::{
     debuginfo::DebugInfoSymbolProvider, http_symbol_supplier, simple_symbol_supplier,
@@ -27,6 +32,8 @@ use clap::{
 use tracing::error;
 use tracing::level_filters::LevelFilter;
 
+mod gdb_server;
+
 /// Analyzes minidumps and produces a report (either human-readable or JSON)
 ///
 /// NOTES:
@@ -61,6 +68,10 @@ use tracing::level_filters::LevelFilter;
     "human",
     "cyborg",
     "dump",
+    "elf_core",
+    "gdb_server",
+    "redact",
+    "lint",
     "help_markdown",
 ])))]
 #[clap(override_usage("minidump-stackwalk [FLAGS] [OPTIONS] <minidump> [--] [symbols-path]..."))]
@@ -104,6 +115,65 @@ struct Cli {
     #[arg(long)]
     dump: bool,
 
+    /// Convert the minidump into a Linux ELF core file
+    ///
+    /// This is an implementation of the functionality of Breakpad's old minidump-2-core
+    /// tool. The threads, memory, modules and crash signal of the minidump are written as
+    /// an ELF core file (to --output-file, or stdout), which can then be loaded into gdb
+    /// or lldb alongside the binaries of the crashed process.
+    ///
+    /// Only minidumps of x86-64 and ARM64 processes can be converted.
+    #[arg(long)]
+    elf_core: bool,
+
+    /// Serve the minidump to gdb or lldb over the GDB remote serial protocol
+    ///
+    /// Listens on the given address (e.g. `localhost:1234`) until a client kills the
+    /// process. The crashed process can then be inspected with `target remote localhost:1234`
+    /// in gdb or `gdb-remote localhost:1234` in lldb. Load the binaries of the crashed
+    /// process into the debugger for symbols.
+    ///
+    /// Registers, memory, threads and the list of loaded libraries are provided by the
+    /// minidump. Nothing can be resumed or modified.
+    ///
+    /// Only minidumps of x86-64 and ARM64 processes can be served.
+    #[arg(long, value_name = "ADDR")]
+    gdb_server: Option<String>,
+
+    /// Write a copy of the minidump with personal data removed
+    ///
+    /// The copy is written to --output-file (or stdout), and a report of everything that
+    /// was removed is printed to stderr. Memory outside of thread stacks and module images
+    /// is zeroed, the command line, comments and access tokens are dropped, environment
+    /// variables and Crashpad annotations with keys matching --redact-key are dropped, and
+    /// user names are scrubbed from the paths of modules, their PDBs, handles, thread names
+    /// and the process's memory mappings.
+    ///
+    /// Redaction is best-effort: thread stacks and unknown streams are kept as-is.
+    #[arg(long)]
+    redact: bool,
+
+    /// A key pattern for --redact (can be repeated)
+    ///
+    /// Environment variables and Crashpad annotations whose keys match any of the patterns
+    /// are dropped. Keys are matched as a whole, ignoring case, with `*` matching any
+    /// number of characters and `?` matching one. If no patterns are given, a default list
+    /// of common user, path and credential keys (`HOME`, `*TOKEN*`, ...) is used.
+    #[arg(long, value_name = "PATTERN", requires = "redact")]
+    redact_key: Vec<String>,
+
+    /// Check the minidump for spec violations
+    ///
+    /// Unlike the other modes, which do their best with whatever the minidump contains,
+    /// this reports every problem found in the raw structures of the file: streams or data
+    /// out of bounds of the file, truncated lists, duplicate or unknown streams, overlapping
+    /// memory regions or modules, and thread contexts that don't match the CPU.
+    ///
+    /// Each problem is printed on a line of its own, prefixed with `warning` or `error`.
+    /// The exit status is non-zero if there are any errors.
+    #[arg(long)]
+    lint: bool,
+
     /// Print --help but formatted as markdown (used for generating docs)
     #[arg(long, hide = true)]
     help_markdown: bool,
@@ -197,6 +267,45 @@ struct Cli {
     #[arg(long)]
     recover_function_args: bool,
 
+    /// **UNSTABLE** Include the command line of the crashed process in the output
+    ///
+    /// Command lines often contain urls, paths, and tokens, so they are left out
+    /// unless requested. The current supported values are:
+    ///  
+    /// * none (default): don't include the command line
+    /// * redacted: keep the program and switches (and the value of `--type`), redact the rest
+    /// * full: include the command line verbatim
+    ///  
+    /// Currently this only works for Linux minidumps.
+    #[arg(long, default_value = "none")]
+    #[arg(value_parser = ["none", "redacted", "full"])]
+    #[arg(verbatim_doc_comment)]
+    command_line: String,
+
+    /// **UNSTABLE** Include the floating-point and SIMD registers of the crashing frame in --json output
+    ///
+    /// These are reported as `float_registers` next to `registers`. Currently this
+    /// supports the x87/SSE state of x86 and x86-64, and the VFP/NEON state of ARM and ARM64.
+    #[arg(long)]
+    float_registers: bool,
+
+    /// **UNSTABLE** Read the code of modules from their binaries where the minidump has none
+    ///
+    /// The binaries are looked up like symbol files (see --symbols-url and --symbols-path,
+    /// or the paths in the minidump with --use-local-debuginfo). This lets the crashing
+    /// instruction be disassembled, and return addresses found by stack scanning be checked.
+    #[arg(long)]
+    code_from_binaries: bool,
+
+    /// **UNSTABLE** Compare the code of modules in the minidump with their binaries
+    ///
+    /// The binaries are looked up like with --code-from-binaries. Inline hooks, other
+    /// patches, and bit flips are reported as `code_integrity` in --json output. Only
+    /// the code that's in the minidump can be checked, which is usually very little
+    /// unless it's a full-memory dump.
+    #[arg(long)]
+    check_code_integrity: bool,
+
     /// Use debug information from local files referred to by the minidump, if present.
     #[arg(long)]
     use_local_debuginfo: bool,
@@ -254,6 +363,9 @@ struct Cli {
     symbols_download_timeout_secs: u64,
 
     /// Path to the minidump file to analyze
+    ///
+    /// Linux ELF core files are also accepted, and are converted to a minidump
+    /// before being analyzed.
     minidump: PathBuf,
 
     /// Path to a symbol file.
@@ -352,15 +464,18 @@ async fn main_result() -> std::io::Result<()> {
 
     let timeout = Duration::from_secs(cli.symbols_download_timeout_secs);
 
-    // Determine the kind of output we're producing -- dump, json, human, or cyborg (both).
+    // Determine the kind of output we're producing -- dump, elf core, redacted dump, lint,
+    // json, human, or cyborg (both).
     // Although we have a --human argument it's mostly just there to make the documentation
     // more clear. human output is enabled by default, and --json disables it.
     // Mutual exclusion is enforced by an ArgGroup, but it doesn't understand that "human"
     // is the implicit default, so we have to do some munging here.
     // Human is just enabled if nothing else is
     let raw_dump = cli.dump;
+    let elf_core = cli.elf_core;
     let mut json = cli.json;
-    let mut human = !json && !raw_dump;
+    let mut human =
+        !json && !raw_dump && !elf_core && cli.gdb_server.is_none() && !cli.redact && !cli.lint;
     // Cyborg is just "desugarred" to --json --human
     if cli.cyborg.is_some() {
         human = true;
@@ -390,6 +505,15 @@ async fn main_result() -> std::io::Result<()> {
     // Now overload the defaults
     options.evil_json = cli.evil_json.as_deref();
     options.recover_function_args = cli.recover_function_args;
+    options.code_from_binaries = cli.code_from_binaries;
+    options.check_code_integrity = cli.check_code_integrity;
+    let redact_arguments = RedactArguments::default();
+    options.command_line_redactor = match &*cli.command_line {
+        "none" => None,
+        "redacted" => Some(&redact_arguments),
+        "full" => Some(&NoRedaction),
+        _ => unimplemented!("unknown --command-line value"),
+    };
 
     // Register for instractive updates, if we want them
     let interactive_enabled = !json && !cli.no_interactive && cli.output_file.is_none();
@@ -404,11 +528,11 @@ async fn main_result() -> std::io::Result<()> {
 
     // Ok now let's do the thing!!!!
 
-    match Minidump::read_path(cli.minidump) {
+    match read_dump(&cli.minidump) {
         Ok(dump) => {
             let mut stdout;
             let mut output_f;
-            let cyborg_output_f = cli.cyborg.map(File::create).transpose()?;
+            let mut cyborg_output_f = cli.cyborg.map(File::create).transpose()?;
 
             let mut output: &mut dyn Write = if let Some(output_path) = cli.output_file {
                 output_f = File::create(output_path)?;
@@ -423,6 +547,70 @@ async fn main_result() -> std::io::Result<()> {
                 return print_minidump_dump(&dump, &mut output, cli.brief);
             }
 
+            // minidump-2-core mode
+            if elf_core {
+                return match elf_core::minidump_to_elf_core(&dump) {
+                    Ok(core) => output.write_all(&core),
+                    Err(err) => {
+                        error!("{} - Error converting dump: {}", err.name(), err);
+                        std::process::exit(1);
+                    }
+                };
+            }
+
+            // gdb server mode
+            if let Some(addr) = &cli.gdb_server {
+                return match gdb_server::GdbServer::new(&dump) {
+                    Ok(server) => server.listen(addr.as_str()),
+                    Err(err) => {
+                        error!("{} - Error serving dump: {}", err.name(), err);
+                        std::process::exit(1);
+                    }
+                };
+            }
+
+            // lint mode
+            if cli.lint {
+                let findings = dump.validate();
+                for finding in &findings {
+                    writeln!(output, "{finding}")?;
+                }
+                let errors = findings
+                    .iter()
+                    .filter(|finding| finding.severity() == validate::Severity::Error)
+                    .count();
+                writeln!(
+                    output,
+                    "{} errors, {} warnings",
+                    errors,
+                    findings.len() - errors
+                )?;
+                output.flush()?;
+                if errors > 0 {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            // redaction mode
+            if cli.redact {
+                let mut redact_options = redact::RedactionOptions::default();
+                if !cli.redact_key.is_empty() {
+                    redact_options.key_patterns = cli.redact_key;
+                }
+                let (builder, report) = redact::redact(&dump, &redact_options);
+                return match builder.build() {
+                    Ok(redacted) => {
+                        output.write_all(&redacted)?;
+                        report.print(&mut std::io::stderr())
+                    }
+                    Err(err) => {
+                        error!("{} - Error redacting dump: {}", err.name(), err);
+                        std::process::exit(1);
+                    }
+                };
+            }
+
             let mut provider = MultiSymbolProvider::new();
 
             if cli.use_local_debuginfo {
@@ -490,10 +678,14 @@ async fn main_result() -> std::io::Result<()> {
 
                     // Print the json output if requested (using "cyborg" output if available).
                     if json {
-                        if let Some(mut cyborg_output_f) = cyborg_output_f {
-                            state.print_json(&mut cyborg_output_f, cli.pretty)?;
+                        let mut json_output: &mut dyn Write = match &mut cyborg_output_f {
+                            Some(cyborg_output_f) => cyborg_output_f,
+                            None => &mut output,
+                        };
+                        if cli.float_registers {
+                            state.print_json_with_float_registers(&mut json_output, cli.pretty)?;
                         } else {
-                            state.print_json(&mut output, cli.pretty)?;
+                            state.print_json(&mut json_output, cli.pretty)?;
                         }
                     }
                     Ok(())
@@ -511,6 +703,35 @@ async fn main_result() -> std::io::Result<()> {
     }
 }
 
+/// The contents of the file being analyzed: either a minidump mapped straight
+/// from disk, or a minidump converted from an ELF core file.
+enum DumpBytes {
+    Mapped(Mmap),
+    Converted(Vec<u8>),
+}
+
+impl Deref for DumpBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            DumpBytes::Mapped(mmap) => mmap,
+            DumpBytes::Converted(bytes) => bytes,
+        }
+    }
+}
+
+fn read_dump<'a>(path: &Path) -> Result<Minidump<'a, DumpBytes>, Error> {
+    let f = File::open(path).or(Err(Error::FileNotFound))?;
+    let mmap = unsafe { Mmap::map(&f).or(Err(Error::IoError))? };
+    if mmap.starts_with(b"\x7fELF") {
+        let converted = elf_core::ElfCore::read(&mmap[..])?.to_minidump_bytes()?;
+        Minidump::read(DumpBytes::Converted(converted))
+    } else {
+        Minidump::read(DumpBytes::Mapped(mmap))
+    }
+}
+
 fn print_help_markdown(out: &mut dyn Write) -> std::io::Result<()> {
     let app_name = "minidump-stackwalk";
     let pretty_app_name = "minidump-stackwalk";
@@ -676,6 +897,21 @@ where
     if let Ok(handles) = dump.get_stream::<MinidumpHandleDataStream>() {
         handles.print(output)?;
     }
+    if let Ok(handle_operations) = dump.get_stream::<MinidumpHandleOperationList>() {
+        handle_operations.print(output)?;
+    }
+    if let Ok(tokens) = dump.get_stream::<MinidumpTokenList>() {
+        tokens.print(output)?;
+    }
+    if let Ok(function_tables) = dump.get_stream::<MinidumpFunctionTableList>() {
+        function_tables.print(output)?;
+    }
+    if let Ok(auxv) = dump.get_stream::<MinidumpLinuxAuxv>() {
+        auxv.print(output, unified_memory.as_ref())?;
+    }
+    if let Ok(dso_debug) = dump.get_stream::<MinidumpLinuxDsoDebug>() {
+        dso_debug.print(output)?;
+    }
     if let Some(memory_list) = unified_memory {
         memory_list.print(output, brief)?;
     }
@@ -700,9 +936,18 @@ where
     if let Some(misc_info) = misc_info {
         misc_info.print(output)?;
     }
+    if let Ok(system_memory_info) = dump.get_stream::<MinidumpSystemMemoryInfo>() {
+        system_memory_info.print(output)?;
+    }
+    if let Ok(vm_counters) = dump.get_stream::<MinidumpProcessVmCounters>() {
+        vm_counters.print(output)?;
+    }
     if let Ok(thread_names) = dump.get_stream::<MinidumpThreadNames>() {
         thread_names.print(output)?;
     }
+    if let Ok(thread_infos) = dump.get_stream::<MinidumpThreadInfoList>() {
+        thread_infos.print(output)?;
+    }
     if let Ok(breakpad_info) = dump.get_stream::<MinidumpBreakpadInfo>() {
         breakpad_info.print(output)?;
     }
@@ -717,6 +962,9 @@ where
     if let Ok(mac_bootargs) = dump.get_stream::<MinidumpMacBootargs>() {
         mac_bootargs.print(output)?;
     }
+    if let Some(comment) = dump.get_comment() {
+        comment.print(output)?;
+    }
 
     // Handle Linux streams that are just a dump of some system "file".
     macro_rules! streams {
@@ -743,7 +991,8 @@ where
         LinuxMaps,
         MozLinuxLimits
     ) {
-        if let Ok(contents) = dump.get_raw_stream(stream as u32) {
+        // Show every copy of a stream, in case the minidump has several.
+        for contents in dump.get_raw_streams(stream as u32).flatten() {
             print_raw_stream(name, contents, output)?;
         }
     }
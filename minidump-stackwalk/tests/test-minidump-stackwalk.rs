@@ -648,6 +648,48 @@ fn test_brief_robots() {
     assert!(!stderr.is_empty());
 }
 
+#[test]
+fn test_dump_duplicate_streams() {
+    let cmd_line = |bytes: &[u8]| SimpleStream {
+        stream_type: minidump_common::format::MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+        section: Section::new().append_bytes(bytes),
+    };
+    let dump = minimal_minidump()
+        .add_stream(cmd_line(b"app\0--first\0"))
+        .add_stream(cmd_line(b"app\0--second\0"))
+        .finish()
+        .unwrap();
+    let dump_path = test_output("duplicate-streams.dmp");
+    File::create(&dump_path).unwrap().write_all(&dump).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--dump")
+        .arg(&dump_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(stdout.matches("(LinuxCmdLine)\n").count(), 2, "{}", stdout);
+    assert_eq!(
+        stdout.matches("(LinuxCmdLine) at index").count(),
+        2,
+        "{}",
+        stdout
+    );
+    let first = stdout
+        .find("Stream LinuxCmdLine:\napp\\0\n--first")
+        .unwrap();
+    let second = stdout
+        .find("Stream LinuxCmdLine:\napp\\0\n--second")
+        .unwrap();
+    assert!(first < second);
+}
+
 fn minimal_minidump() -> SynthMinidump {
     let context = minidump_synth::x86_context(Endian::Little, 0xf00800, 0x1010);
     let stack = Memory::with_section(
@@ -805,6 +847,344 @@ fn test_unloaded() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn test_elf_core() {
+    // ELF core files are converted to minidumps and processed as usual.
+    let stack = Section::with_endian(Endian::Little);
+    stack.start().set_const(0x7ffe_0000);
+    let stack = stack.append_repeated(0, 0x100);
+    let core = SynthElfCore::new(EM_X86_64)
+        .add_thread(1234, 11, amd64_user_regs(0x4000_0810, 0x7ffe_0008, 0))
+        .add_siginfo(11, 1, 0xdead_beef)
+        .add_memory(0x1000, PF_R | PF_W, stack)
+        .finish()
+        .unwrap();
+    let core_path = test_output("elf-core.core");
+    File::create(&core_path).unwrap().write_all(&core).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg(&core_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stdout.contains(r#""os":"Linux""#), "{}", stdout);
+    assert!(
+        stdout.contains(r#""type":"SIGSEGV / SEGV_MAPERR""#),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains(r#""address":"0x00000000deadbeef""#),
+        "{}",
+        stdout
+    );
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_elf_core_output() {
+    let stack = Section::with_endian(Endian::Little);
+    stack.start().set_const(0x7ffe_0000);
+    let stack = stack.append_repeated(0xab, 0x100);
+    let core = SynthElfCore::new(EM_X86_64)
+        .add_thread(1234, 11, amd64_user_regs(0x4000_0810, 0x7ffe_0008, 0))
+        .add_siginfo(11, 1, 0xdead_beef)
+        .add_memory(0x1000, PF_R | PF_W, stack)
+        .finish()
+        .unwrap();
+    let core_path = test_output("elf-core-input.core");
+    File::create(&core_path).unwrap().write_all(&core).unwrap();
+    let output_path = test_output("elf-core-output.core");
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--elf-core")
+        .arg("--output-file")
+        .arg(&output_path)
+        .arg(&core_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(stderr, "");
+
+    let core = minidump::elf_core::ElfCore::read_path(&output_path).unwrap();
+    let threads = core.threads();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].thread_id, 1234);
+    assert_eq!(threads[0].signal, 11);
+    assert_eq!(threads[0].context.get_instruction_pointer(), 0x4000_0810);
+    assert_eq!(
+        core.memory_at_address(0x7ffe_0000, 0x100),
+        Some(&[0xab; 0x100][..])
+    );
+}
+
+#[test]
+fn test_elf_core_unsupported() {
+    let dump_path = test_output("elf-core-x86.dmp");
+    let dump = minimal_minidump().finish().unwrap();
+    File::create(&dump_path).unwrap().write_all(&dump).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--elf-core")
+        .arg(&dump_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("UnsupportedElfCore"), "{}", stderr);
+}
+
+#[test]
+fn test_redact() {
+    let heap = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0xcd, 0x20),
+        0x10000,
+    );
+    let dump = minimal_minidump()
+        .add_memory(heap)
+        .set_linux_environ(b"HOME=/home/alice\0LANG=C\0")
+        .set_linux_cmd_line(b"app\0--password\0hunter2\0")
+        .finish()
+        .unwrap();
+    let dump_path = test_output("redact-input.dmp");
+    File::create(&dump_path).unwrap().write_all(&dump).unwrap();
+    let output_path = test_output("redact-output.dmp");
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--redact")
+        .arg("--redact-key")
+        .arg("home")
+        .arg("--output-file")
+        .arg(&output_path)
+        .arg(&dump_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(
+        stderr,
+        "zeroed 32 bytes of memory in 1 regions\n\
+         removed the command line\n\
+         removed environment variable HOME\n"
+    );
+
+    let dump = minidump::Minidump::read_path(&output_path).unwrap();
+    let environ = dump.get_stream::<minidump::MinidumpLinuxEnviron>().unwrap();
+    assert_eq!(&environ.raw_bytes()[..], b"LANG=C\0");
+    assert!(dump.get_stream::<minidump::MinidumpLinuxCmdLine>().is_err());
+    let memory = dump.get_stream::<minidump::MinidumpMemoryList>().unwrap();
+    let heap = memory.memory_at_address(0x10000).unwrap();
+    assert_eq!(heap.bytes, &[0; 0x20][..]);
+    let threads = dump.get_stream::<minidump::MinidumpThreadList>().unwrap();
+    assert_eq!(threads.threads[0].raw.thread_id, 0x1234);
+}
+
+#[test]
+fn test_lint() {
+    let dump_path = test_output("lint.dmp");
+    let dump = minimal_minidump().finish().unwrap();
+    File::create(&dump_path).unwrap().write_all(&dump).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--lint")
+        .arg(&dump_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(stdout, "0 errors, 0 warnings\n");
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_lint_errors() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--lint")
+        .arg("../testdata/invalid-range.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    insta::assert_snapshot!("lint-invalid-range", stdout);
+}
+
+/// A minimal client for the GDB remote serial protocol.
+struct GdbClient {
+    stream: std::net::TcpStream,
+    ack: bool,
+}
+
+impl GdbClient {
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn send(&mut self, packet: &str) -> String {
+        let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${packet}#{checksum:02x}").unwrap();
+        if self.ack {
+            assert_eq!(self.read_byte(), b'+');
+        }
+        assert_eq!(self.read_byte(), b'$');
+        let mut reply = vec![];
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => reply.push(byte),
+            }
+        }
+        self.read_byte();
+        self.read_byte();
+        if self.ack {
+            self.stream.write_all(b"+").unwrap();
+        }
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+#[test]
+fn test_gdb_server() {
+    use minidump_common::format::{PlatformId, ProcessorArchitecture};
+
+    let context = minidump_synth::amd64_context(Endian::Little, 0x4000_0810, 0x7ffe_0008);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D64(0x0123_4567_89ab_cdef)
+            .append_repeated(0, 0xf8),
+        0x7ffe_0000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let name = DumpString::new("/usr/lib/libfoo.so", Endian::Little);
+    let module = Module::new(Endian::Little, 0x4000_0000, 0x3000, &name, 0, 0, None);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16)
+        .set_platform_id(PlatformId::Linux as u32);
+    let mut exception = Exception::new(Endian::Little);
+    exception.thread_id = 0x1234;
+    exception.exception_record.exception_code = 11;
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_exception(exception)
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add_memory(stack)
+        .finish()
+        .unwrap();
+    let dump_path = test_output("gdb-server.dmp");
+    File::create(&dump_path).unwrap().write_all(&dump).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let mut server = Command::new(bin)
+        .arg("--gdb-server")
+        .arg("127.0.0.1:0")
+        .arg("--verbose=info")
+        .arg("--no-color")
+        .arg(&dump_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stderr = BufReader::new(server.stderr.take().unwrap());
+    let mut line = String::new();
+    std::io::BufRead::read_line(&mut stderr, &mut line).unwrap();
+    let addr = line
+        .trim()
+        .split("Listening for gdb connections on ")
+        .nth(1)
+        .unwrap_or_else(|| panic!("unexpected output: {}", line));
+    let mut client = GdbClient {
+        stream: std::net::TcpStream::connect(addr).unwrap(),
+        ack: true,
+    };
+
+    let supported = client.send("qSupported:multiprocess+;xmlRegisters=i386");
+    assert!(supported.contains("qXfer:features:read+"), "{}", supported);
+    assert!(supported.contains("qXfer:libraries:read+"), "{}", supported);
+    assert_eq!(client.send("QStartNoAckMode"), "OK");
+    client.ack = false;
+
+    let target = client.send("qXfer:features:read:target.xml:0,3fff");
+    assert!(target.starts_with("l<?xml"), "{}", target);
+    assert!(
+        target.contains("<architecture>i386:x86-64</architecture>"),
+        "{}",
+        target
+    );
+
+    assert_eq!(client.send("?"), "T0bthread:1234;");
+    assert_eq!(client.send("qfThreadInfo"), "m1234");
+    assert_eq!(client.send("qsThreadInfo"), "l");
+    assert_eq!(client.send("qC"), "QC1234");
+    assert_eq!(client.send("Hg1234"), "OK");
+    assert_eq!(client.send("Hg5678"), "E16");
+
+    // rsp and rip are registers 7 and 16.
+    let registers = client.send("g");
+    assert_eq!(&registers[7 * 16..8 * 16], "0800fe7f00000000");
+    assert_eq!(&registers[16 * 16..17 * 16], "1008004000000000");
+    assert_eq!(client.send("p10"), "1008004000000000");
+    // The x87 registers are all empty, and the last instruction pointers aren't
+    // in the minidump.
+    assert_eq!(client.send("p22"), "ffff0000");
+    assert_eq!(client.send("p23"), "xxxxxxxx");
+
+    assert_eq!(client.send("m7ffe0000,8"), "efcdab8967452301");
+    // Reads stop at the end of the memory in the dump.
+    assert_eq!(client.send("m7ffe00fe,4"), "0000");
+    assert_eq!(client.send("m1000,4"), "E0e");
+
+    assert_eq!(
+        client.send("qXfer:libraries:read::0,3fff"),
+        "l<library-list>\n  <library name=\"/usr/lib/libfoo.so\">\
+         <segment address=\"0x40000000\"/></library>\n</library-list>\n"
+    );
+
+    // Nothing can run.
+    assert_eq!(client.send("c"), "E16");
+    assert_eq!(client.send("vMustReplyEmpty"), "");
+
+    // Killing the process stops the server.
+    write!(client.stream, "$k#6b").unwrap();
+    assert!(server.wait().unwrap().success());
+}
+
 #[test]
 fn test_macos_inlines_json_pretty() {
     // For a while this didn't parse right
@@ -65,6 +65,8 @@ pub struct SynthMinidump {
     linux_cpu_info: Option<SimpleStream>,
     /// /proc/self/environ string
     linux_environ: Option<SimpleStream>,
+    /// /proc/self/cmdline string
+    linux_cmd_line: Option<SimpleStream>,
     /// /proc/self/status string
     linux_proc_status: Option<SimpleStream>,
     /// /proc/self/limits string
@@ -73,6 +75,10 @@ pub struct SynthMinidump {
     memory64_section: Option<Section>,
     /// List of handles in this minidump.
     handle_data_stream: Option<ExListStream<HandleDescriptor>>,
+    /// List of extra info about threads in this minidump.
+    thread_info_list: Option<ExListStream<ThreadInfo>>,
+    /// List of handle operations in this minidump.
+    handle_operation_list: Option<ExListStream<HandleOperation>>,
 }
 
 /// A block of data contained in a minidump.
@@ -225,6 +231,7 @@ impl SynthMinidump {
             linux_maps: None,
             linux_lsb_release: None,
             linux_environ: None,
+            linux_cmd_line: None,
             linux_cpu_info: None,
             linux_proc_status: None,
             linux_proc_limits: None,
@@ -236,6 +243,17 @@ impl SynthMinidump {
                 mem::size_of::<md::MINIDUMP_HANDLE_DESCRIPTOR>(),
                 endian,
             )),
+            thread_info_list: Some(ExListStream::new(
+                md::MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
+                mem::size_of::<md::MINIDUMP_THREAD_INFO>(),
+                endian,
+            )),
+            handle_operation_list: Some(ExListStream::new_with_header_size(
+                md::MINIDUMP_STREAM_TYPE::HandleOperationListStream,
+                mem::size_of::<md::MINIDUMP_HANDLE_OPERATION_LIST>(),
+                mem::size_of::<md::AVRF_HANDLE_OPERATION>(),
+                endian,
+            )),
         }
     }
 
@@ -328,6 +346,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Add `info` to `self`, adding it to the thread info list stream as well.
+    pub fn add_thread_info(mut self, info: ThreadInfo) -> SynthMinidump {
+        self.thread_info_list = self
+            .thread_info_list
+            .take()
+            .map(|info_list| info_list.add(info));
+        self
+    }
+
     /// Add `handle` to `self`, adding it to the handle data stream as well.
     pub fn add_handle_descriptor(mut self, handle: HandleDescriptor) -> SynthMinidump {
         self.handle_data_stream = self
@@ -337,6 +364,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Add `operation` to `self`, adding it to the handle operation list stream as well.
+    pub fn add_handle_operation(mut self, operation: HandleOperation) -> SynthMinidump {
+        self.handle_operation_list = self
+            .handle_operation_list
+            .take()
+            .map(|operation_list| operation_list.add(operation));
+        self
+    }
+
     /// Add crashpad module and annotation extension information.
     pub fn add_crashpad_info(mut self, crashpad_info: CrashpadInfo) -> Self {
         self.crashpad_info = Some(crashpad_info);
@@ -409,6 +445,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Set the contents of the `LinuxCmdLine` stream.
+    pub fn set_linux_cmd_line(mut self, cmd_line: &[u8]) -> SynthMinidump {
+        self.linux_cmd_line = Some(SimpleStream {
+            stream_type: md::MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+            section: Section::new().append_bytes(cmd_line),
+        });
+        self
+    }
+
     /// Append `stream` to `self`, setting its location appropriately and adding it to the stream directory.
     pub fn add_stream<T: Stream>(mut self, stream: T) -> SynthMinidump {
         self.stream_directory = stream.cite_stream_in(self.stream_directory);
@@ -468,6 +513,9 @@ impl SynthMinidump {
         // Add thread names stream if any names were added.
         let thread_names = self.thread_names_list.take();
         self = self.finish_list(thread_names);
+        // Add thread info list stream if any thread infos were added.
+        let thread_infos = self.thread_info_list.take();
+        self = self.finish_ex_list(thread_infos);
         // Add crashpad info stream if any.
         if let Some(crashpad_info) = self.crashpad_info.take() {
             self = self.add_stream(crashpad_info);
@@ -496,12 +544,18 @@ impl SynthMinidump {
         if let Some(stream) = self.linux_environ.take() {
             self = self.add_stream(stream);
         }
+        if let Some(stream) = self.linux_cmd_line.take() {
+            self = self.add_stream(stream);
+        }
         if let Some(memory64_section) = self.memory64_section.take() {
             self = self.add(memory64_section);
         }
         // Add the handle data stream if any handle descriptors were added.
         let handle_data = self.handle_data_stream.take();
         self = self.finish_ex_list(handle_data);
+        // Add the handle operation list stream if any operations were added.
+        let handle_operations = self.handle_operation_list.take();
+        self = self.finish_ex_list(handle_operations);
 
         let SynthMinidump {
             section,
@@ -1219,6 +1273,46 @@ impl From<MemoryInfo> for Section {
     }
 }
 
+/// A minidump thread information element.
+pub struct ThreadInfo {
+    section: Section,
+}
+
+impl ThreadInfo {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        endian: Endian,
+        thread_id: u32,
+        dump_flags: u32,
+        create_time: u64,
+        exit_time: u64,
+        kernel_time: u64,
+        user_time: u64,
+        start_address: u64,
+    ) -> ThreadInfo {
+        let section = Section::with_endian(endian)
+            .D32(thread_id)
+            .D32(dump_flags)
+            .D32(0) // dump_error
+            .D32(0) // exit_status
+            .D64(create_time)
+            .D64(exit_time)
+            .D64(kernel_time)
+            .D64(user_time)
+            .D64(start_address)
+            .D64(0); // affinity
+        ThreadInfo { section }
+    }
+}
+
+impl_dumpsection!(ThreadInfo);
+
+impl From<ThreadInfo> for Section {
+    fn from(info: ThreadInfo) -> Self {
+        info.section
+    }
+}
+
 /// A minidump handle descriptor.
 pub struct HandleDescriptor {
     section: Section,
@@ -1258,6 +1352,47 @@ impl From<HandleDescriptor> for Section {
     }
 }
 
+/// A handle operation, as recorded by handle tracing.
+pub struct HandleOperation {
+    section: Section,
+}
+
+impl HandleOperation {
+    pub fn new(
+        endian: Endian,
+        handle: u64,
+        thread_id: u32,
+        operation_type: md::eHANDLE_TRACE_OPERATIONS,
+        backtrace: &[u64],
+    ) -> HandleOperation {
+        assert!(backtrace.len() <= md::AVRF_MAX_TRACES);
+        let section = Section::with_endian(endian)
+            .D64(handle)
+            .D32(0) // process_id
+            .D32(thread_id)
+            .D32(operation_type as u32)
+            .D32(0) // spare0
+            .D32(backtrace.len() as u32)
+            .D32(0); // index
+        let section = backtrace
+            .iter()
+            .fold(section, |section, &address| section.D64(address))
+            .append_repeated(
+                0,
+                mem::size_of::<u64>() * (md::AVRF_MAX_TRACES - backtrace.len()),
+            );
+        HandleOperation { section }
+    }
+}
+
+impl_dumpsection!(HandleOperation);
+
+impl From<HandleOperation> for Section {
+    fn from(operation: HandleOperation) -> Self {
+        operation.section
+    }
+}
+
 /// MINIDUMP_MISC_INFO stream.
 ///
 /// Fields that must be initialized together (i.e. because they are guarded
@@ -1529,6 +1664,249 @@ impl Stream for MiscStream {
     }
 }
 
+/// A MINIDUMP_SYSTEM_MEMORY_INFO_1 stream.
+///
+/// Only the fields describing physical memory and the commit charge can be
+/// set, everything else is zeroed.
+pub struct SystemMemoryInfo {
+    section: Section,
+    /// The size of a page, in bytes
+    pub page_size: u32,
+    /// The amount of physical memory, in pages
+    pub number_of_physical_pages: u32,
+    /// The 32-bit commit counters that are always present, in pages
+    pub perf_info: SystemMemoryFieldsCommit<u32>,
+    /// The 64-bit commit counters guarded by MINIDUMP_SYSMEMINFO1_BASICPERF, in pages
+    pub basic_perf_info: Option<SystemMemoryFieldsCommit<u64>>,
+}
+
+/// SYSTEM_MEMORY_INFO fields describing the commit charge, in pages
+#[derive(Default)]
+pub struct SystemMemoryFieldsCommit<T> {
+    pub available_pages: T,
+    pub committed_pages: T,
+    pub commit_limit: T,
+    pub peak_commitment: T,
+}
+
+impl SystemMemoryInfo {
+    pub fn new(endian: Endian) -> SystemMemoryInfo {
+        SystemMemoryInfo {
+            section: Section::with_endian(endian),
+            page_size: 0x1000,
+            number_of_physical_pages: 0,
+            perf_info: Default::default(),
+            basic_perf_info: None,
+        }
+    }
+}
+
+impl From<SystemMemoryInfo> for Section {
+    fn from(info: SystemMemoryInfo) -> Self {
+        let mut flags = md::SystemMemoryInfoFlags::empty();
+        if info.basic_perf_info.is_some() {
+            flags |= md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF;
+        }
+        let basic_perf_info = info.basic_perf_info.unwrap_or_default();
+        let perf_info = info.perf_info;
+
+        let section = info
+            .section
+            .D16(1) // revision
+            .D16(flags.bits())
+            // basic_info
+            .D32(0) // timer_resolution
+            .D32(info.page_size)
+            .D32(info.number_of_physical_pages)
+            .append_repeated(0, mem::size_of::<u32>() * 3) // lowest/highest page, granularity
+            .append_repeated(0, mem::size_of::<u64>() * 3) // user mode addresses, affinity
+            .D32(0) // number_of_processors
+            // file_cache_info
+            .append_repeated(0, md::MINIDUMP_SYSTEM_FILECACHE_INFORMATION::size_with(&LE))
+            // basic_perf_info
+            .D64(basic_perf_info.available_pages)
+            .D64(basic_perf_info.committed_pages)
+            .D64(basic_perf_info.commit_limit)
+            .D64(basic_perf_info.peak_commitment)
+            // perf_info
+            .append_repeated(0, mem::size_of::<u64>() * 4) // idle time, io transfers
+            .append_repeated(0, mem::size_of::<u32>() * 3) // io operations
+            .D32(perf_info.available_pages)
+            .D32(perf_info.committed_pages)
+            .D32(perf_info.commit_limit)
+            .D32(perf_info.peak_commitment)
+            .append_repeated(0, mem::size_of::<u32>() * 63) // page_fault_count..system_calls
+            .append_repeated(0, mem::size_of::<u64>() * 4); // dirty pages, shared commit
+        assert_eq!(
+            section.size(),
+            md::MINIDUMP_SYSTEM_MEMORY_INFO_1::size_with(&LE) as u64
+        );
+        section
+    }
+}
+
+impl_dumpsection!(SystemMemoryInfo);
+
+impl Stream for SystemMemoryInfo {
+    fn stream_type(&self) -> u32 {
+        md::MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32
+    }
+}
+
+/// A MINIDUMP_PROCESS_VM_COUNTERS stream.
+///
+/// Fields that must be initialized together (i.e. because they are guarded
+/// by the same flag) are grouped under substructs to enforce this. If any of
+/// the fields only found in MINIDUMP_PROCESS_VM_COUNTERS_2 are set, that
+/// version will be emitted, otherwise MINIDUMP_PROCESS_VM_COUNTERS_1 is.
+pub struct ProcessVmCounters {
+    section: Section,
+
+    /// Fields guarded by MINIDUMP_PROCESS_VM_COUNTERS
+    pub counters: Option<VmCountersFields>,
+    /// Field guarded by MINIDUMP_PROCESS_VM_COUNTERS_EX
+    pub private_usage: Option<u64>,
+
+    /// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE
+    pub virtual_size: Option<VmCountersFieldsVirtualSize>,
+    /// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_EX2
+    pub ex2: Option<VmCountersFieldsEx2>,
+    /// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_JOB
+    pub job: Option<VmCountersFieldsJob>,
+}
+
+/// VM_COUNTERS fields guarded by MINIDUMP_PROCESS_VM_COUNTERS
+#[derive(Default)]
+pub struct VmCountersFields {
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+}
+
+/// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE
+#[derive(Default)]
+pub struct VmCountersFieldsVirtualSize {
+    pub peak_virtual_size: u64,
+    pub virtual_size: u64,
+}
+
+/// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_EX2
+#[derive(Default)]
+pub struct VmCountersFieldsEx2 {
+    pub private_working_set_size: u64,
+    pub shared_commit_usage: u64,
+}
+
+/// VM_COUNTERS_2 fields guarded by MINIDUMP_PROCESS_VM_COUNTERS_JOB
+#[derive(Default)]
+pub struct VmCountersFieldsJob {
+    pub job_shared_commit_usage: u64,
+    pub job_private_commit_usage: u64,
+    pub job_peak_private_commit_usage: u64,
+    pub job_private_commit_limit: u64,
+    pub job_total_commit_limit: u64,
+}
+
+impl ProcessVmCounters {
+    pub fn new(endian: Endian) -> ProcessVmCounters {
+        ProcessVmCounters {
+            section: Section::with_endian(endian),
+            counters: None,
+            private_usage: None,
+            virtual_size: None,
+            ex2: None,
+            job: None,
+        }
+    }
+}
+
+impl From<ProcessVmCounters> for Section {
+    fn from(stream: ProcessVmCounters) -> Self {
+        let ProcessVmCounters {
+            section,
+            counters,
+            private_usage,
+            virtual_size,
+            ex2,
+            job,
+        } = stream;
+
+        // Derive the flags and version we'll be using.
+        let mut flags = md::ProcessVmCountersFlags::empty();
+        if counters.is_some() {
+            flags |= md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS;
+        }
+        if private_usage.is_some() {
+            flags |= md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX;
+        }
+        if virtual_size.is_some() {
+            flags |= md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE;
+        }
+        if ex2.is_some() {
+            flags |= md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX2;
+        }
+        if job.is_some() {
+            flags |= md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_JOB;
+        }
+        let version = if virtual_size.is_some() || ex2.is_some() || job.is_some() {
+            2
+        } else {
+            1
+        };
+
+        // Now emit all the fields for that version, leaning on Default to fill
+        // in values that are None.
+        let counters = counters.unwrap_or_default();
+        let private_usage = private_usage.unwrap_or_default();
+        let mut section = section
+            .D16(version)
+            .D16(if version >= 2 { flags.bits() } else { 0 })
+            .D32(counters.page_fault_count)
+            .D64(counters.peak_working_set_size)
+            .D64(counters.working_set_size)
+            .D64(counters.quota_peak_paged_pool_usage)
+            .D64(counters.quota_paged_pool_usage)
+            .D64(counters.quota_peak_non_paged_pool_usage)
+            .D64(counters.quota_non_paged_pool_usage)
+            .D64(counters.pagefile_usage)
+            .D64(counters.peak_pagefile_usage);
+
+        if version >= 2 {
+            let virtual_size = virtual_size.unwrap_or_default();
+            let ex2 = ex2.unwrap_or_default();
+            let job = job.unwrap_or_default();
+            section = section
+                .D64(virtual_size.peak_virtual_size)
+                .D64(virtual_size.virtual_size)
+                .D64(private_usage)
+                .D64(ex2.private_working_set_size)
+                .D64(ex2.shared_commit_usage)
+                .D64(job.job_shared_commit_usage)
+                .D64(job.job_private_commit_usage)
+                .D64(job.job_peak_private_commit_usage)
+                .D64(job.job_private_commit_limit)
+                .D64(job.job_total_commit_limit);
+        } else {
+            section = section.D64(private_usage);
+        }
+        section
+    }
+}
+
+impl_dumpsection!(ProcessVmCounters);
+
+impl Stream for ProcessVmCounters {
+    fn stream_type(&self) -> u32 {
+        md::MINIDUMP_STREAM_TYPE::ProcessVmCountersStream as u32
+    }
+}
+
 /// Populate a `CONTEXT_X86` struct with the given `endian`, `eip`, and `esp`.
 pub fn x86_context(endian: Endian, eip: u32, esp: u32) -> Section {
     let section = Section::with_endian(endian)
@@ -1546,6 +1924,247 @@ pub fn x86_context(endian: Endian, eip: u32, esp: u32) -> Section {
     section
 }
 
+/// A MINIDUMP_TOKEN_INFO_LIST stream.
+pub struct TokenList {
+    section: Section,
+    endian: Endian,
+    tokens: Vec<(u32, u64, Section)>,
+}
+
+impl TokenList {
+    pub fn new(endian: Endian) -> TokenList {
+        TokenList {
+            section: Section::with_endian(endian),
+            endian,
+            tokens: vec![],
+        }
+    }
+
+    /// Add a token with the given id and handle, with `data` as its information.
+    pub fn add_token(mut self, token_id: u32, token_handle: u64, data: impl Into<Section>) -> Self {
+        self.tokens.push((token_id, token_handle, data.into()));
+        self
+    }
+}
+
+impl From<TokenList> for Section {
+    fn from(list: TokenList) -> Self {
+        let header_size = md::MINIDUMP_TOKEN_INFO_LIST::size_with(&LE) as u32;
+        let element_header_size = md::MINIDUMP_TOKEN_INFO_HEADER::size_with(&LE) as u32;
+        let count = list.tokens.len() as u32;
+        let tokens = list.tokens.into_iter().fold(
+            Section::with_endian(list.endian),
+            |section, (token_id, token_handle, data)| {
+                section
+                    .D32(element_header_size + data.size() as u32)
+                    .D32(token_id)
+                    .D64(token_handle)
+                    .append_section(data)
+            },
+        );
+        list.section
+            .D32(header_size + tokens.size() as u32)
+            .D32(count)
+            .D32(header_size)
+            .D32(element_header_size)
+            .append_section(tokens)
+    }
+}
+
+impl_dumpsection!(TokenList);
+
+impl Stream for TokenList {
+    fn stream_type(&self) -> u32 {
+        md::MINIDUMP_STREAM_TYPE::TokenStream as u32
+    }
+}
+
+/// A security identifier.
+#[derive(Clone, Debug)]
+pub struct Sid {
+    pub identifier_authority: u64,
+    pub sub_authorities: Vec<u32>,
+}
+
+impl Sid {
+    fn size(&self) -> usize {
+        8 + self.sub_authorities.len() * mem::size_of::<u32>()
+    }
+}
+
+/// The TOKEN_ACCESS_INFORMATION of a 64-bit process, for use with [`TokenList`].
+///
+/// The pointers in it are absolute, as if the structure was located at `base`.
+pub struct TokenAccessInformation {
+    section: Section,
+    pub base: u64,
+    pub authentication_id: u64,
+    pub token_type: u32,
+    pub impersonation_level: u32,
+    pub flags: u32,
+    /// The user and group SIDs with their attributes, the user comes first
+    pub sids: Vec<(Sid, u32)>,
+    /// The LUIDs of the privileges with their attributes
+    pub privileges: Vec<(u64, u32)>,
+}
+
+impl TokenAccessInformation {
+    pub fn new(endian: Endian) -> TokenAccessInformation {
+        TokenAccessInformation {
+            section: Section::with_endian(endian),
+            base: 0x1000,
+            authentication_id: 0,
+            token_type: 1,
+            impersonation_level: 0,
+            flags: 0,
+            sids: vec![],
+            privileges: vec![],
+        }
+    }
+}
+
+impl From<TokenAccessInformation> for Section {
+    fn from(info: TokenAccessInformation) -> Self {
+        // The structure is followed by everything it points to.
+        const FIXED_SIZE: u64 = 88;
+        const SID_HASH_SIZE: u64 = 16 + 32 * 8;
+        const SID_AND_ATTRIBUTES_SIZE: u64 = 16;
+        let sid_hash = info.base + FIXED_SIZE;
+        let sid_attrs = sid_hash + SID_HASH_SIZE;
+        let sids = sid_attrs + SID_AND_ATTRIBUTES_SIZE * info.sids.len() as u64;
+        let privileges = sids
+            + info
+                .sids
+                .iter()
+                .map(|(sid, _)| sid.size() as u64)
+                .sum::<u64>();
+
+        let section = info
+            .section
+            .D64(sid_hash)
+            .D64(0) // restricted_sid_hash
+            .D64(privileges)
+            .D32(info.authentication_id as u32)
+            .D32((info.authentication_id >> 32) as u32)
+            .D32(info.token_type)
+            .D32(info.impersonation_level)
+            .D32(0) // mandatory_policy
+            .D32(info.flags)
+            .D32(0) // app_container_number
+            .D32(0) // padding
+            .append_repeated(0, mem::size_of::<u64>() * 4); // package_sid..security_attributes
+        assert_eq!(section.size(), FIXED_SIZE);
+
+        let section = section
+            .D32(info.sids.len() as u32)
+            .D32(0) // padding
+            .D64(sid_attrs)
+            .append_repeated(0, mem::size_of::<u64>() * 32); // hash
+
+        let (section, _) =
+            info.sids
+                .iter()
+                .fold((section, sids), |(section, sid_ptr), (sid, attributes)| {
+                    let section = section.D64(sid_ptr).D32(*attributes).D32(0);
+                    (section, sid_ptr + sid.size() as u64)
+                });
+        let section = info.sids.iter().fold(section, |section, (sid, _)| {
+            let section = section
+                .D8(1) // revision
+                .D8(sid.sub_authorities.len() as u8)
+                .append_bytes(&sid.identifier_authority.to_be_bytes()[2..]);
+            sid.sub_authorities
+                .iter()
+                .fold(section, |section, &sub_authority| {
+                    section.D32(sub_authority)
+                })
+        });
+
+        let section = section.D32(info.privileges.len() as u32);
+        info.privileges
+            .iter()
+            .fold(section, |section, &(luid, attributes)| {
+                section
+                    .D32(luid as u32)
+                    .D32((luid >> 32) as u32)
+                    .D32(attributes)
+            })
+    }
+}
+
+/// A function table in a MINIDUMP_FUNCTION_TABLE_STREAM.
+pub struct FunctionTable {
+    pub minimum_address: u64,
+    pub maximum_address: u64,
+    pub base_address: u64,
+    /// The `(begin_address, end_address, unwind_info_address)` of each function.
+    pub functions: Vec<(u32, u32, u32)>,
+}
+
+/// A MINIDUMP_FUNCTION_TABLE_STREAM stream, with x86-64 function entries.
+pub struct FunctionTableList {
+    section: Section,
+    tables: Vec<FunctionTable>,
+}
+
+impl FunctionTableList {
+    pub fn new(endian: Endian) -> FunctionTableList {
+        FunctionTableList {
+            section: Section::with_endian(endian),
+            tables: vec![],
+        }
+    }
+
+    pub fn add_table(mut self, table: FunctionTable) -> Self {
+        self.tables.push(table);
+        self
+    }
+}
+
+impl From<FunctionTableList> for Section {
+    fn from(list: FunctionTableList) -> Self {
+        // The size of a DYNAMIC_FUNCTION_TABLE, whose contents we don't care about.
+        const NATIVE_DESCRIPTOR_SIZE: u32 = 88;
+        let header_size = md::MINIDUMP_FUNCTION_TABLE_STREAM::size_with(&LE) as u32;
+        let descriptor_size = md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR::size_with(&LE) as u32;
+        let function_size = md::IMAGE_RUNTIME_FUNCTION_ENTRY::size_with(&LE) as u32;
+        let section = list
+            .section
+            .D32(header_size)
+            .D32(descriptor_size)
+            .D32(NATIVE_DESCRIPTOR_SIZE)
+            .D32(function_size)
+            .D32(list.tables.len() as u32)
+            .D32(0);
+        list.tables.into_iter().fold(section, |section, table| {
+            let section = section
+                .D64(table.minimum_address)
+                .D64(table.maximum_address)
+                .D64(table.base_address)
+                .D32(table.functions.len() as u32)
+                .D32(0)
+                .append_repeated(0, NATIVE_DESCRIPTOR_SIZE as usize);
+            table.functions.into_iter().fold(
+                section,
+                |section, (begin_address, end_address, unwind_info_address)| {
+                    section
+                        .D32(begin_address)
+                        .D32(end_address)
+                        .D32(unwind_info_address)
+                },
+            )
+        })
+    }
+}
+
+impl_dumpsection!(FunctionTableList);
+
+impl Stream for FunctionTableList {
+    fn stream_type(&self) -> u32 {
+        md::MINIDUMP_STREAM_TYPE::FunctionTable as u32
+    }
+}
+
 /// Populate a `CONTEXT_AMD64` struct with the given `endian`, `rip`, and `rsp`.
 pub fn amd64_context(endian: Endian, rip: u64, rsp: u64) -> Section {
     let section = Section::with_endian(endian)
@@ -1566,6 +2185,71 @@ pub fn amd64_context(endian: Endian, rip: u64, rsp: u64) -> Section {
     section
 }
 
+/// Populate a `CONTEXT_AMD64` struct followed by AVX, AVX-512 and CET state,
+/// and return it along with the XSAVE layout describing it (for a
+/// `MINIDUMP_MISC_INFO_5`).
+///
+/// Every byte of xmm`n` is `0x10 + n`, every byte of the upper half of ymm`n`
+/// is `0x20 + n`, every byte of the upper half of zmm`n` is `0x30 + n` and every
+/// byte of zmm`16 + n` is `0x40 + n`. k`n` is `n`.
+pub fn amd64_context_with_xstate(
+    endian: Endian,
+    rip: u64,
+    rsp: u64,
+    ssp: u64,
+) -> (Section, md::XSTATE_CONFIG_FEATURE_MSC_INFO) {
+    use md::XstateFeatureIndex::*;
+
+    let layout = [
+        (LEGACY_FLOATING_POINT, 0, 160),
+        (LEGACY_SSE, 160, 256),
+        (GSSE_AND_AVX, 576, 256),
+        (AVX512_KMASK, 1088, 64),
+        (AVX512_ZMM_H, 1152, 512),
+        (ACK512_ZMM, 1664, 1024),
+        (XSTATE_CET_U, 2688, 16),
+    ];
+    let mut config = md::XSTATE_CONFIG_FEATURE_MSC_INFO::default();
+    for (feature, offset, size) in layout {
+        config.enabled_features |= 1 << feature as usize;
+        config.features[feature as usize] = md::XSTATE_FEATURE { offset, size };
+    }
+
+    // XSAVE areas are always little-endian.
+    let mut xsave = vec![0u8; 2704];
+    let mut fill = |offset: usize, size: usize, count: usize, base: u8| {
+        for i in 0..count {
+            let start = offset + i * size;
+            xsave[start..start + size].fill(base + i as u8);
+        }
+    };
+    fill(160, 16, 16, 0x10);
+    fill(576, 16, 16, 0x20);
+    fill(1152, 32, 16, 0x30);
+    fill(1664, 64, 16, 0x40);
+    for i in 0..8 {
+        xsave[1088 + i * 8..1096 + i * 8].copy_from_slice(&(i as u64).to_le_bytes());
+    }
+    xsave[512..520].copy_from_slice(&config.enabled_features.to_le_bytes());
+    xsave[2688..2696].copy_from_slice(&1u64.to_le_bytes()); // SH_STK_EN
+    xsave[2696..2704].copy_from_slice(&ssp.to_le_bytes());
+    config.context_size = 0x100 + xsave.len() as u32;
+
+    let section = Section::with_endian(endian)
+        .append_repeated(0, mem::size_of::<u64>() * 6) // p[1-6]_home
+        .D32(0x10005f) // context_flags: CONTEXT_AMD64_ALL | CONTEXT_XSTATE
+        .D32(0) // mx_csr
+        .append_repeated(0, mem::size_of::<u16>() * 6) // cs,ds,es,fs,gs,ss
+        .D32(0) // eflags
+        .append_repeated(0, mem::size_of::<u64>() * 6) // dr0,1,2,3,6,7
+        .append_repeated(0, mem::size_of::<u64>() * 4) // rax,rcx,rdx,rbx
+        .D64(rsp)
+        .append_repeated(0, mem::size_of::<u64>() * 11) // rbp-r15
+        .D64(rip)
+        .append_bytes(&xsave); // float_save and everything after it
+    (section, config)
+}
+
 /// Populate a `CONTEXT_ARM64` struct with the given `endian`, `pc`, and `sp`.
 pub fn arm64_context(endian: Endian, pc: u64, sp: u64) -> Section {
     let section = Section::with_endian(endian)
@@ -1585,6 +2269,44 @@ pub fn arm64_context(endian: Endian, pc: u64, sp: u64) -> Section {
     section
 }
 
+pub fn mips64_context(endian: Endian, pc: u64, sp: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(0x80000) // context_flags: CONTEXT_MIPS64
+        .D32(0) // _pad0
+        .append_repeated(0, mem::size_of::<u64>() * 29) // iregs[zero, at, ..., gp]
+        .D64(sp) // sp
+        .append_repeated(0, mem::size_of::<u64>() * 2) // iregs[fp, ra]
+        .D64(0) // mdhi
+        .D64(0) // mdlo
+        .append_repeated(0, mem::size_of::<u32>() * 3) // hi
+        .append_repeated(0, mem::size_of::<u32>() * 3) // lo
+        .D32(0) // dsp_control
+        .D32(0) // _pad1
+        .D64(pc) // epc
+        .D64(0) // badvaddr
+        .D32(0) // status
+        .D32(0) // cause
+        .append_repeated(0, mem::size_of::<u64>() * 32) // float_save.regs
+        .D32(0) // float_save.fpcsr
+        .D32(0); // float_save.fir
+    assert_eq!(section.size(), md::CONTEXT_MIPS::size_with(&LE) as u64);
+    section
+}
+
+pub fn riscv64_context(endian: Endian, pc: u64, sp: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(0x08000005) // context_flags: CONTEXT_RISCV64_FULL
+        .D32(1) // version
+        .D64(pc) // pc
+        .D64(0) // ra
+        .D64(sp) // sp
+        .append_repeated(0, mem::size_of::<u64>() * 29) // iregs[x3, x4, ..., x31]
+        .append_repeated(0, mem::size_of::<u64>() * 32) // float_save.regs[f0, f1, ..., f31]
+        .D32(0); // float_save.fpcsr
+    assert_eq!(section.size(), md::CONTEXT_RISCV64::size_with(&LE) as u64);
+    section
+}
+
 pub struct SectionRef {
     section: Section,
     data_section: Section,
@@ -2048,6 +2770,289 @@ impl Stream for Exception {
     }
 }
 
+/// The `e_machine` of an x86-64 ELF file.
+pub const EM_X86_64: u16 = 62;
+/// The `e_machine` of an ARM64 ELF file.
+pub const EM_AARCH64: u16 = 183;
+
+/// Executable segment flag for [`SynthElfCore::add_memory`].
+pub const PF_X: u32 = 1;
+/// Writable segment flag for [`SynthElfCore::add_memory`].
+pub const PF_W: u32 = 2;
+/// Readable segment flag for [`SynthElfCore::add_memory`].
+pub const PF_R: u32 = 4;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_SIGINFO: u32 = 0x53494749;
+const NT_FILE: u32 = 0x46494c45;
+const NT_GNU_BUILD_ID: u32 = 3;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const ELF_HEADER_SIZE: u64 = 64;
+const PROGRAM_HEADER_SIZE: u64 = 56;
+
+/// A writer of synthetic 64-bit little-endian ELF core files.
+///
+/// Notes are written in the order they're added, like the kernel does: the
+/// registers of the crashing thread come first, followed by the process-wide
+/// notes, followed by the registers of the other threads.
+pub struct SynthElfCore {
+    machine: u16,
+    notes: Section,
+    /// The address, size and permissions of each `PT_LOAD` segment, and the
+    /// part of its contents included in the core.
+    segments: Vec<(u64, u64, u32, Section)>,
+}
+
+impl SynthElfCore {
+    pub fn new(machine: u16) -> SynthElfCore {
+        SynthElfCore {
+            machine,
+            notes: Section::with_endian(Endian::Little),
+            segments: vec![],
+        }
+    }
+
+    /// Append a note of type `n_type` with name `name` and contents `desc`.
+    pub fn add_note(mut self, n_type: u32, name: &str, desc: Section) -> SynthElfCore {
+        let desc = desc.get_contents().unwrap();
+        self.notes = self
+            .notes
+            .D32(name.len() as u32 + 1) // n_namesz
+            .D32(desc.len() as u32) // n_descsz
+            .D32(n_type) // n_type
+            .append_bytes(name.as_bytes())
+            .D8(0)
+            .align(4)
+            .append_bytes(&desc)
+            .align(4);
+        self
+    }
+
+    /// Add an `NT_PRSTATUS` note for a thread, with `regs` as its `pr_reg`.
+    pub fn add_thread(self, pid: u32, signal: u16, regs: Section) -> SynthElfCore {
+        let desc = Section::with_endian(Endian::Little)
+            .D32(0) // si_signo
+            .D32(0) // si_code
+            .D32(0) // si_errno
+            .D16(signal) // pr_cursig
+            .D16(0) // padding
+            .D64(0) // pr_sigpend
+            .D64(0) // pr_sighold
+            .D32(pid) // pr_pid
+            .D32(0) // pr_ppid
+            .D32(0) // pr_pgrp
+            .D32(0) // pr_sid
+            .append_repeated(0, 16 * 4) // pr_utime, pr_stime, pr_cutime, pr_cstime
+            .append_section(regs) // pr_reg
+            .D32(1) // pr_fpvalid
+            .D32(0); // padding
+        self.add_note(NT_PRSTATUS, "CORE", desc)
+    }
+
+    /// Add an `NT_PRFPREG` note for the last thread.
+    pub fn add_fpregs(self, fpregs: Section) -> SynthElfCore {
+        self.add_note(NT_PRFPREG, "CORE", fpregs)
+    }
+
+    /// Add an `NT_PRPSINFO` note for the process.
+    pub fn add_psinfo(self, pid: u32, fname: &str) -> SynthElfCore {
+        let mut fname = fname.as_bytes().to_vec();
+        fname.resize(16, 0);
+        let desc = Section::with_endian(Endian::Little)
+            .append_repeated(0, 8) // pr_state, pr_sname, pr_zomb, pr_nice, padding
+            .D64(0) // pr_flag
+            .D32(0) // pr_uid
+            .D32(0) // pr_gid
+            .D32(pid) // pr_pid
+            .D32(0) // pr_ppid
+            .D32(0) // pr_pgrp
+            .D32(0) // pr_sid
+            .append_bytes(&fname) // pr_fname
+            .append_repeated(0, 80); // pr_psargs
+        self.add_note(NT_PRPSINFO, "CORE", desc)
+    }
+
+    /// Add an `NT_SIGINFO` note for the signal which caused the crash.
+    pub fn add_siginfo(self, signal: u32, code: u32, address: u64) -> SynthElfCore {
+        let desc = Section::with_endian(Endian::Little)
+            .D32(signal) // si_signo
+            .D32(0) // si_errno
+            .D32(code) // si_code
+            .D32(0) // padding
+            .D64(address) // si_addr
+            .append_repeated(0, 104);
+        self.add_note(NT_SIGINFO, "CORE", desc)
+    }
+
+    /// Add an `NT_AUXV` note with `(type, value)` entries.
+    pub fn add_auxv(self, entries: &[(u64, u64)]) -> SynthElfCore {
+        let mut desc = Section::with_endian(Endian::Little);
+        for &(auxv_type, value) in entries.iter().chain(&[(0, 0)]) {
+            desc = desc.D64(auxv_type).D64(value);
+        }
+        self.add_note(NT_AUXV, "CORE", desc)
+    }
+
+    /// Add an `NT_FILE` note with `(start, end, file offset, name)` entries.
+    pub fn add_files(self, files: &[(u64, u64, u64, &str)]) -> SynthElfCore {
+        const PAGE_SIZE: u64 = 4096;
+        let mut desc = Section::with_endian(Endian::Little)
+            .D64(files.len() as u64)
+            .D64(PAGE_SIZE);
+        for &(start, end, offset, _) in files {
+            desc = desc.D64(start).D64(end).D64(offset / PAGE_SIZE);
+        }
+        for &(_, _, _, name) in files {
+            desc = desc.append_bytes(name.as_bytes()).D8(0);
+        }
+        self.add_note(NT_FILE, "CORE", desc)
+    }
+
+    /// Add a `PT_LOAD` segment of `size` bytes at the start address of `contents`,
+    /// which may be smaller than the segment.
+    pub fn add_memory(mut self, size: u64, flags: u32, contents: Section) -> SynthElfCore {
+        let base = contents.start().value().unwrap();
+        self.segments.push((base, size, flags, contents));
+        self
+    }
+
+    pub fn finish(self) -> Option<Vec<u8>> {
+        let notes = self.notes.get_contents()?;
+        let phnum = self.segments.len() as u64 + 1;
+        let notes_offset = ELF_HEADER_SIZE + phnum * PROGRAM_HEADER_SIZE;
+        let mut data_offset = notes_offset + notes.len() as u64;
+
+        let mut section = Section::with_endian(Endian::Little)
+            .append_bytes(b"\x7fELF")
+            .D8(2) // EI_CLASS: ELFCLASS64
+            .D8(1) // EI_DATA: ELFDATA2LSB
+            .D8(1) // EI_VERSION
+            .append_repeated(0, 9) // EI_OSABI, EI_ABIVERSION, padding
+            .D16(4) // e_type: ET_CORE
+            .D16(self.machine) // e_machine
+            .D32(1) // e_version
+            .D64(0) // e_entry
+            .D64(ELF_HEADER_SIZE) // e_phoff
+            .D64(0) // e_shoff
+            .D32(0) // e_flags
+            .D16(ELF_HEADER_SIZE as u16) // e_ehsize
+            .D16(PROGRAM_HEADER_SIZE as u16) // e_phentsize
+            .D16(phnum as u16) // e_phnum
+            .D16(0) // e_shentsize
+            .D16(0) // e_shnum
+            .D16(0) // e_shstrndx
+            .D32(PT_NOTE)
+            .D32(0) // p_flags
+            .D64(notes_offset) // p_offset
+            .D64(0) // p_vaddr
+            .D64(0) // p_paddr
+            .D64(notes.len() as u64) // p_filesz
+            .D64(0) // p_memsz
+            .D64(4); // p_align
+        let mut contents = vec![];
+        for (base, size, flags, memory) in self.segments {
+            let memory = memory.get_contents()?;
+            section = section
+                .D32(PT_LOAD)
+                .D32(flags)
+                .D64(data_offset) // p_offset
+                .D64(base) // p_vaddr
+                .D64(0) // p_paddr
+                .D64(memory.len() as u64) // p_filesz
+                .D64(size) // p_memsz
+                .D64(4096); // p_align
+            data_offset += memory.len() as u64;
+            contents.push(memory);
+        }
+        section = section.append_bytes(&notes);
+        for memory in contents {
+            section = section.append_bytes(&memory);
+        }
+        section.get_contents()
+    }
+}
+
+/// The x86-64 `struct user_regs_struct`, with just `rip`, `rsp` and `rbp` set.
+pub fn amd64_user_regs(rip: u64, rsp: u64, rbp: u64) -> Section {
+    Section::with_endian(Endian::Little)
+        .append_repeated(0, 4 * 8) // r15, r14, r13, r12
+        .D64(rbp)
+        .append_repeated(0, 11 * 8) // rbx, r11, ..., rdi, orig_rax
+        .D64(rip)
+        .D64(0x33) // cs
+        .D64(0x246) // eflags
+        .D64(rsp)
+        .D64(0x2b) // ss
+        .append_repeated(0, 6 * 8) // fs_base, gs_base, ds, es, fs, gs
+}
+
+/// The ARM64 `struct user_pt_regs`, with just `pc`, `sp`, `fp` and `lr` set.
+pub fn arm64_user_regs(pc: u64, sp: u64, fp: u64, lr: u64) -> Section {
+    Section::with_endian(Endian::Little)
+        .append_repeated(0, 29 * 8) // x0, ..., x28
+        .D64(fp)
+        .D64(lr)
+        .D64(sp)
+        .D64(pc)
+        .D64(0) // pstate
+}
+
+/// The first page of an ELF shared library, with a build id note.
+///
+/// The headers claim that the library is mapped at address 0, so the memory
+/// can be placed anywhere.
+pub fn elf_headers_with_build_id(base: u64, build_id: &[u8]) -> Section {
+    let notes_offset = ELF_HEADER_SIZE + 2 * PROGRAM_HEADER_SIZE;
+    let notes_size = 16 + build_id.len() as u64;
+    let section = Section::with_endian(Endian::Little);
+    section.start().set_const(base);
+    section
+        .append_bytes(b"\x7fELF")
+        .D8(2) // EI_CLASS: ELFCLASS64
+        .D8(1) // EI_DATA: ELFDATA2LSB
+        .D8(1) // EI_VERSION
+        .append_repeated(0, 9) // EI_OSABI, EI_ABIVERSION, padding
+        .D16(3) // e_type: ET_DYN
+        .D16(EM_X86_64) // e_machine
+        .D32(1) // e_version
+        .D64(0) // e_entry
+        .D64(ELF_HEADER_SIZE) // e_phoff
+        .D64(0) // e_shoff
+        .D32(0) // e_flags
+        .D16(ELF_HEADER_SIZE as u16) // e_ehsize
+        .D16(PROGRAM_HEADER_SIZE as u16) // e_phentsize
+        .D16(2) // e_phnum
+        .D16(0) // e_shentsize
+        .D16(0) // e_shnum
+        .D16(0) // e_shstrndx
+        .D32(PT_LOAD)
+        .D32(PF_R | PF_X)
+        .D64(0) // p_offset
+        .D64(0) // p_vaddr
+        .D64(0) // p_paddr
+        .D64(0x1000) // p_filesz
+        .D64(0x1000) // p_memsz
+        .D64(0x1000) // p_align
+        .D32(PT_NOTE)
+        .D32(PF_R)
+        .D64(notes_offset) // p_offset
+        .D64(notes_offset) // p_vaddr
+        .D64(notes_offset) // p_paddr
+        .D64(notes_size) // p_filesz
+        .D64(notes_size) // p_memsz
+        .D64(4) // p_align
+        .D32(4) // n_namesz
+        .D32(build_id.len() as u32) // n_descsz
+        .D32(NT_GNU_BUILD_ID) // n_type
+        .append_bytes(b"GNU\0")
+        .append_bytes(build_id)
+        .align(4)
+}
+
 #[test]
 fn test_dump_header() {
     let dump = SynthMinidump::with_endian(Endian::Little).flags(0x9f738b33685cc84c);
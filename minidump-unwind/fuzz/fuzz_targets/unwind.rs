@@ -2,9 +2,13 @@
 use libfuzzer_sys::fuzz_target;
 
 use minidump::system_info::{Cpu, Os};
-use minidump::{MinidumpContext, MinidumpContextValidity, MinidumpMemory, UnifiedMemory};
+use minidump::{
+    MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpMemory, UnifiedMemory,
+};
 use minidump::{MinidumpModule, MinidumpModuleList};
-use minidump_unwind::{string_symbol_supplier, walk_stack, CallStack, Symbolizer, SystemInfo};
+use minidump_unwind::{
+    string_symbol_supplier, walk_stack, CallStack, Symbolizer, SystemInfo, WalkStackOptions,
+};
 use std::collections::HashMap;
 use test_assembler::Section;
 
@@ -32,6 +36,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: self.raw,
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
 
         let base = stack.start().value().unwrap();
@@ -44,6 +49,8 @@ impl TestFixture {
             bytes: &stack,
             endian: minidump::Endian::Little,
         };
+        let stack_memory =
+            MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory));
         let system_info = SystemInfo {
             os: Os::Windows,
             os_version: None,
@@ -56,11 +63,13 @@ impl TestFixture {
 
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         let mut stack = CallStack::with_context(context);
+        let mut options = WalkStackOptions::default();
+        options.stack_memory = Some(&stack_memory);
 
         walk_stack(
             (),
             &mut stack,
-            Some(UnifiedMemory::Memory(&stack_memory)),
+            options,
             &self.modules,
             &system_info,
             &symbolizer,
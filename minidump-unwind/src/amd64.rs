@@ -7,10 +7,11 @@
 // worth the effort to *actually* unify the implementations.
 
 use super::impl_prelude::*;
-use minidump::format::CONTEXT_AMD64;
+use minidump::format::{CONTEXT_AMD64, IMAGE_RUNTIME_FUNCTION_ENTRY};
 use minidump::system_info::Os;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, UnifiedMemory,
+    CpuContext, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -22,12 +23,14 @@ const STACK_POINTER_REGISTER: &str = "rsp";
 const FRAME_POINTER_REGISTER: &str = "rbp";
 // FIXME: rdi and rsi are also preserved on windows (but not in sysv) -- we should handle that?
 const CALLEE_SAVED_REGS: &[&str] = &["rbx", "rbp", "r12", "r13", "r14", "r15"];
+const WINDOWS_CALLEE_SAVED_REGS: &[&str] =
+    &["rbx", "rbp", "rdi", "rsi", "r12", "r13", "r14", "r15"];
 
 async fn get_caller_by_cfi<P>(
     ctx: &CONTEXT_AMD64,
     callee: &StackFrame,
     grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -87,6 +90,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Amd64(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -102,10 +106,282 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
     }
 }
 
+// Unwind codes of the Windows x64 `UNWIND_INFO`, see
+// https://learn.microsoft.com/en-us/cpp/build/exception-handling-x64
+const UWOP_PUSH_NONVOL: u8 = 0;
+const UWOP_ALLOC_LARGE: u8 = 1;
+const UWOP_ALLOC_SMALL: u8 = 2;
+const UWOP_SET_FPREG: u8 = 3;
+const UWOP_SAVE_NONVOL: u8 = 4;
+const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+const UWOP_EPILOG: u8 = 6;
+const UWOP_SPARE_CODE: u8 = 7;
+const UWOP_SAVE_XMM128: u8 = 8;
+const UWOP_SAVE_XMM128_FAR: u8 = 9;
+const UWOP_PUSH_MACHFRAME: u8 = 10;
+const UNW_FLAG_CHAININFO: u8 = 4;
+/// The size of the fixed part of an `UNWIND_INFO`, before the unwind codes.
+const UNWIND_INFO_HEADER_SIZE: u64 = 4;
+/// How many chained `UNWIND_INFO`s to follow before giving up.
+const MAX_CHAINED_UNWIND_INFOS: usize = 32;
+
+/// The registers in the order of the operation info of the unwind codes.
+const UNWIND_REGISTERS: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+/// The decoded header of an `UNWIND_INFO`.
+struct UnwindInfo {
+    flags: u8,
+    size_of_prolog: u8,
+    frame_register: u8,
+    frame_offset: u8,
+    /// The unwind codes, as raw 16-bit slots.
+    codes: Vec<u16>,
+}
+
+impl UnwindInfo {
+    fn read(memory: &MinidumpAddressSpace, address: u64) -> Option<UnwindInfo> {
+        let read_u8 = |address: u64| -> Option<u8> { memory.get_memory_at_address(address) };
+        let version_and_flags = read_u8(address)?;
+        let size_of_prolog = read_u8(address.checked_add(1)?)?;
+        let count_of_codes = read_u8(address.checked_add(2)?)?;
+        let frame = read_u8(address.checked_add(3)?)?;
+
+        let version = version_and_flags & 0x7;
+        if version != 1 && version != 2 {
+            trace!("unsupported UNWIND_INFO version {}", version);
+            return None;
+        }
+
+        let codes_address = address.checked_add(UNWIND_INFO_HEADER_SIZE)?;
+        let codes = (0..count_of_codes as u64)
+            .map(|i| memory.get_memory_at_address::<u16>(codes_address.checked_add(i * 2)?))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(UnwindInfo {
+            flags: version_and_flags >> 3,
+            size_of_prolog,
+            frame_register: frame & 0xf,
+            frame_offset: frame >> 4,
+            codes,
+        })
+    }
+
+    /// The address of the chained function entry, which follows the codes
+    /// (padded to an even number of slots).
+    fn chained_function_address(&self, address: u64) -> Option<u64> {
+        let slots = (self.codes.len() as u64 + 1) & !1;
+        address
+            .checked_add(UNWIND_INFO_HEADER_SIZE)?
+            .checked_add(slots * 2)
+    }
+}
+
+fn read_runtime_function(
+    memory: &MinidumpAddressSpace,
+    address: u64,
+) -> Option<IMAGE_RUNTIME_FUNCTION_ENTRY> {
+    Some(IMAGE_RUNTIME_FUNCTION_ENTRY {
+        begin_address: memory.get_memory_at_address(address)?,
+        end_address: memory.get_memory_at_address(address.checked_add(4)?)?,
+        unwind_info_address: memory.get_memory_at_address(address.checked_add(8)?)?,
+    })
+}
+
+/// Unwind code that isn't part of any module (e.g. JIT code), using the
+/// function tables the process registered for it at runtime.
+///
+/// This is the same algorithm as Windows's `RtlVirtualUnwind`, except that
+/// it doesn't try to detect whether the callee is in an epilog.
+fn get_caller_by_function_table(
+    ctx: &CONTEXT_AMD64,
+    callee: &StackFrame,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+    function_tables: Option<DynamicFunctionTables<'_>>,
+    modules: &MinidumpModuleList,
+) -> Option<StackFrame> {
+    let function_tables = function_tables?;
+    // Modules have their own unwind information which we get from the symbols.
+    if modules.module_at_address(callee.instruction).is_some() {
+        return None;
+    }
+    let (table, function) = function_tables
+        .tables
+        .function_at_address(callee.instruction)?;
+
+    trace!("trying function table");
+
+    let valid = &callee.context.valid;
+    if !ctx.register_is_valid(STACK_POINTER_REGISTER, valid) {
+        return None;
+    }
+
+    let base = table.raw.base_address;
+    let memory = function_tables.memory;
+    // Only the context frame can be in the middle of the prolog, callers are
+    // always past it.
+    let prolog_offset = if callee.trust == FrameTrust::Context {
+        base.checked_add(function.begin_address as u64)
+            .and_then(|begin| ctx.rip.checked_sub(begin))
+    } else {
+        None
+    };
+
+    let mut caller_ctx = ctx.clone();
+    let mut caller_validity: HashSet<&'static str> = match valid {
+        MinidumpContextValidity::All => WINDOWS_CALLEE_SAVED_REGS.iter().copied().collect(),
+        MinidumpContextValidity::Some(which) => WINDOWS_CALLEE_SAVED_REGS
+            .iter()
+            .filter(|&reg| which.contains(reg))
+            .copied()
+            .collect(),
+    };
+    let mut machine_frame = false;
+
+    let mut unwind_info_address = base.checked_add(function.unwind_info_address as u64)?;
+    let mut info = UnwindInfo::read(memory, unwind_info_address)?;
+
+    // The frame register is only set up once the prolog got to it.
+    let mut frame = ctx.rsp;
+    if info.frame_register != 0 {
+        let set_fpreg_offset = info
+            .codes
+            .iter()
+            .find(|&&code| (code >> 8) as u8 & 0xf == UWOP_SET_FPREG)
+            .map(|&code| code as u8);
+        let frame_is_set = match (prolog_offset, set_fpreg_offset) {
+            (Some(prolog_offset), Some(offset)) if prolog_offset < info.size_of_prolog as u64 => {
+                prolog_offset >= offset as u64
+            }
+            _ => true,
+        };
+        if frame_is_set {
+            let reg = UNWIND_REGISTERS[info.frame_register as usize];
+            frame = ctx
+                .get_register(reg, valid)?
+                .checked_sub(info.frame_offset as u64 * 16)?;
+        }
+    }
+
+    let read_stack = |address: u64| -> Option<u64> { stack_memory.get_memory_at_address(address) };
+
+    for chain_depth in 0..MAX_CHAINED_UNWIND_INFOS {
+        // Chained infos describe the parts of the prolog that were already
+        // executed, so they're always applied in full.
+        let prolog_offset =
+            prolog_offset.filter(|&offset| chain_depth == 0 && offset < info.size_of_prolog as u64);
+
+        let mut i = 0;
+        while i < info.codes.len() {
+            let code = info.codes[i];
+            let code_offset = code as u8;
+            let op = (code >> 8) as u8 & 0xf;
+            let op_info = (code >> 12) as u8;
+            let slot = |n: usize| info.codes.get(i + n).copied();
+
+            let slots = match op {
+                UWOP_ALLOC_LARGE if op_info == 0 => 2,
+                UWOP_ALLOC_LARGE => 3,
+                UWOP_SAVE_NONVOL | UWOP_SAVE_XMM128 | UWOP_EPILOG => 2,
+                UWOP_SAVE_NONVOL_FAR | UWOP_SAVE_XMM128_FAR | UWOP_SPARE_CODE => 3,
+                _ => 1,
+            };
+
+            // Skip the codes for the parts of the prolog that didn't run yet.
+            let executed = prolog_offset.is_none_or(|offset| offset >= code_offset as u64);
+            if executed {
+                match op {
+                    UWOP_PUSH_NONVOL => {
+                        let reg = UNWIND_REGISTERS[op_info as usize];
+                        let val = read_stack(caller_ctx.rsp)?;
+                        caller_ctx.set_register(reg, val)?;
+                        caller_validity.insert(caller_ctx.memoize_register(reg)?);
+                        caller_ctx.rsp = caller_ctx.rsp.checked_add(POINTER_WIDTH)?;
+                    }
+                    UWOP_ALLOC_LARGE => {
+                        let size = if op_info == 0 {
+                            slot(1)? as u64 * 8
+                        } else {
+                            slot(1)? as u64 | (slot(2)? as u64) << 16
+                        };
+                        caller_ctx.rsp = caller_ctx.rsp.checked_add(size)?;
+                    }
+                    UWOP_ALLOC_SMALL => {
+                        caller_ctx.rsp = caller_ctx.rsp.checked_add(op_info as u64 * 8 + 8)?;
+                    }
+                    UWOP_SET_FPREG => {
+                        caller_ctx.rsp = frame;
+                    }
+                    UWOP_SAVE_NONVOL | UWOP_SAVE_NONVOL_FAR => {
+                        let offset = if op == UWOP_SAVE_NONVOL {
+                            slot(1)? as u64 * 8
+                        } else {
+                            slot(1)? as u64 | (slot(2)? as u64) << 16
+                        };
+                        let reg = UNWIND_REGISTERS[op_info as usize];
+                        let val = read_stack(frame.checked_add(offset)?)?;
+                        caller_ctx.set_register(reg, val)?;
+                        caller_validity.insert(caller_ctx.memoize_register(reg)?);
+                    }
+                    UWOP_PUSH_MACHFRAME => {
+                        // The processor pushed an interrupt frame, optionally
+                        // preceded by an error code.
+                        if op_info != 0 {
+                            caller_ctx.rsp = caller_ctx.rsp.checked_add(POINTER_WIDTH)?;
+                        }
+                        caller_ctx.rip = read_stack(caller_ctx.rsp)?;
+                        caller_ctx.rsp =
+                            read_stack(caller_ctx.rsp.checked_add(POINTER_WIDTH * 3)?)?;
+                        machine_frame = true;
+                    }
+                    // We don't recover the xmm registers, and the epilog
+                    // codes only matter for detecting epilogs.
+                    UWOP_SAVE_XMM128 | UWOP_SAVE_XMM128_FAR | UWOP_EPILOG | UWOP_SPARE_CODE => {}
+                    _ => {
+                        trace!("unknown unwind code {}", op);
+                        return None;
+                    }
+                }
+            }
+            i += slots;
+        }
+
+        if info.flags & UNW_FLAG_CHAININFO == 0 {
+            break;
+        }
+        let chained =
+            read_runtime_function(memory, info.chained_function_address(unwind_info_address)?)?;
+        unwind_info_address = base.checked_add(chained.unwind_info_address as u64)?;
+        info = UnwindInfo::read(memory, unwind_info_address)?;
+    }
+
+    if !machine_frame {
+        caller_ctx.rip = read_stack(caller_ctx.rsp)?;
+        caller_ctx.rsp = caller_ctx.rsp.checked_add(POINTER_WIDTH)?;
+    }
+
+    trace!(
+        "function table unwinding was successful -- caller_ip: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_ctx.rip,
+        caller_ctx.rsp,
+    );
+
+    caller_validity.insert(INSTRUCTION_REGISTER);
+    caller_validity.insert(STACK_POINTER_REGISTER);
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Amd64(caller_ctx),
+        valid: MinidumpContextValidity::Some(caller_validity),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
 fn get_caller_by_frame_pointer<P>(
     ctx: &CONTEXT_AMD64,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
     _modules: &MinidumpModuleList,
     system_info: &SystemInfo,
     _symbol_provider: &P,
@@ -113,6 +389,7 @@ fn get_caller_by_frame_pointer<P>(
 where
     P: SymbolProvider + Sync,
 {
+    let stack_memory = memory.stack?;
     // On Windows x64, frame-pointer unwinding purely with the data on the stack
     // is not possible, as proper unwinding requires access to `UNWIND_INFO`,
     // because the frame pointer does not necessarily point to the end of the
@@ -181,14 +458,17 @@ where
     }
     // Since we're assuming coherent frame pointers, check that the resulting
     // frame pointer is still inside stack memory.
-    let _unused: Pointer = stack_memory.get_memory_at_address(caller_bp)?;
+    if !memory.stack_contains(caller_bp, POINTER_WIDTH) {
+        trace!("rejecting frame pointer result for frame pointer outside the stack");
+        return None;
+    }
     // Don't accept obviously wrong instruction pointers.
     if is_non_canonical(caller_ip) {
         trace!("rejecting frame pointer result for unreasonable instruction pointer");
         return None;
     }
     // Don't accept obviously wrong stack pointers.
-    if !stack_seems_valid(caller_sp, last_sp, stack_memory) {
+    if !stack_seems_valid(caller_sp, last_sp, memory) {
         trace!("rejecting frame pointer result for unreasonable stack pointer");
         return None;
     }
@@ -212,6 +492,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Amd64(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -219,13 +500,14 @@ where
 async fn get_caller_by_scan<P>(
     ctx: &CONTEXT_AMD64,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider + Sync,
 {
+    let stack_memory = memory.stack?;
     trace!("trying scan");
     // Stack scanning is just walking from the end of the frame until we encounter
     // a value on the stack that looks like a pointer into some code (it's an address
@@ -264,7 +546,7 @@ where
     for i in 0..scan_range {
         let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
         let caller_ip = stack_memory.get_memory_at_address(address_of_ip)?;
-        if instruction_seems_valid(caller_ip, modules, symbol_provider).await {
+        if instruction_seems_valid(caller_ip, memory, modules, symbol_provider).await {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
             let caller_sp = address_of_ip.checked_add(POINTER_WIDTH)?;
 
@@ -308,7 +590,7 @@ where
                         && bp - address_of_bp <= MAX_REASONABLE_GAP_BETWEEN_FRAMES
                     {
                         // Final sanity check that resulting bp is still inside stack memory.
-                        if stack_memory.get_memory_at_address::<Pointer>(bp).is_some() {
+                        if memory.stack_contains(bp, POINTER_WIDTH) {
                             caller_bp = Some(bp);
                         }
                     } else if last_bp >= caller_sp {
@@ -339,6 +621,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Amd64(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -370,8 +653,12 @@ where
 /// If we applied this more rigorous validation to cfi/fp methods, we
 /// would just discard the correct register values from the known frame
 /// and immediately start doing unreliable scans.
+///
+/// When the code in front of the instruction is available (from the
+/// minidump or the module's binary), we also check that it's a call.
 async fn instruction_seems_valid<P>(
     instruction: Pointer,
+    memory: UnwindMemory<'_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> bool
@@ -382,23 +669,22 @@ where
         return false;
     }
 
+    // A return address follows the call that pushed it.
+    if crate::follows_x86_call(instruction, memory, modules, symbol_provider).await == Some(false) {
+        return false;
+    }
+
     super::instruction_seems_valid_by_symbols(instruction, modules, symbol_provider).await
 }
 
-fn stack_seems_valid(
-    caller_sp: Pointer,
-    callee_sp: Pointer,
-    stack_memory: UnifiedMemory<'_, '_>,
-) -> bool {
+fn stack_seems_valid(caller_sp: Pointer, callee_sp: Pointer, memory: UnwindMemory<'_>) -> bool {
     // The stack shouldn't *grow* when we unwind
     if caller_sp <= callee_sp {
         return false;
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp)
-        .is_some()
+    memory.stack_contains(caller_sp, POINTER_WIDTH)
 }
 
 fn is_non_canonical(ptr: Pointer) -> bool {
@@ -423,7 +709,7 @@ impl Unwind for CONTEXT_AMD64 {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
         syms: &P,
@@ -431,7 +717,7 @@ impl Unwind for CONTEXT_AMD64 {
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
@@ -439,10 +725,14 @@ impl Unwind for CONTEXT_AMD64 {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
         if frame.is_none() {
-            frame = get_caller_by_frame_pointer(self, callee, stack, modules, system_info, syms);
+            frame =
+                get_caller_by_function_table(self, callee, stack, memory.function_tables, modules);
+        }
+        if frame.is_none() {
+            frame = get_caller_by_frame_pointer(self, callee, memory, modules, system_info, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, memory, modules, syms).await;
         }
         let mut frame = frame?;
 
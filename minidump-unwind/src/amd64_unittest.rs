@@ -2,7 +2,9 @@
 // file at the top-level directory of this distribution.
 
 use crate::*;
-use minidump::format::CONTEXT_AMD64;
+use minidump::format::{
+    CONTEXT_AMD64, IMAGE_RUNTIME_FUNCTION_ENTRY, MINIDUMP_FUNCTION_TABLE_DESCRIPTOR,
+};
 use minidump::system_info::{Cpu, Os};
 use std::collections::HashMap;
 use test_assembler::*;
@@ -12,6 +14,11 @@ struct TestFixture {
     pub modules: MinidumpModuleList,
     pub system_info: SystemInfo,
     pub symbols: HashMap<String, String>,
+    pub function_tables: MinidumpFunctionTableList<'static>,
+    /// Memory other than the stack, as `(base_address, contents)`.
+    pub memory: Vec<(u64, Vec<u8>)>,
+    /// The code of the modules, as `(address, contents)`.
+    pub code: Vec<(u64, Vec<u8>)>,
 }
 
 impl TestFixture {
@@ -34,6 +41,9 @@ impl TestFixture {
                 cpu_count: 1,
             },
             symbols: HashMap::new(),
+            function_tables: MinidumpFunctionTableList::default(),
+            memory: vec![],
+            code: vec![],
         }
     }
 
@@ -41,6 +51,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Amd64(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -52,13 +63,49 @@ impl TestFixture {
             bytes: &stack,
             endian: scroll::LE,
         };
+        let regions = self
+            .memory
+            .iter()
+            .map(|(base, bytes)| MinidumpMemory {
+                desc: Default::default(),
+                base_address: *base,
+                size: bytes.len() as u64,
+                bytes,
+                endian: scroll::LE,
+            })
+            .collect();
+        let memory = UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(regions));
+        let memory = MinidumpAddressSpace::from(&memory);
+        let function_tables = DynamicFunctionTables {
+            tables: &self.function_tables,
+            memory: &memory,
+        };
+        let code = self
+            .code
+            .iter()
+            .map(|(base, bytes)| MinidumpMemory {
+                desc: Default::default(),
+                base_address: *base,
+                size: bytes.len() as u64,
+                bytes,
+                endian: scroll::LE,
+            })
+            .collect();
+        let code = UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(code));
+        let stack_memory = MinidumpAddressSpace::new()
+            .with_region(UnifiedMemory::Memory(stack_memory))
+            .with_memory_list(&code);
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         let mut stack = CallStack::with_context(context);
 
         walk_stack(
             (),
             &mut stack,
-            Some(UnifiedMemory::Memory(stack_memory)),
+            WalkStackOptions {
+                stack_memory: Some(&stack_memory),
+                function_tables: Some(function_tables),
+                ..Default::default()
+            },
             &self.modules,
             &self.system_info,
             &symbolizer,
@@ -71,6 +118,31 @@ impl TestFixture {
     pub fn add_symbols(&mut self, name: String, symbols: String) {
         self.symbols.insert(name, symbols);
     }
+
+    /// Register a function table for JIT code at `base`, with the unwind
+    /// information in `unwind_info` (which is also placed at `base`).
+    pub fn add_function_table(
+        &mut self,
+        base: u64,
+        functions: Vec<IMAGE_RUNTIME_FUNCTION_ENTRY>,
+        unwind_info: Section,
+    ) {
+        let maximum_address = base + functions.iter().map(|f| f.end_address).max().unwrap() as u64;
+        self.function_tables.tables.push(MinidumpFunctionTable {
+            raw: MINIDUMP_FUNCTION_TABLE_DESCRIPTOR {
+                minimum_address: base,
+                maximum_address,
+                base_address: base,
+                entry_count: functions.len() as u32,
+                size_of_align_pad: 0,
+            },
+            native_descriptor: &[],
+            raw_entries: &[],
+            functions,
+        });
+        self.memory
+            .push((base, unwind_info.get_contents().unwrap()));
+    }
 }
 
 #[tokio::test]
@@ -164,6 +236,54 @@ async fn test_caller_pushed_rbp() {
     }
 }
 
+#[tokio::test]
+async fn test_caller_rbp_outside_stack() {
+    // The stack memory also contains the code of the modules, but a frame
+    // pointer that points into it rather than into the stack isn't one.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x0000000080000000;
+    let return_address = 0x00007500b0000110;
+    let code_address = 0x00007400c0000010;
+    stack.start().set_const(stack_start);
+
+    let frame0_rbp = Label::new();
+    let frame1_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .mark(&frame0_rbp)
+        .D64(code_address) // "caller-pushed %rbp" that points into module1
+        .D64(return_address) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.code.push((0x00007400c0000000, vec![0xcc; 0x1000]));
+
+    f.raw.rip = 0x00007400c0000200;
+    f.raw.rbp = frame0_rbp.value().unwrap();
+    f.raw.rsp = stack.start().value().unwrap();
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::Scan);
+    if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+        assert!(!which.contains("rbp"));
+    } else {
+        unreachable!();
+    }
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+    } else {
+        unreachable!();
+    }
+}
+
 #[tokio::test]
 async fn test_windows_rbp_scan() {
     let mut f = TestFixture::new();
@@ -325,6 +445,47 @@ async fn test_scan_without_symbols() {
     }
 }
 
+#[tokio::test]
+async fn test_scan_with_code() {
+    // When the code of the module is available, scanning should skip values
+    // that point into it but don't follow a call.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    stack.start().set_const(stack_start);
+
+    let return_address = 0x00007400c0000208;
+
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0
+        .D64(0x00007400c0000100) // points into module1, but not after a call
+        .D64(return_address) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 16); // end of stack
+
+    // int3 everywhere, except for a `call [rip+disp32]` right before the return address.
+    let mut text = vec![0xcc; 0x1000];
+    text[0x202..0x208].copy_from_slice(&[0xff, 0x15, 0xf2, 0x0d, 0x00, 0x00]);
+    f.code.push((0x00007400c0000000, text));
+
+    f.raw.rip = 0x00007400c0000500;
+    f.raw.rsp = stack.start().value().unwrap();
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::Scan);
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+    } else {
+        unreachable!();
+    }
+}
+
 #[tokio::test]
 async fn test_scan_with_symbols() {
     // Test that we can refine our scanning using symbols. Specifically we
@@ -807,3 +968,212 @@ async fn test_scan_walk_overflow() {
         }
     }
 }
+
+// JIT code registered with a function table, whose unwind information is at
+// the start of the table (before any of the code).
+const JIT_BASE: u64 = 0x00007ff000000000;
+const JIT_FUNCTION: IMAGE_RUNTIME_FUNCTION_ENTRY = IMAGE_RUNTIME_FUNCTION_ENTRY {
+    begin_address: 0x1000,
+    end_address: 0x1100,
+    unwind_info_address: 0,
+};
+
+/// Encode an unwind code.
+fn unwind_code(offset: u8, op: u8, info: u8) -> u16 {
+    (info as u16) << 12 | (op as u16) << 8 | offset as u16
+}
+
+/// The unwind information for:
+///
+/// ```text
+/// 0: push rbp
+/// 1: push rbx
+/// 2: sub rsp, 0x20
+/// 6: ...
+/// ```
+fn jit_unwind_info() -> Section {
+    Section::new()
+        .D8(1) // version 1, no flags
+        .D8(6) // size of prolog
+        .D8(3) // count of codes
+        .D8(0) // no frame register
+        .D16(unwind_code(6, 2, 3)) // UWOP_ALLOC_SMALL 0x20
+        .D16(unwind_code(2, 0, 3)) // UWOP_PUSH_NONVOL rbx
+        .D16(unwind_code(1, 0, 5)) // UWOP_PUSH_NONVOL rbp
+        .D16(0) // padding
+}
+
+#[tokio::test]
+async fn test_function_table() {
+    let mut f = TestFixture::new();
+    f.system_info.os = Os::Windows;
+    f.add_function_table(JIT_BASE, vec![JIT_FUNCTION], jit_unwind_info());
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0
+        .append_repeated(0, 0x20) // locals
+        .D64(0x1111111111111111) // saved rbx
+        .D64(0x2222222222222222) // saved rbp
+        .D64(return_address)
+        // frame 1
+        .mark(&frame1_sp);
+
+    f.raw.rip = JIT_BASE + 0x1050;
+    f.raw.rsp = stack_start;
+    f.raw.rbx = 0xbbbb;
+    f.raw.rbp = 0xaaaa;
+    f.raw.r12 = 0x1212;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        // To avoid reusing locals by mistake
+        let f0 = &s.frames[0];
+        assert_eq!(f0.trust, FrameTrust::Context);
+        assert!(f0.module.is_none());
+    }
+
+    {
+        // To avoid reusing locals by mistake
+        let f1 = &s.frames[1];
+        assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+        assert_eq!(f1.module.as_ref().unwrap().code_file(), "module2");
+        if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+            assert!(which.contains("rip"));
+            assert!(which.contains("rsp"));
+            assert!(which.contains("rbx"));
+            assert!(which.contains("rbp"));
+            assert!(which.contains("r12"));
+            assert!(!which.contains("rax"));
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+            assert_eq!(ctx.rip, return_address);
+            assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+            assert_eq!(ctx.rbx, 0x1111111111111111);
+            assert_eq!(ctx.rbp, 0x2222222222222222);
+            assert_eq!(ctx.r12, 0x1212);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_function_table_in_prolog() {
+    let mut f = TestFixture::new();
+    f.system_info.os = Os::Windows;
+    f.add_function_table(JIT_BASE, vec![JIT_FUNCTION], jit_unwind_info());
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0, stopped right before allocating its locals
+        .D64(0x1111111111111111) // saved rbx
+        .D64(0x2222222222222222) // saved rbp
+        .D64(return_address)
+        // frame 1
+        .mark(&frame1_sp);
+
+    f.raw.rip = JIT_BASE + 0x1002;
+    f.raw.rsp = stack_start;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+        assert_eq!(ctx.rbx, 0x1111111111111111);
+        assert_eq!(ctx.rbp, 0x2222222222222222);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_function_table_frame_pointer_chained() {
+    // The prolog is split in two, the first part being described by a chained
+    // unwind info:
+    //
+    // ```text
+    // 0: push rbp
+    // 1: mov rbp, rsp
+    // 4: sub rsp, 0x40
+    // 8: ...
+    // ```
+    let mut f = TestFixture::new();
+    f.system_info.os = Os::Windows;
+    let chained_unwind_info = Label::new();
+    let unwind_info = Section::new();
+    let unwind_info_start = unwind_info.start();
+    unwind_info_start.set_const(0);
+    let unwind_info = unwind_info
+        .D8(1 | 4 << 3) // version 1, UNW_FLAG_CHAININFO
+        .D8(8) // size of prolog
+        .D8(2) // count of codes
+        .D8(5) // rbp is the frame register, at offset 0
+        .D16(unwind_code(8, 2, 7)) // UWOP_ALLOC_SMALL 0x40
+        .D16(unwind_code(4, 3, 0)) // UWOP_SET_FPREG
+        // chained function
+        .D32(JIT_FUNCTION.begin_address)
+        .D32(JIT_FUNCTION.end_address)
+        .D32(&chained_unwind_info)
+        // chained unwind info
+        .mark(&chained_unwind_info)
+        .D8(1) // version 1, no flags
+        .D8(1) // size of prolog
+        .D8(1) // count of codes
+        .D8(0) // no frame register
+        .D16(unwind_code(1, 0, 5)) // UWOP_PUSH_NONVOL rbp
+        .D16(0); // padding
+    f.add_function_table(JIT_BASE, vec![JIT_FUNCTION], unwind_info);
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame0_rbp = Label::new();
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0, which also did a dynamic allocation
+        .append_repeated(0, 0x80) // locals
+        .mark(&frame0_rbp)
+        .D64(0x2222222222222222) // saved rbp
+        .D64(return_address)
+        // frame 1
+        .mark(&frame1_sp);
+
+    f.raw.rip = JIT_BASE + 0x1050;
+    f.raw.rsp = stack_start;
+    f.raw.rbp = frame0_rbp.value().unwrap();
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+        assert_eq!(ctx.rbp, 0x2222222222222222);
+    } else {
+        unreachable!();
+    }
+}
@@ -7,8 +7,8 @@
 use super::impl_prelude::*;
 use minidump::system_info::Os;
 use minidump::{
-    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
-    UnifiedMemory,
+    CpuContext, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -28,7 +28,7 @@ async fn get_caller_by_cfi<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -79,6 +79,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -97,7 +98,7 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
 fn get_caller_by_frame_pointer<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     _modules: &MinidumpModuleList,
     system_info: &SystemInfo,
     _symbol_provider: &P,
@@ -176,6 +177,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -183,7 +185,7 @@ where
 async fn get_caller_by_scan<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -239,6 +241,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Arm(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -287,7 +290,7 @@ where
 fn stack_seems_valid(
     caller_sp: Pointer,
     callee_sp: Pointer,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
 ) -> bool {
     // The stack shouldn't *grow* when we unwind
     if caller_sp < callee_sp {
@@ -295,9 +298,7 @@ fn stack_seems_valid(
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
-        .is_some()
+    memory.stack_contains(caller_sp as u64, POINTER_WIDTH as u64)
 }
 */
 
@@ -307,7 +308,7 @@ impl Unwind for ArmContext {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
         syms: &P,
@@ -315,7 +316,7 @@ impl Unwind for ArmContext {
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
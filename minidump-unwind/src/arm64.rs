@@ -6,8 +6,8 @@
 
 use super::impl_prelude::*;
 use minidump::{
-    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
-    Module, UnifiedMemory,
+    CpuContext, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext, Module,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -29,7 +29,7 @@ async fn get_caller_by_cfi<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -110,6 +110,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm64(stack_walker.caller_ctx),
         valid: new_valid,
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -129,7 +130,7 @@ fn get_caller_by_frame_pointer<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     _grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     _symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -250,6 +251,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::Arm64(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -315,7 +317,7 @@ fn ptr_auth_strip(modules: &MinidumpModuleList, ptr: Pointer) -> Pointer {
 async fn get_caller_by_scan<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -371,6 +373,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Arm64(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -428,7 +431,7 @@ fn is_non_canonical(instruction: Pointer) -> bool {
 fn stack_seems_valid(
     caller_sp: Pointer,
     callee_sp: Pointer,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
 ) -> bool {
     // The stack shouldn't *grow* when we unwind
     if caller_sp < callee_sp {
@@ -436,9 +439,7 @@ fn stack_seems_valid(
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
-        .is_some()
+    memory.stack_contains(caller_sp as u64, POINTER_WIDTH as u64)
 }
 */
 
@@ -448,7 +449,7 @@ impl Unwind for ArmContext {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
         syms: &P,
@@ -456,7 +457,7 @@ impl Unwind for ArmContext {
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
@@ -7,8 +7,8 @@
 use super::impl_prelude::*;
 use crate::{SymbolProvider, SystemInfo};
 use minidump::{
-    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
-    Module, UnifiedMemory,
+    CpuContext, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext, Module,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -30,7 +30,7 @@ async fn get_caller_by_cfi<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -111,6 +111,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::OldArm64(stack_walker.caller_ctx),
         valid: new_valid,
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -130,7 +131,7 @@ fn get_caller_by_frame_pointer<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     _grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     _symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -251,6 +252,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::OldArm64(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -316,7 +318,7 @@ fn ptr_auth_strip(modules: &MinidumpModuleList, ptr: Pointer) -> Pointer {
 async fn get_caller_by_scan<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -372,6 +374,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::OldArm64(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -429,7 +432,7 @@ fn is_non_canonical(instruction: Pointer) -> bool {
 fn stack_seems_valid(
     caller_sp: Pointer,
     callee_sp: Pointer,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
 ) -> bool {
     // The stack shouldn't *grow* when we unwind
     if caller_sp < callee_sp {
@@ -437,9 +440,7 @@ fn stack_seems_valid(
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
-        .is_some()
+    memory.stack_contains(caller_sp as u64, POINTER_WIDTH as u64)
 }
 */
 
@@ -449,7 +450,7 @@ impl Unwind for ArmContext {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
         syms: &P,
@@ -457,7 +458,7 @@ impl Unwind for ArmContext {
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
@@ -62,6 +62,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Arm64(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -88,7 +89,12 @@ impl TestFixture {
         walk_stack(
             (),
             &mut stack,
-            Some(UnifiedMemory::Memory(&stack_memory)),
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
             &self.modules,
             &system_info,
             &symbolizer,
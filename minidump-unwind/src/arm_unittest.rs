@@ -41,6 +41,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Arm(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -58,7 +59,12 @@ impl TestFixture {
         walk_stack(
             (),
             &mut stack,
-            Some(UnifiedMemory::Memory(&stack_memory)),
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
             &self.modules,
             &self.system_info,
             &symbolizer,
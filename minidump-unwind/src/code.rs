@@ -0,0 +1,455 @@
+//! The code of modules, read from their binaries.
+//!
+//! Most minidumps don't contain the code of the modules, only the stacks of the threads
+//! and maybe a little memory around the crashing instruction. [`ModuleCode`] reads the
+//! code of a module from its binary (as found by [`SymbolProvider::get_file_path`]) and
+//! lays it out at the address the module was loaded at, so that it can be layered behind
+//! the memory of the minidump in a [`MinidumpAddressSpace`]. [`ModuleCodeCache`] only
+//! does so for the modules whose code is actually looked at.
+//!
+//! [`MinidumpAddressSpace`]: minidump::MinidumpAddressSpace
+
+use std::convert::TryInto;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cachemap2::CacheMap;
+use futures_channel::oneshot;
+use futures_util::lock::Mutex;
+
+use goblin::elf::{self, Elf};
+use goblin::mach::{self, Mach, MachO};
+use goblin::pe::{self, PE};
+use goblin::Object;
+use minidump::{MinidumpMemory, MinidumpMemoryList, Module, UnifiedMemoryList};
+use tracing::{debug, warn};
+
+use crate::{FileKind, SymbolProvider};
+
+/// A section of code of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeSection {
+    /// The name of the section in the binary, such as `.text`.
+    pub name: String,
+    /// The address the section was loaded at.
+    pub address: u64,
+    /// The contents of the section, as they were loaded.
+    pub bytes: Vec<u8>,
+}
+
+/// The code of a module, read from its binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleCode {
+    /// The base address of the module.
+    pub base_address: u64,
+    /// The sections of the binary that contain code, at the addresses they were loaded at.
+    pub sections: Vec<CodeSection>,
+    /// The endianness of the binary.
+    pub endian: scroll::Endian,
+}
+
+impl ModuleCode {
+    /// Read the code of `module` from its binary, which is looked up with `symbol_provider`.
+    ///
+    /// Return `None` if the binary can't be found, isn't an ELF, PE or Mach-O file, or
+    /// isn't the one the module was loaded from.
+    pub async fn load<P>(module: &(dyn Module + Sync), symbol_provider: &P) -> Option<ModuleCode>
+    where
+        P: SymbolProvider + Sync,
+    {
+        let path = symbol_provider
+            .get_file_path(module, FileKind::Binary)
+            .await
+            .map_err(|e| debug!("no binary for {}: {}", module.code_file(), e))
+            .ok()?;
+        let binary = read_file(path.clone())
+            .await
+            .map_err(|e| warn!("failed to read binary {}: {}", path.display(), e))
+            .ok()?;
+        let code = ModuleCode::from_binary(&binary, module);
+        if code.is_none() {
+            warn!(
+                "couldn't get the code of {} from {}",
+                module.code_file(),
+                path.display()
+            );
+        }
+        code
+    }
+
+    /// Read the code of `module` from `binary`, the contents of its binary.
+    ///
+    /// Return `None` if `binary` isn't an ELF, PE or Mach-O file, or isn't the one the
+    /// module was loaded from (according to its build id, code id or UUID).
+    pub fn from_binary(binary: &[u8], module: &dyn Module) -> Option<ModuleCode> {
+        let (sections, endian) = match Object::parse(binary).ok()? {
+            Object::Elf(elf) => (
+                elf_sections(&elf, binary, module)?,
+                endian(elf.little_endian),
+            ),
+            Object::PE(pe) => (pe_sections(&pe, binary, module)?, scroll::LE),
+            Object::Mach(Mach::Binary(macho)) => {
+                (macho_sections(&macho, module)?, endian(macho.little_endian))
+            }
+            Object::Mach(Mach::Fat(fat)) => {
+                (0..fat.narches).find_map(|index| match fat.get(index).ok()? {
+                    mach::SingleArch::MachO(macho) => {
+                        Some((macho_sections(&macho, module)?, endian(macho.little_endian)))
+                    }
+                    mach::SingleArch::Archive(_) => None,
+                })?
+            }
+            _ => return None,
+        };
+
+        // Leave out anything that isn't where the module was loaded.
+        let module_end = module.base_address().checked_add(module.size())?;
+        let sections = sections
+            .into_iter()
+            .filter(|section| {
+                section.address >= module.base_address()
+                    && section
+                        .address
+                        .checked_add(section.bytes.len() as u64)
+                        .is_some_and(|end| end <= module_end)
+            })
+            .collect();
+
+        Some(ModuleCode {
+            base_address: module.base_address(),
+            sections,
+            endian,
+        })
+    }
+
+    /// Return the section containing `address`, if one exists.
+    pub fn section_at_address(&self, address: u64) -> Option<&CodeSection> {
+        self.sections.iter().find(|section| {
+            address
+                .checked_sub(section.address)
+                .is_some_and(|offset| offset < section.bytes.len() as u64)
+        })
+    }
+
+    /// The sections of this module, as memory regions.
+    pub fn regions(&self) -> impl Iterator<Item = MinidumpMemory<'_>> {
+        self.sections.iter().map(move |section| MinidumpMemory {
+            desc: Default::default(),
+            base_address: section.address,
+            size: section.bytes.len() as u64,
+            bytes: &section.bytes,
+            endian: self.endian,
+        })
+    }
+}
+
+/// The code of modules, each read from its binary the first time it's needed.
+///
+/// Only a few modules are usually looked at (the crashing one and the ones stack
+/// scanning lands in), so this avoids reading the binaries of all the others.
+#[derive(Default)]
+pub struct ModuleCodeCache {
+    /// The code of each module by base address, `None` if it couldn't be read.
+    code: CacheMap<u64, Mutex<Option<Option<Arc<ModuleCode>>>>>,
+}
+
+impl ModuleCodeCache {
+    /// Get the code of `module`, reading it from its binary (see [`ModuleCode::load`]) if
+    /// that hasn't been tried yet.
+    pub async fn get<P>(
+        &self,
+        module: &(dyn Module + Sync),
+        symbol_provider: &P,
+    ) -> Option<Arc<ModuleCode>>
+    where
+        P: SymbolProvider + Sync,
+    {
+        let mut code = self.code.cache_default(module.base_address()).lock().await;
+        if code.is_none() {
+            *code = Some(
+                ModuleCode::load(module, symbol_provider)
+                    .await
+                    .map(Arc::new),
+            );
+        }
+        code.clone().flatten()
+    }
+}
+
+/// Make a memory list out of the code of `modules`.
+///
+/// This can be layered behind the memory of a minidump with
+/// [`MinidumpAddressSpace::with_memory_list`][minidump::MinidumpAddressSpace::with_memory_list],
+/// to fill in the code it doesn't contain.
+pub fn code_memory_list<'c>(
+    modules: impl IntoIterator<Item = &'c ModuleCode>,
+) -> UnifiedMemoryList<'c> {
+    let regions = modules.into_iter().flat_map(ModuleCode::regions).collect();
+    UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(regions))
+}
+
+/// Read the file at `path` on a thread of its own, so as not to block the executor.
+async fn read_file(path: PathBuf) -> io::Result<Vec<u8>> {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || sender.send(std::fs::read(path)));
+    receiver
+        .await
+        .unwrap_or_else(|canceled| Err(io::Error::other(canceled)))
+}
+
+fn endian(little_endian: bool) -> scroll::Endian {
+    if little_endian {
+        scroll::LE
+    } else {
+        scroll::BE
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn elf_sections(elf: &Elf, binary: &[u8], module: &dyn Module) -> Option<Vec<CodeSection>> {
+    // Breakpad uses the build id as the code id of ELF modules.
+    let build_id = elf
+        .iter_note_headers(binary)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .find(|note| note.n_type == elf::note::NT_GNU_BUILD_ID);
+    if let (Some(code_id), Some(build_id)) = (module.code_identifier(), build_id) {
+        if !code_id
+            .as_str()
+            .eq_ignore_ascii_case(&to_hex(build_id.desc))
+        {
+            debug!(
+                "build id of the binary doesn't match {}",
+                module.code_file()
+            );
+            return None;
+        }
+    }
+
+    // Position-independent modules are linked at 0 and loaded anywhere, others are loaded
+    // at the address they were linked at.
+    let loads = elf
+        .program_headers
+        .iter()
+        .filter(|header| header.p_type == elf::program_header::PT_LOAD);
+    let first_address = loads.clone().map(|header| header.p_vaddr).min()? & !0xfff;
+    let bias = module.base_address().wrapping_sub(first_address);
+
+    let file_range = |offset: u64, size: u64| -> Option<Vec<u8>> {
+        let start: usize = offset.try_into().ok()?;
+        let end = start.checked_add(size.try_into().ok()?)?;
+        Some(binary.get(start..end)?.to_vec())
+    };
+    let sections: Vec<_> = elf
+        .section_headers
+        .iter()
+        .filter(|header| {
+            header.sh_type == elf::section_header::SHT_PROGBITS
+                && header.sh_flags & elf::section_header::SHF_EXECINSTR as u64 != 0
+        })
+        .filter_map(|header| {
+            Some(CodeSection {
+                name: elf
+                    .shdr_strtab
+                    .get_at(header.sh_name)
+                    .unwrap_or_default()
+                    .to_owned(),
+                address: bias.wrapping_add(header.sh_addr),
+                bytes: file_range(header.sh_offset, header.sh_size)?,
+            })
+        })
+        .collect();
+    if !sections.is_empty() {
+        return Some(sections);
+    }
+
+    // Without section headers, fall back to the executable segments.
+    Some(
+        loads
+            .filter(|header| header.p_flags & elf::program_header::PF_X != 0)
+            .filter_map(|header| {
+                Some(CodeSection {
+                    name: String::new(),
+                    address: bias.wrapping_add(header.p_vaddr),
+                    bytes: file_range(header.p_offset, header.p_filesz)?,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn pe_sections(pe: &PE, binary: &[u8], module: &dyn Module) -> Option<Vec<CodeSection>> {
+    let optional_header = pe.header.optional_header?;
+    if let Some(code_id) = module.code_identifier() {
+        let expected = format!(
+            "{:08X}{:x}",
+            pe.header.coff_header.time_date_stamp, optional_header.windows_fields.size_of_image
+        );
+        if !code_id.as_str().eq_ignore_ascii_case(&expected) {
+            debug!("code id of the binary doesn't match {}", module.code_file());
+            return None;
+        }
+    }
+
+    let base = module.base_address();
+    let mut sections: Vec<_> = pe
+        .sections
+        .iter()
+        .filter(|section| {
+            section.characteristics
+                & (pe::section_table::IMAGE_SCN_CNT_CODE | pe::section_table::IMAGE_SCN_MEM_EXECUTE)
+                != 0
+        })
+        .filter_map(|section| {
+            // The raw data is padded to the file alignment, the virtual size is the
+            // actual size (and is zero in some old binaries).
+            let size = match section.virtual_size {
+                0 => section.size_of_raw_data,
+                size => size.min(section.size_of_raw_data),
+            };
+            let start = section.pointer_to_raw_data as usize;
+            Some(CodeSection {
+                name: section.name().unwrap_or_default().to_owned(),
+                address: base.checked_add(section.virtual_address as u64)?,
+                bytes: binary
+                    .get(start..start.checked_add(size as usize)?)?
+                    .to_vec(),
+            })
+        })
+        .collect();
+
+    // Absolute addresses in the code are fixed up by the loader when the module isn't
+    // loaded at its preferred address, so do the same.
+    let delta = base.wrapping_sub(pe.image_base as u64);
+    if delta != 0 {
+        if let Some(table) = optional_header.data_directories.get_base_relocation_table() {
+            let relocations = pe_file_offset(pe, table.virtual_address)
+                .and_then(|offset| binary.get(offset..offset.checked_add(table.size as usize)?));
+            if let Some(relocations) = relocations {
+                apply_base_relocations(&mut sections, relocations, base, delta);
+            }
+        }
+    }
+
+    Some(sections)
+}
+
+/// The offset in the file of the data at `rva`.
+fn pe_file_offset(pe: &PE, rva: u32) -> Option<usize> {
+    pe.sections.iter().find_map(|section| {
+        let offset = rva.checked_sub(section.virtual_address)?;
+        if offset < section.virtual_size.max(section.size_of_raw_data) {
+            Some(section.pointer_to_raw_data.checked_add(offset)? as usize)
+        } else {
+            None
+        }
+    })
+}
+
+/// Apply the base relocations in `relocations` (the contents of a `.reloc` section) to
+/// `sections`, for a module loaded `delta` bytes away from its preferred address.
+fn apply_base_relocations(sections: &mut [CodeSection], relocations: &[u8], base: u64, delta: u64) {
+    const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+    const IMAGE_REL_BASED_DIR64: u16 = 10;
+
+    let mut blocks = relocations;
+    while let (Some(page), Some(block_size)) = (
+        blocks
+            .get(0..4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())),
+        blocks
+            .get(4..8)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())),
+    ) {
+        let block_size = block_size as usize;
+        if block_size < 8 || block_size > blocks.len() {
+            break;
+        }
+        for entry in blocks[8..block_size].chunks_exact(2) {
+            let entry = u16::from_le_bytes([entry[0], entry[1]]);
+            let address = base
+                .wrapping_add(page as u64)
+                .wrapping_add((entry & 0xfff) as u64);
+            match entry >> 12 {
+                IMAGE_REL_BASED_HIGHLOW => relocate(sections, address, 4, delta),
+                IMAGE_REL_BASED_DIR64 => relocate(sections, address, 8, delta),
+                _ => {}
+            }
+        }
+        blocks = &blocks[block_size..];
+    }
+}
+
+/// Add `delta` to the little-endian `width`-byte value at `address` in `sections`.
+fn relocate(sections: &mut [CodeSection], address: u64, width: usize, delta: u64) {
+    let bytes = sections.iter_mut().find_map(|section| {
+        let offset: usize = address.checked_sub(section.address)?.try_into().ok()?;
+        section.bytes.get_mut(offset..offset.checked_add(width)?)
+    });
+    match bytes {
+        Some(bytes) if width == 4 => {
+            let value = u32::from_le_bytes((&*bytes).try_into().unwrap());
+            bytes.copy_from_slice(&value.wrapping_add(delta as u32).to_le_bytes());
+        }
+        Some(bytes) => {
+            let value = u64::from_le_bytes((&*bytes).try_into().unwrap());
+            bytes.copy_from_slice(&value.wrapping_add(delta).to_le_bytes());
+        }
+        None => {}
+    }
+}
+
+fn macho_sections(macho: &MachO, module: &dyn Module) -> Option<Vec<CodeSection>> {
+    // Breakpad uses the UUID as the debug id of Mach-O modules.
+    let uuid = macho
+        .load_commands
+        .iter()
+        .find_map(|command| match command.command {
+            mach::load_command::CommandVariant::Uuid(command) => Some(command.uuid),
+            _ => None,
+        });
+    if let (Some(debug_id), Some(uuid)) = (module.debug_identifier(), uuid) {
+        if debug_id.uuid().as_bytes() != &uuid {
+            debug!("UUID of the binary doesn't match {}", module.code_file());
+            return None;
+        }
+    }
+
+    // The module is loaded at the address of the segment with the Mach-O header (`__TEXT`),
+    // and everything else is slid by the same amount.
+    let header_segment = macho
+        .segments
+        .iter()
+        .find(|segment| segment.fileoff == 0 && segment.filesize > 0)?;
+    let slide = module.base_address().wrapping_sub(header_segment.vmaddr);
+
+    let mut sections = Vec::new();
+    for segment in macho
+        .segments
+        .iter()
+        .filter(|segment| segment.initprot & mach::constants::VM_PROT_EXECUTE != 0)
+    {
+        for (section, data) in segment.sections().ok()? {
+            let instructions = mach::constants::S_ATTR_PURE_INSTRUCTIONS
+                | mach::constants::S_ATTR_SOME_INSTRUCTIONS;
+            if section.flags & instructions == 0 || data.is_empty() {
+                continue;
+            }
+            sections.push(CodeSection {
+                name: format!(
+                    "{},{}",
+                    section.segname().unwrap_or_default(),
+                    section.name().unwrap_or_default()
+                ),
+                address: slide.wrapping_add(section.addr),
+                bytes: data.to_vec(),
+            });
+        }
+    }
+    Some(sections)
+}
@@ -10,7 +10,12 @@ mod amd64;
 mod arm;
 mod arm64;
 mod arm64_old;
+#[cfg(feature = "module-code")]
+pub mod code;
 mod mips;
+mod ppc;
+mod riscv64;
+mod sparc;
 pub mod symbols;
 pub mod system_info;
 mod unwind;
@@ -26,12 +31,15 @@ use std::convert::TryFrom;
 use std::io::{self, Write};
 use tracing::trace;
 
+#[cfg(feature = "module-code")]
+pub use crate::code::*;
 pub use crate::symbols::*;
 pub use crate::system_info::*;
 
 mod impl_prelude {
     pub(crate) use super::{
-        unwind::Unwind, CfiStackWalker, FrameTrust, StackFrame, SymbolProvider, SystemInfo,
+        unwind::Unwind, CfiStackWalker, DynamicFunctionTables, FrameTrust, StackFrame,
+        SymbolProvider, SystemInfo, UnwindMemory,
     };
 }
 
@@ -336,6 +344,8 @@ pub struct CallStack {
     pub thread_name: Option<String>,
     /// The GetLastError() value stored in the TEB.
     pub last_error_value: Option<CrashReason>,
+    /// Extra information about the thread from the `ThreadInfoListStream`, if present.
+    pub thread_info: Option<MinidumpThreadInfo>,
 }
 
 impl CallStack {
@@ -349,6 +359,7 @@ impl CallStack {
             thread_id: 0,
             thread_name: None,
             last_error_value: None,
+            thread_info: None,
         }
     }
 
@@ -360,6 +371,7 @@ impl CallStack {
             thread_id: id,
             thread_name: None,
             last_error_value: None,
+            thread_info: None,
         }
     }
 
@@ -503,8 +515,16 @@ impl CallStack {
             if let Some(args) = &frame.arguments {
                 use MinidumpRawContext::*;
                 let pointer_width = match &frame.context.raw {
-                    X86(_) | Ppc(_) | Sparc(_) | Arm(_) | Mips(_) => 4,
-                    Ppc64(_) | Amd64(_) | Arm64(_) | OldArm64(_) => 8,
+                    X86(_) | Ppc(_) | Sparc(_) | Arm(_) => 4,
+                    Ppc64(_) | Amd64(_) | Arm64(_) | OldArm64(_) | Riscv64(_) => 8,
+                    Mips(ctx) => {
+                        let flags = format::ContextFlagsCpu::from_flags(ctx.context_flags);
+                        if flags.contains(format::ContextFlagsCpu::CONTEXT_MIPS64) {
+                            8
+                        } else {
+                            4
+                        }
+                    }
                 };
 
                 let cc_summary = match args.calling_convention {
@@ -547,7 +567,7 @@ struct CfiStackWalker<'a, C: CpuContext> {
     caller_ctx: C,
     caller_validity: HashSet<&'static str>,
 
-    stack_memory: UnifiedMemory<'a, 'a>,
+    stack_memory: &'a MinidumpAddressSpace<'a, 'a>,
 }
 
 impl<'a, C> FrameWalker for CfiStackWalker<'a, C>
@@ -555,7 +575,7 @@ where
     C: CpuContext,
     C::Register: TryFrom<u64>,
     u64: TryFrom<C::Register>,
-    C::Register: TryFromCtx<'a, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
+    C::Register: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
 {
     fn get_instruction(&self) -> u64 {
         self.instruction
@@ -605,7 +625,7 @@ async fn get_caller_frame<P>(
     _frame_idx: usize,
     callee_frame: &StackFrame,
     grand_callee_frame: Option<&StackFrame>,
-    stack_memory: Option<UnifiedMemory<'_, '_>>,
+    memory: UnwindMemory<'_>,
     modules: &MinidumpModuleList,
     system_info: &SystemInfo,
     symbol_provider: &P,
@@ -614,16 +634,11 @@ where
     P: SymbolProvider + Sync,
 {
     match callee_frame.context.raw {
-        /*
-        MinidumpRawContext::PPC(ctx) => ctx.get_caller_frame(stack_memory),
-        MinidumpRawContext::PPC64(ctx) => ctx.get_caller_frame(stack_memory),
-        MinidumpRawContext::SPARC(ctx) => ctx.get_caller_frame(stack_memory),
-         */
         MinidumpRawContext::Arm(ref ctx) => {
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -634,7 +649,7 @@ where
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -645,7 +660,7 @@ where
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -656,7 +671,7 @@ where
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -667,7 +682,7 @@ where
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -678,14 +693,57 @@ where
             ctx.get_caller_frame(
                 callee_frame,
                 grand_callee_frame,
-                stack_memory,
+                memory,
+                modules,
+                system_info,
+                symbol_provider,
+            )
+            .await
+        }
+        MinidumpRawContext::Riscv64(ref ctx) => {
+            ctx.get_caller_frame(
+                callee_frame,
+                grand_callee_frame,
+                memory,
+                modules,
+                system_info,
+                symbol_provider,
+            )
+            .await
+        }
+        MinidumpRawContext::Ppc(ref ctx) => {
+            ctx.get_caller_frame(
+                callee_frame,
+                grand_callee_frame,
+                memory,
+                modules,
+                system_info,
+                symbol_provider,
+            )
+            .await
+        }
+        MinidumpRawContext::Ppc64(ref ctx) => {
+            ctx.get_caller_frame(
+                callee_frame,
+                grand_callee_frame,
+                memory,
+                modules,
+                system_info,
+                symbol_provider,
+            )
+            .await
+        }
+        MinidumpRawContext::Sparc(ref ctx) => {
+            ctx.get_caller_frame(
+                callee_frame,
+                grand_callee_frame,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
             )
             .await
         }
-        _ => None,
     }
 }
 
@@ -713,6 +771,53 @@ async fn fill_source_line_info<P>(
     }
 }
 
+/// The function tables registered at runtime by the process, for unwinding
+/// through code that isn't part of any module.
+///
+/// JIT engines register the unwind information of the code they generate with
+/// `RtlAddFunctionTable` on Windows x64. The minidump records the tables in its
+/// `FunctionTable` stream, but the `UNWIND_INFO` they refer to is in the
+/// process's memory.
+#[derive(Clone, Copy)]
+pub struct DynamicFunctionTables<'a> {
+    /// The function tables from the minidump.
+    pub tables: &'a MinidumpFunctionTableList<'a>,
+    /// The memory of the process, containing the unwind information.
+    pub memory: &'a MinidumpAddressSpace<'a, 'a>,
+}
+
+/// The memory of the process the unwinders can read from.
+#[derive(Clone, Copy)]
+pub(crate) struct UnwindMemory<'a> {
+    /// The memory of the thread's stack.
+    pub stack: Option<&'a MinidumpAddressSpace<'a, 'a>>,
+    /// The function tables registered at runtime, if any.
+    pub function_tables: Option<DynamicFunctionTables<'a>>,
+    /// The code of the modules, for the code that isn't in the stack memory.
+    #[cfg(feature = "module-code")]
+    pub code: Option<&'a ModuleCodeCache>,
+    /// The first and last address of the contiguous memory around the stack
+    /// pointer of the first frame.
+    pub stack_range: Option<(u64, u64)>,
+}
+
+impl UnwindMemory<'_> {
+    /// Whether the `size` bytes at `address` are in the thread's stack.
+    ///
+    /// `stack` can contain other memory (the heap, modules...) that reads are
+    /// free to span, but an unwound stack or frame pointer only seems valid if
+    /// it points into the stack itself.
+    pub fn stack_contains(&self, address: u64, size: u64) -> bool {
+        match (
+            self.stack_range,
+            address.checked_add(size.saturating_sub(1)),
+        ) {
+            (Some((start, end)), Some(last)) => start <= address && last <= end,
+            _ => false,
+        }
+    }
+}
+
 /// An optional callback when walking frames.
 ///
 /// One may convert from other types to this callback type:
@@ -736,11 +841,37 @@ impl<'a, F: FnMut(usize, &StackFrame) + Send + 'a> From<F> for OnWalkedFrame<'a>
     }
 }
 
+/// The inputs of [`walk_stack`] that it can do without, at the cost of worse unwinding.
+///
+/// All fields are `pub`, but the type is `non_exhaustive`, so that new inputs can be
+/// added without breaking anything. Start from [`Default::default`] and set the ones
+/// you have:
+///
+/// ```
+/// use minidump::MinidumpAddressSpace;
+/// use minidump_unwind::WalkStackOptions;
+///
+/// let stack_memory = MinidumpAddressSpace::new();
+/// let mut options = WalkStackOptions::default();
+/// options.stack_memory = Some(&stack_memory);
+/// ```
+#[derive(Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct WalkStackOptions<'a> {
+    /// The memory of the thread's stack. Without it, only the context frame is produced.
+    pub stack_memory: Option<&'a MinidumpAddressSpace<'a, 'a>>,
+    /// The function tables registered at runtime, for unwinding through JIT code.
+    pub function_tables: Option<DynamicFunctionTables<'a>>,
+    /// The code of the modules, for the code the stack memory doesn't contain.
+    #[cfg(feature = "module-code")]
+    pub module_code: Option<&'a ModuleCodeCache>,
+}
+
 #[tracing::instrument(name = "unwind_thread", level = "trace", skip_all, fields(tid = stack.thread_id, tname = stack.thread_name.as_deref().unwrap_or("")))]
 pub async fn walk_stack<P>(
     on_walked_frame: impl Into<OnWalkedFrame<'_>>,
     stack: &mut CallStack,
-    stack_memory: Option<UnifiedMemory<'_, '_>>,
+    options: WalkStackOptions<'_>,
     modules: &MinidumpModuleList,
     system_info: &SystemInfo,
     symbol_provider: &P,
@@ -753,6 +884,20 @@ pub async fn walk_stack<P>(
         stack.thread_name.as_deref().unwrap_or(""),
     );
     // Begin with the context frame, and keep getting callers until there are no more.
+    let stack_range = options
+        .stack_memory
+        .zip(stack.frames.first())
+        .and_then(|(memory, frame)| {
+            memory.memory_range_at_address(frame.context.get_stack_pointer())
+        })
+        .map(|range| (range.start, range.end));
+    let memory = UnwindMemory {
+        stack: options.stack_memory,
+        function_tables: options.function_tables,
+        #[cfg(feature = "module-code")]
+        code: options.module_code,
+        stack_range,
+    };
     let mut has_new_frame = !stack.frames.is_empty();
     let mut on_walked_frame = on_walked_frame.into();
     while has_new_frame {
@@ -782,7 +927,7 @@ pub async fn walk_stack<P>(
             frame_idx,
             callee_frame,
             grand_callee_frame,
-            stack_memory,
+            memory,
             modules,
             system_info,
             symbol_provider,
@@ -888,6 +1033,88 @@ where
     }
 }
 
+/// Checks whether the code in front of `return_address` ends with an x86 or x86-64 call
+/// instruction, as it does for the return addresses that calls push.
+///
+/// The code is read from the stack memory if it's there, and otherwise from the binary of
+/// the module containing it (if `memory` has a `ModuleCodeCache`).
+///
+/// Return `None` if that code isn't available.
+#[cfg_attr(not(feature = "module-code"), allow(unused_variables))]
+async fn follows_x86_call<P>(
+    return_address: u64,
+    memory: UnwindMemory<'_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Option<bool>
+where
+    P: SymbolProvider + Sync,
+{
+    // The longest calls we look for (with a register, an offset and a prefix) are 7 bytes.
+    const MAX_CALL_LENGTH: usize = 7;
+    let address = return_address.checked_sub(MAX_CALL_LENGTH as u64)?;
+    let in_memory = memory
+        .stack
+        .and_then(|stack| stack.read_bytes(address, MAX_CALL_LENGTH));
+    #[cfg(feature = "module-code")]
+    let module_code;
+    let code = match in_memory.as_deref() {
+        Some(code) => code,
+        #[cfg(feature = "module-code")]
+        None => {
+            let module = modules.module_at_address(address)?;
+            module_code = memory.code?.get(module, symbol_provider).await?;
+            let section = module_code.section_at_address(address)?;
+            let offset = (address - section.address) as usize;
+            section
+                .bytes
+                .get(offset..offset.checked_add(MAX_CALL_LENGTH)?)?
+        }
+        #[cfg(not(feature = "module-code"))]
+        None => return None,
+    };
+    Some((2..=MAX_CALL_LENGTH).any(|len| {
+        let call = &code[MAX_CALL_LENGTH - len..];
+        // call rel32
+        (len == 5 && call[0] == 0xe8) || is_x86_indirect_call(call)
+    }))
+}
+
+/// Checks whether `code` is exactly one indirect near call instruction (`FF /2`), with or
+/// without a REX prefix.
+fn is_x86_indirect_call(code: &[u8]) -> bool {
+    let code = match code {
+        [0x40..=0x4f, rest @ ..] => rest,
+        _ => code,
+    };
+    let (modrm, operand) = match code {
+        [0xff, modrm, operand @ ..] => (*modrm, operand),
+        _ => return false,
+    };
+    if (modrm >> 3) & 0x7 != 2 {
+        return false;
+    }
+    // The length of the SIB byte and displacement that follow the ModRM byte.
+    let has_sib = modrm & 0x7 == 4;
+    let operand_len = match modrm >> 6 {
+        // call reg
+        3 => 0,
+        // call [rip+disp32] or call [disp32]
+        0 if modrm & 0x7 == 5 => 4,
+        // call [base+index*scale], which has a disp32 instead of a base if the base is 5
+        0 if has_sib => match operand.first() {
+            Some(sib) if sib & 0x7 == 5 => 5,
+            _ => 1,
+        },
+        0 => 0,
+        // call [reg+disp8]
+        1 => 1 + has_sib as usize,
+        // call [reg+disp32]
+        _ => 4 + has_sib as usize,
+    };
+    operand.len() == operand_len
+}
+
 #[cfg(test)]
 mod amd64_unittest;
 #[cfg(test)]
@@ -895,4 +1122,12 @@ mod arm64_unittest;
 #[cfg(test)]
 mod arm_unittest;
 #[cfg(test)]
+mod mips_unittest;
+#[cfg(test)]
+mod ppc_unittest;
+#[cfg(test)]
+mod riscv64_unittest;
+#[cfg(test)]
+mod sparc_unittest;
+#[cfg(test)]
 mod x86_unittest;
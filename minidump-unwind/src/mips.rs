@@ -1,8 +1,9 @@
 use super::impl_prelude::*;
 use minidump::format::ContextFlagsCpu;
+use minidump::system_info::Cpu;
 use minidump::{
-    CpuContext, Endian, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
-    MinidumpRawContext, UnifiedMemory,
+    CpuContext, Endian, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity,
+    MinidumpModuleList, MinidumpRawContext,
 };
 use scroll::ctx::{SizeWith, TryFromCtx};
 use std::collections::HashSet;
@@ -13,16 +14,78 @@ type MipsContext = minidump::format::CONTEXT_MIPS;
 type Pointer = <MipsContext as CpuContext>::Register;
 
 const STACK_POINTER: &str = "sp";
+const FRAME_POINTER: &str = "fp";
 const PROGRAM_COUNTER: &str = "pc";
-const CALLEE_SAVED_REGS: &[&str] = &[
+/// The callee-saved registers of the o32 ABI, where gp is caller-saved.
+const O32_CALLEE_SAVED_REGS: &[&str] =
+    &["s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "sp", "fp"];
+/// The callee-saved registers of the n32 and n64 ABIs.
+const N64_CALLEE_SAVED_REGS: &[&str] = &[
     "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "gp", "sp", "fp",
 ];
+/// How far above the frame pointer to look for the saved return address.
+const MAX_FRAME_SIZE: u64 = 4096;
+
+/// The calling convention of the code being unwound.
+///
+/// MIPS has three ABIs in common use, which differ in the size of registers
+/// and pointers, and in which registers are callee-saved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MipsAbi {
+    /// 32-bit registers and pointers.
+    O32,
+    /// 64-bit registers, but 32-bit pointers.
+    N32,
+    /// 64-bit registers and pointers.
+    N64,
+}
+
+impl MipsAbi {
+    fn new(ctx: &MipsContext, system_info: &SystemInfo) -> Self {
+        let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+        if !flags.contains(ContextFlagsCpu::CONTEXT_MIPS64) {
+            MipsAbi::O32
+        } else if system_info.cpu == Cpu::Mips {
+            MipsAbi::N32
+        } else {
+            MipsAbi::N64
+        }
+    }
+
+    /// The size of a register, which is also the size of a stack slot.
+    fn register_width(self) -> u64 {
+        match self {
+            MipsAbi::O32 => 4,
+            MipsAbi::N32 | MipsAbi::N64 => 8,
+        }
+    }
+
+    fn callee_saved_regs(self) -> &'static [&'static str] {
+        match self {
+            MipsAbi::O32 => O32_CALLEE_SAVED_REGS,
+            MipsAbi::N32 | MipsAbi::N64 => N64_CALLEE_SAVED_REGS,
+        }
+    }
+
+    /// The highest address user-space code could be at.
+    fn max_instruction(self) -> u64 {
+        match self {
+            // The upper half of the address space belongs to the kernel.
+            MipsAbi::O32 | MipsAbi::N32 => 0x7fff_ffff,
+            // The user segment is at most 2^48 bytes on any implementation
+            // Linux supports. This notably rejects the sign-extended 32-bit
+            // values that are all over 64-bit stacks.
+            MipsAbi::N64 => 0x0000_ffff_ffff_ffff,
+        }
+    }
+}
 
 async fn get_caller_by_cfi<'a, C, P>(
     ctx: &'a C,
+    abi: MipsAbi,
     callee: &'a StackFrame,
     grand_callee: Option<&'a StackFrame>,
-    stack_memory: UnifiedMemory<'a, '_>,
+    stack_memory: &'a MinidumpAddressSpace<'a, '_>,
     modules: &'a MinidumpModuleList,
     symbol_provider: &'a P,
 ) -> Option<StackFrame>
@@ -32,7 +95,7 @@ where
     C: CpuContext + IntoRawContext + Clone + Send + Sync,
     C::Register: TryFrom<u64>,
     u64: TryFrom<C::Register>,
-    C::Register: TryFromCtx<'a, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
+    C::Register: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
 {
     trace!("trying cfi");
     let valid = &callee.context.valid;
@@ -53,7 +116,7 @@ where
         // The CFI evaluator may clear or overwrite these values.
         // The stack pointer and instruction pointer are not included.
         caller_ctx: ctx.clone(),
-        caller_validity: callee_forwarded_regs(valid),
+        caller_validity: callee_forwarded_regs(abi, valid),
 
         stack_memory,
     };
@@ -76,14 +139,16 @@ where
     let context = MinidumpContext {
         raw: stack_walker.caller_ctx.into_ctx(),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
-fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
+fn callee_forwarded_regs(abi: MipsAbi, valid: &MinidumpContextValidity) -> HashSet<&'static str> {
+    let callee_saved_regs = abi.callee_saved_regs();
     match valid {
-        MinidumpContextValidity::All => CALLEE_SAVED_REGS.iter().copied().collect(),
-        MinidumpContextValidity::Some(ref which) => CALLEE_SAVED_REGS
+        MinidumpContextValidity::All => callee_saved_regs.iter().copied().collect(),
+        MinidumpContextValidity::Some(ref which) => callee_saved_regs
             .iter()
             .filter(|&reg| which.contains(reg))
             .copied()
@@ -91,68 +156,81 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
     }
 }
 
-async fn get_caller_by_scan32<P>(
-    ctx: &Mips32Context,
+async fn get_caller_by_frame_pointer<P>(
+    ctx: &MipsContext,
+    abi: MipsAbi,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider + Sync,
 {
-    const MAX_STACK_SIZE: u32 = 1024;
-    const MIN_ARGS: u32 = 4;
-    const POINTER_WIDTH: u32 = 4;
-    trace!("trying scan");
-    // Stack scanning is just walking from the end of the frame until we encounter
-    // a value on the stack that looks like a pointer into some code (it's an address
-    // in a range covered by one of our modules). If we find such an instruction,
-    // we assume it's a `ra` value that was saved on the stack by the callee in
-    // its function prologue, following a `jal` (call) instruction of the caller.
-    // The next frame is then assumed to end just before that `ra` value.
+    trace!("trying frame pointer");
+    // A function that uses a frame pointer (fp, also known as s8) has a
+    // prologue like this:
+    //
+    // sp := sp - frame_size
+    // *(sp + frame_size - reg) := ra
+    // *(sp + frame_size - reg*2) := fp
+    // fp := sp
+    //
+    // So unlike most other architectures, the frame pointer points at the
+    // *bottom* of the frame, and we can't know where the saved ra and fp are
+    // without knowing the size of the frame. They are always the two highest
+    // slots of the frame though, and the caller's frame pointer is at or
+    // above the top of the frame (it's equal to the top, which is the
+    // caller's stack pointer, unless the caller used alloca). So we look for
+    // such a pair above the frame pointer:
+    //
+    // sp := address_of_fp + reg*2
+    // fp := *address_of_fp, where fp >= sp (or 0 for the outermost frame)
+    // pc := *(address_of_fp + reg), where pc looks like code
+    //
+    // This is more reliable than plain stack scanning, since the saved frame
+    // pointer has to line up with the return address. It's still a search
+    // though, not a read of the actual frame layout, so the frames it finds
+    // are only trusted as much as scanned ones.
     let valid = &callee.context.valid;
-    let mut last_sp = ctx.get_register(STACK_POINTER, valid)?;
-
-    let mut count = MAX_STACK_SIZE / POINTER_WIDTH;
-    // In case of mips32 ABI the stack frame of a non-leaf function
-    // must have a minimum stack frame size for 4 arguments (4 words).
-    // Move stack pointer for 4 words to avoid reporting non-existing frames
-    // for all frames except the topmost one.
-    // There is no way of knowing if topmost frame belongs to a leaf or
-    // a non-leaf function.
-    if callee.trust != FrameTrust::Context {
-        last_sp = last_sp.checked_add(MIN_ARGS * POINTER_WIDTH)?;
-        count -= MIN_ARGS;
+    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
+    if last_fp == 0 || last_fp < last_sp {
+        return None;
     }
 
-    for i in 0..count {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_pc: u32 = stack_memory.get_memory_at_address(address_of_pc as u64)?;
-        //trace!("unwind: trying addr 0x{address_of_pc:08x}: 0x{caller_pc:08x}");
-        if instruction_seems_valid(caller_pc as u64, modules, symbol_provider).await {
-            // `ra` is usually saved directly at the bottom of the frame,
-            // so sp is just address_of_pc + ptr
-            let caller_sp = address_of_pc.checked_add(POINTER_WIDTH)?;
-
-            // Don't do any more validation, and don't try to restore fp
-            // (that's what breakpad does!)
-
+    let reg_width = abi.register_width();
+    for i in 0..MAX_FRAME_SIZE / reg_width {
+        let address_of_fp = last_fp.checked_add(i * reg_width)?;
+        let address_of_pc = address_of_fp.checked_add(reg_width)?;
+        let caller_sp = address_of_pc.checked_add(reg_width)?;
+        let caller_fp = read_register(stack_memory, abi, address_of_fp)?;
+        if caller_fp != 0 && caller_fp < caller_sp {
+            continue;
+        }
+        let caller_pc = read_register(stack_memory, abi, address_of_pc)?;
+        if instruction_seems_valid(caller_pc, abi, modules, symbol_provider).await {
             trace!(
-                "scan seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+                "frame pointer seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
             );
 
-            let mut caller_ctx = MipsContext::default();
-            caller_ctx.set_register(PROGRAM_COUNTER, caller_pc as u64);
-            caller_ctx.set_register(STACK_POINTER, caller_sp as u64);
+            let mut caller_ctx = MipsContext {
+                context_flags: ctx.context_flags,
+                ..MipsContext::default()
+            };
+            caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+            caller_ctx.set_register(STACK_POINTER, caller_sp);
+            caller_ctx.set_register(FRAME_POINTER, caller_fp);
 
             let mut valid = HashSet::new();
             valid.insert(PROGRAM_COUNTER);
             valid.insert(STACK_POINTER);
+            valid.insert(FRAME_POINTER);
 
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Mips(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -161,10 +239,11 @@ where
     None
 }
 
-async fn get_caller_by_scan64<P>(
+async fn get_caller_by_scan<P>(
     ctx: &MipsContext,
+    abi: MipsAbi,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -172,7 +251,7 @@ where
     P: SymbolProvider + Sync,
 {
     const MAX_STACK_SIZE: u64 = 1024;
-    const POINTER_WIDTH: u64 = 8;
+    const MIN_ARGS: u64 = 4;
     trace!("trying scan");
     // Stack scanning is just walking from the end of the frame until we encounter
     // a value on the stack that looks like a pointer into some code (it's an address
@@ -180,18 +259,33 @@ where
     // we assume it's a `ra` value that was saved on the stack by the callee in
     // its function prologue, following a `jal` (call) instruction of the caller.
     // The next frame is then assumed to end just before that `ra` value.
+    //
+    // Registers are saved in slots of their own size, so the n32 ABI saves
+    // 64-bit return addresses even though its pointers are 32-bit.
     let valid = &callee.context.valid;
-    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let mut last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let reg_width = abi.register_width();
 
-    let count = MAX_STACK_SIZE / POINTER_WIDTH;
+    let mut count = MAX_STACK_SIZE / reg_width;
+
+    // In case of mips32 ABI the stack frame of a non-leaf function
+    // must have a minimum stack frame size for 4 arguments (4 words).
+    // Move stack pointer for 4 words to avoid reporting non-existing frames
+    // for all frames except the topmost one.
+    // There is no way of knowing if topmost frame belongs to a leaf or
+    // a non-leaf function. The n32 and n64 ABIs don't reserve this space.
+    if abi == MipsAbi::O32 && callee.trust != FrameTrust::Context {
+        last_sp = last_sp.checked_add(MIN_ARGS * reg_width)?;
+        count -= MIN_ARGS;
+    }
 
     for i in 0..count {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
-        let caller_pc = stack_memory.get_memory_at_address(address_of_pc)?;
-        if instruction_seems_valid(caller_pc, modules, symbol_provider).await {
+        let address_of_pc = last_sp.checked_add(i * reg_width)?;
+        let caller_pc = read_register(stack_memory, abi, address_of_pc)?;
+        if instruction_seems_valid(caller_pc, abi, modules, symbol_provider).await {
             // `ra` is usually saved directly at the bottom of the frame,
             // so sp is just address_of_pc + ptr
-            let caller_sp = address_of_pc.checked_add(POINTER_WIDTH)?;
+            let caller_sp = address_of_pc.checked_add(reg_width)?;
 
             // Don't do any more validation, and don't try to restore fp
             // (that's what breakpad does!)
@@ -200,7 +294,10 @@ where
                 "scan seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
             );
 
-            let mut caller_ctx = MipsContext::default();
+            let mut caller_ctx = MipsContext {
+                context_flags: ctx.context_flags,
+                ..MipsContext::default()
+            };
             caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
             caller_ctx.set_register(STACK_POINTER, caller_sp);
 
@@ -211,6 +308,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::Mips(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -219,15 +317,37 @@ where
     None
 }
 
+/// Read a register saved on the stack.
+fn read_register(
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+    abi: MipsAbi,
+    address: u64,
+) -> Option<u64> {
+    if abi.register_width() == 4 {
+        stack_memory
+            .get_memory_at_address::<u32>(address)
+            .map(u64::from)
+    } else {
+        stack_memory.get_memory_at_address::<u64>(address)
+    }
+}
+
 async fn instruction_seems_valid<P>(
     instruction: Pointer,
+    abi: MipsAbi,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> bool
 where
     P: SymbolProvider + Sync,
 {
-    if instruction < 0x1000 {
+    if instruction < 0x1000 || instruction > abi.max_instruction() {
+        return false;
+    }
+
+    // Instructions are 4-byte aligned, except in the MIPS16 and microMIPS
+    // modes, where return addresses have the lowest bit set instead.
+    if instruction & 0b11 == 0b10 {
         return false;
     }
 
@@ -240,40 +360,34 @@ impl Unwind for MipsContext {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
-        _system_info: &SystemInfo,
+        system_info: &SystemInfo,
         syms: &P,
     ) -> Option<StackFrame>
     where
         P: SymbolProvider + Sync,
     {
-        let ctx = Mips32Context::try_from(self.clone());
-        let stack = stack_memory?;
+        let abi = MipsAbi::new(self, system_info);
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
         if frame.is_none() {
-            match &ctx {
-                Ok(mips32) => {
-                    frame =
-                        get_caller_by_cfi(mips32, callee, grand_callee, stack, modules, syms).await
-                }
-                Err(mips64) => {
-                    frame =
-                        get_caller_by_cfi(mips64, callee, grand_callee, stack, modules, syms).await
-                }
+            if abi == MipsAbi::O32 {
+                let mips32 = Mips32Context(self.clone());
+                frame = get_caller_by_cfi(&mips32, abi, callee, grand_callee, stack, modules, syms)
+                    .await;
+            } else {
+                frame =
+                    get_caller_by_cfi(self, abi, callee, grand_callee, stack, modules, syms).await;
             }
         }
         if frame.is_none() {
-            match &ctx {
-                Ok(mips32) => {
-                    frame = get_caller_by_scan32(mips32, callee, stack, modules, syms).await
-                }
-                Err(mips64) => {
-                    frame = get_caller_by_scan64(mips64, callee, stack, modules, syms).await
-                }
-            }
+            frame = get_caller_by_frame_pointer(self, abi, callee, stack, modules, syms).await;
+        }
+        if frame.is_none() {
+            frame = get_caller_by_scan(self, abi, callee, stack, modules, syms).await;
         }
         let mut frame = frame?;
 
@@ -317,8 +431,8 @@ impl Unwind for MipsContext {
     }
 }
 
-/// This is a hack to have a different [`CpuContext`] type/impl depending on the
-/// context flags of the inner [`MipsContext`]
+/// This is a hack to have a different [`CpuContext`] type/impl for the o32 ABI,
+/// see [`MipsAbi`].
 #[derive(Clone)]
 struct Mips32Context(MipsContext);
 
@@ -359,16 +473,3 @@ impl IntoRawContext for MipsContext {
         MinidumpRawContext::Mips(self)
     }
 }
-
-impl TryFrom<MipsContext> for Mips32Context {
-    type Error = MipsContext;
-
-    fn try_from(ctx: MipsContext) -> Result<Self, Self::Error> {
-        if ContextFlagsCpu::from_flags(ctx.context_flags).contains(ContextFlagsCpu::CONTEXT_MIPS64)
-        {
-            Err(ctx)
-        } else {
-            Ok(Self(ctx))
-        }
-    }
-}
@@ -0,0 +1,381 @@
+use crate::*;
+use minidump::format::ContextFlagsCpu;
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+type Context = minidump::format::CONTEXT_MIPS;
+
+struct TestFixture {
+    pub raw: Context,
+    pub cpu: Cpu,
+    pub modules: MinidumpModuleList,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    /// A 32-bit process using the o32 ABI.
+    pub fn new_o32() -> TestFixture {
+        TestFixture::new(Cpu::Mips, ContextFlagsCpu::CONTEXT_MIPS)
+    }
+
+    /// A 32-bit process using the n32 ABI, which has 64-bit registers.
+    pub fn new_n32() -> TestFixture {
+        TestFixture::new(Cpu::Mips, ContextFlagsCpu::CONTEXT_MIPS64)
+    }
+
+    /// A 64-bit process using the n64 ABI.
+    pub fn new_n64() -> TestFixture {
+        TestFixture::new(Cpu::Mips64, ContextFlagsCpu::CONTEXT_MIPS64)
+    }
+
+    fn new(cpu: Cpu, flags: ContextFlagsCpu) -> TestFixture {
+        TestFixture {
+            raw: Context {
+                context_flags: flags.bits(),
+                ..Context::default()
+            },
+            cpu,
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Mips(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+            xstate: None,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::LE,
+        };
+        let system_info = SystemInfo {
+            os: Os::Linux,
+            os_version: None,
+            os_build: None,
+            cpu: self.cpu,
+            cpu_info: None,
+            cpu_microcode_version: None,
+            cpu_count: 1,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            (),
+            &mut stack,
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
+            &self.modules,
+            &system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+fn frame_registers(frame: &StackFrame) -> (&Context, &MinidumpContextValidity) {
+    match &frame.context.raw {
+        MinidumpRawContext::Mips(ctx) => (ctx, &frame.context.valid),
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let mut f = TestFixture::new_o32();
+    let stack = Section::new();
+    stack.start().set_const(0x80000000);
+    // There should be no references to the stack in this walk: we don't
+    // provide any call frame information, so trying to reconstruct the
+    // context frame's caller should fail. So there's no need for us to
+    // provide stack contents.
+    f.raw.set_register("pc", 0x4000c020);
+    f.raw.set_register("sp", 0x80000000);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[tokio::test]
+async fn test_scan_o32() {
+    let mut f = TestFixture::new_o32();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    let return_address2 = 0x50000900u32;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .D32(0x40090000) // junk that's not
+        .D32(0x60000000) // a return address
+        .D32(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .D32(0x50000200) // in the argument save area, so skipped
+        .append_repeated(0, 12) // the rest of the argument save area
+        .D32(0x0000000D) // junk
+        .D32(return_address2) // actual return address
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 64); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    for (frame, return_address, sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let (ctx, valid) = frame_registers(frame);
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        assert_eq!(ctx.get_register("pc", valid), Some(return_address as u64));
+        assert_eq!(ctx.get_register("sp", valid), sp.value());
+        assert_eq!(frame.instruction, return_address as u64 - 8);
+    }
+}
+
+#[tokio::test]
+async fn test_scan_n64() {
+    let mut f = TestFixture::new_n64();
+    let mut stack = Section::new();
+    stack.start().set_const(0x7fff_0000_0000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(0xffff_ffff_8000_1234) // a sign-extended kernel address
+        .D64(0x50000102) // not 4-byte aligned
+        .D64(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .D64(0x0000000D) // junk
+        .D64(return_address2) // actual return address, n64 has no argument save area
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 64); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    for (frame, return_address, sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let (ctx, valid) = frame_registers(frame);
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        assert_eq!(ctx.get_register("pc", valid), Some(return_address));
+        assert_eq!(ctx.get_register("sp", valid), sp.value());
+        // The caller is still unwound with the n64 ABI.
+        assert_eq!(ctx.context_flags, f.raw.context_flags);
+    }
+}
+
+#[tokio::test]
+async fn test_scan_n32() {
+    // n32 has 32-bit pointers, but saves 64-bit registers.
+    let mut f = TestFixture::new_n32();
+    let mut stack = Section::new();
+    stack.start().set_const(0x7fff0000);
+
+    let return_address = 0x50000100u64;
+    let frame1_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(0x1_5000_0100) // too large for a 32-bit pointer
+        .D64(return_address) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 64); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let (ctx, valid) = frame_registers(frame);
+    assert_eq!(frame.trust, FrameTrust::Scan);
+    assert_eq!(ctx.get_register("pc", valid), Some(return_address));
+    assert_eq!(ctx.get_register("sp", valid), frame1_sp.value());
+}
+
+#[tokio::test]
+async fn test_frame_pointer_n64() {
+    let mut f = TestFixture::new_n64();
+    let mut stack = Section::new();
+    stack.start().set_const(0x7fff_0000_0000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+    let frame2_sp = Label::new();
+
+    // The frame pointer points at the bottom of the frame, and the saved
+    // frame pointer and return address are at the top.
+    stack = stack
+        // frame 0
+        .mark(&frame0_fp)
+        .D64(0x0000000D) // junk that could be taken
+        .D64(0x40001000) // for a saved fp and ra
+        .append_repeated(0, 32) // space
+        .D64(&frame1_fp) // saved fp
+        .D64(return_address1) // saved ra
+        // frame 1
+        .mark(&frame1_fp)
+        .append_repeated(0, 16) // space
+        .D64(0) // saved fp, this is the outermost frame
+        .D64(return_address2) // saved ra
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+    f.raw.set_register("s8", frame0_fp.value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    for (frame, return_address, sp, fp) in [
+        (&s.frames[1], return_address1, &frame1_fp, frame1_fp.value()),
+        (&s.frames[2], return_address2, &frame2_sp, Some(0)),
+    ] {
+        let (ctx, valid) = frame_registers(frame);
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        assert_eq!(ctx.get_register("pc", valid), Some(return_address));
+        assert_eq!(ctx.get_register("sp", valid), sp.value());
+        assert_eq!(ctx.get_register("fp", valid), fp);
+        assert_eq!(ctx.get_register("s8", valid), fp);
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer_o32() {
+    let mut f = TestFixture::new_o32();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address = 0x50000100u32;
+    let frame1_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // argument save area
+        .append_repeated(0, 8) // space
+        .D32(&frame1_fp) // saved fp
+        .D32(return_address) // saved ra
+        // frame 1
+        .mark(&frame1_fp)
+        .append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+    f.raw.set_register("fp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let (ctx, valid) = frame_registers(frame);
+    assert_eq!(frame.trust, FrameTrust::Scan);
+    assert_eq!(ctx.get_register("pc", valid), Some(return_address as u64));
+    assert_eq!(ctx.get_register("sp", valid), frame1_fp.value());
+    assert_eq!(ctx.get_register("fp", valid), frame1_fp.value());
+}
+
+async fn check_cfi(mut f: TestFixture, expected_regs: &[&str]) {
+    let symbols = [
+        // The youngest frame's function, which hasn't touched the stack.
+        "FUNC 4000 1000 10 enchiridion\n",
+        "STACK CFI INIT 4000 100 .cfa: sp 0 + .ra: ra\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let stack = Section::new().append_repeated(0, 64);
+    stack.start().set_const(0x80000000);
+
+    f.raw.set_register("pc", 0x40004010);
+    f.raw.set_register("ra", 0x40005510);
+    f.raw.set_register("sp", 0x80000000);
+    f.raw.set_register("gp", 0x40008000);
+    f.raw.set_register("s0", 0x12345678);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let (ctx, valid) = frame_registers(frame);
+    assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(ctx.get_register("pc", valid), Some(0x40005510));
+    assert_eq!(ctx.get_register("s0", valid), Some(0x12345678));
+    if let MinidumpContextValidity::Some(which) = valid {
+        let mut which: Vec<_> = which.iter().copied().collect();
+        which.sort_unstable();
+        let mut expected_regs = expected_regs.to_vec();
+        expected_regs.sort_unstable();
+        assert_eq!(which, expected_regs);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_cfi_o32() {
+    // gp is caller-saved in the o32 ABI.
+    let expected_regs = [
+        "pc", "sp", "fp", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7",
+    ];
+    check_cfi(TestFixture::new_o32(), &expected_regs).await;
+}
+
+#[tokio::test]
+async fn test_cfi_n64() {
+    let expected_regs = [
+        "pc", "sp", "fp", "gp", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7",
+    ];
+    check_cfi(TestFixture::new_n64(), &expected_regs).await;
+}
@@ -0,0 +1,324 @@
+// Unwinding for PowerPC, which is shared by the 32-bit and 64-bit contexts.
+
+use super::impl_prelude::*;
+use minidump::format::{CONTEXT_PPC, CONTEXT_PPC64};
+use minidump::system_info::Os;
+use minidump::{
+    CpuContext, Endian, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity,
+    MinidumpModuleList, MinidumpRawContext,
+};
+use scroll::ctx::{SizeWith, TryFromCtx};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use tracing::trace;
+
+const STACK_POINTER: &str = "r1";
+const PROGRAM_COUNTER: &str = "srr0";
+const CALLEE_SAVED_REGS: &[&str] = &[
+    "r14", "r15", "r16", "r17", "r18", "r19", "r20", "r21", "r22", "r23", "r24", "r25", "r26",
+    "r27", "r28", "r29", "r30", "r31",
+];
+
+/// The parts of unwinding that differ between PPC and PPC64.
+trait PpcContext: CpuContext + Clone + Default + Send + Sync {
+    const POINTER_WIDTH: u64;
+
+    /// The offset from a frame's back chain at which the return address of
+    /// the frame's callee is saved.
+    fn saved_lr_offset(os: Os) -> u64;
+
+    fn into_ctx(self) -> MinidumpRawContext;
+}
+
+impl PpcContext for CONTEXT_PPC {
+    const POINTER_WIDTH: u64 = 4;
+
+    fn saved_lr_offset(os: Os) -> u64 {
+        // The SVR4 ABI puts the LR save word right after the back chain, but
+        // the Darwin ABI has a CR save word in between.
+        if os == Os::MacOs {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn into_ctx(self) -> MinidumpRawContext {
+        MinidumpRawContext::Ppc(self)
+    }
+}
+
+impl PpcContext for CONTEXT_PPC64 {
+    const POINTER_WIDTH: u64 = 8;
+
+    fn saved_lr_offset(_os: Os) -> u64 {
+        // The same for ELFv1, ELFv2 and Darwin.
+        16
+    }
+
+    fn into_ctx(self) -> MinidumpRawContext {
+        MinidumpRawContext::Ppc64(self)
+    }
+}
+
+async fn get_caller_by_cfi<'a, C, P>(
+    ctx: &'a C,
+    callee: &'a StackFrame,
+    grand_callee: Option<&'a StackFrame>,
+    stack_memory: &'a MinidumpAddressSpace<'a, '_>,
+    modules: &'a MinidumpModuleList,
+    symbol_provider: &'a P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+    // all these bounds are essentially duplicated from `CfiStackWalker` :-(
+    C: PpcContext,
+    C::Register: TryFrom<u64>,
+    u64: TryFrom<C::Register>,
+    C::Register: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
+{
+    trace!("trying cfi");
+    let valid = &callee.context.valid;
+    let _last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let module = modules.module_at_address(callee.instruction)?;
+    let grand_callee_parameter_size = grand_callee.and_then(|f| f.parameter_size).unwrap_or(0);
+    let has_grand_callee = grand_callee.is_some();
+
+    let mut stack_walker = CfiStackWalker {
+        instruction: callee.instruction,
+        has_grand_callee,
+        grand_callee_parameter_size,
+
+        callee_ctx: ctx,
+        callee_validity: valid,
+
+        // Default to forwarding all callee-saved regs verbatim.
+        // The CFI evaluator may clear or overwrite these values.
+        // The stack pointer and instruction pointer are not included.
+        caller_ctx: ctx.clone(),
+        caller_validity: callee_forwarded_regs(valid),
+
+        stack_memory,
+    };
+
+    symbol_provider
+        .walk_frame(module, &mut stack_walker)
+        .await?;
+    let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
+    let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
+
+    trace!(
+        "cfi evaluation was successful -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+    );
+
+    let context = MinidumpContext {
+        raw: stack_walker.caller_ctx.into_ctx(),
+        valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
+fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
+    match valid {
+        MinidumpContextValidity::All => CALLEE_SAVED_REGS.iter().copied().collect(),
+        MinidumpContextValidity::Some(ref which) => CALLEE_SAVED_REGS
+            .iter()
+            .filter(|&reg| which.contains(reg))
+            .copied()
+            .collect(),
+    }
+}
+
+fn get_caller_by_frame_pointer<C>(
+    ctx: &C,
+    callee: &StackFrame,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+    system_info: &SystemInfo,
+) -> Option<StackFrame>
+where
+    C: PpcContext,
+    C::Register: TryFrom<u64>,
+    u64: TryFrom<C::Register>,
+{
+    trace!("trying frame pointer");
+    // PowerPC doesn't have a dedicated frame pointer, but every frame starts
+    // with a "back chain" word that points to the start of the caller's frame,
+    // which is stored by the same instruction that allocates the frame
+    // (`stwu r1, -frame_size(r1)`). So the stack pointer (r1) always points at
+    // a linked list of frames:
+    //
+    // sp := *sp
+    //
+    // The return address is passed in lr, which a non-leaf function saves in
+    // a slot that the ABI reserves in its *caller's* frame, a fixed distance
+    // from the back chain:
+    //
+    // pc := *(sp + saved_lr_offset)
+    //
+    // As with the frame pointer on ARM64, there's no way to tell whether the
+    // callee is a leaf function which hasn't saved lr (or even allocated a
+    // frame) without CFI, so we always assume it isn't.
+    let valid = &callee.context.valid;
+    let last_sp = u64::try_from(ctx.get_register(STACK_POINTER, valid)?).ok()?;
+
+    let caller_sp = read_pointer::<C>(stack_memory, last_sp)?;
+    if caller_sp == 0 {
+        // The back chain of the outermost frame is null.
+        trace!("back chain is null, assuming unwind complete");
+        return None;
+    }
+    let saved_lr_offset = C::saved_lr_offset(system_info.os);
+    let caller_pc = read_pointer::<C>(stack_memory, caller_sp.checked_add(saved_lr_offset)?)?;
+
+    trace!(
+        "frame pointer seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+    );
+
+    let mut caller_ctx = C::default();
+    caller_ctx.set_register(PROGRAM_COUNTER, C::Register::try_from(caller_pc).ok()?);
+    caller_ctx.set_register(STACK_POINTER, C::Register::try_from(caller_sp).ok()?);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+
+    let context = MinidumpContext {
+        raw: caller_ctx.into_ctx(),
+        valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::FramePointer))
+}
+
+fn read_pointer<C: PpcContext>(
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+    address: u64,
+) -> Option<u64> {
+    if C::POINTER_WIDTH == 4 {
+        stack_memory
+            .get_memory_at_address::<u32>(address)
+            .map(u64::from)
+    } else {
+        stack_memory.get_memory_at_address::<u64>(address)
+    }
+}
+
+async fn get_caller_frame<'a, C, P>(
+    ctx: &'a C,
+    callee: &'a StackFrame,
+    grand_callee: Option<&'a StackFrame>,
+    stack_memory: Option<&'a MinidumpAddressSpace<'a, '_>>,
+    modules: &'a MinidumpModuleList,
+    system_info: &'a SystemInfo,
+    syms: &'a P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+    C: PpcContext,
+    C::Register: TryFrom<u64>,
+    u64: TryFrom<C::Register>,
+    C::Register: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error> + SizeWith<Endian>,
+{
+    let stack = stack_memory?;
+
+    // .await doesn't like closures, so don't use Option chaining
+    let mut frame = None;
+    if frame.is_none() {
+        frame = get_caller_by_cfi(ctx, callee, grand_callee, stack, modules, syms).await;
+    }
+    if frame.is_none() {
+        frame = get_caller_by_frame_pointer(ctx, callee, stack, system_info);
+    }
+    let mut frame = frame?;
+
+    // We now check the frame to see if it looks like unwinding is complete,
+    // based on the frame we computed having a nonsense value. Returning
+    // None signals to the unwinder to stop unwinding.
+
+    // if the instruction is within the first ~page of memory, it's basically
+    // null, and we can assume unwinding is complete.
+    if frame.context.get_instruction_pointer() < 4096 {
+        trace!("instruction pointer was nullish, assuming unwind complete");
+        return None;
+    }
+
+    // If the new stack pointer is at a lower address than the old,
+    // then that's clearly incorrect. Treat this as end-of-stack to
+    // enforce progress and avoid infinite loops.
+    let sp = frame.context.get_stack_pointer();
+    let last_sp = u64::try_from(ctx.get_register_always(STACK_POINTER)).ok()?;
+    if sp <= last_sp {
+        // Leaf functions may not allocate a frame at all (the return address
+        // is in lr), so permit the stack pointer to not change for the first
+        // frame of the unwind.
+        let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
+        if !is_leaf {
+            trace!("stack pointer went backwards, assuming unwind complete");
+            return None;
+        }
+    }
+
+    // Ok, the frame now seems well and truly valid, do final cleanup.
+
+    // All instructions are 4 bytes, and `bl` sets lr to the instruction after
+    // it, so the call is 4 bytes before the return address.
+    let ip = frame.context.get_instruction_pointer();
+    frame.instruction = ip - 4;
+
+    Some(frame)
+}
+
+#[async_trait::async_trait]
+impl Unwind for CONTEXT_PPC {
+    async fn get_caller_frame<P>(
+        &self,
+        callee: &StackFrame,
+        grand_callee: Option<&StackFrame>,
+        memory: UnwindMemory<'_>,
+        modules: &MinidumpModuleList,
+        system_info: &SystemInfo,
+        syms: &P,
+    ) -> Option<StackFrame>
+    where
+        P: SymbolProvider + Sync,
+    {
+        get_caller_frame(
+            self,
+            callee,
+            grand_callee,
+            memory.stack,
+            modules,
+            system_info,
+            syms,
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl Unwind for CONTEXT_PPC64 {
+    async fn get_caller_frame<P>(
+        &self,
+        callee: &StackFrame,
+        grand_callee: Option<&StackFrame>,
+        memory: UnwindMemory<'_>,
+        modules: &MinidumpModuleList,
+        system_info: &SystemInfo,
+        syms: &P,
+    ) -> Option<StackFrame>
+    where
+        P: SymbolProvider + Sync,
+    {
+        get_caller_frame(
+            self,
+            callee,
+            grand_callee,
+            memory.stack,
+            modules,
+            system_info,
+            syms,
+        )
+        .await
+    }
+}
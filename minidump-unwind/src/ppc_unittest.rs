@@ -0,0 +1,307 @@
+use crate::*;
+use minidump::format::{CONTEXT_PPC, CONTEXT_PPC64};
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+struct TestFixture {
+    pub os: Os,
+    pub modules: MinidumpModuleList,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    pub fn new(os: Os) -> TestFixture {
+        TestFixture {
+            os,
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub async fn walk_stack(&self, raw: MinidumpRawContext, stack: Section) -> CallStack {
+        let cpu = match raw {
+            MinidumpRawContext::Ppc64(_) => Cpu::Ppc64,
+            _ => Cpu::Ppc,
+        };
+        let context = MinidumpContext {
+            raw,
+            valid: MinidumpContextValidity::All,
+            xstate: None,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::BE,
+        };
+        let system_info = SystemInfo {
+            os: self.os,
+            os_version: None,
+            os_build: None,
+            cpu,
+            cpu_info: None,
+            cpu_microcode_version: None,
+            cpu_count: 1,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            (),
+            &mut stack,
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
+            &self.modules,
+            &system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+fn ppc_context(pc: u32, sp: u32) -> CONTEXT_PPC {
+    let mut ctx = CONTEXT_PPC::default();
+    ctx.set_register("srr0", pc);
+    ctx.set_register("r1", sp);
+    ctx
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let f = TestFixture::new(Os::Linux);
+    let stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+    // No back chain on the stack, so unwinding stops immediately.
+    let raw = MinidumpRawContext::Ppc(ppc_context(0x4000c020, 0x80000000));
+
+    let s = f.walk_stack(raw, stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+async fn check_back_chain_ppc(os: Os, saved_lr_offset: u64) {
+    let f = TestFixture::new(os);
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    let return_address2 = 0x50000900u32;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D32(&frame1_sp) // back chain
+        .append_repeated(0, 28) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .D32(&frame2_sp) // back chain
+        .append_repeated(0, saved_lr_offset as usize - 4)
+        .D32(return_address1) // lr saved by frame 0
+        .append_repeated(0, 32) // locals
+        // frame 2
+        .mark(&frame2_sp)
+        .D32(0) // end of the back chain
+        .append_repeated(0, saved_lr_offset as usize - 4)
+        .D32(return_address2) // lr saved by frame 1
+        .append_repeated(0, 32);
+
+    let raw = MinidumpRawContext::Ppc(ppc_context(
+        0x40005510,
+        stack.start().value().unwrap() as u32,
+    ));
+    let s = f.walk_stack(raw, stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    for (frame, return_address, sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            assert_eq!(which.len(), 2);
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Ppc(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("srr0", valid).unwrap(), return_address);
+            assert_eq!(
+                ctx.get_register("r1", valid).unwrap() as u64,
+                sp.value().unwrap()
+            );
+        } else {
+            unreachable!();
+        }
+        assert_eq!(frame.instruction, return_address as u64 - 4);
+    }
+}
+
+#[tokio::test]
+async fn test_back_chain_linux() {
+    check_back_chain_ppc(Os::Linux, 4).await;
+}
+
+#[tokio::test]
+async fn test_back_chain_mac() {
+    check_back_chain_ppc(Os::MacOs, 8).await;
+}
+
+#[tokio::test]
+async fn test_back_chain_ppc64() {
+    let f = TestFixture::new(Os::Linux);
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x7fff_0000_0000);
+
+    let return_address1 = 0x50000100u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(&frame1_sp) // back chain
+        .append_repeated(0, 56) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .D64(&frame2_sp) // back chain
+        .D64(0) // cr save
+        .D64(return_address1) // lr saved by frame 0
+        .append_repeated(0, 40) // locals
+        // frame 2
+        .mark(&frame2_sp)
+        .D64(0) // end of the back chain
+        .append_repeated(0, 56);
+
+    let mut ctx = CONTEXT_PPC64::default();
+    ctx.set_register("srr0", 0x40005510);
+    ctx.set_register("r1", stack.start().value().unwrap());
+    let s = f.walk_stack(MinidumpRawContext::Ppc64(ctx), stack).await;
+    // There's no return address saved in frame 2, so unwinding stops there.
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(frame.instruction, return_address1 - 4);
+    if let MinidumpRawContext::Ppc64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("srr0", valid).unwrap(), return_address1);
+        assert_eq!(
+            ctx.get_register("r1", valid).unwrap(),
+            frame1_sp.value().unwrap()
+        );
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_back_chain_backwards() {
+    // A back chain that points down the stack must not be followed.
+    let f = TestFixture::new(Os::Linux);
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+
+    stack = stack
+        .D32(0x7fff0000) // back chain
+        .D32(0x50000100)
+        .append_repeated(0, 32);
+
+    let raw = MinidumpRawContext::Ppc(ppc_context(
+        0x40005510,
+        stack.start().value().unwrap() as u32,
+    ));
+    let s = f.walk_stack(raw, stack).await;
+    assert_eq!(s.frames.len(), 1);
+}
+
+#[tokio::test]
+async fn test_cfi() {
+    let mut f = TestFixture::new(Os::Linux);
+    let symbols = [
+        // The youngest frame's function.
+        "FUNC 4000 1000 10 enchiridion\n",
+        // Initially, nothing has been pushed on the stack,
+        // and the return address is still in lr.
+        "STACK CFI INIT 4000 100 .cfa: r1 .ra: lr\n",
+        // After the prologue, the frame is allocated and r31 is saved.
+        "STACK CFI 4008 .cfa: r1 32 + .ra: .cfa 4 + ^ r31: .cfa -4 + ^\n",
+        // The calling function.
+        "FUNC 5000 1000 10 epictetus\n",
+        // Mark it as end of stack.
+        "STACK CFI INIT 5000 1000 .cfa: 0 .ra: 0\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0
+        .D32(&frame1_sp) // back chain
+        .append_repeated(0, 24) // locals
+        .D32(0x5e68b5d5) // saved r31
+        // frame 1
+        .mark(&frame1_sp)
+        .D32(0) // back chain
+        .D32(0x40005510) // lr saved by frame 0
+        .append_repeated(0, 32);
+
+    let mut ctx = ppc_context(0x40004010, stack.start().value().unwrap() as u32);
+    ctx.set_register("lr", 0x1fe0fe10);
+    ctx.set_register("r30", 0x34f3ebd1);
+    ctx.set_register("r31", 0x2c5f3a7c);
+    let s = f.walk_stack(MinidumpRawContext::Ppc(ctx), stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frame.instruction, 0x4000550c);
+    if let MinidumpContextValidity::Some(ref which) = valid {
+        // pc, sp and the callee-saved registers r14-r31
+        assert_eq!(which.len(), 20);
+    } else {
+        unreachable!();
+    }
+    if let MinidumpRawContext::Ppc(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("srr0", valid), Some(0x40005510));
+        assert_eq!(
+            ctx.get_register("r1", valid).map(u64::from),
+            frame1_sp.value()
+        );
+        assert_eq!(ctx.get_register("r30", valid), Some(0x34f3ebd1));
+        assert_eq!(ctx.get_register("r31", valid), Some(0x5e68b5d5));
+    } else {
+        unreachable!();
+    }
+}
@@ -0,0 +1,403 @@
+use crate::*;
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+type Context = minidump::format::CONTEXT_RISCV64;
+
+struct TestFixture {
+    pub raw: Context,
+    pub modules: MinidumpModuleList,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    pub fn new() -> TestFixture {
+        TestFixture {
+            raw: Context::default(),
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Riscv64(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+            xstate: None,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::LE,
+        };
+        let system_info = SystemInfo {
+            os: Os::Linux,
+            os_version: None,
+            os_build: None,
+            cpu: Cpu::Riscv64,
+            cpu_info: None,
+            cpu_microcode_version: None,
+            cpu_count: 1,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            (),
+            &mut stack,
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
+            &self.modules,
+            &system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let mut f = TestFixture::new();
+    let stack = Section::new();
+    stack.start().set_const(0x80000000);
+    // There should be no references to the stack in this walk: we don't
+    // provide any call frame information, so trying to reconstruct the
+    // context frame's caller should fail. So there's no need for us to
+    // provide stack contents.
+    f.raw.set_register("pc", 0x4000c020);
+    f.raw.set_register("fp", 0x80000000);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[tokio::test]
+async fn test_scan_without_symbols() {
+    // Scanning should work without any symbols
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .D64(0x40090000) // junk that's not
+        .D64(0x60000000) // a return address
+        .D64(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 16) // space
+        .D64(0xF0000000) // more junk
+        .D64(0x0000000D)
+        .D64(return_address2) // actual return address
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 64); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    for (frame, return_address, sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            assert_eq!(which.len(), 2);
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Riscv64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address);
+            assert_eq!(ctx.get_register("sp", valid).unwrap(), sp.value().unwrap());
+        } else {
+            unreachable!();
+        }
+        // The instruction is moved back into the call instruction.
+        assert_eq!(frame.instruction, return_address - 2);
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer() {
+    // Frame-pointer-based unwinding
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+    let frame2_fp = Label::new();
+
+    // The frame pointer points just past the saved ra and fp, at the top of
+    // the frame (which is the bottom of the caller's frame).
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // space
+        .D64(0x0000000D) // junk that's not
+        .D64(0xF0000000) // a return address
+        .D64(&frame1_fp) // saved frame pointer
+        .D64(return_address1) // saved return address
+        .mark(&frame0_fp)
+        // frame 1
+        .append_repeated(0, 64) // space
+        .D64(0x0000000D) // junk that's not
+        .D64(0xF0000000) // a return address
+        .D64(&frame2_fp)
+        .D64(return_address2)
+        .mark(&frame1_fp)
+        // frame 2
+        .append_repeated(0, 64) // Whatever values on the stack.
+        .D64(0)
+        .D64(0)
+        .mark(&frame2_fp);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("ra", 0x1fe0fe10);
+    f.raw.set_register("fp", frame0_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    for (frame, return_address, sp, fp) in [
+        (&s.frames[1], return_address1, &frame0_fp, &frame1_fp),
+        (&s.frames[2], return_address2, &frame1_fp, &frame2_fp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            assert_eq!(which.len(), 3);
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Riscv64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address);
+            assert_eq!(ctx.get_register("sp", valid).unwrap(), sp.value().unwrap());
+            // fp is an alias of s0
+            assert_eq!(ctx.get_register("fp", valid).unwrap(), fp.value().unwrap());
+            assert_eq!(ctx.get_register("s0", valid).unwrap(), fp.value().unwrap());
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer_underflow() {
+    // Make sure we don't explode when the frame pointer is too small to
+    // have anything saved below it.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0);
+    stack = stack.append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("fp", 8);
+    f.raw.set_register("sp", 0);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+}
+
+const CALLEE_SAVE_REGS: &[&str] = &[
+    "pc", "sp", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+];
+
+fn init_cfi_state() -> (TestFixture, Section, Context, MinidumpContextValidity) {
+    let mut f = TestFixture::new();
+    let symbols = [
+        // The youngest frame's function.
+        "FUNC 4000 1000 10 enchiridion\n",
+        // Initially, nothing has been pushed on the stack,
+        // and the return address is still in ra.
+        "STACK CFI INIT 4000 100 .cfa: sp 0 + .ra: ra\n",
+        // Push ra, the frame pointer and s1.
+        "STACK CFI 4001 .cfa: sp 32 + .ra: .cfa -8 + ^",
+        " s0: .cfa -16 + ^ s1: .cfa -24 + ^\n",
+        // The calling function.
+        "FUNC 5000 1000 10 epictetus\n",
+        // Mark it as end of stack.
+        "STACK CFI INIT 5000 1000 .cfa: 0 .ra: 0\n",
+        // A function whose CFI makes the stack pointer
+        // go backwards.
+        "FUNC 6000 1000 20 palinal\n",
+        "STACK CFI INIT 6000 1000 .cfa: sp 8 - .ra: ra\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    f.raw.set_register("pc", 0x0000_0000_4000_5510);
+    f.raw.set_register("sp", 0x0000_0000_8000_0000);
+    f.raw.set_register("s0", 0x0000_00a2_8112_e110);
+    f.raw.set_register("s1", 0x5e68b5d5b5d55e68);
+    f.raw.set_register("s2", 0x34f3ebd1ebd134f3);
+    f.raw.set_register("s3", 0x74bca31ea31e74bc);
+    f.raw.set_register("s4", 0x16b32dcb2dcb16b3);
+    f.raw.set_register("s5", 0x21372ada2ada2137);
+    f.raw.set_register("s6", 0x557dbbbbbbbb557d);
+    f.raw.set_register("s7", 0x8ca748bf48bf8ca7);
+    f.raw.set_register("s8", 0x21f0ab46ab4621f0);
+    f.raw.set_register("s9", 0x146732b732b71467);
+    f.raw.set_register("s10", 0xa673645fa673645f);
+    f.raw.set_register("s11", 0x2c5f3a7c2c5f3a7c);
+
+    let raw_valid = MinidumpContextValidity::All;
+
+    let expected = f.raw.clone();
+    let expected_valid = MinidumpContextValidity::Some(CALLEE_SAVE_REGS.iter().copied().collect());
+
+    let stack = Section::new();
+    stack
+        .start()
+        .set_const(f.raw.get_register("sp", &raw_valid).unwrap());
+
+    (f, stack, expected, expected_valid)
+}
+
+async fn check_cfi(
+    f: TestFixture,
+    stack: Section,
+    expected: Context,
+    expected_valid: MinidumpContextValidity,
+) {
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    {
+        // Frame 1
+        let MinidumpContextValidity::Some(ref expected_regs) = expected_valid else {
+            unreachable!();
+        };
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            assert_eq!(which.len(), expected_regs.len());
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Riscv64(ctx) = &frame.context.raw {
+            for reg in expected_regs {
+                assert_eq!(
+                    ctx.get_register(reg, valid),
+                    expected.get_register(reg, &expected_valid),
+                    "{reg} registers didn't match!"
+                );
+            }
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cfi_at_4000() {
+    // A leaf function that hasn't touched the stack yet, the return address
+    // is in ra.
+    let (mut f, mut stack, expected, expected_valid) = init_cfi_state();
+
+    stack = stack.append_repeated(0, 120);
+
+    f.raw.set_register("pc", 0x0000000040004000);
+    f.raw.set_register("ra", 0x0000000040005510);
+
+    check_cfi(f, stack, expected, expected_valid).await;
+}
+
+#[tokio::test]
+async fn test_cfi_at_4001() {
+    let (mut f, mut stack, mut expected, expected_valid) = init_cfi_state();
+
+    let frame1_sp = Label::new();
+    stack = stack
+        .D64(0) // padding
+        .D64(0x5e68b5d5b5d55e68) // saved s1
+        .D64(0x0000_00a2_8112_e110) // saved fp
+        .D64(0x0000_0000_4000_5510) // return address
+        .mark(&frame1_sp)
+        .append_repeated(0, 120);
+
+    expected.set_register("sp", frame1_sp.value().unwrap());
+    f.raw.set_register("pc", 0x0000000040004001);
+    f.raw.set_register("ra", 0x1fe0fe10);
+    f.raw.set_register("s0", 0x5fc4be14be145fc4);
+    f.raw.set_register("s1", 0xadc9f635a635adc9);
+
+    check_cfi(f, stack, expected, expected_valid).await;
+}
+
+#[tokio::test]
+async fn test_cfi_reject_backwards() {
+    // Check that we reject rules that would cause the stack pointer to
+    // move in the wrong direction.
+
+    let (mut f, mut stack, _expected, _expected_valid) = init_cfi_state();
+
+    stack = stack.append_repeated(0, 120);
+
+    f.raw.set_register("pc", 0x0000000040006000);
+    f.raw.set_register("sp", 0x0000000080000000);
+    f.raw.set_register("ra", 0x0000000040005510);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+}
@@ -0,0 +1,223 @@
+// Unwinding for SPARC, which covers both the 32-bit (V8) and 64-bit (V9) ABIs.
+
+use super::impl_prelude::*;
+use minidump::{
+    CpuContext, MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext,
+};
+use std::collections::HashSet;
+use tracing::trace;
+
+type SparcContext = minidump::format::CONTEXT_SPARC;
+type Registers = minidump::format::SparcRegisterNumbers;
+
+const STACK_POINTER: &str = Registers::StackPointer.name();
+const RETURN_ADDRESS: &str = Registers::ReturnAddress.name();
+const FRAME_POINTER: &str = Registers::FramePointer.name();
+const CALLEE_RETURN_ADDRESS: &str = Registers::CalleeReturnAddress.name();
+const PROGRAM_COUNTER: &str = "pc";
+/// The local and in registers (`l0-l7`, `i0-i7`), in the order they're
+/// stored in a register window save area.
+const WINDOW_REGS: &[&str] = &[
+    "g_r16", "g_r17", "g_r18", "g_r19", "g_r20", "g_r21", "g_r22", "g_r23", "g_r24", "g_r25",
+    "g_r26", "g_r27", "g_r28", "g_r29", "g_r30", "g_r31",
+];
+/// The V9 ABI offsets the stack and frame pointers by this much.
+const STACK_BIAS: u64 = 2047;
+
+async fn get_caller_by_cfi<P>(
+    ctx: &SparcContext,
+    callee: &StackFrame,
+    grand_callee: Option<&StackFrame>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    trace!("trying cfi");
+    let valid = &callee.context.valid;
+    let _last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let module = modules.module_at_address(callee.instruction)?;
+    let grand_callee_parameter_size = grand_callee.and_then(|f| f.parameter_size).unwrap_or(0);
+    let has_grand_callee = grand_callee.is_some();
+
+    let mut stack_walker = CfiStackWalker {
+        instruction: callee.instruction,
+        has_grand_callee,
+        grand_callee_parameter_size,
+
+        callee_ctx: ctx,
+        callee_validity: valid,
+
+        // Thanks to register windows, no register of the callee is also a
+        // register of the caller, so nothing is forwarded. The CFI has to
+        // say where the caller's registers are.
+        caller_ctx: ctx.clone(),
+        caller_validity: HashSet::new(),
+
+        stack_memory,
+    };
+
+    symbol_provider
+        .walk_frame(module, &mut stack_walker)
+        .await?;
+    let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
+    let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
+
+    trace!(
+        "cfi evaluation was successful -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+    );
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Sparc(stack_walker.caller_ctx),
+        valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
+fn get_caller_by_frame_pointer(
+    ctx: &SparcContext,
+    callee: &StackFrame,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
+) -> Option<StackFrame> {
+    trace!("trying frame pointer");
+    // SPARC functions start with a `save` instruction, which allocates a new
+    // frame and rotates the register window: the caller's out registers
+    // become the callee's in registers. So the callee's frame pointer (i6)
+    // is the caller's stack pointer (o6), and i7 is the address of the `call`
+    // in the caller (which put it in o7). Calls have a delay slot, so the
+    // return address is 8 bytes after that.
+    //
+    // The caller's own locals and ins (including *its* frame pointer and
+    // return address) are saved to the 16 words at the bottom of its frame
+    // (i.e. at the caller's stack pointer) when the register windows are
+    // flushed, which crash handlers always do:
+    //
+    // sp := fp
+    // pc := i7 + 8
+    // l0-l7, i0-i7 := *(fp), ..., *(fp + ptr*15)
+    //
+    // The 64-bit ABI stores 64-bit registers and biases the stack and frame
+    // pointers by 2047, which makes them odd, so that's how we tell the two
+    // ABIs apart.
+    //
+    // As usual we can't tell whether the callee is a leaf function that
+    // hasn't executed `save`, and we always assume that it isn't.
+    let valid = &callee.context.valid;
+    let _last_sp = ctx.get_register(STACK_POINTER, valid)?;
+    let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
+    let last_i7 = ctx.get_register(CALLEE_RETURN_ADDRESS, valid)?;
+
+    if last_fp == 0 {
+        // The frame pointer of the outermost frame is null.
+        trace!("frame pointer is null, assuming unwind complete");
+        return None;
+    }
+
+    let (save_area, pointer_width) = if last_fp & 1 == 1 {
+        (last_fp.checked_add(STACK_BIAS)?, 8)
+    } else {
+        (last_fp, 4)
+    };
+
+    let mut caller_ctx = SparcContext::default();
+    let mut valid = HashSet::new();
+    for (i, &reg) in WINDOW_REGS.iter().enumerate() {
+        let address = save_area.checked_add(i as u64 * pointer_width)?;
+        let val = if pointer_width == 4 {
+            stack_memory.get_memory_at_address::<u32>(address)?.into()
+        } else {
+            stack_memory.get_memory_at_address::<u64>(address)?
+        };
+        caller_ctx.set_register(reg, val);
+        valid.insert(reg);
+    }
+
+    let caller_pc = last_i7.checked_add(8)?;
+    let caller_sp = last_fp;
+
+    trace!(
+        "frame pointer seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+    );
+
+    caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+    caller_ctx.set_register(STACK_POINTER, caller_sp);
+    caller_ctx.set_register(RETURN_ADDRESS, last_i7);
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+    valid.insert(RETURN_ADDRESS);
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Sparc(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
+    };
+    Some(StackFrame::from_context(context, FrameTrust::FramePointer))
+}
+
+#[async_trait::async_trait]
+impl Unwind for SparcContext {
+    async fn get_caller_frame<P>(
+        &self,
+        callee: &StackFrame,
+        grand_callee: Option<&StackFrame>,
+        memory: UnwindMemory<'_>,
+        modules: &MinidumpModuleList,
+        _system_info: &SystemInfo,
+        syms: &P,
+    ) -> Option<StackFrame>
+    where
+        P: SymbolProvider + Sync,
+    {
+        let stack = memory.stack?;
+
+        // .await doesn't like closures, so don't use Option chaining
+        let mut frame = None;
+        if frame.is_none() {
+            frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
+        }
+        if frame.is_none() {
+            frame = get_caller_by_frame_pointer(self, callee, stack);
+        }
+        let mut frame = frame?;
+
+        // We now check the frame to see if it looks like unwinding is complete,
+        // based on the frame we computed having a nonsense value. Returning
+        // None signals to the unwinder to stop unwinding.
+
+        // if the instruction is within the first ~page of memory, it's basically
+        // null, and we can assume unwinding is complete.
+        if frame.context.get_instruction_pointer() < 4096 {
+            trace!("instruction pointer was nullish, assuming unwind complete");
+            return None;
+        }
+
+        // If the new stack pointer is at a lower address than the old,
+        // then that's clearly incorrect. Treat this as end-of-stack to
+        // enforce progress and avoid infinite loops.
+        let sp = frame.context.get_stack_pointer();
+        let last_sp = self.get_register_always(STACK_POINTER);
+        if sp <= last_sp {
+            // Leaf functions may not execute `save` at all (the return address
+            // is in o7), so permit the stack pointer to not change for the
+            // first frame of the unwind.
+            let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
+            if !is_leaf {
+                trace!("stack pointer went backwards, assuming unwind complete");
+                return None;
+            }
+        }
+
+        // Ok, the frame now seems well and truly valid, do final cleanup.
+
+        // The return address is 8 bytes after the call, because of the delay
+        // slot, so go back to the call itself.
+        let ip = frame.context.get_instruction_pointer();
+        frame.instruction = ip - 8;
+
+        Some(frame)
+    }
+}
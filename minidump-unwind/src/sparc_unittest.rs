@@ -0,0 +1,271 @@
+use crate::*;
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+type Context = minidump::format::CONTEXT_SPARC;
+
+struct TestFixture {
+    pub raw: Context,
+    pub modules: MinidumpModuleList,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    pub fn new() -> TestFixture {
+        TestFixture {
+            raw: Context::default(),
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Sparc(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+            xstate: None,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::BE,
+        };
+        let system_info = SystemInfo {
+            os: Os::Solaris,
+            os_version: None,
+            os_build: None,
+            cpu: Cpu::Sparc,
+            cpu_info: None,
+            cpu_microcode_version: None,
+            cpu_count: 1,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            (),
+            &mut stack,
+            WalkStackOptions {
+                stack_memory: Some(
+                    &MinidumpAddressSpace::new().with_region(UnifiedMemory::Memory(&stack_memory)),
+                ),
+                ..Default::default()
+            },
+            &self.modules,
+            &system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let mut f = TestFixture::new();
+    let stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+    // There's no frame pointer, so there's nothing to unwind.
+    f.raw.set_register("pc", 0x4000c020);
+    f.raw.set_register("o6", 0x80000000);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[tokio::test]
+async fn test_register_windows() {
+    let mut f = TestFixture::new();
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+
+    let call_address1 = 0x50000100u64;
+    let call_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // register window save area
+        .append_repeated(0, 32) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0x11, 8 * 4) // l0-l7
+        .append_repeated(0x22, 6 * 4) // i0-i5
+        .D32(&frame2_sp) // i6
+        .D32(call_address2 as u32) // i7
+        .append_repeated(0, 32) // locals
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 14 * 4) // l0-l7, i0-i5
+        .D32(0) // i6
+        .D32(0) // i7
+        .append_repeated(0, 32);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("o6", stack.start().value().unwrap());
+    f.raw.set_register("i6", frame1_sp.value().unwrap());
+    f.raw.set_register("i7", call_address1);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    for (frame, call_address, sp) in [
+        (&s.frames[1], call_address1, &frame1_sp),
+        (&s.frames[2], call_address2, &frame2_sp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            // pc, sp, o7 and the whole register window
+            assert_eq!(which.len(), 19);
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Sparc(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), call_address + 8);
+            assert_eq!(ctx.get_register("o6", valid).unwrap(), sp.value().unwrap());
+            assert_eq!(ctx.get_register("o7", valid).unwrap(), call_address);
+        } else {
+            unreachable!();
+        }
+        assert_eq!(frame.instruction, call_address);
+    }
+
+    if let MinidumpRawContext::Sparc(ctx) = &s.frames[1].context.raw {
+        let valid = &s.frames[1].context.valid;
+        assert_eq!(ctx.get_register("l0", valid).unwrap(), 0x11111111);
+        assert_eq!(ctx.get_register("i5", valid).unwrap(), 0x22222222);
+        assert_eq!(
+            ctx.get_register("i6", valid).unwrap(),
+            frame2_sp.value().unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_register_windows_biased() {
+    // The 64-bit ABI biases the stack and frame pointers by 2047, and saves
+    // 64-bit registers.
+    const BIAS: u64 = 2047;
+    let mut f = TestFixture::new();
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    let stack_start = 0x7fff_0000_0000;
+    stack.start().set_const(stack_start);
+
+    let call_address1 = 0x50000100u64;
+    let frame1_sp = stack_start + 192;
+    let frame2_sp = frame1_sp + 192;
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 128) // register window save area
+        .append_repeated(0, 64) // locals
+        // frame 1
+        .append_repeated(0, 14 * 8) // l0-l7, i0-i5
+        .D64(frame2_sp - BIAS) // i6
+        .D64(0) // i7
+        .append_repeated(0, 64); // locals
+    assert_eq!(stack.size(), frame2_sp - stack_start);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("o6", stack_start - BIAS);
+    f.raw.set_register("i6", frame1_sp - BIAS);
+    f.raw.set_register("i7", call_address1);
+
+    let s = f.walk_stack(stack).await;
+    // Frame 2's register window isn't in the stack memory, so unwinding
+    // stops there.
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    assert_eq!(frame.instruction, call_address1);
+    if let MinidumpRawContext::Sparc(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("o6", valid).unwrap(), frame1_sp - BIAS);
+        assert_eq!(ctx.get_register("i6", valid).unwrap(), frame2_sp - BIAS);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_cfi() {
+    let mut f = TestFixture::new();
+    let symbols = [
+        // The youngest frame's function.
+        "FUNC 4000 1000 10 enchiridion\n",
+        // The caller's stack pointer is in i6, the return address is 8
+        // bytes after the call in i7, and the caller's i6 and i7 are saved
+        // in its register window save area.
+        "STACK CFI INIT 4000 100 .cfa: i6 .ra: i7 8 + i6: .cfa 56 + ^ i7: .cfa 60 + ^\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let mut stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(0x80000000);
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // register window save area
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 14 * 4) // l0-l7, i0-i5
+        .D32(0) // i6
+        .D32(0) // i7
+        .append_repeated(0, 32);
+
+    f.raw.set_register("pc", 0x40004010);
+    f.raw.set_register("o6", stack.start().value().unwrap());
+    f.raw.set_register("i6", frame1_sp.value().unwrap());
+    f.raw.set_register("i7", 0x50000100);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frame.instruction, 0x50000100);
+    if let MinidumpContextValidity::Some(ref which) = valid {
+        assert_eq!(which.len(), 4);
+    } else {
+        unreachable!();
+    }
+    if let MinidumpRawContext::Sparc(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid), Some(0x50000108));
+        assert_eq!(ctx.get_register("o6", valid), frame1_sp.value());
+        assert_eq!(ctx.get_register("i6", valid), Some(0));
+        assert_eq!(ctx.get_register("i7", valid), Some(0));
+    } else {
+        unreachable!();
+    }
+}
@@ -1,8 +1,8 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
-use super::{StackFrame, SymbolProvider, SystemInfo};
-use minidump::{MinidumpModuleList, UnifiedMemory};
+use super::{StackFrame, SymbolProvider, SystemInfo, UnwindMemory};
+use minidump::MinidumpModuleList;
 
 /// A trait for things that can unwind to a caller.
 #[async_trait::async_trait]
@@ -12,7 +12,7 @@ pub trait Unwind {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
         symbol_provider: &P,
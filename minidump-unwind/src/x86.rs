@@ -9,7 +9,8 @@
 use super::impl_prelude::*;
 use minidump::format::CONTEXT_X86;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, UnifiedMemory,
+    MinidumpAddressSpace, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -25,7 +26,7 @@ async fn get_caller_by_cfi<P>(
     ctx: &CONTEXT_X86,
     callee: &StackFrame,
     grand_callee: Option<&StackFrame>,
-    stack_memory: UnifiedMemory<'_, '_>,
+    stack_memory: &MinidumpAddressSpace<'_, '_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
@@ -98,6 +99,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::X86(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
@@ -116,13 +118,14 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
 fn get_caller_by_frame_pointer<P>(
     ctx: &CONTEXT_X86,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
     _modules: &MinidumpModuleList,
     _symbol_provider: &P,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider + Sync,
 {
+    let stack_memory = memory.stack?;
     trace!("trying frame pointer");
     if let MinidumpContextValidity::Some(ref which) = callee.context.valid {
         if !which.contains(FRAME_POINTER_REGISTER) {
@@ -189,6 +192,7 @@ where
     let context = MinidumpContext {
         raw: MinidumpRawContext::X86(caller_ctx),
         valid: MinidumpContextValidity::Some(valid),
+        xstate: None,
     };
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
@@ -196,13 +200,14 @@ where
 async fn get_caller_by_scan<P>(
     ctx: &CONTEXT_X86,
     callee: &StackFrame,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
     P: SymbolProvider + Sync,
 {
+    let stack_memory = memory.stack?;
     trace!("trying scan");
     // Stack scanning is just walking from the end of the frame until we encounter
     // a value on the stack that looks like a pointer into some code (it's an address
@@ -241,7 +246,7 @@ where
     for i in 0..scan_range {
         let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
         let caller_ip = stack_memory.get_memory_at_address(address_of_ip as u64)?;
-        if instruction_seems_valid(caller_ip, modules, symbol_provider).await {
+        if instruction_seems_valid(caller_ip, memory, modules, symbol_provider).await {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
             let caller_sp = address_of_ip.checked_add(POINTER_WIDTH)?;
 
@@ -276,19 +281,13 @@ where
 
                 if bp > address_of_ip && bp - address_of_bp <= MAX_REASONABLE_GAP_BETWEEN_FRAMES {
                     // Sanity check that resulting bp is still inside stack memory.
-                    if stack_memory
-                        .get_memory_at_address::<Pointer>(bp as u64)
-                        .is_some()
-                    {
+                    if memory.stack_contains(bp as u64, POINTER_WIDTH as u64) {
                         caller_bp = Some(bp);
                     }
                 } else if let Some(last_bp) = last_bp {
                     if last_bp >= caller_sp {
                         // Sanity check that resulting bp is still inside stack memory.
-                        if stack_memory
-                            .get_memory_at_address::<Pointer>(last_bp as u64)
-                            .is_some()
-                        {
+                        if memory.stack_contains(last_bp as u64, POINTER_WIDTH as u64) {
                             caller_bp = Some(last_bp);
                         }
                     }
@@ -316,6 +315,7 @@ where
             let context = MinidumpContext {
                 raw: MinidumpRawContext::X86(caller_ctx),
                 valid: MinidumpContextValidity::Some(valid),
+                xstate: None,
             };
             return Some(StackFrame::from_context(context, FrameTrust::Scan));
         }
@@ -347,8 +347,12 @@ where
 /// If we applied this more rigorous validation to cfi/fp methods, we
 /// would just discard the correct register values from the known frame
 /// and immediately start doing unreliable scans.
+///
+/// When the code in front of the instruction is available (from the
+/// minidump or the module's binary), we also check that it's a call.
 async fn instruction_seems_valid<P>(
     instruction: Pointer,
+    memory: UnwindMemory<'_>,
     modules: &MinidumpModuleList,
     symbol_provider: &P,
 ) -> bool
@@ -359,6 +363,13 @@ where
         return false;
     }
 
+    // A return address follows the call that pushed it.
+    if crate::follows_x86_call(instruction as u64, memory, modules, symbol_provider).await
+        == Some(false)
+    {
+        return false;
+    }
+
     super::instruction_seems_valid_by_symbols(instruction as u64, modules, symbol_provider).await
 }
 
@@ -368,7 +379,7 @@ where
 fn stack_seems_valid(
     caller_sp: Pointer,
     callee_sp: Pointer,
-    stack_memory: UnifiedMemory<'_, '_>,
+    memory: UnwindMemory<'_>,
 ) -> bool {
     // The stack shouldn't *grow* when we unwind
     if caller_sp <= callee_sp {
@@ -376,9 +387,7 @@ fn stack_seems_valid(
     }
 
     // The stack pointer should be in the stack
-    stack_memory
-        .get_memory_at_address::<Pointer>(caller_sp as u64)
-        .is_some()
+    memory.stack_contains(caller_sp as u64, POINTER_WIDTH as u64)
 }
 */
 
@@ -388,7 +397,7 @@ impl Unwind for CONTEXT_X86 {
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
-        stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory: UnwindMemory<'_>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
         syms: &P,
@@ -396,7 +405,7 @@ impl Unwind for CONTEXT_X86 {
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = memory.stack?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
@@ -404,10 +413,10 @@ impl Unwind for CONTEXT_X86 {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
         if frame.is_none() {
-            frame = get_caller_by_frame_pointer(self, callee, stack, modules, syms);
+            frame = get_caller_by_frame_pointer(self, callee, memory, modules, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, memory, modules, syms).await;
         }
         let mut frame = frame?;
 
@@ -11,6 +11,8 @@ struct TestFixture {
     pub raw: CONTEXT_X86,
     pub modules: MinidumpModuleList,
     pub symbols: HashMap<String, String>,
+    /// The code of the modules, as `(address, contents)`.
+    pub code: Vec<(u64, Vec<u8>)>,
 }
 
 impl TestFixture {
@@ -24,6 +26,7 @@ impl TestFixture {
                 MinidumpModule::new(0x50000000, 0x10000, "module2"),
             ]),
             symbols: HashMap::new(),
+            code: vec![],
         }
     }
 
@@ -31,6 +34,7 @@ impl TestFixture {
         let context = MinidumpContext {
             raw: MinidumpRawContext::X86(self.raw.clone()),
             valid: MinidumpContextValidity::All,
+            xstate: None,
         };
         let base = stack.start().value().unwrap();
         let size = stack.size();
@@ -51,13 +55,31 @@ impl TestFixture {
             cpu_microcode_version: None,
             cpu_count: 1,
         };
+        let code = self
+            .code
+            .iter()
+            .map(|(base, bytes)| MinidumpMemory {
+                desc: Default::default(),
+                base_address: *base,
+                size: bytes.len() as u64,
+                bytes,
+                endian: scroll::LE,
+            })
+            .collect();
+        let code = UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(code));
+        let memory = MinidumpAddressSpace::new()
+            .with_region(UnifiedMemory::Memory(&stack_memory))
+            .with_memory_list(&code);
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         let mut stack = CallStack::with_context(context);
 
         walk_stack(
             (),
             &mut stack,
-            Some(UnifiedMemory::Memory(&stack_memory)),
+            WalkStackOptions {
+                stack_memory: Some(&memory),
+                ..Default::default()
+            },
             &self.modules,
             &system_info,
             &symbolizer,
@@ -205,6 +227,47 @@ async fn test_traditional_scan() {
     }
 }
 
+// When the code of the module is available, scanning should skip values that
+// point into it but don't follow a call.
+#[tokio::test]
+async fn test_scan_with_code() {
+    let mut f = TestFixture::new();
+    let frame1_esp = Label::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    stack = stack
+        // frame 0
+        .D32(0x40001100) // points into module1, but not after a call
+        .D32(0x4000129d) // return address
+        // frame 1
+        .mark(&frame1_esp)
+        .D32(0) // saved %ebp (stack end)
+        .D32(0); // return address (stack end)
+
+    // int3 everywhere, except for a `call rel32` right before the return address.
+    let mut text = vec![0xcc; 0x1000];
+    text[0x298..0x29d].copy_from_slice(&[0xe8, 0x63, 0x0d, 0x00, 0x00]);
+    f.code.push((0x40001000, text));
+
+    f.raw.eip = 0x4000f49d;
+    f.raw.esp = stack.start().value().unwrap() as u32;
+    // Make the frame pointer bogus, to make the stackwalker scan the stack
+    // for something that looks like a return address.
+    f.raw.ebp = 0xd43eed6e;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::Scan);
+    assert_eq!(f1.resume_address, 0x4000129d);
+    if let MinidumpRawContext::X86(ctx) = &f1.context.raw {
+        assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32);
+    } else {
+        unreachable!();
+    }
+}
+
 // Force scanning for a return address a long way down the stack
 #[tokio::test]
 async fn test_traditional_scan_long_way() {
@@ -30,6 +30,7 @@ pub enum MinidumpRawContext {
     Arm64(md::CONTEXT_ARM64),
     OldArm64(md::CONTEXT_ARM64_OLD),
     Mips(md::CONTEXT_MIPS),
+    Riscv64(md::CONTEXT_RISCV64),
 }
 
 /// Generic over the specifics of a CPU context.
@@ -605,6 +606,121 @@ impl CpuContext for md::CONTEXT_ARM64 {
     }
 }
 
+impl CpuContext for md::CONTEXT_RISCV64 {
+    type Register = u64;
+
+    const REGISTERS: &'static [&'static str] = &[
+        "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+        "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5",
+        "t6", "pc",
+    ];
+
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        match reg {
+            "fp" => Some("s0"),
+            _ => default_memoize_register(Self::REGISTERS, reg),
+        }
+    }
+
+    fn register_is_valid(&self, reg: &str, valid: &MinidumpContextValidity) -> bool {
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            match reg {
+                "s0" | "fp" => which.contains("s0") || which.contains("fp"),
+                _ => which.contains(reg),
+            }
+        } else {
+            self.memoize_register(reg).is_some()
+        }
+    }
+
+    fn get_register_always(&self, reg: &str) -> u64 {
+        match reg {
+            "ra" => self.iregs[0],
+            "sp" => self.iregs[1],
+            "gp" => self.iregs[2],
+            "tp" => self.iregs[3],
+            "t0" => self.iregs[4],
+            "t1" => self.iregs[5],
+            "t2" => self.iregs[6],
+            "s0" => self.iregs[7],
+            "s1" => self.iregs[8],
+            "a0" => self.iregs[9],
+            "a1" => self.iregs[10],
+            "a2" => self.iregs[11],
+            "a3" => self.iregs[12],
+            "a4" => self.iregs[13],
+            "a5" => self.iregs[14],
+            "a6" => self.iregs[15],
+            "a7" => self.iregs[16],
+            "s2" => self.iregs[17],
+            "s3" => self.iregs[18],
+            "s4" => self.iregs[19],
+            "s5" => self.iregs[20],
+            "s6" => self.iregs[21],
+            "s7" => self.iregs[22],
+            "s8" => self.iregs[23],
+            "s9" => self.iregs[24],
+            "s10" => self.iregs[25],
+            "s11" => self.iregs[26],
+            "t3" => self.iregs[27],
+            "t4" => self.iregs[28],
+            "t5" => self.iregs[29],
+            "t6" => self.iregs[30],
+            "pc" => self.pc,
+            "fp" => self.iregs[md::Riscv64RegisterNumbers::FramePointer as usize],
+            _ => unreachable!("Invalid riscv64 register! {}", reg),
+        }
+    }
+
+    fn set_register(&mut self, reg: &str, val: Self::Register) -> Option<()> {
+        match reg {
+            "ra" => self.iregs[0] = val,
+            "sp" => self.iregs[1] = val,
+            "gp" => self.iregs[2] = val,
+            "tp" => self.iregs[3] = val,
+            "t0" => self.iregs[4] = val,
+            "t1" => self.iregs[5] = val,
+            "t2" => self.iregs[6] = val,
+            "s0" => self.iregs[7] = val,
+            "s1" => self.iregs[8] = val,
+            "a0" => self.iregs[9] = val,
+            "a1" => self.iregs[10] = val,
+            "a2" => self.iregs[11] = val,
+            "a3" => self.iregs[12] = val,
+            "a4" => self.iregs[13] = val,
+            "a5" => self.iregs[14] = val,
+            "a6" => self.iregs[15] = val,
+            "a7" => self.iregs[16] = val,
+            "s2" => self.iregs[17] = val,
+            "s3" => self.iregs[18] = val,
+            "s4" => self.iregs[19] = val,
+            "s5" => self.iregs[20] = val,
+            "s6" => self.iregs[21] = val,
+            "s7" => self.iregs[22] = val,
+            "s8" => self.iregs[23] = val,
+            "s9" => self.iregs[24] = val,
+            "s10" => self.iregs[25] = val,
+            "s11" => self.iregs[26] = val,
+            "t3" => self.iregs[27] = val,
+            "t4" => self.iregs[28] = val,
+            "t5" => self.iregs[29] = val,
+            "t6" => self.iregs[30] = val,
+            "pc" => self.pc = val,
+            "fp" => self.iregs[md::Riscv64RegisterNumbers::FramePointer as usize] = val,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn stack_pointer_register_name(&self) -> &'static str {
+        "sp"
+    }
+
+    fn instruction_pointer_register_name(&self) -> &'static str {
+        "pc"
+    }
+}
+
 impl CpuContext for md::CONTEXT_PPC {
     type Register = u32;
 
@@ -832,11 +948,29 @@ impl CpuContext for md::CONTEXT_MIPS {
         "gp", "sp", "fp", "ra", "pc", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7",
     ];
 
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        match reg {
+            "s8" => Some("fp"),
+            _ => default_memoize_register(Self::REGISTERS, reg),
+        }
+    }
+
+    fn register_is_valid(&self, reg: &str, valid: &MinidumpContextValidity) -> bool {
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            match reg {
+                "s8" | "fp" => which.contains("s8") || which.contains("fp"),
+                _ => which.contains(reg),
+            }
+        } else {
+            self.memoize_register(reg).is_some()
+        }
+    }
+
     fn get_register_always(&self, reg: &str) -> Self::Register {
         match reg {
             "gp" => self.iregs[md::MipsRegisterNumbers::GlobalPointer as usize],
             "sp" => self.iregs[md::MipsRegisterNumbers::StackPointer as usize],
-            "fp" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize],
+            "fp" | "s8" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize],
             "ra" => self.iregs[md::MipsRegisterNumbers::ReturnAddress as usize],
             "pc" => self.epc,
             "s0" => self.iregs[md::MipsRegisterNumbers::S0 as usize],
@@ -855,7 +989,7 @@ impl CpuContext for md::CONTEXT_MIPS {
         match reg {
             "gp" => self.iregs[md::MipsRegisterNumbers::GlobalPointer as usize] = val,
             "sp" => self.iregs[md::MipsRegisterNumbers::StackPointer as usize] = val,
-            "fp" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize] = val,
+            "fp" | "s8" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize] = val,
             "ra" => self.iregs[md::MipsRegisterNumbers::ReturnAddress as usize] = val,
             "pc" => self.epc = val,
             "s0" => self.iregs[md::MipsRegisterNumbers::S0 as usize] = val,
@@ -880,6 +1014,15 @@ impl CpuContext for md::CONTEXT_MIPS {
     }
 }
 
+/// The conventional names of the SPARC registers in `g_r`, in the same order.
+///
+/// SPARC has 8 global registers and a window of 8 out, 8 local and 8 in
+/// registers, which are usually referred to by these names.
+const SPARC_REGISTER_ALIASES: &[&str] = &[
+    "g0", "g1", "g2", "g3", "g4", "g5", "g6", "g7", "o0", "o1", "o2", "o3", "o4", "o5", "o6", "o7",
+    "l0", "l1", "l2", "l3", "l4", "l5", "l6", "l7", "i0", "i1", "i2", "i3", "i4", "i5", "i6", "i7",
+];
+
 impl CpuContext for md::CONTEXT_SPARC {
     type Register = u64;
 
@@ -890,6 +1033,26 @@ impl CpuContext for md::CONTEXT_SPARC {
         "g_r31", "ccr", "pc", "npc", "y", "asi", "fprs",
     ];
 
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        match SPARC_REGISTER_ALIASES
+            .iter()
+            .position(|&alias| alias == reg)
+        {
+            Some(idx) => Some(Self::REGISTERS[idx]),
+            None => default_memoize_register(Self::REGISTERS, reg),
+        }
+    }
+
+    fn register_is_valid(&self, reg: &str, valid: &MinidumpContextValidity) -> bool {
+        match self.memoize_register(reg) {
+            Some(reg) => match valid {
+                MinidumpContextValidity::Some(ref which) => which.contains(reg),
+                MinidumpContextValidity::All => true,
+            },
+            None => false,
+        }
+    }
+
     fn get_register_always(&self, reg: &str) -> Self::Register {
         match reg {
             "g_r0" | "g0" => self.g_r[0],
@@ -1015,6 +1178,64 @@ pub struct MinidumpContext {
     pub raw: MinidumpRawContext,
     /// Which registers are valid in `raw`.
     pub valid: MinidumpContextValidity,
+    /// The extended state of x86 and x86-64 contexts (AVX, AVX-512, CET).
+    ///
+    /// This is only available for contexts read from a minidump which has a
+    /// `MINIDUMP_MISC_INFO_5` describing the XSAVE layout, and never for
+    /// frames recovered by unwinding.
+    pub xstate: Option<MinidumpXState>,
+}
+
+/// The extended processor state (XSTATE) of an x86 or x86-64 context.
+///
+/// The vector registers are stored as little-endian bytes, like the CPU
+/// stores them. Registers of features which were in their initial
+/// configuration when the context was saved are all zeros.
+///
+/// See [`md::XSTATE_CONFIG_FEATURE_MSC_INFO`] for the gory details of the format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinidumpXState {
+    /// Which features were not in their initial configuration (`XSTATE_BV`),
+    /// as a mask of [`md::XstateFeatureIndex`] bits.
+    pub features_in_use: u64,
+    /// The 256-bit AVX registers (ymm0-ymm15, or ymm0-ymm7 on x86).
+    pub ymm: Vec<[u8; 32]>,
+    /// The AVX-512 opmask registers (k0-k7).
+    pub kmask: Vec<u64>,
+    /// The 512-bit AVX-512 registers (zmm0-zmm31, or zmm0-zmm7 on x86).
+    pub zmm: Vec<[u8; 64]>,
+    /// The user-mode CET configuration (the `IA32_U_CET` MSR).
+    pub cet_control: Option<u64>,
+    /// The CET shadow stack pointer (the `IA32_PL3_SSP` MSR).
+    pub shadow_stack_pointer: Option<u64>,
+}
+
+/// What a [`FloatRegister`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatRegisterKind {
+    /// An x87 register (st0-st7), holding an 80-bit extended precision float.
+    X87,
+    /// A 128-bit SIMD register (xmm on x86, v0-v31 on ARM64).
+    Vector,
+    /// A 64-bit floating-point register (d0-d31 on ARM, f0-f31 on RISC-V).
+    Double,
+    /// A floating-point control or status register (mxcsr, fpcr, fpscr...).
+    Control,
+}
+
+/// A floating-point or SIMD register of a [`MinidumpContext`].
+///
+/// Returned by [`MinidumpContext::float_registers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatRegister {
+    /// The name of the register.
+    pub name: &'static str,
+    /// What the register holds.
+    pub kind: FloatRegisterKind,
+    /// The contents of the register, zero-extended to 128 bits.
+    pub value: u128,
+    /// The size of the register in bytes.
+    pub size: usize,
 }
 
 /// Errors encountered while reading a `MinidumpContext`.
@@ -1029,12 +1250,329 @@ pub enum ContextError {
 //======================================================
 // Implementations
 
+/// The offset of the XSAVE area in a `CONTEXT_X86` (`extended_registers`).
+const X86_XSAVE_OFFSET: usize = 0xcc;
+/// The offset of the XSAVE area in a `CONTEXT_AMD64` (`float_save`).
+const AMD64_XSAVE_OFFSET: usize = 0x100;
+/// The offset of the XMM registers in the legacy part of the XSAVE area.
+const XSAVE_XMM_OFFSET: usize = 160;
+/// The offset of the XSAVE header (which starts with `XSTATE_BV`) in the XSAVE area.
+const XSAVE_HEADER_OFFSET: usize = 512;
+
+impl MinidumpXState {
+    /// Read the XSTATE of a context, if the minidump describes its layout.
+    fn read_context(
+        bytes: &[u8],
+        xsave_offset: usize,
+        context_flags: u32,
+        misc: Option<&MinidumpMiscInfo>,
+        endian: scroll::Endian,
+    ) -> Option<MinidumpXState> {
+        let config = misc.and_then(|misc| misc.raw.xstate_data());
+        if config.is_none() && context_flags & md::CONTEXT_HAS_XSTATE != 0 {
+            warn!("Cpu context has extra XSTATE but no MISC_INFO_5 describing it");
+        }
+        MinidumpXState::read(bytes, xsave_offset, config?, endian)
+    }
+
+    /// Read the XSTATE of a context, given the offset of its XSAVE area in
+    /// `bytes` and the layout of the XSAVE area.
+    ///
+    /// Returns `None` if none of the features we know about are present.
+    pub fn read(
+        bytes: &[u8],
+        xsave_offset: usize,
+        config: &md::XSTATE_CONFIG_FEATURE_MSC_INFO,
+        endian: scroll::Endian,
+    ) -> Option<MinidumpXState> {
+        use md::XstateFeatureIndex::*;
+
+        let xsave = bytes.get(xsave_offset..)?;
+        let features_in_use: u64 = xsave.pread_with(XSAVE_HEADER_OFFSET, endian).ok()?;
+
+        // The contents of a feature, or `None` if it isn't in the context.
+        // Features in their initial configuration are all zeros, whatever the
+        // context contains.
+        let feature = |idx: md::XstateFeatureIndex| -> Option<Vec<u8>> {
+            let idx = idx as usize;
+            if config.enabled_features & (1 << idx) == 0 {
+                return None;
+            }
+            let md::XSTATE_FEATURE { offset, size } = config.features[idx];
+            let data = xsave.get(offset as usize..)?.get(..size as usize)?;
+            if features_in_use & (1 << idx) == 0 {
+                Some(vec![0; data.len()])
+            } else {
+                Some(data.to_vec())
+            }
+        };
+
+        let mut xstate = MinidumpXState {
+            features_in_use,
+            ..MinidumpXState::default()
+        };
+
+        // The lower halves of the ymm registers are the xmm registers.
+        if let Some(avx) = feature(GSSE_AND_AVX) {
+            let xmm = xsave.get(XSAVE_XMM_OFFSET..)?;
+            for (i, upper) in avx.chunks_exact(16).enumerate() {
+                let mut ymm = [0; 32];
+                ymm[..16].copy_from_slice(xmm.get(i * 16..(i + 1) * 16)?);
+                ymm[16..].copy_from_slice(upper);
+                xstate.ymm.push(ymm);
+            }
+        }
+
+        if let Some(kmask) = feature(AVX512_KMASK) {
+            for k in kmask.chunks_exact(8) {
+                xstate.kmask.push(k.pread_with(0, endian).ok()?);
+            }
+        }
+
+        // The lower halves of zmm0-zmm15 are the ymm registers, and zmm16-zmm31
+        // are saved separately.
+        if let Some(zmm_h) = feature(AVX512_ZMM_H) {
+            for (ymm, upper) in xstate.ymm.iter().zip(zmm_h.chunks_exact(32)) {
+                let mut zmm = [0; 64];
+                zmm[..32].copy_from_slice(ymm);
+                zmm[32..].copy_from_slice(upper);
+                xstate.zmm.push(zmm);
+            }
+            if let Some(zmm_hi) = feature(ACK512_ZMM) {
+                for full in zmm_hi.chunks_exact(64) {
+                    let mut zmm = [0; 64];
+                    zmm.copy_from_slice(full);
+                    xstate.zmm.push(zmm);
+                }
+            }
+        }
+
+        if let Some(cet) = feature(XSTATE_CET_U) {
+            xstate.cet_control = cet.pread_with(0, endian).ok();
+            xstate.shadow_stack_pointer = cet.pread_with(8, endian).ok();
+        }
+
+        if xstate.ymm.is_empty()
+            && xstate.kmask.is_empty()
+            && xstate.zmm.is_empty()
+            && xstate.cet_control.is_none()
+        {
+            return None;
+        }
+        Some(xstate)
+    }
+
+    /// All the registers, as pairs of their name and their value formatted
+    /// as a hex number.
+    pub fn registers(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        let ymm = self
+            .ymm
+            .iter()
+            .enumerate()
+            .map(|(i, reg)| (format!("ymm{i}"), format_vector_register(reg)));
+        let kmask = self
+            .kmask
+            .iter()
+            .enumerate()
+            .map(|(i, reg)| (format!("k{i}"), format!("{reg:#018x}")));
+        let zmm = self
+            .zmm
+            .iter()
+            .enumerate()
+            .map(|(i, reg)| (format!("zmm{i}"), format_vector_register(reg)));
+        let cet = self
+            .cet_control
+            .map(|reg| (String::from("cet_u"), format!("{reg:#018x}")))
+            .into_iter()
+            .chain(
+                self.shadow_stack_pointer
+                    .map(|reg| (String::from("ssp"), format!("{reg:#018x}"))),
+            );
+        ymm.chain(kmask).chain(zmm).chain(cet)
+    }
+
+    /// Write a human-readable description of this `MinidumpXState` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        writeln!(f, "XSTATE")?;
+        writeln!(f, "  features_in_use = {:#x}", self.features_in_use)?;
+        for (name, value) in self.registers() {
+            writeln!(f, "  {name:<15} = {value}")?;
+        }
+        writeln!(f)
+    }
+}
+
+fn arm64_float_registers(
+    fpcr: u32,
+    fpsr: u32,
+    float_regs: &[u128; 32],
+) -> impl Iterator<Item = FloatRegister> + '_ {
+    vec![
+        FloatRegister::new("fpcr", FloatRegisterKind::Control, fpcr.into(), 4),
+        FloatRegister::new("fpsr", FloatRegisterKind::Control, fpsr.into(), 4),
+    ]
+    .into_iter()
+    .chain(
+        ARM64_VECTOR_REGISTERS
+            .iter()
+            .zip(float_regs)
+            .map(|(&name, &value)| FloatRegister::new(name, FloatRegisterKind::Vector, value, 16)),
+    )
+}
+
+/// Write the floating point and vector state shared by PPC and PPC64 contexts.
+fn print_ppc_float_and_vector_state<T: Write>(
+    f: &mut T,
+    float_save: &md::FLOATING_SAVE_AREA_PPC,
+    vector_save: &md::VECTOR_SAVE_AREA_PPC,
+) -> io::Result<()> {
+    for (i, reg) in float_save.fpregs.iter().enumerate() {
+        writeln!(f, "  float_save.fpregs[{i:2}]     = {reg:#x}")?;
+    }
+    writeln!(f, "  float_save.fpscr         = {:#x}", float_save.fpscr)?;
+    for (i, reg) in vector_save.save_vr.iter().enumerate() {
+        writeln!(f, "  vector_save.save_vr[{i:2}]  = {reg:#x}")?;
+    }
+    writeln!(
+        f,
+        "  vector_save.save_vscr    = {:#x}",
+        vector_save.save_vscr
+    )?;
+    writeln!(
+        f,
+        "  vector_save.save_vrvalid = {:#x}",
+        vector_save.save_vrvalid
+    )?;
+    Ok(())
+}
+
+/// Format a little-endian vector register as a single hex number.
+fn format_vector_register(bytes: &[u8]) -> String {
+    let mut output = String::from("0x");
+    for byte in bytes.iter().rev() {
+        output.push_str(&format!("{byte:02x}"));
+    }
+    output
+}
+
+/// The x87 registers, in the order they're stored in.
+const X87_REGISTERS: [&str; 8] = ["st0", "st1", "st2", "st3", "st4", "st5", "st6", "st7"];
+
+/// The SSE registers, in the order they're stored in.
+const XMM_REGISTERS: [&str; 16] = [
+    "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7", "xmm8", "xmm9", "xmm10",
+    "xmm11", "xmm12", "xmm13", "xmm14", "xmm15",
+];
+
+/// The ARM64 SIMD registers, in the order they're stored in.
+const ARM64_VECTOR_REGISTERS: [&str; 32] = [
+    "v0", "v1", "v2", "v3", "v4", "v5", "v6", "v7", "v8", "v9", "v10", "v11", "v12", "v13", "v14",
+    "v15", "v16", "v17", "v18", "v19", "v20", "v21", "v22", "v23", "v24", "v25", "v26", "v27",
+    "v28", "v29", "v30", "v31",
+];
+
+/// The RISC-V floating-point registers, in the order they're stored in.
+const RISCV_FLOAT_REGISTERS: [&str; 32] = [
+    "f0", "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12", "f13", "f14",
+    "f15", "f16", "f17", "f18", "f19", "f20", "f21", "f22", "f23", "f24", "f25", "f26", "f27",
+    "f28", "f29", "f30", "f31",
+];
+
+/// The ARM VFP registers, in the order they're stored in.
+const ARM_DOUBLE_REGISTERS: [&str; 32] = [
+    "d0", "d1", "d2", "d3", "d4", "d5", "d6", "d7", "d8", "d9", "d10", "d11", "d12", "d13", "d14",
+    "d15", "d16", "d17", "d18", "d19", "d20", "d21", "d22", "d23", "d24", "d25", "d26", "d27",
+    "d28", "d29", "d30", "d31",
+];
+
+impl FloatRegister {
+    fn new(name: &'static str, kind: FloatRegisterKind, value: u128, size: usize) -> Self {
+        FloatRegister {
+            name,
+            kind,
+            value,
+            size,
+        }
+    }
+
+    fn x87(name: &'static str, bytes: &[u8]) -> Self {
+        let mut value = [0; 16];
+        value[..10].copy_from_slice(&bytes[..10]);
+        Self::new(name, FloatRegisterKind::X87, u128::from_le_bytes(value), 10)
+    }
+
+    /// The value of the register formatted as a hex number of its natural width.
+    pub fn format(&self) -> String {
+        format!("0x{:01$x}", self.value, self.size * 2)
+    }
+
+    /// The register interpreted as a scalar float.
+    ///
+    /// This is the value of x87 registers (rounded to the nearest `f64`) and
+    /// of ARM's double registers. Vector and control registers have no
+    /// single float value, and return `None`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.kind {
+            FloatRegisterKind::X87 => Some(x87_to_f64(self.value)),
+            FloatRegisterKind::Double => Some(f64::from_bits(self.value as u64)),
+            FloatRegisterKind::Vector | FloatRegisterKind::Control => None,
+        }
+    }
+
+    /// The lanes of a vector or double register, interpreted as `f32`s.
+    ///
+    /// The lowest lane comes first. Other registers have no lanes.
+    pub fn f32_lanes(&self) -> Vec<f32> {
+        self.lanes(4)
+            .map(|lane| f32::from_bits(lane as u32))
+            .collect()
+    }
+
+    /// The lanes of a vector or double register, interpreted as `f64`s.
+    ///
+    /// The lowest lane comes first. Other registers have no lanes.
+    pub fn f64_lanes(&self) -> Vec<f64> {
+        self.lanes(8)
+            .map(|lane| f64::from_bits(lane as u64))
+            .collect()
+    }
+
+    fn lanes(&self, lane_size: usize) -> impl Iterator<Item = u128> + '_ {
+        let count = match self.kind {
+            FloatRegisterKind::Vector | FloatRegisterKind::Double => self.size / lane_size,
+            FloatRegisterKind::X87 | FloatRegisterKind::Control => 0,
+        };
+        let mask = (1u128 << (lane_size * 8)) - 1;
+        (0..count).map(move |i| (self.value >> (i * lane_size * 8)) & mask)
+    }
+}
+
+/// Convert an 80-bit x87 extended precision float to the nearest `f64`.
+fn x87_to_f64(value: u128) -> f64 {
+    // The 64-bit significand has an explicit integer bit, so it's just a
+    // fixed point number with 63 fractional bits.
+    let significand = value as u64;
+    let exponent = ((value >> 64) & 0x7fff) as i32;
+    let sign = if (value >> 79) & 1 != 0 { -1.0 } else { 1.0 };
+    if exponent == 0x7fff {
+        return if significand << 1 == 0 {
+            sign * f64::INFINITY
+        } else {
+            f64::NAN
+        };
+    }
+    // Denormals use the same scale as the smallest normal exponent.
+    let exponent = exponent.max(1) - 16383;
+    sign * (significand as f64 / 2f64.powi(63)) * 2f64.powi(exponent)
+}
+
 impl MinidumpContext {
     /// Return a MinidumpContext given a `MinidumpRawContext`.
     pub fn from_raw(raw: MinidumpRawContext) -> MinidumpContext {
         MinidumpContext {
             raw,
             valid: MinidumpContextValidity::All,
+            xstate: None,
         }
     }
 
@@ -1043,7 +1581,7 @@ impl MinidumpContext {
         bytes: &[u8],
         endian: scroll::Endian,
         system_info: &MinidumpSystemInfo,
-        _misc: Option<&MinidumpMiscInfo>,
+        misc: Option<&MinidumpMiscInfo>,
     ) -> Result<MinidumpContext, ContextError> {
         use md::ProcessorArchitecture::*;
 
@@ -1064,11 +1602,17 @@ impl MinidumpContext {
 
                 let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
                 if flags == ContextFlagsCpu::CONTEXT_X86 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
-                    }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::X86(ctx)))
+                    let xstate = MinidumpXState::read_context(
+                        bytes,
+                        X86_XSAVE_OFFSET,
+                        ctx.context_flags,
+                        misc,
+                        endian,
+                    );
+                    Ok(MinidumpContext {
+                        xstate,
+                        ..MinidumpContext::from_raw(MinidumpRawContext::X86(ctx))
+                    })
                 } else {
                     Err(ContextError::ReadFailure)
                 }
@@ -1080,11 +1624,17 @@ impl MinidumpContext {
 
                 let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
                 if flags == ContextFlagsCpu::CONTEXT_AMD64 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
-                    }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
+                    let xstate = MinidumpXState::read_context(
+                        bytes,
+                        AMD64_XSAVE_OFFSET,
+                        ctx.context_flags,
+                        misc,
+                        endian,
+                    );
+                    Ok(MinidumpContext {
+                        xstate,
+                        ..MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx))
+                    })
                 } else {
                     Err(ContextError::ReadFailure)
                 }
@@ -1161,18 +1711,34 @@ impl MinidumpContext {
                     Err(ContextError::ReadFailure)
                 }
             }
-            Some(PROCESSOR_ARCHITECTURE_MIPS) => {
+            Some(PROCESSOR_ARCHITECTURE_MIPS) | Some(PROCESSOR_ARCHITECTURE_MIPS64) => {
                 let ctx: md::CONTEXT_MIPS = bytes
                     .gread_with(&mut offset, endian)
                     .or(Err(ContextError::ReadFailure))?;
 
+                // The n32 ABI has 64-bit registers on a 32-bit architecture,
+                // so any combination is possible.
                 let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
-                if flags == ContextFlagsCpu::CONTEXT_MIPS {
+                if flags == ContextFlagsCpu::CONTEXT_MIPS
+                    || flags == ContextFlagsCpu::CONTEXT_MIPS64
+                {
                     Ok(MinidumpContext::from_raw(MinidumpRawContext::Mips(ctx)))
                 } else {
                     Err(ContextError::ReadFailure)
                 }
             }
+            Some(PROCESSOR_ARCHITECTURE_RISCV64) => {
+                let ctx: md::CONTEXT_RISCV64 = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(ContextError::ReadFailure))?;
+
+                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+                if flags == ContextFlagsCpu::CONTEXT_RISCV64 {
+                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Riscv64(ctx)))
+                } else {
+                    Err(ContextError::ReadFailure)
+                }
+            }
             _ => Err(ContextError::UnknownCpuContext),
         }
     }
@@ -1190,6 +1756,7 @@ impl MinidumpContext {
             MinidumpRawContext::Sparc(ref ctx) => ctx.pc,
             MinidumpRawContext::X86(ref ctx) => ctx.eip as u64,
             MinidumpRawContext::Mips(ref ctx) => ctx.epc,
+            MinidumpRawContext::Riscv64(ref ctx) => ctx.pc,
         }
     }
 
@@ -1214,6 +1781,9 @@ impl MinidumpContext {
             MinidumpRawContext::Mips(ref ctx) => {
                 ctx.iregs[md::MipsRegisterNumbers::StackPointer as usize]
             }
+            MinidumpRawContext::Riscv64(ref ctx) => {
+                ctx.iregs[md::Riscv64RegisterNumbers::StackPointer as usize]
+            }
         }
     }
 
@@ -1228,6 +1798,7 @@ impl MinidumpContext {
             MinidumpRawContext::Sparc(ref ctx) => ctx.get_register_always(reg),
             MinidumpRawContext::X86(ref ctx) => ctx.get_register_always(reg).into(),
             MinidumpRawContext::Mips(ref ctx) => ctx.get_register_always(reg),
+            MinidumpRawContext::Riscv64(ref ctx) => ctx.get_register_always(reg),
         }
     }
 
@@ -1242,6 +1813,7 @@ impl MinidumpContext {
             MinidumpRawContext::Arm64(ctx) => ctx.register_is_valid(reg, &self.valid),
             MinidumpRawContext::OldArm64(ctx) => ctx.register_is_valid(reg, &self.valid),
             MinidumpRawContext::Mips(ctx) => ctx.register_is_valid(reg, &self.valid),
+            MinidumpRawContext::Riscv64(ctx) => ctx.register_is_valid(reg, &self.valid),
         };
 
         if valid {
@@ -1262,6 +1834,7 @@ impl MinidumpContext {
             MinidumpRawContext::Sparc(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::X86(ref ctx) => ctx.format_register(reg),
             MinidumpRawContext::Mips(ref ctx) => ctx.format_register(reg),
+            MinidumpRawContext::Riscv64(ref ctx) => ctx.format_register(reg),
         }
     }
 
@@ -1276,6 +1849,7 @@ impl MinidumpContext {
             MinidumpRawContext::Sparc(_) => md::CONTEXT_SPARC::REGISTERS,
             MinidumpRawContext::X86(_) => md::CONTEXT_X86::REGISTERS,
             MinidumpRawContext::Mips(_) => md::CONTEXT_MIPS::REGISTERS,
+            MinidumpRawContext::Riscv64(_) => md::CONTEXT_RISCV64::REGISTERS,
         }
     }
 
@@ -1299,6 +1873,7 @@ impl MinidumpContext {
             MinidumpRawContext::Arm64(ctx) => ctx.register_is_valid(reg, &self.valid),
             MinidumpRawContext::OldArm64(ctx) => ctx.register_is_valid(reg, &self.valid),
             MinidumpRawContext::Mips(ctx) => ctx.register_is_valid(reg, &self.valid),
+            MinidumpRawContext::Riscv64(ctx) => ctx.register_is_valid(reg, &self.valid),
         })
     }
 
@@ -1318,9 +1893,146 @@ impl MinidumpContext {
             MinidumpRawContext::Arm64(ctx) => get(ctx),
             MinidumpRawContext::OldArm64(ctx) => get(ctx),
             MinidumpRawContext::Mips(ctx) => get(ctx),
+            MinidumpRawContext::Riscv64(ctx) => get(ctx),
         }
     }
 
+    /// The floating-point and SIMD registers of this context.
+    ///
+    /// These are available for x86 (st0-st7, and xmm0-xmm7 if the context
+    /// has `extended_registers`), x86-64 (st0-st7 and xmm0-xmm15), ARM64
+    /// (v0-v31) and ARM (d0-d31), along with their control and status
+    /// registers. Other CPUs, contexts which don't contain the floating-point
+    /// state according to their `context_flags`, and contexts of frames
+    /// recovered by unwinding (which only restores general purpose registers)
+    /// have none.
+    ///
+    /// For the 256 and 512-bit registers of AVX and AVX-512 see [`Self::xstate`].
+    pub fn float_registers(&self) -> impl Iterator<Item = FloatRegister> {
+        use FloatRegisterKind::*;
+
+        let mut registers = Vec::new();
+        if self.valid != MinidumpContextValidity::All {
+            return registers.into_iter();
+        }
+
+        match self.raw {
+            MinidumpRawContext::X86(ref ctx) => {
+                let flags = md::ContextFlagsX86::from_bits_truncate(ctx.context_flags);
+                if flags.contains(md::ContextFlagsX86::CONTEXT_X86_FLOATING_POINT) {
+                    let fpu = &ctx.float_save;
+                    registers.extend([
+                        FloatRegister::new("fcw", Control, fpu.control_word.into(), 2),
+                        FloatRegister::new("fsw", Control, fpu.status_word.into(), 2),
+                        FloatRegister::new("ftw", Control, fpu.tag_word.into(), 2),
+                    ]);
+                    registers.extend(
+                        X87_REGISTERS
+                            .iter()
+                            .zip(fpu.register_area.chunks_exact(10))
+                            .map(|(&name, bytes)| FloatRegister::x87(name, bytes)),
+                    );
+                }
+                if flags.contains(md::ContextFlagsX86::CONTEXT_X86_EXTENDED_REGISTERS) {
+                    // This is an FXSAVE area, but only the first 8 xmm registers exist.
+                    if let Ok(area) = ctx
+                        .extended_registers
+                        .pread_with::<md::XMM_SAVE_AREA32>(0, scroll::LE)
+                    {
+                        registers.push(FloatRegister::new("mxcsr", Control, area.mx_csr.into(), 4));
+                        registers.extend(
+                            XMM_REGISTERS
+                                .iter()
+                                .zip(&area.xmm_registers[..8])
+                                .map(|(&name, &value)| FloatRegister::new(name, Vector, value, 16)),
+                        );
+                    }
+                }
+            }
+            MinidumpRawContext::Amd64(ref ctx) => {
+                let flags = md::ContextFlagsAmd64::from_bits_truncate(ctx.context_flags);
+                if flags.contains(md::ContextFlagsAmd64::CONTEXT_AMD64_FLOATING_POINT) {
+                    if let Ok(area) = ctx
+                        .float_save
+                        .pread_with::<md::XMM_SAVE_AREA32>(0, scroll::LE)
+                    {
+                        registers.extend([
+                            FloatRegister::new("fcw", Control, area.control_word.into(), 2),
+                            FloatRegister::new("fsw", Control, area.status_word.into(), 2),
+                            FloatRegister::new("ftw", Control, area.tag_word.into(), 1),
+                            FloatRegister::new("mxcsr", Control, ctx.mx_csr.into(), 4),
+                        ]);
+                        registers.extend(
+                            X87_REGISTERS.iter().zip(&area.float_registers).map(
+                                |(&name, value)| FloatRegister::x87(name, &value.to_le_bytes()),
+                            ),
+                        );
+                        registers.extend(
+                            XMM_REGISTERS
+                                .iter()
+                                .zip(&area.xmm_registers)
+                                .map(|(&name, &value)| FloatRegister::new(name, Vector, value, 16)),
+                        );
+                    }
+                }
+            }
+            MinidumpRawContext::Arm64(ref ctx) => {
+                let flags = md::ContextFlagsArm64::from_bits_truncate(ctx.context_flags);
+                if flags.contains(md::ContextFlagsArm64::CONTEXT_ARM64_FLOATING_POINT) {
+                    registers.extend(arm64_float_registers(ctx.fpcr, ctx.fpsr, &ctx.float_regs));
+                }
+            }
+            MinidumpRawContext::OldArm64(ref ctx) => {
+                let flags = md::ContextFlagsArm64Old::from_bits_truncate(ctx.context_flags as u32);
+                if flags.contains(md::ContextFlagsArm64Old::CONTEXT_ARM64_OLD_FLOATING_POINT) {
+                    registers.extend(arm64_float_registers(ctx.fpcr, ctx.fpsr, &ctx.float_regs));
+                }
+            }
+            MinidumpRawContext::Arm(ref ctx) => {
+                let flags = md::ContextFlagsArm::from_bits_truncate(ctx.context_flags);
+                if flags.contains(md::ContextFlagsArm::CONTEXT_ARM_FLOATING_POINT) {
+                    let fpu = &ctx.float_save;
+                    registers.push(FloatRegister::new("fpscr", Control, fpu.fpscr.into(), 8));
+                    registers.extend(
+                        ARM_DOUBLE_REGISTERS
+                            .iter()
+                            .zip(&fpu.regs)
+                            .map(|(&name, &value)| {
+                                FloatRegister::new(name, Double, value.into(), 8)
+                            }),
+                    );
+                }
+            }
+            MinidumpRawContext::Riscv64(ref ctx) => {
+                let flags = md::ContextFlagsRiscv64::from_bits_truncate(ctx.context_flags);
+                if flags.contains(md::ContextFlagsRiscv64::CONTEXT_RISCV64_FLOATING_POINT) {
+                    let fpu = &ctx.float_save;
+                    registers.push(FloatRegister::new("fcsr", Control, fpu.fpcsr.into(), 4));
+                    registers.extend(
+                        RISCV_FLOAT_REGISTERS
+                            .iter()
+                            .zip(&fpu.regs)
+                            .map(|(&name, &value)| {
+                                FloatRegister::new(name, Double, value.into(), 8)
+                            }),
+                    );
+                }
+            }
+            MinidumpRawContext::Ppc(_)
+            | MinidumpRawContext::Ppc64(_)
+            | MinidumpRawContext::Sparc(_)
+            | MinidumpRawContext::Mips(_) => {}
+        }
+        registers.into_iter()
+    }
+
+    /// Get the floating-point or SIMD register named `reg`, if this context has it.
+    ///
+    /// See [`Self::float_registers`].
+    pub fn get_float_register(&self, reg: &str) -> Option<FloatRegister> {
+        self.float_registers().find(|r| r.name == reg)
+    }
+
     /// Write a human-readable description of this `MinidumpContext` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -1405,11 +2117,56 @@ impl MinidumpContext {
                 write_bytes(f, &raw.extended_registers)?;
                 write!(f, "\n\n")?;
             }
-            MinidumpRawContext::Ppc(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_PPC
+  context_flags            = {:#x}
+  srr0                     = {:#x}
+  srr1                     = {:#x}
+"#,
+                    raw.context_flags, raw.srr0, raw.srr1
+                )?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{i:2}]                  = {reg:#x}")?;
+                }
+                write!(
+                    f,
+                    r#"  cr                       = {:#x}
+  xer                      = {:#x}
+  lr                       = {:#x}
+  ctr                      = {:#x}
+  mq                       = {:#x}
+  vrsave                   = {:#x}
+"#,
+                    raw.cr, raw.xer, raw.lr, raw.ctr, raw.mq, raw.vrsave
+                )?;
+                print_ppc_float_and_vector_state(f, &raw.float_save, &raw.vector_save)?;
             }
-            MinidumpRawContext::Ppc64(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc64(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_PPC64
+  context_flags            = {:#x}
+  srr0                     = {:#x}
+  srr1                     = {:#x}
+"#,
+                    raw.context_flags, raw.srr0, raw.srr1
+                )?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{i:2}]                  = {reg:#x}")?;
+                }
+                write!(
+                    f,
+                    r#"  cr                       = {:#x}
+  xer                      = {:#x}
+  lr                       = {:#x}
+  ctr                      = {:#x}
+  vrsave                   = {:#x}
+"#,
+                    raw.cr, raw.xer, raw.lr, raw.ctr, raw.vrsave
+                )?;
+                print_ppc_float_and_vector_state(f, &raw.float_save, &raw.vector_save)?;
             }
             MinidumpRawContext::Amd64(ref raw) => {
                 write!(
@@ -1495,8 +2252,37 @@ impl MinidumpContext {
                     raw.rip,
                 )?;
             }
-            MinidumpRawContext::Sparc(_) => {
-                unimplemented!();
+            MinidumpRawContext::Sparc(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_SPARC
+  context_flags        = {:#x}
+"#,
+                    raw.context_flags
+                )?;
+                for (i, reg) in raw.g_r.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "  g_r[{i:2}] ({:2})         = {reg:#x}",
+                        SPARC_REGISTER_ALIASES[i]
+                    )?;
+                }
+                write!(
+                    f,
+                    r#"  ccr                  = {:#x}
+  pc                   = {:#x}
+  npc                  = {:#x}
+  y                    = {:#x}
+  asi                  = {:#x}
+  fprs                 = {:#x}
+"#,
+                    raw.ccr, raw.pc, raw.npc, raw.y, raw.asi, raw.fprs
+                )?;
+                for (i, reg) in raw.float_save.regs.iter().enumerate() {
+                    writeln!(f, "  float_save.regs[{i:2}]  = {reg:#x}")?;
+                }
+                writeln!(f, "  float_save.filler    = {:#x}", raw.float_save.filler)?;
+                writeln!(f, "  float_save.fsr       = {:#x}", raw.float_save.fsr)?;
             }
             MinidumpRawContext::Arm(ref raw) => {
                 write!(
@@ -1612,6 +2398,28 @@ impl MinidumpContext {
                     )?;
                 }
             }
+            MinidumpRawContext::Riscv64(ref raw) => {
+                write!(
+                    f,
+                    r#"CONTEXT_RISCV64
+  context_flags        = {:#x}
+  version              = {}
+  pc                   = {:#x}
+"#,
+                    raw.context_flags, raw.version, raw.pc
+                )?;
+                for (i, reg) in raw.iregs.iter().enumerate() {
+                    let name = format!("x{} ({})", i + 1, md::CONTEXT_RISCV64::REGISTERS[i]);
+                    writeln!(f, "  {name:<20} = {reg:#x}")?;
+                }
+                writeln!(f, "  fcsr                 = {:#x}", raw.float_save.fpcsr)?;
+                for (i, reg) in raw.float_save.regs.iter().enumerate() {
+                    writeln!(f, "  f{i:<2} = {reg:#x}")?;
+                }
+            }
+        }
+        if let Some(xstate) = &self.xstate {
+            xstate.print(f)?;
         }
         Ok(())
     }
@@ -1646,4 +2454,159 @@ mod tests {
         assert_eq!(context.memoize_register("fp"), Some("fp"));
         assert_eq!(context.memoize_register("foo"), None);
     }
+
+    #[test]
+    /// Test MIPS register aliases by example of `fp`.
+    fn test_memoize_mips_alias() {
+        let context = md::CONTEXT_MIPS::default();
+        assert_eq!(context.memoize_register("s8"), Some("fp"));
+        assert_eq!(context.memoize_register("fp"), Some("fp"));
+        assert_eq!(context.memoize_register("foo"), None);
+    }
+
+    #[test]
+    /// Test SPARC register aliases by example of the stack and frame pointers.
+    fn test_memoize_sparc_alias() {
+        let mut context = md::CONTEXT_SPARC::default();
+        assert_eq!(context.memoize_register("o6"), Some("g_r14"));
+        assert_eq!(context.memoize_register("i6"), Some("g_r30"));
+        assert_eq!(context.memoize_register("g_r30"), Some("g_r30"));
+        assert_eq!(context.memoize_register("foo"), None);
+
+        context.set_register("i6", 0x1234);
+        let valid = MinidumpContextValidity::Some(["g_r30"].iter().copied().collect());
+        assert_eq!(context.get_register("i6", &valid), Some(0x1234));
+        assert_eq!(context.get_register("g_r30", &valid), Some(0x1234));
+        assert_eq!(context.get_register("o6", &valid), None);
+    }
+
+    #[test]
+    fn test_print_ppc_sparc() {
+        let mut ppc = md::CONTEXT_PPC {
+            srr0: 0x1000,
+            ..Default::default()
+        };
+        ppc.gpr[1] = 0x8000;
+        let ppc64 = md::CONTEXT_PPC64 {
+            lr: 0x2000,
+            ..Default::default()
+        };
+        let mut sparc = md::CONTEXT_SPARC {
+            pc: 0x1000,
+            ..Default::default()
+        };
+        sparc.g_r[14] = 0x8000;
+
+        let print = |raw| {
+            let mut output = Vec::new();
+            MinidumpContext::from_raw(raw).print(&mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let output = print(MinidumpRawContext::Ppc(ppc));
+        assert!(output.starts_with("CONTEXT_PPC\n"));
+        assert!(output.contains("  srr0                     = 0x1000\n"));
+        assert!(output.contains("  gpr[ 1]                  = 0x8000\n"));
+        assert!(output.contains("  vector_save.save_vr[31]  = 0x0\n"));
+        let output = print(MinidumpRawContext::Ppc64(ppc64));
+        assert!(output.starts_with("CONTEXT_PPC64\n"));
+        assert!(output.contains("  lr                       = 0x2000\n"));
+        let output = print(MinidumpRawContext::Sparc(sparc));
+        assert!(output.starts_with("CONTEXT_SPARC\n"));
+        assert!(output.contains("  g_r[14] (o6)         = 0x8000\n"));
+        assert!(output.contains("  pc                   = 0x1000\n"));
+    }
+
+    #[test]
+    fn test_float_registers_amd64() {
+        let mut raw = md::CONTEXT_AMD64 {
+            context_flags: md::ContextFlagsAmd64::CONTEXT_AMD64_FULL.bits(),
+            mx_csr: 0x1f80,
+            ..Default::default()
+        };
+        raw.float_save[0..2].copy_from_slice(&0x37fu16.to_le_bytes());
+        // st0 = 1.5
+        raw.float_save[32..40].copy_from_slice(&0xc000_0000_0000_0000u64.to_le_bytes());
+        raw.float_save[40..42].copy_from_slice(&0x3fffu16.to_le_bytes());
+        // xmm1 = [1.0f32, 2.0f32, -1.0f32, 0.5f32]
+        let xmm1: u128 = 0x3f000000_bf800000_40000000_3f800000;
+        raw.float_save[176..192].copy_from_slice(&xmm1.to_le_bytes());
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(raw));
+
+        let names: Vec<_> = context.float_registers().map(|reg| reg.name).collect();
+        assert_eq!(names.len(), 4 + 8 + 16);
+        assert_eq!(&names[..5], ["fcw", "fsw", "ftw", "mxcsr", "st0"]);
+        assert_eq!(names[names.len() - 1], "xmm15");
+
+        let fcw = context.get_float_register("fcw").unwrap();
+        assert_eq!(fcw.format(), "0x037f");
+        assert_eq!(context.get_float_register("mxcsr").unwrap().value, 0x1f80);
+
+        let st0 = context.get_float_register("st0").unwrap();
+        assert_eq!(st0.kind, FloatRegisterKind::X87);
+        assert_eq!(st0.format(), "0x3fffc000000000000000");
+        assert_eq!(st0.as_f64(), Some(1.5));
+        assert!(st0.f32_lanes().is_empty());
+
+        let xmm1 = context.get_float_register("xmm1").unwrap();
+        assert_eq!(xmm1.kind, FloatRegisterKind::Vector);
+        assert_eq!(xmm1.format(), "0x3f000000bf800000400000003f800000");
+        assert_eq!(xmm1.f32_lanes(), [1.0, 2.0, -1.0, 0.5]);
+        assert_eq!(xmm1.f64_lanes().len(), 2);
+        assert_eq!(xmm1.as_f64(), None);
+
+        // Without the floating point state in the context, there's nothing to report.
+        let raw = md::CONTEXT_AMD64 {
+            context_flags: md::ContextFlagsAmd64::CONTEXT_AMD64_CONTROL.bits(),
+            ..Default::default()
+        };
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(raw));
+        assert_eq!(context.float_registers().count(), 0);
+    }
+
+    #[test]
+    fn test_float_registers_arm() {
+        let mut raw = md::CONTEXT_ARM64 {
+            context_flags: md::ContextFlagsArm64::CONTEXT_ARM64_FULL.bits(),
+            fpcr: 0x300000,
+            ..Default::default()
+        };
+        raw.float_regs[31] = (2.0f64.to_bits() as u128) << 64 | 0.25f64.to_bits() as u128;
+        let mut context = MinidumpContext::from_raw(MinidumpRawContext::Arm64(raw));
+
+        let v31 = context.get_float_register("v31").unwrap();
+        assert_eq!(v31.size, 16);
+        assert_eq!(v31.f64_lanes(), [0.25, 2.0]);
+        assert_eq!(
+            context.get_float_register("fpcr").unwrap().format(),
+            "0x00300000"
+        );
+        assert_eq!(context.float_registers().count(), 2 + 32);
+
+        // Unwound frames don't restore these.
+        context.valid = MinidumpContextValidity::Some(["pc", "sp"].iter().copied().collect());
+        assert_eq!(context.float_registers().count(), 0);
+
+        let mut raw = md::CONTEXT_ARM {
+            context_flags: md::ContextFlagsArm::CONTEXT_ARM_FULL.bits(),
+            ..Default::default()
+        };
+        raw.float_save.regs[3] = (-3.5f64).to_bits();
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Arm(raw));
+        let d3 = context.get_float_register("d3").unwrap();
+        assert_eq!(d3.kind, FloatRegisterKind::Double);
+        assert_eq!(d3.as_f64(), Some(-3.5));
+        assert_eq!(d3.format(), "0xc00c000000000000");
+        assert_eq!(context.float_registers().count(), 1 + 32);
+    }
+
+    #[test]
+    fn test_x87_to_f64() {
+        assert_eq!(x87_to_f64(0), 0.0);
+        assert_eq!(x87_to_f64(0x3fff_8000_0000_0000_0000), 1.0);
+        assert_eq!(x87_to_f64(0xc000_c000_0000_0000_0000), -3.0);
+        assert_eq!(x87_to_f64(0x7fff_8000_0000_0000_0000), f64::INFINITY);
+        assert!(x87_to_f64(0x7fff_c000_0000_0000_0000).is_nan());
+        // Too large for an f64
+        assert_eq!(x87_to_f64(0x7ffe_8000_0000_0000_0000), f64::INFINITY);
+    }
 }
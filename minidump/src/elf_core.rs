@@ -0,0 +1,2002 @@
+//! Converting between ELF core files and minidumps.
+//!
+//! When a process crashes on Linux, the kernel can write an [ELF core file][core] with
+//! roughly the same contents as a full-memory minidump:
+//!
+//! * an `NT_PRSTATUS` note with the registers of each thread
+//! * `PT_LOAD` segments with the contents of (most of) the process's memory
+//! * an `NT_FILE` note listing the files mapped into the process
+//! * an `NT_AUXV` note with the auxiliary vector of the process
+//! * `NT_SIGINFO` and `NT_PRPSINFO` notes describing the signal and the process
+//!
+//! [`ElfCore`] parses those, and [`ElfCore::to_minidump`] converts them into an equivalent
+//! [`Minidump`], so that a core file can be inspected and processed with exactly the same
+//! APIs as a minidump (including `minidump-processor`).
+//!
+//! Going the other way, [`minidump_to_elf_core`] turns a minidump into an ELF core file,
+//! so that the state of the crashed process can be inspected in gdb or lldb.
+//!
+//! Only 64-bit x86-64 and ARM64 processes are currently supported.
+//!
+//! [core]: https://man7.org/linux/man-pages/man5/core.5.html
+
+use memmap2::Mmap;
+use scroll::ctx::SizeWith;
+use scroll::{Pread, Pwrite, SizeWith, BE, LE};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::Path;
+
+use crate::context::{MinidumpContext, MinidumpRawContext};
+use crate::minidump::{
+    Error, Minidump, MinidumpException, MinidumpLinuxAuxv, MinidumpLinuxAuxvEntry,
+    MinidumpLinuxCmdLine, MinidumpLinuxMaps, MinidumpMemoryInfoList, MinidumpMiscInfo,
+    MinidumpModuleList, MinidumpSystemInfo, MinidumpThreadList, UnifiedMemoryInfoList,
+};
+use crate::system_info::{Cpu, Os};
+use crate::writer::{
+    MinidumpBuilder, ReferenceKind, ReferenceTarget, StreamData, EXCEPTION_CONTEXT_OFFSET,
+    MODULE_CV_RECORD_OFFSET, MODULE_NAME_OFFSET, MODULE_SIZE, SYSTEM_INFO_CSD_VERSION_OFFSET,
+    THREAD_CONTEXT_OFFSET, THREAD_SIZE,
+};
+use minidump_common::format::{self as md, MINIDUMP_STREAM_TYPE};
+use minidump_common::traits::Module;
+
+pub(crate) const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+pub(crate) const EI_NIDENT: usize = 16;
+pub(crate) const EI_CLASS: usize = 4;
+pub(crate) const EI_DATA: usize = 5;
+pub(crate) const EI_VERSION: usize = 6;
+pub(crate) const ELFCLASS64: u8 = 2;
+pub(crate) const ELFDATA2LSB: u8 = 1;
+pub(crate) const ELFDATA2MSB: u8 = 2;
+pub(crate) const EV_CURRENT: u8 = 1;
+pub(crate) const ET_CORE: u16 = 4;
+pub(crate) const EM_X86_64: u16 = 62;
+pub(crate) const EM_AARCH64: u16 = 183;
+pub(crate) const PT_LOAD: u32 = 1;
+pub(crate) const PT_NOTE: u32 = 4;
+/// The value of `e_phnum` when the number of program headers is in the `sh_info`
+/// of the first section header instead.
+pub(crate) const PN_XNUM: u16 = 0xffff;
+
+/// [`ElfCoreSegment::flags`] bit for executable memory.
+pub const PF_X: u32 = 1;
+/// [`ElfCoreSegment::flags`] bit for writable memory.
+pub const PF_W: u32 = 2;
+/// [`ElfCoreSegment::flags`] bit for readable memory.
+pub const PF_R: u32 = 4;
+
+pub(crate) const NT_PRSTATUS: u32 = 1;
+pub(crate) const NT_PRFPREG: u32 = 2;
+pub(crate) const NT_PRPSINFO: u32 = 3;
+pub(crate) const NT_AUXV: u32 = 6;
+pub(crate) const NT_SIGINFO: u32 = 0x53494749;
+pub(crate) const NT_FILE: u32 = 0x46494c45;
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// The offsets of fields in the 64-bit `struct elf_prstatus` from <linux/elfcore.h>.
+pub(crate) const PRSTATUS_CURSIG_OFFSET: usize = 12;
+pub(crate) const PRSTATUS_PID_OFFSET: usize = 32;
+pub(crate) const PRSTATUS_REGS_OFFSET: usize = 112;
+/// The offset of `pr_pid` in the 64-bit `struct elf_prpsinfo` from <linux/elfcore.h>.
+pub(crate) const PRPSINFO_PID_OFFSET: usize = 24;
+/// The offset of `si_addr` in a 64-bit `siginfo_t`.
+pub(crate) const SIGINFO_ADDR_OFFSET: usize = 16;
+
+/// The number of registers in the x86-64 `struct user_regs_struct` from <sys/user.h>.
+pub(crate) const AMD64_USER_REGS_COUNT: usize = 27;
+/// The number of registers in the ARM64 `struct user_pt_regs` from <asm/ptrace.h>.
+pub(crate) const ARM64_USER_REGS_COUNT: usize = 34;
+
+/// The name Breakpad gives to the vDSO, which isn't backed by a file.
+const VDSO_NAME: &str = "linux-gate.so";
+
+/// The header at the start of a 64-bit ELF file (`Elf64_Ehdr`).
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub(crate) struct Elf64Header {
+    pub e_ident: [u8; 16],
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u64,
+    pub e_phoff: u64,
+    pub e_shoff: u64,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+/// A 64-bit ELF program header (`Elf64_Phdr`).
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub(crate) struct Elf64ProgramHeader {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+/// A 64-bit ELF section header (`Elf64_Shdr`).
+///
+/// Cores don't have sections, this is only used for `PN_XNUM`.
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub(crate) struct Elf64SectionHeader {
+    pub sh_name: u32,
+    pub sh_type: u32,
+    pub sh_flags: u64,
+    pub sh_addr: u64,
+    pub sh_offset: u64,
+    pub sh_size: u64,
+    pub sh_link: u32,
+    pub sh_info: u32,
+    pub sh_addralign: u64,
+    pub sh_entsize: u64,
+}
+
+/// The header of an ELF note (`Elf64_Nhdr`).
+#[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
+pub(crate) struct ElfNoteHeader {
+    pub n_namesz: u32,
+    pub n_descsz: u32,
+    pub n_type: u32,
+}
+
+/// A single note from a `PT_NOTE` segment.
+struct ElfNote<'a> {
+    n_type: u32,
+    name: &'a [u8],
+    desc: &'a [u8],
+}
+
+/// Parse the notes in `bytes`, stopping at the first malformed one.
+fn read_notes(bytes: &[u8], align: usize, endian: scroll::Endian) -> Vec<ElfNote<'_>> {
+    let align_up = |offset: usize| (offset + align - 1) & !(align - 1);
+    let mut notes = vec![];
+    let mut offset = 0;
+    while let Ok(header) = bytes.gread_with::<ElfNoteHeader>(&mut offset, endian) {
+        let name_end = offset.checked_add(header.n_namesz as usize);
+        let name = match name_end.and_then(|end| bytes.get(offset..end)) {
+            Some(name) => name,
+            None => break,
+        };
+        offset = align_up(offset + name.len());
+        let desc_end = offset.checked_add(header.n_descsz as usize);
+        let desc = match desc_end.and_then(|end| bytes.get(offset..end)) {
+            Some(desc) => desc,
+            None => break,
+        };
+        offset = align_up(offset + desc.len());
+        // The name includes its nul terminator.
+        let name = name.split(|&b| b == 0).next().unwrap_or_default();
+        notes.push(ElfNote {
+            n_type: header.n_type,
+            name,
+            desc,
+        });
+    }
+    notes
+}
+
+/// The information from the notes of an ELF core.
+#[derive(Default)]
+struct CoreNotes {
+    threads: Vec<ElfCoreThread>,
+    mapped_files: Vec<ElfCoreMappedFile>,
+    auxv: Vec<MinidumpLinuxAuxvEntry>,
+    signal_info: Option<ElfCoreSignalInfo>,
+    process_id: Option<u32>,
+}
+
+impl CoreNotes {
+    fn read_note(&mut self, note: &ElfNote, cpu: Cpu, endian: scroll::Endian) -> Result<(), Error> {
+        let desc = note.desc;
+        match note.n_type {
+            NT_PRSTATUS => {
+                let signal: i16 = desc
+                    .pread_with(PRSTATUS_CURSIG_OFFSET, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let thread_id: u32 = desc
+                    .pread_with(PRSTATUS_PID_OFFSET, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let regs = desc
+                    .get(PRSTATUS_REGS_OFFSET..)
+                    .ok_or(Error::StreamReadFailure)?;
+                let raw = match cpu {
+                    Cpu::X86_64 => MinidumpRawContext::Amd64(read_amd64_regs(regs, endian)?),
+                    _ => MinidumpRawContext::Arm64(read_arm64_regs(regs, endian)?),
+                };
+                self.threads.push(ElfCoreThread {
+                    thread_id,
+                    signal: signal as u32,
+                    context: MinidumpContext::from_raw(raw),
+                });
+            }
+            NT_PRFPREG => {
+                // The floating point registers follow the general purpose
+                // registers of the same thread.
+                if let Some(thread) = self.threads.last_mut() {
+                    match &mut thread.context.raw {
+                        MinidumpRawContext::Amd64(ctx) => read_amd64_fpregs(ctx, desc, endian)?,
+                        MinidumpRawContext::Arm64(ctx) => read_arm64_fpregs(ctx, desc, endian)?,
+                        _ => {}
+                    }
+                }
+            }
+            NT_PRPSINFO => {
+                let pid: u32 = desc
+                    .pread_with(PRPSINFO_PID_OFFSET, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                self.process_id = Some(pid);
+            }
+            NT_AUXV => {
+                let mut offset = 0;
+                while offset + 16 <= desc.len() {
+                    let raw_type: u64 = desc.gread_with(&mut offset, endian).unwrap_or_default();
+                    let value: u64 = desc.gread_with(&mut offset, endian).unwrap_or_default();
+                    if raw_type == md::AuxvType::AT_NULL as u64 {
+                        break;
+                    }
+                    self.auxv.push(MinidumpLinuxAuxvEntry { raw_type, value });
+                }
+            }
+            NT_SIGINFO => {
+                let signal: u32 = desc
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let code: u32 = desc
+                    .pread_with(8, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                // Only signals raised by a faulting instruction have an address,
+                // the union holds other things for the rest.
+                let address = if is_fault_signal(signal) {
+                    desc.pread_with(SIGINFO_ADDR_OFFSET, endian).ok()
+                } else {
+                    None
+                };
+                self.signal_info = Some(ElfCoreSignalInfo {
+                    signal,
+                    code,
+                    address,
+                });
+            }
+            NT_FILE => {
+                let mut offset = 0;
+                let count: u64 = desc
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let page_size: u64 = desc
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                // Every entry needs at least 24 bytes, don't trust the count beyond that.
+                if count > (desc.len() / 24) as u64 {
+                    return Err(Error::StreamReadFailure);
+                }
+                let mut ranges = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let start: u64 = desc
+                        .gread_with(&mut offset, endian)
+                        .or(Err(Error::StreamReadFailure))?;
+                    let end: u64 = desc
+                        .gread_with(&mut offset, endian)
+                        .or(Err(Error::StreamReadFailure))?;
+                    let page_offset: u64 = desc
+                        .gread_with(&mut offset, endian)
+                        .or(Err(Error::StreamReadFailure))?;
+                    ranges.push((start, end, page_offset.saturating_mul(page_size)));
+                }
+                let names = desc[offset..].split(|&b| b == 0);
+                for ((start, end, file_offset), name) in ranges.into_iter().zip(names) {
+                    if end <= start {
+                        continue;
+                    }
+                    self.mapped_files.push(ElfCoreMappedFile {
+                        start,
+                        end,
+                        file_offset,
+                        name: String::from_utf8_lossy(name).into_owned(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A thread of the crashed process, from an `NT_PRSTATUS` note.
+#[derive(Debug, Clone)]
+pub struct ElfCoreThread {
+    /// The id of the thread (its `pid` on Linux).
+    pub thread_id: u32,
+    /// The signal that was pending for the thread (`pr_cursig`), if any.
+    pub signal: u32,
+    /// The registers of the thread, and its floating point registers if the
+    /// core included an `NT_PRFPREG` note for it.
+    pub context: MinidumpContext,
+}
+
+/// A range of the crashed process's memory, from a `PT_LOAD` program header.
+///
+/// The core may only contain a prefix of the range (or nothing at all, for things
+/// like read-only file mappings the kernel didn't think were worth dumping).
+#[derive(Debug, Clone)]
+pub struct ElfCoreSegment {
+    /// The address of the range in the process.
+    pub base_address: u64,
+    /// The size of the range in the process.
+    pub size: u64,
+    /// How many bytes from the start of the range are included in the core.
+    pub file_size: u64,
+    /// The permissions of the range, a combination of [`PF_R`], [`PF_W`] and [`PF_X`].
+    pub flags: u32,
+    /// Where the contents of the range start in the core.
+    file_offset: u64,
+}
+
+/// A file mapped into the crashed process, from the `NT_FILE` note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfCoreMappedFile {
+    /// The address at which the mapping starts.
+    pub start: u64,
+    /// The address at which the mapping ends (exclusive).
+    pub end: u64,
+    /// The offset into the file of the start of the mapping, in bytes.
+    pub file_offset: u64,
+    /// The path of the file.
+    pub name: String,
+}
+
+/// The signal which caused the core to be written, from the `NT_SIGINFO` note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfCoreSignalInfo {
+    /// The signal number (`si_signo`).
+    pub signal: u32,
+    /// The signal code (`si_code`).
+    pub code: u32,
+    /// The faulting address (`si_addr`), for signals which have one.
+    pub address: Option<u64>,
+}
+
+/// An executable or shared library loaded in the crashed process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfCoreModule {
+    /// The address at which the module was loaded.
+    pub base_address: u64,
+    /// The size of the module's mappings.
+    pub size: u64,
+    /// The path of the module, or `linux-gate.so` for the vDSO.
+    pub name: String,
+    /// The GNU build id of the module, if its headers were included in the core.
+    pub build_id: Option<Vec<u8>>,
+}
+
+/// An ELF core file.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct ElfCore<'a, T>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    data: T,
+    /// The endianness of the core file and of the process.
+    pub endian: scroll::Endian,
+    cpu: Cpu,
+    threads: Vec<ElfCoreThread>,
+    segments: Vec<ElfCoreSegment>,
+    mapped_files: Vec<ElfCoreMappedFile>,
+    auxv: Vec<MinidumpLinuxAuxvEntry>,
+    signal_info: Option<ElfCoreSignalInfo>,
+    process_id: Option<u32>,
+    _phantom: PhantomData<&'a [u8]>,
+}
+
+impl<'a> ElfCore<'a, Mmap> {
+    /// Read an `ElfCore` from a `Path` to a file on disk.
+    pub fn read_path<P>(path: P) -> Result<ElfCore<'a, Mmap>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let f = File::open(path).or(Err(Error::FileNotFound))?;
+        let mmap = unsafe { Mmap::map(&f).or(Err(Error::IoError))? };
+        ElfCore::read(mmap)
+    }
+}
+
+impl<'a> ElfCore<'a, Vec<u8>> {
+    /// Convert `dump` into an `ElfCore`, see [`minidump_to_elf_core`].
+    pub fn from_minidump<'b, T>(dump: &'b Minidump<'b, T>) -> Result<ElfCore<'a, Vec<u8>>, Error>
+    where
+        T: Deref<Target = [u8]> + 'b,
+    {
+        ElfCore::read(minidump_to_elf_core(dump)?)
+    }
+}
+
+impl<'a, T> ElfCore<'a, T>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    /// Read an `ElfCore` from the provided `data`.
+    ///
+    /// Fails with [`Error::HeaderMismatch`] if `data` isn't an ELF core file, and
+    /// [`Error::UnsupportedElfCore`] if it's a core for an unsupported architecture.
+    pub fn read(data: T) -> Result<ElfCore<'a, T>, Error> {
+        let ident = data.get(..EI_NIDENT).ok_or(Error::MissingHeader)?;
+        if &ident[..ELF_MAGIC.len()] != ELF_MAGIC {
+            return Err(Error::HeaderMismatch);
+        }
+        let endian = match ident[EI_DATA] {
+            ELFDATA2LSB => LE,
+            ELFDATA2MSB => BE,
+            _ => return Err(Error::HeaderMismatch),
+        };
+        if ident[EI_CLASS] != ELFCLASS64 {
+            return Err(Error::UnsupportedElfCore);
+        }
+        let header: Elf64Header = data.pread_with(0, endian).or(Err(Error::MissingHeader))?;
+        if header.e_type != ET_CORE {
+            return Err(Error::HeaderMismatch);
+        }
+        let cpu = match header.e_machine {
+            EM_X86_64 => Cpu::X86_64,
+            EM_AARCH64 => Cpu::Arm64,
+            _ => return Err(Error::UnsupportedElfCore),
+        };
+
+        let phdr_count = if header.e_phnum == PN_XNUM {
+            let section_header: Elf64SectionHeader = header
+                .e_shoff
+                .try_into()
+                .ok()
+                .and_then(|offset| data.pread_with(offset, endian).ok())
+                .ok_or(Error::MissingDirectory)?;
+            section_header.sh_info as u64
+        } else {
+            header.e_phnum as u64
+        };
+
+        let mut segments = vec![];
+        let mut notes = vec![];
+        for i in 0..phdr_count {
+            let offset = i
+                .checked_mul(header.e_phentsize as u64)
+                .and_then(|offset| offset.checked_add(header.e_phoff))
+                .and_then(|offset| offset.try_into().ok())
+                .ok_or(Error::MissingDirectory)?;
+            let phdr: Elf64ProgramHeader = data
+                .pread_with(offset, endian)
+                .or(Err(Error::MissingDirectory))?;
+            match phdr.p_type {
+                PT_LOAD => segments.push(ElfCoreSegment {
+                    base_address: phdr.p_vaddr,
+                    size: phdr.p_memsz,
+                    file_size: phdr.p_filesz,
+                    flags: phdr.p_flags,
+                    file_offset: phdr.p_offset,
+                }),
+                PT_NOTE => notes.push(phdr),
+                _ => {}
+            }
+        }
+
+        let mut contents = CoreNotes::default();
+        for phdr in notes {
+            let bytes = phdr
+                .p_offset
+                .try_into()
+                .ok()
+                .zip(phdr.p_filesz.try_into().ok())
+                .and_then(|(start, size): (usize, usize)| data.get(start..start.checked_add(size)?))
+                .ok_or(Error::StreamReadFailure)?;
+            let notes = read_notes(bytes, 4, endian);
+            for note in notes.iter().filter(|note| note.name == b"CORE") {
+                contents.read_note(note, cpu, endian)?;
+            }
+        }
+
+        Ok(ElfCore {
+            data,
+            endian,
+            cpu,
+            threads: contents.threads,
+            segments,
+            mapped_files: contents.mapped_files,
+            auxv: contents.auxv,
+            signal_info: contents.signal_info,
+            process_id: contents.process_id,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The CPU of the crashed process.
+    pub fn cpu(&self) -> Cpu {
+        self.cpu
+    }
+
+    /// The threads of the crashed process.
+    ///
+    /// The first thread is the one which received the signal that caused the core
+    /// to be written.
+    pub fn threads(&self) -> &[ElfCoreThread] {
+        &self.threads
+    }
+
+    /// The memory ranges of the crashed process.
+    pub fn segments(&self) -> &[ElfCoreSegment] {
+        &self.segments
+    }
+
+    /// The files mapped into the crashed process.
+    pub fn mapped_files(&self) -> &[ElfCoreMappedFile] {
+        &self.mapped_files
+    }
+
+    /// The auxiliary vector of the crashed process (not including the terminating `AT_NULL`).
+    pub fn auxv(&self) -> &[MinidumpLinuxAuxvEntry] {
+        &self.auxv
+    }
+
+    /// The signal which caused the core to be written, if the core says.
+    pub fn signal_info(&self) -> Option<&ElfCoreSignalInfo> {
+        self.signal_info.as_ref()
+    }
+
+    /// The id of the crashed process, if the core says.
+    pub fn process_id(&self) -> Option<u32> {
+        self.process_id
+    }
+
+    /// The contents of the segment, as far as they are included in the core.
+    pub fn segment_bytes(&self, segment: &ElfCoreSegment) -> &[u8] {
+        let start = segment.file_offset.try_into().unwrap_or(usize::MAX);
+        let size = segment.file_size.try_into().unwrap_or(usize::MAX);
+        start
+            .checked_add(size)
+            .and_then(|end| self.data.get(start..end))
+            .unwrap_or_default()
+    }
+
+    /// Get `size` bytes of the crashed process's memory at `address`, if they are
+    /// included in the core.
+    pub fn memory_at_address(&self, address: u64, size: usize) -> Option<&[u8]> {
+        let segment = self.segment_at_address(address)?;
+        let offset = (address - segment.base_address).try_into().ok()?;
+        self.segment_bytes(segment)
+            .get(offset..)
+            .and_then(|bytes| bytes.get(..size))
+    }
+
+    fn segment_at_address(&self, address: u64) -> Option<&ElfCoreSegment> {
+        self.segments
+            .iter()
+            .find(|seg| seg.base_address <= address && address - seg.base_address < seg.size)
+    }
+
+    /// The modules loaded in the crashed process.
+    ///
+    /// These are derived from the files in the `NT_FILE` note which look like ELF
+    /// objects (either because their headers are in the core, or because they are
+    /// mapped executable), plus the vDSO found through the auxiliary vector. Build ids
+    /// are read from the headers of the modules, if the core includes them (which
+    /// Linux does by default, see `coredump_filter` in `man 5 core`).
+    pub fn modules(&self) -> Vec<ElfCoreModule> {
+        let mut modules = vec![];
+        let mut seen = HashMap::new();
+        for (i, file) in self.mapped_files.iter().enumerate() {
+            if seen.contains_key(file.name.as_str()) || file.file_offset != 0 {
+                continue;
+            }
+            // A module is mapped in several pieces, with its headers at the start.
+            let mappings = self.mapped_files[i..]
+                .iter()
+                .filter(|other| other.name == file.name);
+            let mut end = file.end;
+            let mut executable = false;
+            for mapping in mappings {
+                end = end.max(mapping.end);
+                executable |= self
+                    .segment_at_address(mapping.start)
+                    .map(|seg| seg.flags & PF_X != 0)
+                    .unwrap_or(false);
+            }
+            let has_elf_header =
+                self.memory_at_address(file.start, ELF_MAGIC.len()) == Some(&ELF_MAGIC[..]);
+            if !has_elf_header && !executable {
+                continue;
+            }
+            let size = match end.checked_sub(file.start) {
+                Some(size) => size,
+                None => continue,
+            };
+            seen.insert(file.name.as_str(), ());
+            modules.push(ElfCoreModule {
+                base_address: file.start,
+                size,
+                name: file.name.clone(),
+                build_id: self.read_build_id(file.start),
+            });
+        }
+
+        let vdso = self
+            .auxv
+            .iter()
+            .find(|entry| entry.raw_type == md::AuxvType::AT_SYSINFO_EHDR as u64)
+            .and_then(|entry| self.segment_at_address(entry.value));
+        if let Some(segment) = vdso {
+            modules.push(ElfCoreModule {
+                base_address: segment.base_address,
+                size: segment.size,
+                name: VDSO_NAME.to_owned(),
+                build_id: self.read_build_id(segment.base_address),
+            });
+        }
+        modules
+    }
+
+    /// Find the GNU build id of the ELF object loaded at `base`.
+    fn read_build_id(&self, base: u64) -> Option<Vec<u8>> {
+        let endian = self.endian;
+        let header_size = Elf64Header::size_with(&endian);
+        let header: Elf64Header = self
+            .memory_at_address(base, header_size)?
+            .pread_with(0, endian)
+            .ok()?;
+        if &header.e_ident[..ELF_MAGIC.len()] != ELF_MAGIC || header.e_ident[EI_CLASS] != ELFCLASS64
+        {
+            return None;
+        }
+        let phdrs_size = header.e_phnum as usize * header.e_phentsize as usize;
+        let phdrs_bytes = self.memory_at_address(base.checked_add(header.e_phoff)?, phdrs_size)?;
+        let phdrs = (0..header.e_phnum as usize)
+            .filter_map(|i| {
+                phdrs_bytes
+                    .pread_with::<Elf64ProgramHeader>(i * header.e_phentsize as usize, endian)
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        // The start of the file is mapped at `base`, which tells us how far the
+        // object was relocated from the addresses in its program headers.
+        let first_load = phdrs.iter().find(|phdr| phdr.p_type == PT_LOAD)?;
+        let bias = base.wrapping_sub(first_load.p_vaddr.wrapping_sub(first_load.p_offset));
+
+        phdrs
+            .iter()
+            .filter(|phdr| phdr.p_type == PT_NOTE)
+            .find_map(|phdr| {
+                let address = bias.wrapping_add(phdr.p_vaddr);
+                let bytes = self.memory_at_address(address, phdr.p_filesz.try_into().ok()?)?;
+                let align = if phdr.p_align == 8 { 8 } else { 4 };
+                read_notes(bytes, align, endian)
+                    .into_iter()
+                    .find(|note| note.n_type == NT_GNU_BUILD_ID && note.name == b"GNU")
+                    .map(|note| note.desc.to_vec())
+            })
+    }
+
+    /// Convert this core into an equivalent [`Minidump`].
+    ///
+    /// The minidump gets the following streams:
+    ///
+    /// * `SystemInfoStream`, for a Linux system with the core's CPU
+    /// * `ThreadListStream`, from the `NT_PRSTATUS` and `NT_PRFPREG` notes
+    /// * `ExceptionStream`, from the `NT_SIGINFO` note and the first thread
+    /// * `ModuleListStream`, from [`ElfCore::modules`]
+    /// * `MiscInfoStream`, with the process id from the `NT_PRPSINFO` note
+    /// * `LinuxMaps`, from the `PT_LOAD` segments and the `NT_FILE` note
+    /// * `LinuxAuxv`, from the `NT_AUXV` note
+    /// * `Memory64ListStream`, with the contents of the `PT_LOAD` segments
+    ///
+    /// Note that this copies all of the memory in the core.
+    pub fn to_minidump<'b>(&self) -> Result<Minidump<'b, Vec<u8>>, Error> {
+        Minidump::read(self.to_minidump_bytes()?)
+    }
+
+    /// Like [`ElfCore::to_minidump`], but returns the contents of the minidump file.
+    pub fn to_minidump_bytes(&self) -> Result<Vec<u8>, Error> {
+        let endian = self.endian;
+        let mut builder = MinidumpBuilder::new(endian);
+
+        // System info
+        let arch = match self.cpu {
+            Cpu::X86_64 => md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64,
+            _ => md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64,
+        };
+        let mut cpu = md::CPU_INFORMATION { data: [0; 24] };
+        if self.cpu == Cpu::Arm64 {
+            let hwcaps = self
+                .auxv
+                .iter()
+                .find(|entry| entry.raw_type == md::AuxvType::AT_HWCAP as u64)
+                .map(|entry| entry.value as u32)
+                .unwrap_or_default();
+            let info = md::ARMCpuInfo {
+                cpuid: 0,
+                elf_hwcaps: hwcaps,
+            };
+            cpu.data
+                .pwrite_with(info, 0, endian)
+                .or(Err(Error::DataError))?;
+        }
+        let system_info = StreamData::from_value(
+            md::MINIDUMP_SYSTEM_INFO {
+                processor_architecture: arch as u16,
+                processor_level: 0,
+                processor_revision: 0,
+                number_of_processors: 0,
+                product_type: 0,
+                major_version: 0,
+                minor_version: 0,
+                build_number: 0,
+                platform_id: md::PlatformId::Linux as u32,
+                csd_version_rva: 0,
+                suite_mask: 0,
+                reserved2: 0,
+                cpu,
+            },
+            endian,
+        )?
+        .with_reference(
+            SYSTEM_INFO_CSD_VERSION_OFFSET,
+            ReferenceKind::Rva,
+            ReferenceTarget::Data(StreamData::string("", endian)),
+        );
+        builder.add_stream(MINIDUMP_STREAM_TYPE::SystemInfoStream, system_info);
+
+        // Threads
+        let mut thread_list = Vec::with_capacity(self.threads.len());
+        let mut contexts = Vec::with_capacity(self.threads.len());
+        for thread in &self.threads {
+            let context = match &thread.context.raw {
+                MinidumpRawContext::Amd64(ctx) => StreamData::from_value(ctx.clone(), endian)?,
+                MinidumpRawContext::Arm64(ctx) => StreamData::from_value(ctx.clone(), endian)?,
+                _ => return Err(Error::UnsupportedElfCore),
+            };
+            contexts.push(context);
+            // Point the stack at the segment containing the stack pointer. Its
+            // contents are in the memory list, which the stack is looked up in
+            // when it has no location of its own.
+            let sp = thread.context.get_stack_pointer();
+            let stack_start = self
+                .segment_at_address(sp)
+                .map(|seg| seg.base_address)
+                .unwrap_or(sp);
+            thread_list.push(md::MINIDUMP_THREAD {
+                thread_id: thread.thread_id,
+                suspend_count: 0,
+                priority_class: 0,
+                priority: 0,
+                teb: 0,
+                stack: md::MINIDUMP_MEMORY_DESCRIPTOR {
+                    start_of_memory_range: stack_start,
+                    memory: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+                },
+                thread_context: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+            });
+        }
+        let mut threads = StreamData::from_list(thread_list, endian)?;
+        for (i, context) in contexts.iter().enumerate() {
+            threads.add_reference(
+                4 + i * THREAD_SIZE + THREAD_CONTEXT_OFFSET,
+                ReferenceKind::Location,
+                ReferenceTarget::Data(context.clone()),
+            );
+        }
+        builder.add_stream(MINIDUMP_STREAM_TYPE::ThreadListStream, threads);
+
+        // Exception
+        if let (Some(thread), Some(context)) = (self.threads.first(), contexts.first()) {
+            let (signal, code, address) = match &self.signal_info {
+                Some(info) => (info.signal, info.code, info.address.unwrap_or_default()),
+                None => (thread.signal, 0, 0),
+            };
+            if signal != 0 {
+                let exception = StreamData::from_value(
+                    md::MINIDUMP_EXCEPTION_STREAM {
+                        thread_id: thread.thread_id,
+                        __align: 0,
+                        exception_record: md::MINIDUMP_EXCEPTION {
+                            exception_code: signal,
+                            exception_flags: code,
+                            exception_address: address,
+                            ..Default::default()
+                        },
+                        thread_context: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+                    },
+                    endian,
+                )?
+                .with_reference(
+                    EXCEPTION_CONTEXT_OFFSET,
+                    ReferenceKind::Location,
+                    ReferenceTarget::Data(context.clone()),
+                );
+                builder.add_stream(MINIDUMP_STREAM_TYPE::ExceptionStream, exception);
+            }
+        }
+
+        // Modules
+        let modules = self.modules();
+        let module_list = modules
+            .iter()
+            .map(|module| md::MINIDUMP_MODULE {
+                base_of_image: module.base_address,
+                size_of_image: module.size.try_into().unwrap_or(u32::MAX),
+                ..Default::default()
+            })
+            .collect();
+        let mut module_list = StreamData::from_list(module_list, endian)?;
+        for (i, module) in modules.iter().enumerate() {
+            let entry = 4 + i * MODULE_SIZE;
+            module_list.add_reference(
+                entry + MODULE_NAME_OFFSET,
+                ReferenceKind::Rva,
+                ReferenceTarget::Data(StreamData::string(&module.name, endian)),
+            );
+            if let Some(build_id) = &module.build_id {
+                let mut cv = vec![0; 4];
+                cv.pwrite_with(md::CvSignature::Elf as u32, 0, endian)
+                    .or(Err(Error::DataError))?;
+                cv.extend_from_slice(build_id);
+                module_list.add_reference(
+                    entry + MODULE_CV_RECORD_OFFSET,
+                    ReferenceKind::Location,
+                    ReferenceTarget::Data(StreamData::new(cv)),
+                );
+            }
+        }
+        builder.add_stream(MINIDUMP_STREAM_TYPE::ModuleListStream, module_list);
+
+        // Misc info
+        if let Some(process_id) = self.process_id {
+            let misc_info = StreamData::from_value(
+                md::MINIDUMP_MISC_INFO {
+                    size_of_info: md::MINIDUMP_MISC_INFO::size_with(&endian) as u32,
+                    flags1: md::MiscInfoFlags::MINIDUMP_MISC1_PROCESS_ID.bits(),
+                    process_id,
+                    process_create_time: 0,
+                    process_user_time: 0,
+                    process_kernel_time: 0,
+                },
+                endian,
+            )?;
+            builder.add_stream(MINIDUMP_STREAM_TYPE::MiscInfoStream, misc_info);
+        }
+
+        // Linux streams
+        let maps = StreamData::new(self.linux_maps().into_bytes());
+        builder.add_stream(MINIDUMP_STREAM_TYPE::LinuxMaps, maps);
+        if !self.auxv.is_empty() {
+            let mut auxv = vec![0; (self.auxv.len() + 1) * 16];
+            for (i, entry) in self.auxv.iter().enumerate() {
+                auxv.pwrite_with(entry.raw_type, i * 16, endian)
+                    .or(Err(Error::DataError))?;
+                auxv.pwrite_with(entry.value, i * 16 + 8, endian)
+                    .or(Err(Error::DataError))?;
+            }
+            builder.add_stream(MINIDUMP_STREAM_TYPE::LinuxAuxv, StreamData::new(auxv));
+        }
+
+        // Memory
+        for seg in &self.segments {
+            let bytes = self.segment_bytes(seg);
+            if !bytes.is_empty() {
+                builder.add_memory(seg.base_address, bytes);
+            }
+        }
+        builder.set_memory64(true);
+        builder.build()
+    }
+
+    /// Describe the memory of the process in the format of `/proc/self/maps`.
+    fn linux_maps(&self) -> String {
+        let vdso = self
+            .auxv
+            .iter()
+            .find(|entry| entry.raw_type == md::AuxvType::AT_SYSINFO_EHDR as u64)
+            .map(|entry| entry.value);
+        let mut maps = String::new();
+        for seg in &self.segments {
+            let file = self
+                .mapped_files
+                .iter()
+                .find(|file| file.start == seg.base_address);
+            let name = match file {
+                Some(file) => file.name.as_str(),
+                None if Some(seg.base_address) == vdso => "[vdso]",
+                None => "",
+            };
+            let perm = |flag, c| if seg.flags & flag != 0 { c } else { '-' };
+            maps.push_str(&format!(
+                "{:x}-{:x} {}{}{}p {:08x} 00:00 0 {}\n",
+                seg.base_address,
+                seg.base_address.saturating_add(seg.size),
+                perm(PF_R, 'r'),
+                perm(PF_W, 'w'),
+                perm(PF_X, 'x'),
+                file.map(|file| file.file_offset).unwrap_or_default(),
+                name,
+            ));
+        }
+        maps
+    }
+}
+
+/// Convert a minidump of a 64-bit x86-64 or ARM64 process into an equivalent ELF core
+/// file, which can be loaded into gdb or lldb.
+///
+/// This is the inverse of [`ElfCore::to_minidump`], in the spirit of Breakpad's
+/// `minidump-2-core`:
+///
+/// * the contexts of the `ThreadListStream` become `NT_PRSTATUS` notes (and
+///   `NT_PRFPREG` notes if they have floating point registers), with the crashing
+///   thread first and using the context of the `ExceptionStream`
+/// * the `ExceptionStream` of a Linux minidump becomes an `NT_SIGINFO` note
+/// * the process id, the name of the main module and the `LinuxCmdLine` stream
+///   become an `NT_PRPSINFO` note
+/// * the `LinuxAuxv` stream becomes an `NT_AUXV` note
+/// * the `ModuleListStream` becomes an `NT_FILE` note
+/// * the memory of the minidump, including the thread stacks, becomes `PT_LOAD`
+///   segments, with permissions from the `LinuxMaps` or `MemoryInfoListStream`
+///   stream if the minidump has one
+///
+/// Fails with [`Error::UnsupportedElfCore`] for minidumps of other CPUs.
+pub fn minidump_to_elf_core<'a, T>(dump: &'a Minidump<'a, T>) -> Result<Vec<u8>, Error>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let endian = dump.endian;
+    let system_info = dump.get_stream::<MinidumpSystemInfo>()?;
+    let machine = match system_info.cpu {
+        Cpu::X86_64 => EM_X86_64,
+        Cpu::Arm64 => EM_AARCH64,
+        _ => return Err(Error::UnsupportedElfCore),
+    };
+    let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+    let thread_list = dump.get_stream::<MinidumpThreadList>()?;
+    let exception = dump.get_stream::<MinidumpException>().ok();
+    let module_list = dump.get_stream::<MinidumpModuleList>().unwrap_or_default();
+    let memory_list = dump.get_memory().unwrap_or_default();
+    let maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
+    let memory_info = match maps {
+        Some(maps) => UnifiedMemoryInfoList::new(None, Some(maps)),
+        None => UnifiedMemoryInfoList::new(dump.get_stream::<MinidumpMemoryInfoList>().ok(), None),
+    };
+    // Exception codes are only signals on Linux.
+    let is_linux = matches!(system_info.os, Os::Linux | Os::Android);
+
+    let mut notes = vec![];
+
+    // Threads, starting with the crashing one.
+    let crashing_thread_id = exception.as_ref().map(|e| e.get_crashing_thread_id());
+    let signal = match &exception {
+        Some(exception) if is_linux => exception.raw.exception_record.exception_code,
+        _ => 0,
+    };
+    let mut threads = thread_list.threads.iter().collect::<Vec<_>>();
+    threads.sort_by_key(|thread| Some(thread.raw.thread_id) != crashing_thread_id);
+    for thread in threads {
+        let is_crashing = Some(thread.raw.thread_id) == crashing_thread_id;
+        let exception_context = exception
+            .as_ref()
+            .filter(|_| is_crashing)
+            .and_then(|e| e.context(&system_info, misc_info.as_ref()));
+        let context =
+            match exception_context.or_else(|| thread.context(&system_info, misc_info.as_ref())) {
+                Some(context) => context,
+                None => continue,
+            };
+        let (regs, fpregs) = match &context.raw {
+            MinidumpRawContext::Amd64(ctx) => {
+                let flags = md::ContextFlagsAmd64::from_bits_truncate(ctx.context_flags);
+                let fpregs = flags
+                    .contains(md::ContextFlagsAmd64::CONTEXT_AMD64_FLOATING_POINT)
+                    .then(|| ctx.float_save.to_vec());
+                (write_amd64_regs(ctx), fpregs)
+            }
+            MinidumpRawContext::Arm64(ctx) => {
+                let flags = md::ContextFlagsArm64::from_bits_truncate(ctx.context_flags);
+                let fpregs =
+                    match flags.contains(md::ContextFlagsArm64::CONTEXT_ARM64_FLOATING_POINT) {
+                        true => Some(write_arm64_fpregs(
+                            &ctx.float_regs,
+                            ctx.fpsr,
+                            ctx.fpcr,
+                            endian,
+                        )?),
+                        false => None,
+                    };
+                (
+                    write_arm64_regs(&ctx.iregs, ctx.sp, ctx.pc, ctx.cpsr),
+                    fpregs,
+                )
+            }
+            MinidumpRawContext::OldArm64(ctx) => {
+                let flags = md::ContextFlagsArm64Old::from_bits_truncate(ctx.context_flags as u32);
+                let fpregs = match flags
+                    .contains(md::ContextFlagsArm64Old::CONTEXT_ARM64_OLD_FLOATING_POINT)
+                {
+                    true => Some(write_arm64_fpregs(
+                        &ctx.float_regs,
+                        ctx.fpsr,
+                        ctx.fpcr,
+                        endian,
+                    )?),
+                    false => None,
+                };
+                (
+                    write_arm64_regs(&ctx.iregs, ctx.sp, ctx.pc, ctx.cpsr),
+                    fpregs,
+                )
+            }
+            _ => continue,
+        };
+        let thread_signal = if is_crashing { signal } else { 0 };
+        let mut prstatus = vec![0; PRSTATUS_REGS_OFFSET + regs.len() * 8 + 8];
+        prstatus
+            .pwrite_with(thread_signal, 0, endian)
+            .or(Err(Error::DataError))?;
+        prstatus
+            .pwrite_with(thread_signal as u16, PRSTATUS_CURSIG_OFFSET, endian)
+            .or(Err(Error::DataError))?;
+        prstatus
+            .pwrite_with(thread.raw.thread_id, PRSTATUS_PID_OFFSET, endian)
+            .or(Err(Error::DataError))?;
+        let mut offset = PRSTATUS_REGS_OFFSET;
+        for reg in regs {
+            prstatus
+                .gwrite_with(reg, &mut offset, endian)
+                .or(Err(Error::DataError))?;
+        }
+        // pr_fpvalid
+        prstatus
+            .pwrite_with(fpregs.is_some() as u32, offset, endian)
+            .or(Err(Error::DataError))?;
+        append_note(&mut notes, NT_PRSTATUS, &prstatus, endian)?;
+        if let Some(fpregs) = fpregs {
+            append_note(&mut notes, NT_PRFPREG, &fpregs, endian)?;
+        }
+    }
+
+    // Process
+    const PRPSINFO_SIZE: usize = 136;
+    const PRPSINFO_FNAME_OFFSET: usize = 40;
+    const PRPSINFO_FNAME_SIZE: usize = 16;
+    const PRPSINFO_PSARGS_OFFSET: usize = 56;
+    const PRPSINFO_PSARGS_SIZE: usize = 80;
+    let mut prpsinfo = vec![0; PRPSINFO_SIZE];
+    if let Some(process_id) = misc_info.as_ref().and_then(|info| info.raw.process_id()) {
+        prpsinfo
+            .pwrite_with(*process_id, PRPSINFO_PID_OFFSET, endian)
+            .or(Err(Error::DataError))?;
+    }
+    if let Some(module) = module_list.main_module() {
+        let path = module.code_file();
+        let name = path.rsplit('/').next().unwrap_or_default().as_bytes();
+        // Both strings must be nul-terminated.
+        let name = &name[..name.len().min(PRPSINFO_FNAME_SIZE - 1)];
+        prpsinfo[PRPSINFO_FNAME_OFFSET..][..name.len()].copy_from_slice(name);
+    }
+    if let Ok(cmd_line) = dump.get_stream::<MinidumpLinuxCmdLine>() {
+        let args = cmd_line
+            .iter()
+            .map(|arg| arg.as_bytes())
+            .collect::<Vec<_>>()
+            .join(&b' ');
+        let args = &args[..args.len().min(PRPSINFO_PSARGS_SIZE - 1)];
+        prpsinfo[PRPSINFO_PSARGS_OFFSET..][..args.len()].copy_from_slice(args);
+    }
+    append_note(&mut notes, NT_PRPSINFO, &prpsinfo, endian)?;
+
+    // Signal
+    if let (Some(exception), true) = (&exception, is_linux) {
+        const SIGINFO_SIZE: usize = 128;
+        let record = &exception.raw.exception_record;
+        let mut siginfo = vec![0; SIGINFO_SIZE];
+        siginfo
+            .pwrite_with(record.exception_code, 0, endian)
+            .or(Err(Error::DataError))?;
+        siginfo
+            .pwrite_with(record.exception_flags, 8, endian)
+            .or(Err(Error::DataError))?;
+        siginfo
+            .pwrite_with(record.exception_address, SIGINFO_ADDR_OFFSET, endian)
+            .or(Err(Error::DataError))?;
+        append_note(&mut notes, NT_SIGINFO, &siginfo, endian)?;
+    }
+
+    // Auxiliary vector
+    if let Ok(auxv) = dump.get_stream::<MinidumpLinuxAuxv>() {
+        let entries = auxv
+            .iter()
+            .map(|entry| (entry.raw_type, entry.value))
+            .chain(Some((md::AuxvType::AT_NULL as u64, 0)));
+        let mut desc = vec![];
+        for (raw_type, value) in entries {
+            desc.extend_from_slice(&[0; 16]);
+            let offset = desc.len() - 16;
+            desc.pwrite_with(raw_type, offset, endian)
+                .or(Err(Error::DataError))?;
+            desc.pwrite_with(value, offset + 8, endian)
+                .or(Err(Error::DataError))?;
+        }
+        append_note(&mut notes, NT_AUXV, &desc, endian)?;
+    }
+
+    // Mapped files
+    const PAGE_SIZE: u64 = 4096;
+    let modules = module_list.iter().collect::<Vec<_>>();
+    let mut desc = vec![0; 16 + modules.len() * 24];
+    desc.pwrite_with(modules.len() as u64, 0, endian)
+        .or(Err(Error::DataError))?;
+    desc.pwrite_with(PAGE_SIZE, 8, endian)
+        .or(Err(Error::DataError))?;
+    for (i, module) in modules.iter().enumerate() {
+        let offset = 16 + i * 24;
+        let end = module.base_address().saturating_add(module.size());
+        desc.pwrite_with(module.base_address(), offset, endian)
+            .or(Err(Error::DataError))?;
+        desc.pwrite_with(end, offset + 8, endian)
+            .or(Err(Error::DataError))?;
+    }
+    for module in &modules {
+        desc.extend_from_slice(module.code_file().as_bytes());
+        desc.push(0);
+    }
+    append_note(&mut notes, NT_FILE, &desc, endian)?;
+
+    // Memory, including the stacks that aren't in the memory list.
+    let mut memory = BTreeMap::new();
+    for region in memory_list.by_addr() {
+        memory.insert(region.base_address(), region.bytes());
+    }
+    for thread in &thread_list.threads {
+        if let Some(stack) = thread.stack_memory(&memory_list) {
+            memory.entry(stack.base_address()).or_insert(stack.bytes());
+        }
+    }
+    // Regions can overlap, but segments must not, so only keep the first copy
+    // of each byte.
+    let mut segments = Vec::with_capacity(memory.len());
+    let mut covered_end = 0u64;
+    for (base_address, bytes) in memory {
+        let start = base_address.max(covered_end);
+        let bytes = match usize::try_from(start - base_address)
+            .ok()
+            .and_then(|skip| bytes.get(skip..))
+        {
+            Some(bytes) if start == base_address || !bytes.is_empty() => bytes,
+            _ => continue,
+        };
+        covered_end = start.saturating_add(bytes.len() as u64);
+        segments.push((start, bytes));
+    }
+    let flags = |address| match memory_info
+        .as_ref()
+        .and_then(|info| info.memory_info_at_address(address))
+    {
+        Some(info) => {
+            let flag = |set, flag| if set { flag } else { 0 };
+            flag(info.is_readable(), PF_R)
+                | flag(info.is_writable(), PF_W)
+                | flag(info.is_executable(), PF_X)
+        }
+        // Without any information, assume modules are code and the rest is data.
+        None if module_list.module_at_address(address).is_some() => PF_R | PF_X,
+        None => PF_R | PF_W,
+    };
+
+    // Lay out the file: the header, the program headers, the notes and the memory.
+    let header_size = Elf64Header::size_with(&endian);
+    let phdr_size = Elf64ProgramHeader::size_with(&endian);
+    let phdr_count = segments.len() + 1;
+    // Like Linux, use the first section header to store the number of program
+    // headers if it doesn't fit in `e_phnum`.
+    let extended_numbering = phdr_count >= PN_XNUM as usize;
+    let section_header_size = if extended_numbering {
+        Elf64SectionHeader::size_with(&endian)
+    } else {
+        0
+    };
+    let notes_offset = header_size + phdr_count * phdr_size + section_header_size;
+    let memory_offset = notes_offset + notes.len();
+    let file_size = memory_offset + segments.iter().map(|(_, bytes)| bytes.len()).sum::<usize>();
+
+    let mut data = vec![0; memory_offset];
+    data.reserve(file_size - memory_offset);
+    let mut e_ident = [0; EI_NIDENT];
+    e_ident[..ELF_MAGIC.len()].copy_from_slice(ELF_MAGIC);
+    e_ident[EI_CLASS] = ELFCLASS64;
+    e_ident[EI_DATA] = match endian {
+        scroll::Endian::Little => ELFDATA2LSB,
+        scroll::Endian::Big => ELFDATA2MSB,
+    };
+    e_ident[EI_VERSION] = EV_CURRENT;
+    let header = Elf64Header {
+        e_ident,
+        e_type: ET_CORE,
+        e_machine: machine,
+        e_version: EV_CURRENT as u32,
+        e_phoff: header_size as u64,
+        e_shoff: if extended_numbering {
+            (header_size + phdr_count * phdr_size) as u64
+        } else {
+            0
+        },
+        e_ehsize: header_size as u16,
+        e_phentsize: phdr_size as u16,
+        e_phnum: phdr_count.min(PN_XNUM as usize) as u16,
+        e_shentsize: section_header_size as u16,
+        e_shnum: extended_numbering as u16,
+        ..Default::default()
+    };
+    let mut offset = 0;
+    data.gwrite_with(header, &mut offset, endian)
+        .or(Err(Error::DataError))?;
+    let note_phdr = Elf64ProgramHeader {
+        p_type: PT_NOTE,
+        p_offset: notes_offset as u64,
+        p_filesz: notes.len() as u64,
+        p_align: 4,
+        ..Default::default()
+    };
+    data.gwrite_with(note_phdr, &mut offset, endian)
+        .or(Err(Error::DataError))?;
+    let mut segment_offset = memory_offset as u64;
+    for &(address, bytes) in &segments {
+        let phdr = Elf64ProgramHeader {
+            p_type: PT_LOAD,
+            p_flags: flags(address),
+            p_offset: segment_offset,
+            p_vaddr: address,
+            p_paddr: 0,
+            p_filesz: bytes.len() as u64,
+            p_memsz: bytes.len() as u64,
+            p_align: 1,
+        };
+        data.gwrite_with(phdr, &mut offset, endian)
+            .or(Err(Error::DataError))?;
+        segment_offset += bytes.len() as u64;
+    }
+    if extended_numbering {
+        let section_header = Elf64SectionHeader {
+            sh_info: phdr_count.try_into().or(Err(Error::DataError))?,
+            ..Default::default()
+        };
+        data.gwrite_with(section_header, &mut offset, endian)
+            .or(Err(Error::DataError))?;
+    }
+    data[notes_offset..memory_offset].copy_from_slice(&notes);
+    for (_, bytes) in &segments {
+        data.extend_from_slice(bytes);
+    }
+    Ok(data)
+}
+
+/// Append a note named "CORE" to `notes`.
+fn append_note(
+    notes: &mut Vec<u8>,
+    n_type: u32,
+    desc: &[u8],
+    endian: scroll::Endian,
+) -> Result<(), Error> {
+    const NAME: &[u8] = b"CORE\0";
+    let pad = |notes: &mut Vec<u8>| notes.resize((notes.len() + 3) & !3, 0);
+    let header = ElfNoteHeader {
+        n_namesz: NAME.len() as u32,
+        n_descsz: desc.len().try_into().or(Err(Error::DataError))?,
+        n_type,
+    };
+    let offset = notes.len();
+    notes.resize(offset + ElfNoteHeader::size_with(&endian), 0);
+    notes
+        .pwrite_with(header, offset, endian)
+        .or(Err(Error::DataError))?;
+    notes.extend_from_slice(NAME);
+    pad(notes);
+    notes.extend_from_slice(desc);
+    pad(notes);
+    Ok(())
+}
+
+/// Whether `signal` is one the kernel raises for a faulting instruction, which
+/// comes with the faulting address.
+fn is_fault_signal(signal: u32) -> bool {
+    const SIGILL: u32 = 4;
+    const SIGTRAP: u32 = 5;
+    const SIGBUS: u32 = 7;
+    const SIGFPE: u32 = 8;
+    const SIGSEGV: u32 = 11;
+    matches!(signal, SIGILL | SIGTRAP | SIGBUS | SIGFPE | SIGSEGV)
+}
+
+fn read_regs<const N: usize>(bytes: &[u8], endian: scroll::Endian) -> Result<[u64; N], Error> {
+    let mut regs = [0; N];
+    let mut offset = 0;
+    for reg in regs.iter_mut() {
+        *reg = bytes
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+    }
+    Ok(regs)
+}
+
+/// Convert a `struct user_regs_struct` from an x86-64 `NT_PRSTATUS` note.
+fn read_amd64_regs(bytes: &[u8], endian: scroll::Endian) -> Result<md::CONTEXT_AMD64, Error> {
+    let regs = read_regs::<AMD64_USER_REGS_COUNT>(bytes, endian)?;
+    let flags = md::ContextFlagsAmd64::CONTEXT_AMD64_CONTROL
+        | md::ContextFlagsAmd64::CONTEXT_AMD64_INTEGER
+        | md::ContextFlagsAmd64::CONTEXT_AMD64_SEGMENTS;
+    Ok(md::CONTEXT_AMD64 {
+        context_flags: flags.bits(),
+        r15: regs[0],
+        r14: regs[1],
+        r13: regs[2],
+        r12: regs[3],
+        rbp: regs[4],
+        rbx: regs[5],
+        r11: regs[6],
+        r10: regs[7],
+        r9: regs[8],
+        r8: regs[9],
+        rax: regs[10],
+        rcx: regs[11],
+        rdx: regs[12],
+        rsi: regs[13],
+        rdi: regs[14],
+        // regs[15] is orig_rax
+        rip: regs[16],
+        cs: regs[17] as u16,
+        eflags: regs[18] as u32,
+        rsp: regs[19],
+        ss: regs[20] as u16,
+        // regs[21] and regs[22] are fs_base and gs_base
+        ds: regs[23] as u16,
+        es: regs[24] as u16,
+        fs: regs[25] as u16,
+        gs: regs[26] as u16,
+        ..Default::default()
+    })
+}
+
+/// Add the `struct user_fpregs_struct` (which is in `FXSAVE` format) from an x86-64
+/// `NT_PRFPREG` note to `ctx`.
+fn read_amd64_fpregs(
+    ctx: &mut md::CONTEXT_AMD64,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<(), Error> {
+    const MXCSR_OFFSET: usize = 24;
+    let fxsave = bytes
+        .get(..ctx.float_save.len())
+        .ok_or(Error::StreamReadFailure)?;
+    ctx.float_save.copy_from_slice(fxsave);
+    ctx.mx_csr = fxsave
+        .pread_with(MXCSR_OFFSET, endian)
+        .or(Err(Error::StreamReadFailure))?;
+    ctx.context_flags |= md::ContextFlagsAmd64::CONTEXT_AMD64_FLOATING_POINT.bits();
+    Ok(())
+}
+
+/// Convert a `struct user_pt_regs` from an ARM64 `NT_PRSTATUS` note.
+fn read_arm64_regs(bytes: &[u8], endian: scroll::Endian) -> Result<md::CONTEXT_ARM64, Error> {
+    let regs = read_regs::<ARM64_USER_REGS_COUNT>(bytes, endian)?;
+    let flags =
+        md::ContextFlagsArm64::CONTEXT_ARM64_CONTROL | md::ContextFlagsArm64::CONTEXT_ARM64_INTEGER;
+    let mut ctx = md::CONTEXT_ARM64 {
+        context_flags: flags.bits(),
+        sp: regs[31],
+        pc: regs[32],
+        cpsr: regs[33] as u32,
+        ..Default::default()
+    };
+    ctx.iregs.copy_from_slice(&regs[..31]);
+    Ok(ctx)
+}
+
+/// Add the `struct user_fpsimd_state` from an ARM64 `NT_PRFPREG` note to `ctx`.
+fn read_arm64_fpregs(
+    ctx: &mut md::CONTEXT_ARM64,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<(), Error> {
+    let mut offset = 0;
+    for reg in ctx.float_regs.iter_mut() {
+        *reg = bytes
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+    }
+    ctx.fpsr = bytes
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
+    ctx.fpcr = bytes
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
+    ctx.context_flags |= md::ContextFlagsArm64::CONTEXT_ARM64_FLOATING_POINT.bits();
+    Ok(())
+}
+
+/// Convert `ctx` to a `struct user_regs_struct` for an x86-64 `NT_PRSTATUS` note.
+fn write_amd64_regs(ctx: &md::CONTEXT_AMD64) -> Vec<u64> {
+    vec![
+        ctx.r15,
+        ctx.r14,
+        ctx.r13,
+        ctx.r12,
+        ctx.rbp,
+        ctx.rbx,
+        ctx.r11,
+        ctx.r10,
+        ctx.r9,
+        ctx.r8,
+        ctx.rax,
+        ctx.rcx,
+        ctx.rdx,
+        ctx.rsi,
+        ctx.rdi,
+        // orig_rax, -1 when the thread isn't in a system call
+        u64::MAX,
+        ctx.rip,
+        ctx.cs as u64,
+        ctx.eflags as u64,
+        ctx.rsp,
+        ctx.ss as u64,
+        // fs_base and gs_base aren't in the context
+        0,
+        0,
+        ctx.ds as u64,
+        ctx.es as u64,
+        ctx.fs as u64,
+        ctx.gs as u64,
+    ]
+}
+
+/// Build a `struct user_pt_regs` for an ARM64 `NT_PRSTATUS` note.
+fn write_arm64_regs(iregs: &[u64; 31], sp: u64, pc: u64, cpsr: u32) -> Vec<u64> {
+    let mut regs = iregs.to_vec();
+    regs.extend([sp, pc, cpsr as u64]);
+    regs
+}
+
+/// Build a `struct user_fpsimd_state` for an ARM64 `NT_PRFPREG` note.
+fn write_arm64_fpregs(
+    float_regs: &[u128; 32],
+    fpsr: u32,
+    fpcr: u32,
+    endian: scroll::Endian,
+) -> Result<Vec<u8>, Error> {
+    // The registers, fpsr, fpcr and two reserved words.
+    let mut fpregs = vec![0; float_regs.len() * 16 + 16];
+    let mut offset = 0;
+    for &reg in float_regs {
+        fpregs
+            .gwrite_with(reg, &mut offset, endian)
+            .or(Err(Error::DataError))?;
+    }
+    fpregs
+        .gwrite_with(fpsr, &mut offset, endian)
+        .or(Err(Error::DataError))?;
+    fpregs
+        .gwrite_with(fpcr, &mut offset, endian)
+        .or(Err(Error::DataError))?;
+    Ok(fpregs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        CrashReason, MinidumpException, MinidumpLinuxAuxv, MinidumpLinuxMaps, MinidumpMiscInfo,
+        MinidumpModuleList, MinidumpSystemInfo, MinidumpThreadList, Module,
+    };
+    use minidump_common::errors::ExceptionCodeLinuxSigsegvKind;
+    use minidump_synth::{
+        amd64_context, amd64_user_regs, arm64_user_regs, elf_headers_with_build_id, DumpString,
+        Exception, Memory, Module as SynthModule, SynthElfCore, SynthMinidump,
+        SystemInfo as SynthSystemInfo, Thread, EM_AARCH64, EM_X86_64, PF_R, PF_W, PF_X,
+    };
+    use test_assembler::{Endian as AsmEndian, Section};
+
+    const PID: u32 = 1234;
+    const LIBRARY_BASE: u64 = 0x40000000;
+    const DATA_BASE: u64 = 0x50000000;
+    const STACK_BASE: u64 = 0x7ffe0000;
+    const VDSO_BASE: u64 = 0x7fff0000;
+    const BUILD_ID: &[u8] = &[
+        0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+        0x00, 0xff, 0xee, 0xdd, 0xcc,
+    ];
+    const VDSO_BUILD_ID: &[u8] = &[0x11, 0x22, 0x33, 0x44];
+
+    fn fxsave(mxcsr: u32) -> Section {
+        Section::with_endian(AsmEndian::Little)
+            .append_repeated(0, 24)
+            .D32(mxcsr)
+            .append_repeated(0, 512 - 28)
+    }
+
+    fn amd64_core() -> Vec<u8> {
+        let stack = Section::with_endian(AsmEndian::Little);
+        stack.start().set_const(STACK_BASE);
+        let stack = stack.append_repeated(0xab, 0x100);
+
+        SynthElfCore::new(EM_X86_64)
+            .add_thread(PID, 11, amd64_user_regs(0x40000810, STACK_BASE + 0x10, 0))
+            .add_psinfo(PID, "crasher")
+            .add_siginfo(11, 1, 0xdeadbeef)
+            .add_auxv(&[
+                (md::AuxvType::AT_PAGESZ as u64, 0x1000),
+                (md::AuxvType::AT_SYSINFO_EHDR as u64, VDSO_BASE),
+            ])
+            .add_files(&[
+                (LIBRARY_BASE, LIBRARY_BASE + 0x2000, 0, "/usr/lib/libfoo.so"),
+                (
+                    LIBRARY_BASE + 0x2000,
+                    LIBRARY_BASE + 0x3000,
+                    0x2000,
+                    "/usr/lib/libfoo.so",
+                ),
+                (DATA_BASE, DATA_BASE + 0x1000, 0, "/var/data.bin"),
+            ])
+            .add_fpregs(fxsave(0x1f80))
+            .add_thread(
+                PID + 1,
+                0,
+                amd64_user_regs(0x40000900, STACK_BASE + 0x80, 0),
+            )
+            .add_memory(
+                0x2000,
+                PF_R | PF_X,
+                elf_headers_with_build_id(LIBRARY_BASE, BUILD_ID),
+            )
+            .add_memory(0x1000, PF_R | PF_W, {
+                let section = Section::new();
+                section.start().set_const(LIBRARY_BASE + 0x2000);
+                section
+            })
+            .add_memory(0x1000, PF_R, {
+                let section = Section::new();
+                section.start().set_const(DATA_BASE);
+                section
+            })
+            .add_memory(0x1000, PF_R | PF_W, stack)
+            .add_memory(
+                0x2000,
+                PF_R | PF_X,
+                elf_headers_with_build_id(VDSO_BASE, VDSO_BUILD_ID),
+            )
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_amd64() {
+        let core = ElfCore::read(amd64_core()).unwrap();
+        assert_eq!(core.cpu(), Cpu::X86_64);
+        assert_eq!(core.process_id(), Some(PID));
+        assert_eq!(
+            core.signal_info(),
+            Some(&ElfCoreSignalInfo {
+                signal: 11,
+                code: 1,
+                address: Some(0xdeadbeef),
+            })
+        );
+        assert_eq!(core.auxv().len(), 2);
+        assert_eq!(core.mapped_files().len(), 3);
+        assert_eq!(core.segments().len(), 5);
+
+        let threads = core.threads();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].thread_id, PID);
+        assert_eq!(threads[0].signal, 11);
+        assert_eq!(threads[0].context.get_instruction_pointer(), 0x40000810);
+        assert_eq!(threads[0].context.get_stack_pointer(), STACK_BASE + 0x10);
+        match &threads[0].context.raw {
+            MinidumpRawContext::Amd64(ctx) => {
+                assert_eq!(ctx.mx_csr, 0x1f80);
+                assert_eq!(
+                    ctx.context_flags,
+                    md::ContextFlagsAmd64::CONTEXT_AMD64_FULL.bits()
+                        | md::ContextFlagsAmd64::CONTEXT_AMD64_SEGMENTS.bits()
+                );
+                assert_eq!(ctx.cs, 0x33);
+                assert_eq!(ctx.eflags, 0x246);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(threads[1].thread_id, PID + 1);
+        assert_eq!(threads[1].signal, 0);
+
+        assert_eq!(
+            core.memory_at_address(STACK_BASE + 0x10, 4),
+            Some(&[0xab; 4][..])
+        );
+        // Not included in the core.
+        assert_eq!(core.memory_at_address(STACK_BASE + 0x100, 1), None);
+        assert_eq!(core.memory_at_address(DATA_BASE, 1), None);
+    }
+
+    #[test]
+    fn test_modules() {
+        let core = ElfCore::read(amd64_core()).unwrap();
+        // The data file isn't an ELF object, so it isn't a module.
+        assert_eq!(
+            core.modules(),
+            vec![
+                ElfCoreModule {
+                    base_address: LIBRARY_BASE,
+                    size: 0x3000,
+                    name: "/usr/lib/libfoo.so".to_owned(),
+                    build_id: Some(BUILD_ID.to_vec()),
+                },
+                ElfCoreModule {
+                    base_address: VDSO_BASE,
+                    size: 0x2000,
+                    name: "linux-gate.so".to_owned(),
+                    build_id: Some(VDSO_BUILD_ID.to_vec()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inverted_mapped_file() {
+        let core = SynthElfCore::new(EM_X86_64)
+            .add_files(&[
+                (LIBRARY_BASE + 0x1000, LIBRARY_BASE, 0, "/usr/lib/libbad.so"),
+                (DATA_BASE, DATA_BASE + 0x1000, 0, "/var/data.bin"),
+            ])
+            .add_memory(
+                0x2000,
+                PF_R | PF_X,
+                elf_headers_with_build_id(LIBRARY_BASE, BUILD_ID),
+            )
+            .finish()
+            .unwrap();
+        let core = ElfCore::read(core).unwrap();
+        assert_eq!(core.mapped_files().len(), 1);
+        assert_eq!(core.mapped_files()[0].name, "/var/data.bin");
+        assert_eq!(core.modules(), vec![]);
+    }
+
+    #[test]
+    fn test_to_minidump() {
+        let core = ElfCore::read(amd64_core()).unwrap();
+        let dump = core.to_minidump().unwrap();
+
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(system_info.os, crate::system_info::Os::Linux);
+        assert_eq!(system_info.cpu, Cpu::X86_64);
+
+        let memory = dump.get_memory().unwrap();
+        let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+        assert_eq!(threads.threads.len(), 2);
+        let thread = &threads.threads[0];
+        assert_eq!(thread.raw.thread_id, PID);
+        let context = thread.context(&system_info, None).unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0x40000810);
+        let stack = thread.stack_memory(&memory).unwrap();
+        assert_eq!(stack.base_address(), STACK_BASE);
+        assert_eq!(stack.bytes(), &[0xab; 0x100][..]);
+
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        assert_eq!(exception.get_crashing_thread_id(), PID);
+        assert_eq!(
+            exception.get_crash_reason(system_info.os, system_info.cpu),
+            CrashReason::LinuxSigsegv(ExceptionCodeLinuxSigsegvKind::SEGV_MAPERR)
+        );
+        assert_eq!(
+            exception.get_crash_address(system_info.os, system_info.cpu),
+            0xdeadbeef
+        );
+        let exception_context = exception.context(&system_info, None).unwrap();
+        assert_eq!(exception_context.get_stack_pointer(), STACK_BASE + 0x10);
+
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let module = modules.module_at_address(LIBRARY_BASE + 0x2800).unwrap();
+        assert_eq!(module.code_file(), "/usr/lib/libfoo.so");
+        assert_eq!(
+            module.code_identifier().unwrap().as_str(),
+            "0f0e0d0c0b0a09080706050403020100ffeeddcc"
+        );
+        assert_eq!(
+            module.debug_identifier().unwrap().breakpad().to_string(),
+            "0C0D0E0F0A0B080907060504030201000"
+        );
+        let vdso = modules.module_at_address(VDSO_BASE).unwrap();
+        assert_eq!(vdso.code_file(), "linux-gate.so");
+
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+        assert_eq!(misc_info.raw.process_id(), Some(&PID));
+
+        let maps = dump.get_stream::<MinidumpLinuxMaps>().unwrap();
+        let map = maps.memory_info_at_address(LIBRARY_BASE).unwrap();
+        assert!(map.is_executable());
+        assert!(!map.is_writable());
+        let map = maps.memory_info_at_address(STACK_BASE + 0x10).unwrap();
+        assert!(map.is_writable());
+        assert!(!map.is_executable());
+
+        let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+        assert_eq!(auxv.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_read_arm64() {
+        let mut fpsimd = Section::with_endian(AsmEndian::Little);
+        for i in 0..32u64 {
+            fpsimd = fpsimd.D64(i).D64(0);
+        }
+        let fpsimd = fpsimd.D32(0x10).D32(0x20).D64(0);
+        let bytes = SynthElfCore::new(EM_AARCH64)
+            .add_thread(
+                PID,
+                6,
+                arm64_user_regs(0x40000810, STACK_BASE, 0x7ffe1000, 0x40000900),
+            )
+            .add_fpregs(fpsimd)
+            .finish()
+            .unwrap();
+        let core = ElfCore::read(bytes).unwrap();
+        assert_eq!(core.cpu(), Cpu::Arm64);
+        assert_eq!(core.signal_info(), None);
+        match &core.threads()[0].context.raw {
+            MinidumpRawContext::Arm64(ctx) => {
+                assert_eq!(ctx.pc, 0x40000810);
+                assert_eq!(ctx.sp, STACK_BASE);
+                assert_eq!(ctx.iregs[29], 0x7ffe1000);
+                assert_eq!(ctx.iregs[30], 0x40000900);
+                assert_eq!(ctx.float_regs[31], 31);
+                assert_eq!(ctx.fpsr, 0x10);
+                assert_eq!(ctx.fpcr, 0x20);
+            }
+            _ => unreachable!(),
+        }
+
+        // Without NT_SIGINFO, the exception comes from the crashing thread.
+        let dump = core.to_minidump().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        assert_eq!(exception.raw.exception_record.exception_code, 6);
+        assert_eq!(exception.get_crashing_thread_id(), PID);
+    }
+
+    #[test]
+    fn test_core_round_trip() {
+        let core = ElfCore::read(amd64_core()).unwrap();
+        let dump = core.to_minidump().unwrap();
+        let round_trip = ElfCore::from_minidump(&dump).unwrap();
+
+        assert_eq!(round_trip.cpu(), Cpu::X86_64);
+        assert_eq!(round_trip.process_id(), Some(PID));
+        assert_eq!(round_trip.signal_info(), core.signal_info());
+        assert_eq!(round_trip.auxv(), core.auxv());
+
+        let threads = round_trip.threads();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].thread_id, PID);
+        assert_eq!(threads[0].signal, 11);
+        assert_eq!(threads[1].thread_id, PID + 1);
+        assert_eq!(threads[1].signal, 0);
+        for (thread, original) in threads.iter().zip(core.threads()) {
+            match (&thread.context.raw, &original.context.raw) {
+                (MinidumpRawContext::Amd64(ctx), MinidumpRawContext::Amd64(original)) => {
+                    assert_eq!(ctx.context_flags, original.context_flags);
+                    assert_eq!(ctx.rip, original.rip);
+                    assert_eq!(ctx.rsp, original.rsp);
+                    assert_eq!(ctx.cs, original.cs);
+                    assert_eq!(ctx.eflags, original.eflags);
+                    assert_eq!(ctx.mx_csr, original.mx_csr);
+                    assert_eq!(ctx.float_save, original.float_save);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // Only the modules are mapped files now.
+        assert_eq!(
+            round_trip.mapped_files(),
+            &[
+                ElfCoreMappedFile {
+                    start: LIBRARY_BASE,
+                    end: LIBRARY_BASE + 0x3000,
+                    file_offset: 0,
+                    name: "/usr/lib/libfoo.so".to_owned(),
+                },
+                ElfCoreMappedFile {
+                    start: VDSO_BASE,
+                    end: VDSO_BASE + 0x2000,
+                    file_offset: 0,
+                    name: "linux-gate.so".to_owned(),
+                },
+            ][..]
+        );
+        assert_eq!(round_trip.modules()[0], core.modules()[0]);
+
+        // Only the parts of the segments that were in the core have been kept.
+        let segments = |core: &ElfCore<_>| {
+            core.segments()
+                .iter()
+                .filter(|seg| seg.file_size != 0)
+                .map(|seg| (seg.base_address, seg.file_size, seg.flags))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(segments(&round_trip).len(), 3);
+        assert_eq!(segments(&round_trip), segments(&core));
+        assert_eq!(
+            round_trip.memory_at_address(STACK_BASE, 0x100),
+            core.memory_at_address(STACK_BASE, 0x100)
+        );
+    }
+
+    #[test]
+    fn test_minidump_to_elf_core() {
+        let crashing_context = amd64_context(AsmEndian::Little, 0x40000810, STACK_BASE + 0x10);
+        let crashing_stack = Memory::with_section(
+            Section::with_endian(AsmEndian::Little).append_repeated(0xab, 0x100),
+            STACK_BASE,
+        );
+        let crashing_thread = Thread::new(
+            AsmEndian::Little,
+            PID + 1,
+            &crashing_stack,
+            &crashing_context,
+        );
+        let other_context = amd64_context(AsmEndian::Little, 0x40000900, STACK_BASE + 0x1010);
+        let other_stack = Memory::with_section(
+            Section::with_endian(AsmEndian::Little).append_repeated(0xcd, 0x100),
+            STACK_BASE + 0x1000,
+        );
+        let other_thread = Thread::new(AsmEndian::Little, PID, &other_stack, &other_context);
+        let heap = Memory::with_section(
+            Section::with_endian(AsmEndian::Little).append_repeated(0xef, 0x10),
+            DATA_BASE,
+        );
+        // Overlaps the end of the crashing thread's stack.
+        let stack_tail = Memory::with_section(
+            Section::with_endian(AsmEndian::Little).append_repeated(0x11, 0x100),
+            STACK_BASE + 0x80,
+        );
+        let name = DumpString::new("/usr/bin/crasher", AsmEndian::Little);
+        let module = SynthModule::new(AsmEndian::Little, LIBRARY_BASE, 0x3000, &name, 0, 0, None);
+        let mut exception = Exception::new(AsmEndian::Little);
+        exception.thread_id = PID + 1;
+        exception.exception_record.exception_code = 7;
+        exception.exception_record.exception_flags = 2;
+        exception.exception_record.exception_address = 0xdeadbeef;
+        let system_info = SynthSystemInfo::new(AsmEndian::Little)
+            .set_processor_architecture(
+                md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+            )
+            .set_platform_id(md::PlatformId::Linux as u32);
+        let maps = format!(
+            "{:x}-{:x} r--p 00000000 00:00 0 [stack]\n",
+            STACK_BASE + 0x1000,
+            STACK_BASE + 0x2000
+        );
+        let dump = SynthMinidump::with_endian(AsmEndian::Little)
+            .add_system_info(system_info)
+            .add_thread(other_thread)
+            .add_thread(crashing_thread)
+            .add(crashing_context)
+            .add(other_context)
+            .add(crashing_stack)
+            .add_memory(other_stack)
+            .add_memory(heap)
+            .add_memory(stack_tail)
+            .add_module(module)
+            .add(name)
+            .add_exception(exception)
+            .set_linux_maps(maps.as_bytes())
+            .finish()
+            .unwrap();
+        let dump = Minidump::read(dump).unwrap();
+        let core = ElfCore::from_minidump(&dump).unwrap();
+
+        // The crashing thread comes first.
+        let threads = core.threads();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].thread_id, PID + 1);
+        assert_eq!(threads[0].signal, 7);
+        assert_eq!(threads[0].context.get_instruction_pointer(), 0x40000810);
+        assert_eq!(threads[1].thread_id, PID);
+        assert_eq!(threads[1].signal, 0);
+        assert_eq!(threads[1].context.get_stack_pointer(), STACK_BASE + 0x1010);
+
+        assert_eq!(
+            core.signal_info(),
+            Some(&ElfCoreSignalInfo {
+                signal: 7,
+                code: 2,
+                address: Some(0xdeadbeef),
+            })
+        );
+        assert_eq!(
+            core.mapped_files(),
+            &[ElfCoreMappedFile {
+                start: LIBRARY_BASE,
+                end: LIBRARY_BASE + 0x3000,
+                file_offset: 0,
+                name: "/usr/bin/crasher".to_owned(),
+            }][..]
+        );
+
+        // The stack of the crashing thread isn't in the memory list, but is still
+        // included. Permissions come from the maps, when they know about the memory.
+        // Memory overlapping the stack is trimmed.
+        let segments = core
+            .segments()
+            .iter()
+            .map(|seg| (seg.base_address, seg.size, seg.flags))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            segments,
+            vec![
+                (DATA_BASE, 0x10, PF_R | PF_W),
+                (STACK_BASE, 0x100, PF_R | PF_W),
+                (STACK_BASE + 0x100, 0x80, PF_R | PF_W),
+                (STACK_BASE + 0x1000, 0x100, PF_R),
+            ]
+        );
+        assert_eq!(core.memory_at_address(STACK_BASE, 2), Some(&[0xab; 2][..]));
+        assert_eq!(
+            core.memory_at_address(STACK_BASE + 0x80, 2),
+            Some(&[0xab; 2][..])
+        );
+        assert_eq!(
+            core.memory_at_address(STACK_BASE + 0x100, 2),
+            Some(&[0x11; 2][..])
+        );
+        assert_eq!(
+            core.memory_at_address(STACK_BASE + 0x1000, 2),
+            Some(&[0xcd; 2][..])
+        );
+    }
+
+    #[test]
+    fn test_minidump_to_elf_core_unsupported() {
+        // Defaults to x86
+        let dump = SynthMinidump::with_endian(AsmEndian::Little)
+            .add_system_info(SynthSystemInfo::new(AsmEndian::Little))
+            .finish()
+            .unwrap();
+        let dump = Minidump::read(dump).unwrap();
+        assert_eq!(
+            minidump_to_elf_core(&dump).unwrap_err(),
+            Error::UnsupportedElfCore
+        );
+    }
+
+    #[test]
+    fn test_not_a_core() {
+        let dump = SynthMinidump::new().finish().unwrap();
+        assert_eq!(ElfCore::read(dump).unwrap_err(), Error::HeaderMismatch);
+        assert_eq!(
+            ElfCore::read(&b"\x7fEL"[..]).unwrap_err(),
+            Error::MissingHeader
+        );
+
+        let mut bytes = SynthElfCore::new(0x28).finish().unwrap();
+        assert_eq!(
+            ElfCore::read(&bytes[..]).unwrap_err(),
+            Error::UnsupportedElfCore
+        );
+        // An executable rather than a core
+        bytes[16] = 2;
+        assert_eq!(
+            ElfCore::read(&bytes[..]).unwrap_err(),
+            Error::HeaderMismatch
+        );
+    }
+}
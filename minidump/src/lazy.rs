@@ -0,0 +1,235 @@
+//! Reading minidumps from [`Read`] + [`Seek`] sources, see [`Minidump::read_lazy`].
+//!
+//! [`Minidump::read_lazy`]: crate::Minidump::read_lazy
+
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use minidump_common::format::{self as md, MINIDUMP_STREAM_TYPE};
+use scroll::Pread;
+use tracing::warn;
+
+use crate::minidump::Error;
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<R: Read + Seek + Send> ReadSeek for R {}
+
+/// Read up to `size` bytes at `offset` in `reader`, stopping early at the end of the file.
+pub(crate) fn read_at<R>(reader: &mut R, offset: u64, size: u64) -> io::Result<Vec<u8>>
+where
+    R: Read + Seek + ?Sized,
+{
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    reader.take(size).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The data backing a [`Minidump`][crate::Minidump] read with
+/// [`Minidump::read_lazy`][crate::Minidump::read_lazy].
+///
+/// This dereferences to the part of the file that the location descriptors of streams can
+/// refer to, which is read from the source the first time it is needed. For full-memory
+/// dumps that leaves out the memory contents at the end of the file: those are read a region
+/// at a time, as they are looked up.
+#[derive(Clone)]
+pub struct LazyData {
+    source: Arc<LazySource>,
+}
+
+/// Some bytes of the file, read on first use.
+type LazyBytes = OnceLock<Box<[u8]>>;
+
+struct LazySource {
+    reader: Mutex<Box<dyn ReadSeek>>,
+    /// The length of the whole file.
+    len: u64,
+    /// The length of the part of the file that `image` holds.
+    image_len: u64,
+    image: OnceLock<Vec<u8>>,
+    /// The whole file, if `image` doesn't already hold all of it.
+    contents: OnceLock<Vec<u8>>,
+    /// The contents of each stream, by index in the stream directory.
+    streams: Vec<OnceLock<Result<Box<[u8]>, Error>>>,
+    /// The contents of the memory regions of each memory list stream, by index in the
+    /// stream directory and then by region.
+    memory: Vec<OnceLock<Vec<LazyBytes>>>,
+}
+
+impl LazyData {
+    /// Wrap `reader`, given the stream directory that was read from it.
+    pub(crate) fn new<R>(
+        mut reader: R,
+        directory: &[md::MINIDUMP_DIRECTORY],
+        endian: scroll::Endian,
+    ) -> Result<LazyData, Error>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let len = reader.seek(SeekFrom::End(0)).or(Err(Error::IoError))?;
+
+        // Full-memory dumps store the contents of their memory in one block at the end of the
+        // file, after all the data that other streams can refer to. If this dump is laid out
+        // like that, stop the part that gets read as a whole in front of that block.
+        let metadata_end = directory
+            .iter()
+            .map(|dir| dir.location.rva as u64 + dir.location.data_size as u64)
+            .max()
+            .unwrap_or(0);
+        let memory_rva = directory
+            .iter()
+            .rev()
+            .find(|dir| dir.stream_type == MINIDUMP_STREAM_TYPE::Memory64ListStream as u32)
+            .and_then(|dir| read_at(&mut reader, dir.location.rva as u64 + 8, 8).ok())
+            .and_then(|bytes| bytes.pread_with::<u64>(0, endian).ok());
+        let image_len = match memory_rva {
+            Some(rva) if rva >= metadata_end => rva.min(len),
+            _ => len,
+        };
+
+        Ok(LazyData {
+            source: Arc::new(LazySource {
+                reader: Mutex::new(Box::new(reader)),
+                len,
+                image_len,
+                image: OnceLock::new(),
+                contents: OnceLock::new(),
+                streams: directory.iter().map(|_| OnceLock::new()).collect(),
+                memory: directory.iter().map(|_| OnceLock::new()).collect(),
+            }),
+        })
+    }
+
+    /// The stream at `index` in the stream directory, which is at `location`.
+    pub(crate) fn stream(
+        &self,
+        index: usize,
+        location: md::MINIDUMP_LOCATION_DESCRIPTOR,
+    ) -> LazyStream<'_> {
+        LazyStream {
+            data: self,
+            index,
+            location,
+        }
+    }
+}
+
+impl LazyData {
+    /// The contents of the whole file, which are read from the source the first time
+    /// they're needed.
+    pub(crate) fn contents(&self) -> &[u8] {
+        let source = &self.source;
+        if source.image_len == source.len {
+            return self;
+        }
+        source.contents.get_or_init(|| source.read_all(source.len))
+    }
+
+    /// The length of the whole file.
+    pub(crate) fn file_len(&self) -> u64 {
+        self.source.len
+    }
+}
+
+impl LazySource {
+    fn read_at(&self, offset: u64, size: u64) -> io::Result<Vec<u8>> {
+        let mut reader = self.reader.lock().unwrap_or_else(PoisonError::into_inner);
+        read_at(&mut *reader, offset, size)
+    }
+
+    /// Read the first `size` bytes of the file, or as many as possible.
+    fn read_all(&self, size: u64) -> Vec<u8> {
+        self.read_at(0, size).unwrap_or_else(|e| {
+            warn!("failed to read the minidump: {}", e);
+            Vec::new()
+        })
+    }
+}
+
+impl Deref for LazyData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        let source = &self.source;
+        source
+            .image
+            .get_or_init(|| source.read_all(source.image_len))
+    }
+}
+
+impl fmt::Debug for LazyData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyData")
+            .field("len", &self.source.len)
+            .field("image_len", &self.source.image_len)
+            .field("image_loaded", &self.source.image.get().is_some())
+            .finish()
+    }
+}
+
+/// A stream of a minidump read with [`Minidump::read_lazy`][crate::Minidump::read_lazy],
+/// as passed to [`MinidumpStream::read_lazy`][crate::MinidumpStream::read_lazy].
+#[derive(Clone, Copy, Debug)]
+pub struct LazyStream<'a> {
+    data: &'a LazyData,
+    index: usize,
+    location: md::MINIDUMP_LOCATION_DESCRIPTOR,
+}
+
+impl<'a> LazyStream<'a> {
+    /// The contents of this stream, which are read from the source on first use.
+    pub fn bytes(&self) -> Result<&'a [u8], Error> {
+        let source = &self.data.source;
+        let location = self.location;
+        let bytes = source.streams[self.index].get_or_init(|| {
+            let size = location.data_size as u64;
+            if location.rva as u64 + size > source.len {
+                return Err(Error::StreamReadFailure);
+            }
+            match source.read_at(location.rva as u64, size) {
+                Ok(bytes) if bytes.len() as u64 == size => Ok(bytes.into_boxed_slice()),
+                _ => Err(Error::StreamReadFailure),
+            }
+        });
+        bytes.as_deref().map_err(Clone::clone)
+    }
+
+    /// The part of the minidump that location descriptors can refer to, which is read from
+    /// the source the first time any stream needs it.
+    ///
+    /// This is what [`MinidumpStream::read`][crate::MinidumpStream::read] gets as `all`.
+    pub fn all(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The length of the whole minidump file.
+    pub(crate) fn file_len(&self) -> u64 {
+        self.data.source.len
+    }
+
+    /// The `size` bytes at `offset` in the file, which are the contents of region `region`
+    /// of the `count` memory regions in this stream.
+    ///
+    /// This only reads from the source the first time a region is requested. Failing to
+    /// read it is logged and yields an empty slice, like any other unavailable memory.
+    pub(crate) fn memory(&self, region: usize, count: usize, offset: u64, size: u64) -> &'a [u8] {
+        let source = &self.data.source;
+        let regions =
+            source.memory[self.index].get_or_init(|| (0..count).map(|_| OnceLock::new()).collect());
+        regions[region].get_or_init(|| {
+            source
+                .read_at(offset, size)
+                .map(Vec::into_boxed_slice)
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "failed to read memory region at {:#x} ({} bytes) of the minidump: {}",
+                        offset, size, e
+                    );
+                    Box::default()
+                })
+        })
+    }
+}
@@ -14,7 +14,9 @@
 //!
 //! The primary API for this library is the [`Minidump`][] struct, which can be
 //! instantiated by calling the [`Minidump::read`][] or [`Minidump::read_path`][]
-//! methods.
+//! methods. Minidumps too large to hold in memory can instead be read from any
+//! [`Read`][std::io::Read] + [`Seek`][std::io::Seek] source with [`Minidump::read_lazy`][],
+//! which only reads the parts of the file that are used.
 //!
 //! Successfully parsing a Minidump struct means the minidump has a minimally valid
 //! header and stream directory. Individual streams are only parsed when they're
@@ -70,9 +72,16 @@
 //!
 //! * [`MinidumpAssertion`][]
 //! * [`MinidumpBreakpadInfo`][]
+//! * [`MinidumpCommentA`][]
+//! * [`MinidumpCommentW`][]
 //! * [`MinidumpCrashpadInfo`][]
 //! * [`MinidumpException`][]
+//! * [`MinidumpFunctionTableList`][]
+//! * [`MinidumpHandleOperationList`][]
+//! * [`MinidumpLinuxAuxv`][]
+//! * [`MinidumpLinuxCmdLine`][]
 //! * [`MinidumpLinuxCpuInfo`][]
+//! * [`MinidumpLinuxDsoDebug`][]
 //! * [`MinidumpLinuxEnviron`][]
 //! * [`MinidumpLinuxLsbRelease`][]
 //! * [`MinidumpLinuxMaps`][]
@@ -83,9 +92,13 @@
 //! * [`MinidumpMemoryInfoList`][]
 //! * [`MinidumpMiscInfo`][]
 //! * [`MinidumpModuleList`][]
+//! * [`MinidumpProcessVmCounters`][]
 //! * [`MinidumpSystemInfo`][]
+//! * [`MinidumpSystemMemoryInfo`][]
+//! * [`MinidumpThreadInfoList`][]
 //! * [`MinidumpThreadList`][]
 //! * [`MinidumpThreadNames`][]
+//! * [`MinidumpTokenList`][]
 //! * [`MinidumpUnloadedModuleList`][]
 //! * [`MinidumpLinuxProcLimits`][]
 //!
@@ -356,6 +369,7 @@
 //! Known members of this family:
 //!
 //! * [`MinidumpMemoryInfoList`][] (entries are [`MINIDUMP_MEMORY_INFO`][format::MINIDUMP_MEMORY_INFO])
+//! * [`MinidumpThreadInfoList`][] (entries are [`MINIDUMP_THREAD_INFO`][format::MINIDUMP_THREAD_INFO])
 //! * [`MinidumpUnloadedModuleList`][] (entries are [`MINIDUMP_UNLOADED_MODULE`][format::MINIDUMP_UNLOADED_MODULE])
 //! * [`MinidumpHandleDataStream`][] is a slight variation of this format with different
 //!   filed names and a trailing `u32` member reserved for future use (entries
@@ -405,13 +419,19 @@ pub use scroll::Endian;
 
 mod context;
 mod iostuff;
+mod lazy;
 mod minidump;
 
 pub use minidump_common::format;
 pub use minidump_common::traits::Module;
 
 pub use crate::iostuff::Readable;
+pub use crate::lazy::{LazyData, LazyStream};
 pub use crate::minidump::*;
 
+pub mod elf_core;
+pub mod redact;
 pub mod strings;
 pub mod system_info;
+pub mod validate;
+pub mod writer;
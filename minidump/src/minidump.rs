@@ -8,7 +8,7 @@ use scroll::ctx::{SizeWith, TryFromCtx};
 use scroll::{self, Pread, BE, LE};
 use std::borrow::Cow;
 use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt;
 use std::fs::File;
@@ -20,11 +20,13 @@ use std::mem;
 use std::ops::Deref;
 use std::path::Path;
 use std::str;
+use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 use tracing::warn;
 use uuid::Uuid;
 
 pub use crate::context::*;
+use crate::lazy::{read_at, LazyData, LazyStream};
 use crate::strings::*;
 use crate::system_info::{Cpu, Os, PointerWidth};
 use minidump_common::errors::{self as err};
@@ -62,10 +64,16 @@ where
     data: T,
     /// The raw minidump header from the file.
     pub header: md::MINIDUMP_HEADER,
-    streams: HashMap<u32, (u32, md::MINIDUMP_DIRECTORY)>,
-    system_info: Option<MinidumpSystemInfo>,
+    /// The stream directory, in the order of the file.
+    directory: Vec<md::MINIDUMP_DIRECTORY>,
+    /// The indices in `directory` of the streams of each type, in directory order.
+    streams: HashMap<u32, Vec<usize>>,
+    /// The last `SystemInfoStream`, parsed when the first stream is read.
+    system_info: OnceLock<Option<MinidumpSystemInfo>>,
     /// The endianness of this minidump file.
     pub endian: scroll::Endian,
+    /// Where to read streams from, for minidumps read with [`Minidump::read_lazy`].
+    lazy: Option<LazyData>,
     _phantom: PhantomData<&'a [u8]>,
 }
 
@@ -100,6 +108,8 @@ pub enum Error {
     CodeViewReadFailure,
     #[error("Uknown element type")]
     UknownElementType,
+    #[error("Unsupported ELF core (only 64-bit x86-64 and ARM64 cores are supported)")]
+    UnsupportedElfCore,
 }
 
 impl Error {
@@ -121,6 +131,7 @@ impl Error {
             Error::DataError => "DataError",
             Error::CodeViewReadFailure => "CodeViewReadFailure",
             Error::UknownElementType => "UnknownElementType",
+            Error::UnsupportedElfCore => "UnsupportedElfCore",
         }
     }
 }
@@ -144,6 +155,20 @@ pub trait MinidumpStream<'a>: Sized {
         endian: scroll::Endian,
         system_info: Option<&MinidumpSystemInfo>,
     ) -> Result<Self, Error>;
+
+    /// Read this `MinidumpStream` type from a minidump read with [`Minidump::read_lazy`].
+    ///
+    /// The default implementation reads the contents of the stream and the part of the
+    /// minidump it can refer to, and passes them to [`MinidumpStream::read`]. Streams that
+    /// refer to large amounts of data elsewhere can override this to only read that data
+    /// when it's used.
+    fn read_lazy(
+        stream: LazyStream<'a>,
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        Self::read(stream.bytes()?, stream.all(), endian, system_info)
+    }
 }
 
 /// Provides a unified interface for getting metadata about the process's mapped memory regions
@@ -369,6 +394,117 @@ pub struct MinidumpHandleDataStream {
     pub handles: Vec<MinidumpHandleDescriptor>,
 }
 
+/// A single operation on a handle, recorded by handle tracing.
+#[derive(Debug, Clone)]
+pub struct MinidumpHandleOperation {
+    /// The `AVRF_HANDLE_OPERATION` direct from the minidump file.
+    pub raw: md::AVRF_HANDLE_OPERATION,
+    /// What happened to the handle, if known.
+    pub operation: Option<md::eHANDLE_TRACE_OPERATIONS>,
+}
+
+/// A stream holding the most recent operations on handles, with the stack
+/// backtraces that performed them.
+///
+/// This is only present if handle tracing was enabled in the process (e.g. by
+/// Application Verifier), and is mostly useful for debugging handle leaks.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpHandleOperationList {
+    pub operations: Vec<MinidumpHandleOperation>,
+}
+
+/// A function table registered by the process at runtime.
+#[derive(Debug, Clone)]
+pub struct MinidumpFunctionTable<'a> {
+    /// The `MINIDUMP_FUNCTION_TABLE_DESCRIPTOR` direct from the minidump file.
+    pub raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR,
+    /// The operating system's own description of the table
+    /// (`DYNAMIC_FUNCTION_TABLE` on Windows).
+    pub native_descriptor: &'a [u8],
+    /// The raw bytes of the table's function entries.
+    pub raw_entries: &'a [u8],
+    /// The table's function entries, sorted by address.
+    ///
+    /// This is only filled in if the entries are x86-64 `RUNTIME_FUNCTION`s,
+    /// as the format of the entries depends on the CPU architecture.
+    pub functions: Vec<md::IMAGE_RUNTIME_FUNCTION_ENTRY>,
+}
+
+/// A stream holding the function tables registered by the process at runtime.
+///
+/// JIT engines (e.g. .NET and V8) register the unwind information of the code
+/// they generate with `RtlAddFunctionTable`. That code isn't part of any
+/// module, so these tables are the only way to unwind through it.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpFunctionTableList<'a> {
+    pub tables: Vec<MinidumpFunctionTable<'a>>,
+}
+
+/// A Windows security identifier (SID), e.g. `S-1-5-18`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MinidumpSid {
+    /// The revision of the SID format, always 1.
+    pub revision: u8,
+    /// The authority that issued the SID (e.g. 5 for `SECURITY_NT_AUTHORITY`).
+    pub identifier_authority: u64,
+    /// The sub-authorities (the last one is usually the relative identifier).
+    pub sub_authorities: Vec<u32>,
+}
+
+/// A SID in a token, along with its `SE_GROUP_*` attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpSidAndAttributes {
+    pub sid: MinidumpSid,
+    pub attributes: u32,
+}
+
+/// A privilege held by a token, along with its `SE_PRIVILEGE_*` attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinidumpTokenPrivilege {
+    /// The locally unique identifier of the privilege.
+    pub luid: u64,
+    pub attributes: u32,
+}
+
+/// The decoded contents of a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpTokenAccessInfo {
+    /// The logon session of the token.
+    pub authentication_id: u64,
+    /// Whether this is a primary (1) or impersonation (2) token.
+    pub token_type: u32,
+    /// The `SECURITY_IMPERSONATION_LEVEL` of an impersonation token.
+    pub impersonation_level: u32,
+    /// The `TOKEN_*` flags of the token.
+    pub flags: u32,
+    /// The user the token belongs to.
+    pub user: Option<MinidumpSidAndAttributes>,
+    /// The groups the token belongs to.
+    pub groups: Vec<MinidumpSidAndAttributes>,
+    /// The privileges of the token.
+    pub privileges: Vec<MinidumpTokenPrivilege>,
+}
+
+/// An access token of the process or one of its threads.
+#[derive(Debug, Clone)]
+pub struct MinidumpToken<'a> {
+    /// The `MINIDUMP_TOKEN_INFO_HEADER` direct from the minidump file.
+    pub raw: md::MINIDUMP_TOKEN_INFO_HEADER,
+    /// The token's information, following the header.
+    pub data: &'a [u8],
+    /// The token's SIDs and privileges, if they could be decoded from `data`.
+    pub access_info: Option<MinidumpTokenAccessInfo>,
+}
+
+/// A stream holding the access tokens of the process and its threads.
+///
+/// This is only present if the minidump was written with
+/// `MiniDumpWithTokenInformation`.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpTokenList<'a> {
+    pub tokens: Vec<MinidumpToken<'a>>,
+}
+
 /// The state of a thread from the process when the minidump was written.
 #[derive(Debug)]
 pub struct MinidumpThread<'a> {
@@ -391,6 +527,27 @@ pub struct MinidumpThreadList<'a> {
     thread_ids: HashMap<u32, usize>,
 }
 
+/// Extra information about a thread from the process when the minidump was written.
+///
+/// This comes from the optional `ThreadInfoListStream` and complements the
+/// corresponding [`MinidumpThread`] (matched by thread id).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpThreadInfo {
+    /// The `MINIDUMP_THREAD_INFO` direct from the minidump file.
+    pub raw: md::MINIDUMP_THREAD_INFO,
+    /// Flags describing the state of this entry.
+    pub dump_flags: md::ThreadInfoDumpFlags,
+}
+
+/// A list of `MinidumpThreadInfo`s contained in a `Minidump`.
+#[derive(Debug, Clone)]
+pub struct MinidumpThreadInfoList {
+    /// The thread infos, in the order they were present in the `Minidump`.
+    thread_infos: Vec<MinidumpThreadInfo>,
+    /// A map of thread id to index in `thread_infos`.
+    thread_ids: HashMap<u32, usize>,
+}
+
 /// Information about the system that generated the minidump.
 #[derive(Debug, Clone)]
 pub struct MinidumpSystemInfo {
@@ -436,6 +593,28 @@ pub type MinidumpMemory64<'a> = MinidumpMemoryBase<'a, md::MINIDUMP_MEMORY_DESCR
 pub enum UnifiedMemory<'a, 'mdmp> {
     Memory(&'a MinidumpMemory<'mdmp>),
     Memory64(&'a MinidumpMemory64<'mdmp>),
+    LazyMemory64(&'a MinidumpLazyMemory64<'mdmp>),
+}
+
+/// A large region of memory from a minidump read with [`Minidump::read_lazy`], whose
+/// contents are only read from the file when they're used.
+#[derive(Debug, Clone, Copy)]
+pub struct MinidumpLazyMemory64<'a> {
+    /// The raw `MINIDUMP_MEMORY_DESCRIPTOR64` from the minidump.
+    pub desc: md::MINIDUMP_MEMORY_DESCRIPTOR64,
+    /// The starting address of this range of memory.
+    pub base_address: u64,
+    /// The length of this range of memory.
+    pub size: u64,
+    /// The endianness of the minidump which is used for memory accesses.
+    pub endian: scroll::Endian,
+    stream: LazyStream<'a>,
+    /// The index of this region in its list.
+    index: usize,
+    /// The number of regions in the list.
+    count: usize,
+    /// The offset of the contents of this region in the file.
+    offset: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -483,6 +662,30 @@ pub struct MinidumpMiscInfo {
     pub raw: RawMiscInfo,
 }
 
+/// System-wide memory information, from the time the minidump was written.
+///
+/// Windows-only, written by WER and Crashpad.
+#[derive(Debug, Clone)]
+pub struct MinidumpSystemMemoryInfo {
+    /// The `MINIDUMP_SYSTEM_MEMORY_INFO_1` struct direct from the minidump.
+    pub raw: md::MINIDUMP_SYSTEM_MEMORY_INFO_1,
+}
+
+#[derive(Debug, Clone)]
+pub enum RawProcessVmCounters {
+    V1(md::MINIDUMP_PROCESS_VM_COUNTERS_1),
+    V2(md::MINIDUMP_PROCESS_VM_COUNTERS_2),
+}
+
+/// Memory usage counters of the process that wrote the minidump.
+///
+/// Windows-only, written by WER and Crashpad.
+#[derive(Debug, Clone)]
+pub struct MinidumpProcessVmCounters {
+    /// The `MINIDUMP_PROCESS_VM_COUNTERS_*` struct direct from the minidump.
+    pub raw: RawProcessVmCounters,
+}
+
 /// Additional information about process state.
 ///
 /// MinidumpBreakpadInfo wraps MINIDUMP_BREAKPAD_INFO, which is an optional stream
@@ -509,6 +712,12 @@ pub struct MinidumpLinuxEnviron<'a> {
     data: &'a [u8],
 }
 
+/// The command line of the process, extracted from /proc/self/cmdline
+#[derive(Default, Debug)]
+pub struct MinidumpLinuxCmdLine<'a> {
+    data: &'a [u8],
+}
+
 /// Interesting values extracted from /proc/cpuinfo
 #[derive(Default, Debug)]
 pub struct MinidumpLinuxCpuInfo<'a> {
@@ -527,6 +736,55 @@ pub struct MinidumpLinuxProcLimits<'a> {
     data: &'a [u8],
 }
 
+/// An entry of the auxiliary vector of a Linux process
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MinidumpLinuxAuxvEntry {
+    /// The raw `AT_*` type of this entry, see [`md::AuxvType`].
+    pub raw_type: u64,
+    /// The value of this entry, its meaning depends on the type.
+    pub value: u64,
+}
+
+/// The auxiliary vector of a Linux process, extracted from /proc/self/auxv
+#[derive(Default, Debug, Clone)]
+pub struct MinidumpLinuxAuxv {
+    entries: Vec<MinidumpLinuxAuxvEntry>,
+}
+
+/// A shared object known to the dynamic linker of a Linux process
+///
+/// This is functionally equivalent to the data in `struct link_map` defined in <link.h>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpLinkMapEntry {
+    /// The difference between the addresses in the ELF file and in memory (`l_addr`).
+    ///
+    /// For shared libraries this is usually the address they were loaded at.
+    pub addr: u64,
+    /// The filename of the shared object (`l_name`), empty for the main executable.
+    pub name: String,
+    /// The address of the dynamic section of the shared object (`l_ld`).
+    pub ld: u64,
+}
+
+/// The state of the dynamic linker of a Linux process, from the `LinuxDsoDebug` stream
+///
+/// This is functionally equivalent to the data in `struct r_debug` defined in <link.h>.
+#[derive(Debug, Clone)]
+pub struct MinidumpLinuxDsoDebug {
+    /// The version number of this protocol, from `r_debug.r_version`
+    pub version: u32,
+    /// The address of the function the dynamic linker calls when the `link_map` changes
+    pub brk: u64,
+    /// Base address the linker is loaded at
+    pub ldbase: u64,
+    /// The address of the dynamic section of the main executable
+    pub dynamic: u64,
+    /// The `link_map` entries, as copied into the minidump when it was written
+    pub link_map: Vec<MinidumpLinkMapEntry>,
+    /// Whether the process was 64-bit, which determines the layout of structures in memory
+    is_64bit: bool,
+}
+
 /// The reason for a process crash.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CrashReason {
@@ -626,13 +884,82 @@ pub type MinidumpMemory64List<'a> = MinidumpMemoryListBase<'a, md::MINIDUMP_MEMO
 pub enum UnifiedMemoryList<'a> {
     Memory(MinidumpMemoryList<'a>),
     Memory64(MinidumpMemory64List<'a>),
+    LazyMemory64(MinidumpLazyMemory64List<'a>),
+}
+
+/// A list of large memory regions from a minidump read with [`Minidump::read_lazy`].
+///
+/// This is what [`Minidump::get_memory`][] returns instead of a [`MinidumpMemory64List`][]
+/// for those minidumps, so that only the regions that are looked up get read.
+#[derive(Debug, Clone)]
+pub struct MinidumpLazyMemory64List<'a> {
+    /// The memory regions, in the order they were stored in the minidump.
+    regions: Vec<MinidumpLazyMemory64<'a>>,
+    /// Map from address range to index in regions.
+    regions_by_addr: RangeMap<u64, usize>,
+}
+
+/// The memory of the process that wrote a minidump, as a single address space.
+///
+/// This is made of memory regions and memory lists, which are searched in the order they
+/// were added, so that earlier ones take precedence where they overlap.
+///
+/// Unlike with [`UnifiedMemoryList::memory_at_address`][], reads aren't limited to a single
+/// region: they can span any number of adjacent regions. This matters for full-memory dumps,
+/// which split large mappings such as thread stacks into many adjacent regions.
+///
+/// # Examples
+///
+/// ```
+/// use minidump::{Minidump, MinidumpAddressSpace, UnifiedMemoryList};
+///
+/// # fn foo() -> Result<(), minidump::Error> {
+/// let dump = Minidump::read_path("../testdata/test.dmp")?;
+/// let memory = dump.get_memory().unwrap_or_default();
+/// let address_space = MinidumpAddressSpace::from(&memory);
+/// let pointer = address_space.read_u64(0x1234);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpAddressSpace<'a, 'mdmp> {
+    /// The sources of memory, in the order they're searched.
+    layers: Vec<AddressSpaceLayer<'a, 'mdmp>>,
 }
+
+#[derive(Debug, Clone, Copy)]
+enum AddressSpaceLayer<'a, 'mdmp> {
+    Region(UnifiedMemory<'a, 'mdmp>),
+    List(&'a UnifiedMemoryList<'mdmp>),
+}
+
 impl<'a> Default for UnifiedMemoryList<'a> {
     fn default() -> Self {
         Self::Memory(Default::default())
     }
 }
 
+/// A free-form comment stored as ANSI text in a `CommentStreamA` stream.
+#[derive(Debug, Clone)]
+pub struct MinidumpCommentA {
+    /// The comment, decoded from the Windows-1252 code page.
+    pub comment: String,
+}
+
+/// A free-form comment stored as UTF-16 text in a `CommentStreamW` stream.
+#[derive(Debug, Clone)]
+pub struct MinidumpCommentW {
+    /// The comment.
+    pub comment: String,
+}
+
+/// Provides a unified interface for MinidumpCommentA and MinidumpCommentW
+#[derive(Debug, Clone)]
+pub enum MinidumpComment {
+    Ansi(MinidumpCommentA),
+    Unicode(MinidumpCommentW),
+}
+
 /// Information about an assertion that caused a crash.
 #[derive(Debug)]
 pub struct MinidumpAssertion {
@@ -713,9 +1040,30 @@ fn format_system_time(time: &md::SYSTEMTIME) -> String {
     format_date().unwrap_or_else(|| "<invalid date>".to_owned())
 }
 
+/// The number of 100-nanosecond `FILETIME` ticks between 1601-01-01 and the unix epoch.
+const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+/// Convert a count of 100-nanosecond `FILETIME` ticks to a `Duration`.
+fn duration_from_filetime_ticks(ticks: u64) -> Duration {
+    Duration::from_secs(ticks / 10_000_000) + Duration::from_nanos((ticks % 10_000_000) * 100)
+}
+
+/// Convert a `FILETIME` to a `SystemTime`, treating 0 (and pre-1970 times) as unknown.
+fn systemtime_from_filetime(filetime: u64) -> Option<SystemTime> {
+    let since_epoch = filetime.checked_sub(FILETIME_UNIX_EPOCH)?;
+    SystemTime::UNIX_EPOCH.checked_add(duration_from_filetime_ticks(since_epoch))
+}
+
+fn format_filetime(filetime: u64) -> String {
+    systemtime_from_filetime(filetime)
+        .map(time::OffsetDateTime::from)
+        .and_then(|datetime| datetime.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
 /// Produce a slice of `bytes` corresponding to the offset and size in `loc`, or an
 /// `Error` if the data is not fully contained within `bytes`.
-fn location_slice<'a>(
+pub(crate) fn location_slice<'a>(
     bytes: &'a [u8],
     loc: &md::MINIDUMP_LOCATION_DESCRIPTOR,
 ) -> Result<&'a [u8], Error> {
@@ -727,7 +1075,11 @@ fn location_slice<'a>(
 }
 
 /// Read a u32 length-prefixed UTF-16 string from `bytes` at `offset`.
-fn read_string_utf16(offset: &mut usize, bytes: &[u8], endian: scroll::Endian) -> Option<String> {
+pub(crate) fn read_string_utf16(
+    offset: &mut usize,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Option<String> {
     let u: u32 = bytes.gread_with(offset, endian).ok()?;
     let size = u as usize;
     if size % 2 != 0 || (*offset + size) > bytes.len() {
@@ -745,7 +1097,7 @@ fn read_string_utf16(offset: &mut usize, bytes: &[u8], endian: scroll::Endian) -
 }
 
 #[inline]
-fn read_string_utf8_unterminated<'a>(
+pub(crate) fn read_string_utf8_unterminated<'a>(
     offset: &mut usize,
     bytes: &'a [u8],
     endian: scroll::Endian,
@@ -882,6 +1234,91 @@ fn ensure_count_in_bound(
     Ok((number_of_entries, expected_size))
 }
 
+/// Read and check the header at the start of a minidump, and determine its endianness.
+fn read_header(data: &[u8]) -> Result<(md::MINIDUMP_HEADER, scroll::Endian), Error> {
+    let mut offset = 0;
+    let mut endian = LE;
+    let mut header: md::MINIDUMP_HEADER = data
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::MissingHeader))?;
+    if header.signature != md::MINIDUMP_SIGNATURE {
+        if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+        // Try again with big-endian.
+        endian = BE;
+        offset = 0;
+        header = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::MissingHeader))?;
+        if header.signature != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+    }
+    if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+
+    Ok((header, endian))
+}
+
+/// Read the `count` entries of the stream directory at `offset` in `data`, and index them
+/// by stream type.
+#[allow(clippy::type_complexity)]
+fn read_directory(
+    data: &[u8],
+    mut offset: usize,
+    count: usize,
+    endian: scroll::Endian,
+) -> Result<(Vec<md::MINIDUMP_DIRECTORY>, HashMap<u32, Vec<usize>>), Error> {
+    let (count, _) = ensure_count_in_bound(
+        data,
+        count,
+        <md::MINIDUMP_DIRECTORY>::size_with(&endian),
+        offset,
+    )?;
+
+    let mut directory = Vec::with_capacity(count);
+    let mut streams = HashMap::<u32, Vec<usize>>::with_capacity(count);
+    for i in 0..count {
+        let dir: md::MINIDUMP_DIRECTORY = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::MissingDirectory))?;
+        let indices = streams.entry(dir.stream_type).or_default();
+        if let Some(&old_idx) = indices.last() {
+            let old_dir: &md::MINIDUMP_DIRECTORY = &directory[old_idx];
+            if let Some(known_stream_type) = MINIDUMP_STREAM_TYPE::from_u32(dir.stream_type) {
+                if !(known_stream_type == MINIDUMP_STREAM_TYPE::UnusedStream
+                    && old_dir.location.data_size == 0
+                    && dir.location.data_size == 0)
+                {
+                    warn!("Minidump contains multiple streams of type {} ({:?}) at indices {} ({} bytes) and {} ({} bytes) (using {})",
+                        dir.stream_type,
+                        known_stream_type,
+                        old_idx,
+                        old_dir.location.data_size,
+                        i,
+                        dir.location.data_size,
+                        i,
+                    );
+                }
+            } else {
+                warn!("Minidump contains multiple streams of unknown type {} at indices {} ({} bytes) and {} ({} bytes) (using {})",
+                    dir.stream_type,
+                    old_idx,
+                    old_dir.location.data_size,
+                    i,
+                    dir.location.data_size,
+                    i,
+                );
+            }
+        }
+        indices.push(i);
+        directory.push(dir);
+    }
+    Ok((directory, streams))
+}
+
 impl MinidumpModule {
     /// Create a `MinidumpModule` with some basic info.
     ///
@@ -1966,97 +2403,707 @@ impl<'a> MinidumpStream<'a> for MinidumpHandleDataStream {
     }
 }
 
-impl<'a> MinidumpMemory<'a> {
-    pub fn read(
-        desc: &md::MINIDUMP_MEMORY_DESCRIPTOR,
-        data: &'a [u8],
-        endian: scroll::Endian,
-    ) -> Result<MinidumpMemory<'a>, Error> {
-        if desc.memory.rva == 0 || desc.memory.data_size == 0 {
-            // Windows will sometimes emit null stack RVAs, indicating that
-            // we need to lookup the address in a memory region. It's ok to
-            // emit an error for that here, the thread processing code will
-            // catch it.
-            return Err(Error::MemoryReadFailure);
-        }
-        let bytes = location_slice(data, &desc.memory).or(Err(Error::StreamReadFailure))?;
-        Ok(MinidumpMemory {
-            desc: *desc,
-            base_address: desc.start_of_memory_range,
-            size: desc.memory.data_size as u64,
-            bytes,
-            endian,
-        })
+impl MinidumpHandleOperation {
+    /// The return addresses of the backtrace that performed this operation,
+    /// innermost first.
+    pub fn backtrace(&self) -> &[u64] {
+        let info = &self.raw.back_trace_information;
+        let depth = (info.depth as usize).min(info.return_addresses.len());
+        &info.return_addresses[..depth]
     }
 
-    /// Write a human-readable description of this `MinidumpMemory` to `f`.
-    ///
-    /// This is very verbose, it is the format used by `minidump_dump`.
-    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
         write!(
             f,
-            "MINIDUMP_MEMORY_DESCRIPTOR
-  start_of_memory_range = {:#x}
-  memory.data_size      = {:#x}
-  memory.rva            = {:#x}
+            "AVRF_HANDLE_OPERATION
+  handle                 = {:#x}
+  process_id             = {:#x}
+  thread_id              = {:#x}
+  operation_type         = {:#x} ({})
+  back_trace_information.depth = {}
+  back_trace_information.index = {}
 ",
-            self.desc.start_of_memory_range, self.desc.memory.data_size, self.desc.memory.rva,
+            self.raw.handle,
+            self.raw.process_id,
+            self.raw.thread_id,
+            self.raw.operation_type,
+            self.operation
+                .map_or_else(|| String::from("unknown"), |op| format!("{op:?}")),
+            self.raw.back_trace_information.depth,
+            self.raw.back_trace_information.index,
         )?;
-        if !brief {
-            writeln!(f, "Memory")?;
-            self.print_contents(f)?;
+        for (i, address) in self.backtrace().iter().enumerate() {
+            writeln!(f, "  return_addresses[{i}] = {address:#x}")?;
         }
         writeln!(f)
     }
 }
 
-impl<'a> MinidumpMemory64<'a> {
-    /// Write a human-readable description of this `MinidumpMemory64` to `f`.
-    ///
-    /// This is very verbose, it is the format used by `minidump_dump`.
-    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+impl MinidumpHandleOperationList {
+    /// Iterate over the operations in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.operations.iter()
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
         write!(
             f,
-            "MINIDUMP_MEMORY_DESCRIPTOR64
-  start_of_memory_range = {:#x}
-  memory.data_size      = {:#x}
+            "MinidumpHandleOperationList
+  operation_count = {}
+
 ",
-            self.desc.start_of_memory_range, self.desc.data_size,
+            self.operations.len()
         )?;
-        if !brief {
-            writeln!(f, "Memory")?;
-            self.print_contents(f)?;
+        for (i, operation) in self.operations.iter().enumerate() {
+            writeln!(f, "operation[{i}]")?;
+            operation.print(f)?;
         }
-        writeln!(f)
+        Ok(())
     }
 }
 
-impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
-    /// Get `mem::size_of::<T>()` bytes of memory at `addr` from this region.
-    ///
-    /// Return `None` if the requested address range falls out of the bounds
-    /// of this memory region.
-    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
-    where
-        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
-    {
-        // XXX: Instead of checking the base+size validity on each access, maybe
-        // move this check to a different place?
-        let _end = self.base_address.checked_add(self.size)?;
-        let start = addr.checked_sub(self.base_address)? as usize;
+impl<'a> MinidumpStream<'a> for MinidumpHandleOperationList {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32;
 
-        self.bytes.pread_with::<T>(start, self.endian).ok()
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpHandleOperationList, Error> {
+        let header: md::MINIDUMP_HANDLE_OPERATION_LIST = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_entry = header.size_of_entry as usize;
+        if size_of_entry < md::AVRF_HANDLE_OPERATION::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let (number_of_entries, _) = ensure_count_in_bound(
+            bytes,
+            header.number_of_entries as usize,
+            size_of_entry,
+            header.size_of_header as usize,
+        )?;
+
+        let mut operations = Vec::with_capacity(number_of_entries);
+        for i in 0..number_of_entries {
+            let offset = header.size_of_header as usize + i * size_of_entry;
+            let raw: md::AVRF_HANDLE_OPERATION = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let operation = md::eHANDLE_TRACE_OPERATIONS::from_u32(raw.operation_type);
+            operations.push(MinidumpHandleOperation { raw, operation });
+        }
+
+        Ok(MinidumpHandleOperationList { operations })
     }
+}
 
-    /// Write the contents of this `MinidumpMemory` to `f` as a hex string.
-    pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        const PARAGRAPH_SIZE: usize = 16;
-        let mut offset = 0;
-        for paragraph in self.bytes.chunks(PARAGRAPH_SIZE) {
-            write!(f, "    {offset:08x}: ")?;
-            let mut byte_iter = paragraph.iter().fuse();
-            for _ in 0..PARAGRAPH_SIZE {
-                if let Some(byte) = byte_iter.next() {
+impl<'a> MinidumpFunctionTable<'a> {
+    /// Returns `true` if `address` is covered by this table.
+    pub fn contains_address(&self, address: u64) -> bool {
+        self.raw.minimum_address <= address && address <= self.raw.maximum_address
+    }
+
+    /// Find the function entry for the code at `address`.
+    pub fn function_at_address(&self, address: u64) -> Option<&md::IMAGE_RUNTIME_FUNCTION_ENTRY> {
+        let rva: u32 = address
+            .checked_sub(self.raw.base_address)?
+            .try_into()
+            .ok()?;
+        let idx = self
+            .functions
+            .partition_point(|function| function.end_address <= rva);
+        self.functions
+            .get(idx)
+            .filter(|function| function.begin_address <= rva)
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_FUNCTION_TABLE_DESCRIPTOR
+  minimum_address   = {:#x}
+  maximum_address   = {:#x}
+  base_address      = {:#x}
+  entry_count       = {}
+  size_of_align_pad = {}
+",
+            self.raw.minimum_address,
+            self.raw.maximum_address,
+            self.raw.base_address,
+            self.raw.entry_count,
+            self.raw.size_of_align_pad,
+        )?;
+        for (i, function) in self.functions.iter().enumerate() {
+            writeln!(
+                f,
+                "  functions[{}] = [{:#x}, {:#x}) unwind_info = {:#x}",
+                i, function.begin_address, function.end_address, function.unwind_info_address,
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpFunctionTableList<'a> {
+    /// Iterate over the tables in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpFunctionTable<'a>> {
+        self.tables.iter()
+    }
+
+    /// Find the function entry for the code at `address`, and the table it's in.
+    pub fn function_at_address(
+        &self,
+        address: u64,
+    ) -> Option<(
+        &MinidumpFunctionTable<'a>,
+        &md::IMAGE_RUNTIME_FUNCTION_ENTRY,
+    )> {
+        self.tables
+            .iter()
+            .filter(|table| table.contains_address(address))
+            .find_map(|table| Some((table, table.function_at_address(address)?)))
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpFunctionTableList
+  table_count = {}
+
+",
+            self.tables.len()
+        )?;
+        for (i, table) in self.tables.iter().enumerate() {
+            writeln!(f, "table[{i}]")?;
+            table.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpFunctionTableList<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::FunctionTable as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpFunctionTableList<'a>, Error> {
+        let header: md::MINIDUMP_FUNCTION_TABLE_STREAM = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_descriptor = header.size_of_descriptor as usize;
+        if size_of_descriptor < md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let size_of_native_descriptor = header.size_of_native_descriptor as usize;
+        let size_of_function_entry = header.size_of_function_entry as usize;
+        let is_runtime_function =
+            size_of_function_entry == md::IMAGE_RUNTIME_FUNCTION_ENTRY::size_with(&endian);
+
+        let mut offset = (header.size_of_header as usize)
+            .checked_add(header.size_of_align_pad as usize)
+            .ok_or(Error::StreamReadFailure)?;
+        let mut tables = vec![];
+        for _ in 0..header.number_of_descriptors {
+            let raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            offset += size_of_descriptor;
+
+            let native_descriptor = bytes
+                .get(offset..)
+                .and_then(|rest| rest.get(..size_of_native_descriptor))
+                .ok_or(Error::StreamReadFailure)?;
+            offset += size_of_native_descriptor;
+
+            let (entry_count, end) = ensure_count_in_bound(
+                bytes,
+                raw.entry_count as usize,
+                size_of_function_entry,
+                offset,
+            )?;
+            let raw_entries = &bytes[offset..end];
+            let mut functions = vec![];
+            if is_runtime_function {
+                functions.reserve(entry_count);
+                for i in 0..entry_count {
+                    let function: md::IMAGE_RUNTIME_FUNCTION_ENTRY = raw_entries
+                        .pread_with(i * size_of_function_entry, endian)
+                        .or(Err(Error::StreamReadFailure))?;
+                    functions.push(function);
+                }
+                // The tables should already be sorted, but don't rely on it.
+                functions.sort_by_key(|function| function.begin_address);
+            }
+            offset = end
+                .checked_add(raw.size_of_align_pad as usize)
+                .ok_or(Error::StreamReadFailure)?;
+
+            tables.push(MinidumpFunctionTable {
+                raw,
+                native_descriptor,
+                raw_entries,
+                functions,
+            });
+        }
+
+        Ok(MinidumpFunctionTableList { tables })
+    }
+}
+
+impl MinidumpSid {
+    /// Read a SID from the start of `bytes`, returning it and its size.
+    fn read(bytes: &[u8], endian: scroll::Endian) -> Option<(MinidumpSid, usize)> {
+        let mut offset = 0;
+        let revision: u8 = bytes.gread(&mut offset).ok()?;
+        let sub_authority_count: u8 = bytes.gread(&mut offset).ok()?;
+        // SIDs never have more than 15 sub-authorities.
+        if revision != 1 || sub_authority_count > 15 {
+            return None;
+        }
+        // The identifier authority is always big-endian.
+        let identifier_authority = bytes
+            .get(offset..offset + 6)?
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        offset += 6;
+        let sub_authorities = (0..sub_authority_count)
+            .map(|_| bytes.gread_with::<u32>(&mut offset, endian).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some((
+            MinidumpSid {
+                revision,
+                identifier_authority,
+                sub_authorities,
+            },
+            offset,
+        ))
+    }
+}
+
+impl fmt::Display for MinidumpSid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S-{}-{}", self.revision, self.identifier_authority)?;
+        for sub_authority in &self.sub_authorities {
+            write!(f, "-{sub_authority}")?;
+        }
+        Ok(())
+    }
+}
+
+impl MinidumpTokenPrivilege {
+    /// Whether the privilege was enabled (`SE_PRIVILEGE_ENABLED`).
+    pub fn is_enabled(&self) -> bool {
+        self.attributes & 0x2 != 0
+    }
+
+    /// The name of the privilege (e.g. `SeDebugPrivilege`), if it is a
+    /// well-known one.
+    pub fn name(&self) -> Option<&'static str> {
+        const PRIVILEGES: [&str; 35] = [
+            "SeCreateTokenPrivilege",
+            "SeAssignPrimaryTokenPrivilege",
+            "SeLockMemoryPrivilege",
+            "SeIncreaseQuotaPrivilege",
+            "SeMachineAccountPrivilege",
+            "SeTcbPrivilege",
+            "SeSecurityPrivilege",
+            "SeTakeOwnershipPrivilege",
+            "SeLoadDriverPrivilege",
+            "SeSystemProfilePrivilege",
+            "SeSystemtimePrivilege",
+            "SeProfileSingleProcessPrivilege",
+            "SeIncreaseBasePriorityPrivilege",
+            "SeCreatePagefilePrivilege",
+            "SeCreatePermanentPrivilege",
+            "SeBackupPrivilege",
+            "SeRestorePrivilege",
+            "SeShutdownPrivilege",
+            "SeDebugPrivilege",
+            "SeAuditPrivilege",
+            "SeSystemEnvironmentPrivilege",
+            "SeChangeNotifyPrivilege",
+            "SeRemoteShutdownPrivilege",
+            "SeUndockPrivilege",
+            "SeSyncAgentPrivilege",
+            "SeEnableDelegationPrivilege",
+            "SeManageVolumePrivilege",
+            "SeImpersonatePrivilege",
+            "SeCreateGlobalPrivilege",
+            "SeTrustedCredManAccessPrivilege",
+            "SeRelabelPrivilege",
+            "SeIncreaseWorkingSetPrivilege",
+            "SeTimeZonePrivilege",
+            "SeCreateSymbolicLinkPrivilege",
+            "SeDelegateSessionUserImpersonatePrivilege",
+        ];
+        // The well-known privileges have fixed LUIDs, starting at 2.
+        let index = self.luid.checked_sub(2)?;
+        PRIVILEGES.get::<usize>(index.try_into().ok()?).copied()
+    }
+}
+
+impl MinidumpTokenAccessInfo {
+    /// Decode a `TOKEN_ACCESS_INFORMATION` structure.
+    ///
+    /// This is what `GetTokenInformation(TokenAccessInformation)` returns: a
+    /// fixed-size header followed by the SIDs and privileges it points to. The
+    /// pointers are absolute addresses in the process that wrote the minidump,
+    /// so they are rebased on the assumption that the SIDs immediately follow
+    /// the header, which is how Windows lays it out.
+    fn read(
+        bytes: &[u8],
+        endian: scroll::Endian,
+        pointer_width: PointerWidth,
+    ) -> Option<MinidumpTokenAccessInfo> {
+        let ptr_size = pointer_width.size_in_bytes()? as usize;
+        let read_ptr = |offset: usize| -> Option<u64> {
+            match ptr_size {
+                4 => bytes.pread_with::<u32>(offset, endian).ok().map(u64::from),
+                _ => bytes.pread_with::<u64>(offset, endian).ok(),
+            }
+        };
+        let read_u32 = |offset: usize| bytes.pread_with::<u32>(offset, endian).ok();
+        let align = |offset: usize| offset.div_ceil(ptr_size) * ptr_size;
+
+        // SidHash, RestrictedSidHash, Privileges
+        let luid_offset = 3 * ptr_size;
+        // AuthenticationId, TokenType, ImpersonationLevel, MandatoryPolicy,
+        // Flags, AppContainerNumber
+        let fixed_size = align(luid_offset + 28)
+            // PackageSid, CapabilitiesHash, TrustLevelSid, SecurityAttributes
+            + 4 * ptr_size;
+
+        let sid_hash_ptr = read_ptr(0)?;
+        let base = sid_hash_ptr.checked_sub(fixed_size as u64)?;
+        let rebase = |ptr: u64| -> Option<usize> {
+            let offset: usize = ptr.checked_sub(base)?.try_into().ok()?;
+            Some(offset).filter(|&offset| offset < bytes.len())
+        };
+        let read_luid = |offset: usize| -> Option<u64> {
+            let low = read_u32(offset)?;
+            let high = read_u32(offset + 4)?;
+            Some((high as u64) << 32 | low as u64)
+        };
+
+        // SID_AND_ATTRIBUTES_HASH: SidCount, SidAttr, Hash
+        let sid_hash = rebase(sid_hash_ptr)?;
+        let sid_count = read_u32(sid_hash)? as usize;
+        let sid_attrs = rebase(read_ptr(sid_hash + ptr_size)?)?;
+        // SID_AND_ATTRIBUTES: Sid, Attributes
+        let sid_attr_size = 2 * ptr_size;
+        let (sid_count, _) =
+            ensure_count_in_bound(bytes, sid_count, sid_attr_size, sid_attrs).ok()?;
+        let mut sids = (0..sid_count)
+            .map(|i| {
+                let offset = sid_attrs + i * sid_attr_size;
+                let (sid, _) = MinidumpSid::read(&bytes[rebase(read_ptr(offset)?)?..], endian)?;
+                let attributes = read_u32(offset + ptr_size)?;
+                Some(MinidumpSidAndAttributes { sid, attributes })
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter();
+        // The user always comes first.
+        let user = sids.next();
+        let groups = sids.collect();
+
+        // TOKEN_PRIVILEGES: PrivilegeCount, Privileges
+        let privileges = match read_ptr(2 * ptr_size)? {
+            0 => vec![],
+            ptr => {
+                let privileges = rebase(ptr)?;
+                let count = read_u32(privileges)? as usize;
+                // LUID_AND_ATTRIBUTES: Luid, Attributes
+                let (count, _) = ensure_count_in_bound(bytes, count, 12, privileges + 4).ok()?;
+                (0..count)
+                    .map(|i| {
+                        let offset = privileges + 4 + i * 12;
+                        Some(MinidumpTokenPrivilege {
+                            luid: read_luid(offset)?,
+                            attributes: read_u32(offset + 8)?,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+        };
+
+        Some(MinidumpTokenAccessInfo {
+            authentication_id: read_luid(luid_offset)?,
+            token_type: read_u32(luid_offset + 8)?,
+            impersonation_level: read_u32(luid_offset + 12)?,
+            flags: read_u32(luid_offset + 20)?,
+            user,
+            groups,
+            privileges,
+        })
+    }
+}
+
+impl<'a> MinidumpToken<'a> {
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_TOKEN_INFO_HEADER
+  token_size   = {}
+  token_id     = {:#x}
+  token_handle = {:#x}
+",
+            self.raw.token_size, self.raw.token_id, self.raw.token_handle,
+        )?;
+        if let Some(info) = &self.access_info {
+            writeln!(f, "  (authentication_id)   = {:#x}", info.authentication_id)?;
+            writeln!(f, "  (token_type)          = {}", info.token_type)?;
+            writeln!(f, "  (impersonation_level) = {}", info.impersonation_level)?;
+            writeln!(f, "  (flags)               = {:#x}", info.flags)?;
+            if let Some(user) = &info.user {
+                writeln!(f, "  (user)                = {}", user.sid)?;
+            }
+            for group in &info.groups {
+                writeln!(
+                    f,
+                    "  (group)               = {} ({:#x})",
+                    group.sid, group.attributes
+                )?;
+            }
+            for privilege in &info.privileges {
+                writeln!(
+                    f,
+                    "  (privilege)           = {} ({:#x})",
+                    privilege
+                        .name()
+                        .map_or_else(|| format!("{:#x}", privilege.luid), String::from),
+                    privilege.attributes
+                )?;
+            }
+        } else {
+            writeln!(f, "  (data)                = {} bytes", self.data.len())?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpTokenList<'a> {
+    /// Iterate over the tokens in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpToken<'a>> {
+        self.tokens.iter()
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpTokenList
+  token_count = {}
+
+",
+            self.tokens.len()
+        )?;
+        for (i, token) in self.tokens.iter().enumerate() {
+            writeln!(f, "token[{i}]")?;
+            token.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpTokenList<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::TokenStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpTokenList<'a>, Error> {
+        let header: md::MINIDUMP_TOKEN_INFO_LIST = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let element_header_size = header.element_header_size as usize;
+        if element_header_size < md::MINIDUMP_TOKEN_INFO_HEADER::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let pointer_width =
+            system_info.map_or(PointerWidth::Unknown, |info| info.cpu.pointer_width());
+
+        // The tokens are variable-sized, each one says how big it is.
+        let mut offset = header.list_header_size as usize;
+        let mut tokens = vec![];
+        for _ in 0..header.token_list_entries {
+            let raw: md::MINIDUMP_TOKEN_INFO_HEADER = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let token_size = raw.token_size as usize;
+            let data = bytes
+                .get(offset..)
+                .and_then(|bytes| bytes.get(element_header_size..token_size))
+                .ok_or(Error::StreamReadFailure)?;
+            let access_info = MinidumpTokenAccessInfo::read(data, endian, pointer_width);
+            tokens.push(MinidumpToken {
+                raw,
+                data,
+                access_info,
+            });
+            offset += token_size;
+        }
+
+        Ok(MinidumpTokenList { tokens })
+    }
+}
+
+impl<'a> MinidumpMemory<'a> {
+    pub fn read(
+        desc: &md::MINIDUMP_MEMORY_DESCRIPTOR,
+        data: &'a [u8],
+        endian: scroll::Endian,
+    ) -> Result<MinidumpMemory<'a>, Error> {
+        if desc.memory.rva == 0 || desc.memory.data_size == 0 {
+            // Windows will sometimes emit null stack RVAs, indicating that
+            // we need to lookup the address in a memory region. It's ok to
+            // emit an error for that here, the thread processing code will
+            // catch it.
+            return Err(Error::MemoryReadFailure);
+        }
+        let bytes = location_slice(data, &desc.memory).or(Err(Error::StreamReadFailure))?;
+        Ok(MinidumpMemory {
+            desc: *desc,
+            base_address: desc.start_of_memory_range,
+            size: desc.memory.data_size as u64,
+            bytes,
+            endian,
+        })
+    }
+
+    /// Write a human-readable description of this `MinidumpMemory` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_MEMORY_DESCRIPTOR
+  start_of_memory_range = {:#x}
+  memory.data_size      = {:#x}
+  memory.rva            = {:#x}
+",
+            self.desc.start_of_memory_range, self.desc.memory.data_size, self.desc.memory.rva,
+        )?;
+        if !brief {
+            writeln!(f, "Memory")?;
+            self.print_contents(f)?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpMemory64<'a> {
+    /// Write a human-readable description of this `MinidumpMemory64` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_MEMORY_DESCRIPTOR64
+  start_of_memory_range = {:#x}
+  memory.data_size      = {:#x}
+",
+            self.desc.start_of_memory_range, self.desc.data_size,
+        )?;
+        if !brief {
+            writeln!(f, "Memory")?;
+            self.print_contents(f)?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpLazyMemory64<'a> {
+    /// The contents of the memory, which are read from the file the first time they're
+    /// requested.
+    ///
+    /// If the contents can't be read, this is empty.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.stream
+            .memory(self.index, self.count, self.offset, self.size)
+    }
+
+    /// This region, with its contents read.
+    pub fn load(&self) -> MinidumpMemory64<'a> {
+        self.with_bytes(self.bytes())
+    }
+
+    fn with_bytes(&self, bytes: &'a [u8]) -> MinidumpMemory64<'a> {
+        MinidumpMemory64 {
+            desc: self.desc,
+            base_address: self.base_address,
+            size: self.size,
+            bytes,
+            endian: self.endian,
+        }
+    }
+
+    /// Get `mem::size_of::<T>()` bytes of memory at `addr` from this region.
+    ///
+    /// Return `None` if the requested address range falls out of the bounds
+    /// of this memory region.
+    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
+    where
+        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        self.load().get_memory_at_address(addr)
+    }
+
+    pub fn memory_range(&self) -> Option<Range<u64>> {
+        self.with_bytes(&[]).memory_range()
+    }
+
+    /// Write the contents of this region to `f` as a hex string.
+    pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        self.load().print_contents(f)
+    }
+
+    /// Write a human-readable description of this region to `f`, in the same format as
+    /// [`MinidumpMemory64::print`][].
+    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+        // The brief format leaves out the contents, so don't read them for it.
+        let bytes = if brief { &[] } else { self.bytes() };
+        self.with_bytes(bytes).print(f, brief)
+    }
+}
+
+impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
+    /// Get `mem::size_of::<T>()` bytes of memory at `addr` from this region.
+    ///
+    /// Return `None` if the requested address range falls out of the bounds
+    /// of this memory region.
+    pub fn get_memory_at_address<T>(&self, addr: u64) -> Option<T>
+    where
+        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        // XXX: Instead of checking the base+size validity on each access, maybe
+        // move this check to a different place?
+        let _end = self.base_address.checked_add(self.size)?;
+        let start = addr.checked_sub(self.base_address)? as usize;
+
+        self.bytes.pread_with::<T>(start, self.endian).ok()
+    }
+
+    /// Write the contents of this `MinidumpMemory` to `f` as a hex string.
+    pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        const PARAGRAPH_SIZE: usize = 16;
+        let mut offset = 0;
+        for paragraph in self.bytes.chunks(PARAGRAPH_SIZE) {
+            write!(f, "    {offset:08x}: ")?;
+            let mut byte_iter = paragraph.iter().fuse();
+            for _ in 0..PARAGRAPH_SIZE {
+                if let Some(byte) = byte_iter.next() {
                     write!(f, "{byte:02x} ")?;
                 } else {
                     write!(f, "   ")?;
@@ -2097,6 +3144,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.get_memory_at_address(addr),
             UnifiedMemory::Memory64(this) => this.get_memory_at_address(addr),
+            UnifiedMemory::LazyMemory64(this) => this.get_memory_at_address(addr),
         }
     }
 
@@ -2104,6 +3152,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.memory_range(),
             UnifiedMemory::Memory64(this) => this.memory_range(),
+            UnifiedMemory::LazyMemory64(this) => this.memory_range(),
         }
     }
 
@@ -2111,6 +3160,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.bytes,
             UnifiedMemory::Memory64(this) => this.bytes,
+            UnifiedMemory::LazyMemory64(this) => this.bytes(),
         }
     }
 
@@ -2118,6 +3168,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.base_address,
             UnifiedMemory::Memory64(this) => this.base_address,
+            UnifiedMemory::LazyMemory64(this) => this.base_address,
         }
     }
 
@@ -2125,6 +3176,15 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.size,
             UnifiedMemory::Memory64(this) => this.size,
+            UnifiedMemory::LazyMemory64(this) => this.size,
+        }
+    }
+
+    pub fn endian(&self) -> scroll::Endian {
+        match self {
+            UnifiedMemory::Memory(this) => this.endian,
+            UnifiedMemory::Memory64(this) => this.endian,
+            UnifiedMemory::LazyMemory64(this) => this.endian,
         }
     }
 
@@ -2132,6 +3192,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.print_contents(f),
             UnifiedMemory::Memory64(this) => this.print_contents(f),
+            UnifiedMemory::LazyMemory64(this) => this.print_contents(f),
         }
     }
 
@@ -2139,6 +3200,7 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         match self {
             UnifiedMemory::Memory(this) => this.print(f, brief),
             UnifiedMemory::Memory64(this) => this.print(f, brief),
+            UnifiedMemory::LazyMemory64(this) => this.print(f, brief),
         }
     }
 }
@@ -2241,6 +3303,79 @@ impl<'mdmp> MinidumpMemory64List<'mdmp> {
     }
 }
 
+impl<'mdmp> MinidumpLazyMemory64List<'mdmp> {
+    /// Read the `Memory64ListStream` `stream`, without reading the contents of its regions.
+    fn read(
+        stream: LazyStream<'mdmp>,
+        endian: scroll::Endian,
+    ) -> Result<MinidumpLazyMemory64List<'mdmp>, Error> {
+        let descriptors = read_memory64_descriptors(stream.bytes()?, stream.file_len(), endian)?;
+        let count = descriptors.len();
+        let regions: Vec<_> = descriptors
+            .into_iter()
+            .enumerate()
+            .map(|(index, (desc, offset))| MinidumpLazyMemory64 {
+                desc,
+                base_address: desc.start_of_memory_range,
+                size: desc.data_size,
+                endian,
+                stream,
+                index,
+                count,
+                offset,
+            })
+            .collect();
+        let regions_by_addr = regions
+            .iter()
+            .enumerate()
+            .map(|(i, region)| (region.memory_range(), i))
+            .into_rangemap_safe();
+        Ok(MinidumpLazyMemory64List {
+            regions,
+            regions_by_addr,
+        })
+    }
+
+    /// Return the region containing memory at `address`, if one exists.
+    pub fn memory_at_address(&self, address: u64) -> Option<&MinidumpLazyMemory64<'mdmp>> {
+        self.regions_by_addr
+            .get(address)
+            .map(|&index| &self.regions[index])
+    }
+
+    /// Iterate over the memory regions in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpLazyMemory64<'mdmp>> {
+        self.regions.iter()
+    }
+
+    /// Iterate over the memory regions in order by memory address.
+    pub fn by_addr(&self) -> impl Iterator<Item = &MinidumpLazyMemory64<'mdmp>> {
+        self.regions_by_addr
+            .ranges_values()
+            .map(move |&(_, index)| &self.regions[index])
+    }
+
+    /// Write a human-readable description of this list to `f`, in the same format as
+    /// [`MinidumpMemory64List::print`][].
+    ///
+    /// Unless `brief` is set, this reads the contents of every region.
+    pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpMemory64List
+  region_count = {}
+
+",
+            self.regions.len()
+        )?;
+        for (i, region) in self.regions.iter().enumerate() {
+            writeln!(f, "region[{i}]")?;
+            region.print(f, brief)?;
+        }
+        Ok(())
+    }
+}
+
 impl<'mdmp> UnifiedMemoryList<'mdmp> {
     pub fn memory_at_address<'slf>(&'slf self, address: u64) -> Option<UnifiedMemory<'slf, 'mdmp>> {
         match self {
@@ -2250,6 +3385,9 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
             UnifiedMemoryList::Memory64(this) => {
                 this.memory_at_address(address).map(UnifiedMemory::Memory64)
             }
+            UnifiedMemoryList::LazyMemory64(this) => this
+                .memory_at_address(address)
+                .map(UnifiedMemory::LazyMemory64),
         }
     }
 
@@ -2264,10 +3402,16 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
         } else {
             None
         };
+        let iter3 = if let UnifiedMemoryList::LazyMemory64(this) = self {
+            Some(this.iter().map(UnifiedMemory::LazyMemory64))
+        } else {
+            None
+        };
         iter1
             .into_iter()
             .flatten()
             .chain(iter2.into_iter().flatten())
+            .chain(iter3.into_iter().flatten())
     }
 
     /// Iterate over the memory regions in order by memory address.
@@ -2282,17 +3426,182 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
         } else {
             None
         };
+        let iter3 = if let UnifiedMemoryList::LazyMemory64(this) = self {
+            Some(this.by_addr().map(UnifiedMemory::LazyMemory64))
+        } else {
+            None
+        };
         iter1
             .into_iter()
             .flatten()
             .chain(iter2.into_iter().flatten())
+            .chain(iter3.into_iter().flatten())
     }
 
     pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
         match self {
             UnifiedMemoryList::Memory(this) => this.print(f, brief),
             UnifiedMemoryList::Memory64(this) => this.print(f, brief),
+            UnifiedMemoryList::LazyMemory64(this) => this.print(f, brief),
+        }
+    }
+}
+
+impl<'a, 'mdmp> MinidumpAddressSpace<'a, 'mdmp> {
+    /// Return an empty `MinidumpAddressSpace`.
+    pub fn new() -> MinidumpAddressSpace<'a, 'mdmp> {
+        Self::default()
+    }
+
+    /// Add `region` to this address space, behind everything that's already in it.
+    pub fn with_region(mut self, region: UnifiedMemory<'a, 'mdmp>) -> Self {
+        self.layers.push(AddressSpaceLayer::Region(region));
+        self
+    }
+
+    /// Add the regions of `memory_list` to this address space, behind everything that's
+    /// already in it.
+    pub fn with_memory_list(mut self, memory_list: &'a UnifiedMemoryList<'mdmp>) -> Self {
+        self.layers.push(AddressSpaceLayer::List(memory_list));
+        self
+    }
+
+    /// Return the region containing memory at `address`, if one exists.
+    pub fn memory_at_address(&self, address: u64) -> Option<UnifiedMemory<'a, 'mdmp>> {
+        self.layers.iter().find_map(|layer| match *layer {
+            AddressSpaceLayer::Region(region) => region
+                .memory_range()
+                .filter(|range| range.contains(address))
+                .map(|_| region),
+            AddressSpaceLayer::List(memory_list) => memory_list.memory_at_address(address),
+        })
+    }
+
+    /// Return the range of contiguous memory around `address`, which may span several
+    /// adjacent regions.
+    pub fn memory_range_at_address(&self, address: u64) -> Option<Range<u64>> {
+        let mut start = address;
+        while let Some(previous) = start
+            .checked_sub(1)
+            .filter(|&previous| self.chunks(previous).next().is_some())
+        {
+            start = self.memory_at_address(previous)?.base_address();
+        }
+        let end = self
+            .chunks(address)
+            .try_fold(address, |end, chunk| end.checked_add(chunk.len() as u64))?;
+        if end == address {
+            return None;
+        }
+        Some(Range::new(start, end - 1))
+    }
+
+    /// Iterate over the contiguous memory starting at `address`, a region at a time.
+    fn chunks(&self, address: u64) -> impl Iterator<Item = &'a [u8]> + '_ {
+        let mut next = Some(address);
+        iter::from_fn(move || {
+            let address = next?;
+            let region = self.memory_at_address(address)?;
+            let offset: usize = (address - region.base_address()).try_into().ok()?;
+            // The contents of a region can be shorter than its size if the minidump is
+            // truncated, in which case the memory ends there.
+            let chunk = region
+                .bytes()
+                .get(offset..)
+                .filter(|chunk| !chunk.is_empty())?;
+            next = address.checked_add(chunk.len() as u64);
+            Some(chunk)
+        })
+    }
+
+    /// Read up to `max_len` bytes of memory at `address`, which may span several adjacent
+    /// regions.
+    ///
+    /// This returns fewer bytes if the memory ends before that, and `None` if there's no
+    /// memory at `address`. The bytes are only copied if they span several regions.
+    pub fn read_bytes_up_to(&self, address: u64, max_len: usize) -> Option<Cow<'a, [u8]>> {
+        let mut chunks = self.chunks(address);
+        let first = chunks.next()?;
+        if first.len() >= max_len {
+            return Some(Cow::Borrowed(&first[..max_len]));
+        }
+        let mut bytes = first.to_vec();
+        for chunk in chunks {
+            let remaining = max_len - bytes.len();
+            bytes.extend_from_slice(&chunk[..remaining.min(chunk.len())]);
+            if bytes.len() == max_len {
+                break;
+            }
         }
+        Some(Cow::Owned(bytes))
+    }
+
+    /// Read `len` bytes of memory at `address`, which may span several adjacent regions.
+    ///
+    /// Return `None` if any of the requested memory is missing.
+    pub fn read_bytes(&self, address: u64, len: usize) -> Option<Cow<'a, [u8]>> {
+        self.read_bytes_up_to(address, len)
+            .filter(|bytes| bytes.len() == len)
+    }
+
+    /// Get `mem::size_of::<T>()` bytes of memory at `address`, which may span several
+    /// adjacent regions.
+    ///
+    /// Return `None` if any of the requested memory is missing.
+    pub fn get_memory_at_address<T>(&self, address: u64) -> Option<T>
+    where
+        T: for<'b> TryFromCtx<'b, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        let endian = self.memory_at_address(address)?.endian();
+        let bytes = self.read_bytes(address, mem::size_of::<T>())?;
+        bytes.pread_with::<T>(0, endian).ok()
+    }
+
+    /// Read the `u32` at `address`.
+    pub fn read_u32(&self, address: u64) -> Option<u32> {
+        self.get_memory_at_address(address)
+    }
+
+    /// Read the `u64` at `address`.
+    pub fn read_u64(&self, address: u64) -> Option<u64> {
+        self.get_memory_at_address(address)
+    }
+
+    /// Read the NUL-terminated string at `address`, without its terminator.
+    ///
+    /// Return `None` if the string isn't terminated within `max_len` bytes, including the
+    /// terminator, or before the memory at `address` ends.
+    pub fn read_c_string(&self, address: u64, max_len: usize) -> Option<Cow<'a, [u8]>> {
+        let bytes = self.read_bytes_up_to(address, max_len)?;
+        let len = bytes.iter().position(|&byte| byte == 0)?;
+        Some(match bytes {
+            Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[..len]),
+            Cow::Owned(mut bytes) => {
+                bytes.truncate(len);
+                Cow::Owned(bytes)
+            }
+        })
+    }
+
+    /// Read the NUL-terminated UTF-16 string at `address`.
+    ///
+    /// Return `None` if the string isn't terminated within `max_len` code units, including
+    /// the terminator, or before the memory at `address` ends, or if it isn't valid UTF-16.
+    pub fn read_utf16_string(&self, address: u64, max_len: usize) -> Option<String> {
+        let endian = self.memory_at_address(address)?.endian();
+        let bytes = self.read_bytes_up_to(address, max_len.checked_mul(2)?)?;
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .filter_map(|unit| unit.pread_with(0, endian).ok())
+            .collect();
+        let len = units.iter().position(|&unit| unit == 0)?;
+        String::from_utf16(&units[..len]).ok()
+    }
+}
+
+impl<'a, 'mdmp> From<&'a UnifiedMemoryList<'mdmp>> for MinidumpAddressSpace<'a, 'mdmp> {
+    fn from(memory_list: &'a UnifiedMemoryList<'mdmp>) -> Self {
+        MinidumpAddressSpace::new().with_memory_list(memory_list)
     }
 }
 
@@ -2337,59 +3646,86 @@ impl<'a> MinidumpStream<'a> for MinidumpMemory64List<'a> {
         endian: scroll::Endian,
         _system_info: Option<&MinidumpSystemInfo>,
     ) -> Result<MinidumpMemory64List<'a>, Error> {
-        let mut offset = 0;
-        let u: u64 = bytes
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::StreamReadFailure))?;
+        let regions = read_memory64_descriptors(bytes, all.len() as u64, endian)?
+            .into_iter()
+            .map(|(raw, rva)| MinidumpMemory64 {
+                desc: raw,
+                base_address: raw.start_of_memory_range,
+                size: raw.data_size,
+                bytes: &all[rva as usize..(rva + raw.data_size) as usize],
+                endian,
+            })
+            .collect();
+        Ok(MinidumpMemory64List::from_regions(regions))
+    }
 
-        let mut rva: u64 = bytes
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::StreamReadFailure))?;
+    fn read_lazy(
+        stream: LazyStream<'a>,
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpMemory64List<'a>, Error> {
+        // The contents of the regions aren't in `stream.all()`, so read them one by one.
+        // [`Minidump::get_memory`] avoids this by using a `MinidumpLazyMemory64List`.
+        let list = MinidumpLazyMemory64List::read(stream, endian)?;
+        Ok(MinidumpMemory64List::from_regions(
+            list.iter().map(MinidumpLazyMemory64::load).collect(),
+        ))
+    }
+}
 
-        let (count, counted_size) = ensure_count_in_bound(
-            bytes,
-            u.try_into().map_err(|_| Error::StreamReadFailure)?,
-            md::MINIDUMP_MEMORY_DESCRIPTOR64::size_with(&endian),
-            offset,
-        )?;
+/// Read the descriptors of a `Memory64ListStream`, and check that the contents of the regions
+/// fit in a file of `file_len` bytes.
+///
+/// Returns each descriptor with the offset of the region's contents in the file.
+fn read_memory64_descriptors(
+    bytes: &[u8],
+    file_len: u64,
+    endian: scroll::Endian,
+) -> Result<Vec<(md::MINIDUMP_MEMORY_DESCRIPTOR64, u64)>, Error> {
+    let mut offset = 0;
+    let u: u64 = bytes
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
 
-        if bytes.len() != counted_size {
-            return Err(Error::StreamSizeMismatch {
-                expected: counted_size,
-                actual: bytes.len(),
-            });
-        }
+    let mut rva: u64 = bytes
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
 
-        let mut raw_entries = Vec::with_capacity(count);
-        for _ in 0..count {
-            let raw: md::MINIDUMP_MEMORY_DESCRIPTOR64 = bytes
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::StreamReadFailure))?;
-            raw_entries.push(raw);
-        }
+    let (count, counted_size) = ensure_count_in_bound(
+        bytes,
+        u.try_into().map_err(|_| Error::StreamReadFailure)?,
+        md::MINIDUMP_MEMORY_DESCRIPTOR64::size_with(&endian),
+        offset,
+    )?;
 
-        let mut regions = Vec::with_capacity(raw_entries.len());
-        for raw in raw_entries {
-            let start = rva;
-            let end = rva
-                .checked_add(raw.data_size)
-                .ok_or(Error::StreamReadFailure)?;
-            let bytes = all
-                .get(start as usize..end as usize)
-                .ok_or(Error::StreamReadFailure)?;
+    if bytes.len() != counted_size {
+        return Err(Error::StreamSizeMismatch {
+            expected: counted_size,
+            actual: bytes.len(),
+        });
+    }
 
-            regions.push(MinidumpMemory64 {
-                desc: raw,
-                base_address: raw.start_of_memory_range,
-                size: raw.data_size,
-                bytes,
-                endian,
-            });
+    let mut raw_entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let raw: md::MINIDUMP_MEMORY_DESCRIPTOR64 = bytes
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        raw_entries.push(raw);
+    }
 
-            rva = end;
-        }
-        Ok(MinidumpMemory64List::from_regions(regions))
+    let mut regions = Vec::with_capacity(raw_entries.len());
+    for raw in raw_entries {
+        let start = rva;
+        let end = rva
+            .checked_add(raw.data_size)
+            .filter(|&end| end <= file_len)
+            .ok_or(Error::StreamReadFailure)?;
+
+        regions.push((raw, start));
+
+        rva = end;
     }
+    Ok(regions)
 }
 
 impl<'a> MinidumpStream<'a> for MinidumpMemoryInfoList<'a> {
@@ -3025,6 +4361,22 @@ impl<'a> MinidumpThread<'a> {
         })
     }
 
+    /// The memory this thread can read: its stack, backed by the regions of `memory_list`.
+    ///
+    /// Reads from the stack can run on into adjacent regions of `memory_list`, which is
+    /// where full-memory dumps keep the rest of large stacks.
+    pub fn stack_address_space<'mem>(
+        &'mem self,
+        memory_list: &'mem UnifiedMemoryList<'a>,
+    ) -> MinidumpAddressSpace<'mem, 'a> {
+        let address_space = MinidumpAddressSpace::new();
+        let address_space = match &self.stack {
+            Some(stack) => address_space.with_region(UnifiedMemory::Memory(stack)),
+            None => address_space,
+        };
+        address_space.with_memory_list(memory_list)
+    }
+
     /// Write a human-readable description of this `MinidumpThread` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -3212,6 +4564,147 @@ impl<'a> MinidumpThreadList<'a> {
     }
 }
 
+impl MinidumpThreadInfo {
+    /// When the thread was created, if known.
+    pub fn create_time(&self) -> Option<SystemTime> {
+        systemtime_from_filetime(self.raw.create_time)
+    }
+
+    /// When the thread exited, if it had exited.
+    pub fn exit_time(&self) -> Option<SystemTime> {
+        systemtime_from_filetime(self.raw.exit_time)
+    }
+
+    /// The time the thread spent executing in kernel mode.
+    pub fn kernel_time(&self) -> Duration {
+        duration_from_filetime_ticks(self.raw.kernel_time)
+    }
+
+    /// The time the thread spent executing in user mode.
+    pub fn user_time(&self) -> Duration {
+        duration_from_filetime_ticks(self.raw.user_time)
+    }
+
+    /// Whether the thread had already exited when the minidump was written.
+    pub fn has_exited(&self) -> bool {
+        self.dump_flags
+            .contains(md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_EXITED_THREAD)
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_THREAD_INFO
+  thread_id     = {:#x}
+  dump_flags    = {:#x}
+  dump_error    = {:#x}
+  exit_status   = {:#x}
+  create_time   = {:#x} {}
+  exit_time     = {:#x} {}
+  kernel_time   = {}
+  user_time     = {}
+  start_address = {:#x}
+  affinity      = {:#x}
+",
+            self.raw.thread_id,
+            self.dump_flags,
+            self.raw.dump_error,
+            self.raw.exit_status,
+            self.raw.create_time,
+            format_filetime(self.raw.create_time),
+            self.raw.exit_time,
+            format_filetime(self.raw.exit_time),
+            self.raw.kernel_time,
+            self.raw.user_time,
+            self.raw.start_address,
+            self.raw.affinity,
+        )?;
+        writeln!(f)
+    }
+}
+
+impl MinidumpThreadInfoList {
+    /// Return an empty `MinidumpThreadInfoList`.
+    pub fn new() -> MinidumpThreadInfoList {
+        MinidumpThreadInfoList {
+            thread_infos: vec![],
+            thread_ids: HashMap::new(),
+        }
+    }
+
+    /// Create a `MinidumpThreadInfoList` from a list of `MinidumpThreadInfo`s.
+    pub fn from_thread_infos(thread_infos: Vec<MinidumpThreadInfo>) -> MinidumpThreadInfoList {
+        let thread_ids = thread_infos
+            .iter()
+            .enumerate()
+            .map(|(i, info)| (info.raw.thread_id, i))
+            .collect();
+        MinidumpThreadInfoList {
+            thread_infos,
+            thread_ids,
+        }
+    }
+
+    /// Get the info for the thread with id `id`, if it exists.
+    pub fn get_thread_info(&self, id: u32) -> Option<&MinidumpThreadInfo> {
+        self.thread_ids
+            .get(&id)
+            .map(|&index| &self.thread_infos[index])
+    }
+
+    /// Iterate over the thread infos in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpThreadInfo> {
+        self.thread_infos.iter()
+    }
+
+    /// Write a human-readable description of this `MinidumpThreadInfoList` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpThreadInfoList
+  thread_info_count = {}
+
+",
+            self.thread_infos.len()
+        )?;
+        for (i, info) in self.thread_infos.iter().enumerate() {
+            writeln!(f, "thread_info[{i}]")?;
+            info.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for MinidumpThreadInfoList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpThreadInfoList {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::ThreadInfoListStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpThreadInfoList, Error> {
+        let mut offset = 0;
+        let raw_infos: Vec<md::MINIDUMP_THREAD_INFO> =
+            read_ex_stream_list(&mut offset, bytes, endian)?;
+        let thread_infos = raw_infos
+            .into_iter()
+            .map(|raw| MinidumpThreadInfo {
+                dump_flags: md::ThreadInfoDumpFlags::from_bits_truncate(raw.dump_flags),
+                raw,
+            })
+            .collect();
+        Ok(MinidumpThreadInfoList::from_thread_infos(thread_infos))
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::SystemInfoStream as u32;
 
@@ -3929,15 +5422,28 @@ impl MinidumpMacBootargs {
     }
 }
 
-impl<'a> MinidumpStream<'a> for MinidumpLinuxLsbRelease<'a> {
-    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxLsbRelease as u32;
+impl<'a> MinidumpStream<'a> for MinidumpLinuxLsbRelease<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxLsbRelease as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        _endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxLsbRelease<'a>, Error> {
+        Ok(Self { data: bytes })
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpLinuxCmdLine<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
 
     fn read(
         bytes: &'a [u8],
         _all: &'a [u8],
         _endian: scroll::Endian,
         _system_info: Option<&MinidumpSystemInfo>,
-    ) -> Result<MinidumpLinuxLsbRelease<'a>, Error> {
+    ) -> Result<MinidumpLinuxCmdLine<'a>, Error> {
         Ok(Self { data: bytes })
     }
 }
@@ -4010,6 +5516,26 @@ impl<'a> MinidumpLinuxCpuInfo<'a> {
     }
 }
 
+impl<'a> MinidumpLinuxCmdLine<'a> {
+    /// Get an iterator over the arguments stored in the `/proc/self/cmdline` dump.
+    ///
+    /// The kernel separates (and terminates) each argument with a NUL, so
+    /// this splits on NULs and drops the trailing empty argument. The first
+    /// item is usually the path of the executable (argv\[0\]).
+    pub fn iter(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
+        let data = self.data.strip_suffix(b"\0").unwrap_or(self.data);
+        let empty = data.is_empty();
+        LinuxOsStr::from_bytes(data)
+            .split(b'\0')
+            .filter(move |_| !empty)
+    }
+
+    /// Get the raw bytes of the `/proc/self/cmdline` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
 impl<'a> MinidumpLinuxEnviron<'a> {
     /// Get an iterator over the key-value pairs stored in the `/proc/self/environ` dump.
     ///
@@ -4074,6 +5600,358 @@ impl<'a> MinidumpLinuxLsbRelease<'a> {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpLinuxAuxv {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxAuxv as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxAuxv, Error> {
+        // The entries are pointer-sized, so we need to know what kind of
+        // process this was. Without a usable system info stream, guess from
+        // the size of the stream (a 64-bit auxv is always a multiple of 16 bytes).
+        let pointer_width = system_info
+            .map(|info| info.cpu.pointer_width())
+            .unwrap_or(PointerWidth::Unknown);
+        let is_64bit = match pointer_width {
+            PointerWidth::Bits64 => true,
+            PointerWidth::Bits32 => false,
+            PointerWidth::Unknown => bytes.len() % 16 != 8,
+        };
+        let entry_size = if is_64bit { 16 } else { 8 };
+
+        let mut entries = Vec::with_capacity(bytes.len() / entry_size);
+        let mut offset = 0;
+        while offset + entry_size <= bytes.len() {
+            let (raw_type, value) = if is_64bit {
+                let raw_type: u64 = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let value: u64 = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw_type, value)
+            } else {
+                let raw_type: u32 = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                let value: u32 = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw_type as u64, value as u64)
+            };
+            if raw_type == md::AuxvType::AT_NULL as u64 {
+                break;
+            }
+            entries.push(MinidumpLinuxAuxvEntry { raw_type, value });
+        }
+        Ok(MinidumpLinuxAuxv { entries })
+    }
+}
+
+impl MinidumpLinuxAuxvEntry {
+    /// The type of this entry, if it's one we know about.
+    pub fn auxv_type(&self) -> Option<md::AuxvType> {
+        md::AuxvType::from_u64(self.raw_type)
+    }
+}
+
+/// Read a nul-terminated string at `address` from the memory captured in a minidump.
+fn read_memory_cstring(memory: &UnifiedMemoryList, address: u64) -> Option<String> {
+    let region = memory.memory_at_address(address)?;
+    let offset = (address - region.base_address()) as usize;
+    let bytes = region.bytes().get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+}
+
+impl MinidumpLinuxAuxv {
+    /// Iterate over the entries of the auxiliary vector, in the order they were
+    /// present in the minidump (not including the terminating `AT_NULL`).
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpLinuxAuxvEntry> {
+        self.entries.iter()
+    }
+
+    /// Get the value of the first entry of type `auxv_type`, if present.
+    pub fn get(&self, auxv_type: md::AuxvType) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|entry| entry.raw_type == auxv_type as u64)
+            .map(|entry| entry.value)
+    }
+
+    /// The `AT_HWCAP` CPU capability bits.
+    ///
+    /// These are architecture-specific, see e.g. [`md::ArmElfHwCaps`].
+    pub fn hwcap(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_HWCAP)
+    }
+
+    /// The `AT_HWCAP2` extended CPU capability bits.
+    pub fn hwcap2(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_HWCAP2)
+    }
+
+    /// The base address of the vDSO (`AT_SYSINFO_EHDR`).
+    pub fn sysinfo_ehdr(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_SYSINFO_EHDR)
+    }
+
+    /// The base address of the program interpreter (`AT_BASE`).
+    pub fn base(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_BASE)
+    }
+
+    /// The address of the 16 random bytes provided by the kernel (`AT_RANDOM`).
+    pub fn random(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_RANDOM)
+    }
+
+    /// The 16 random bytes provided by the kernel, if their memory was captured.
+    pub fn random_bytes(&self, memory: &UnifiedMemoryList) -> Option<[u8; 16]> {
+        let address = self.random()?;
+        let region = memory.memory_at_address(address)?;
+        let offset = (address - region.base_address()) as usize;
+        region.bytes().get(offset..offset + 16)?.try_into().ok()
+    }
+
+    /// The platform string (`AT_PLATFORM`), if its memory was captured.
+    pub fn platform(&self, memory: &UnifiedMemoryList) -> Option<String> {
+        read_memory_cstring(memory, self.get(md::AuxvType::AT_PLATFORM)?)
+    }
+
+    /// The filename used to execute the program (`AT_EXECFN`), if its memory was captured.
+    pub fn execfn(&self, memory: &UnifiedMemoryList) -> Option<String> {
+        read_memory_cstring(memory, self.get(md::AuxvType::AT_EXECFN)?)
+    }
+
+    /// Write a human-readable description of this `MinidumpLinuxAuxv` to `f`.
+    ///
+    /// If `memory` is provided, string values will be resolved.
+    pub fn print<T: Write>(&self, f: &mut T, memory: Option<&UnifiedMemoryList>) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpLinuxAuxv
+  entry_count = {}
+
+",
+            self.entries.len()
+        )?;
+        for entry in &self.entries {
+            match entry.auxv_type() {
+                Some(auxv_type) => {
+                    write!(f, "  {:20} = {:#x}", format!("{auxv_type:?}"), entry.value)?
+                }
+                None => write!(f, "  {:20} = {:#x}", entry.raw_type, entry.value)?,
+            }
+            let is_string = matches!(
+                entry.auxv_type(),
+                Some(md::AuxvType::AT_PLATFORM)
+                    | Some(md::AuxvType::AT_BASE_PLATFORM)
+                    | Some(md::AuxvType::AT_EXECFN)
+            );
+            if let Some(string) = memory
+                .filter(|_| is_string)
+                .and_then(|memory| read_memory_cstring(memory, entry.value))
+            {
+                write!(f, " \"{string}\"")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Read a pointer-sized value at `address` from the memory captured in a minidump.
+fn read_memory_pointer(memory: &UnifiedMemoryList, address: u64, is_64bit: bool) -> Option<u64> {
+    let region = memory.memory_at_address(address)?;
+    if is_64bit {
+        region.get_memory_at_address::<u64>(address)
+    } else {
+        region
+            .get_memory_at_address::<u32>(address)
+            .map(|value| value as u64)
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpLinuxDsoDebug {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxDsoDebug, Error> {
+        let pointer_width = system_info
+            .map(|info| info.cpu.pointer_width())
+            .unwrap_or(PointerWidth::Unknown);
+        let is_64bit = match pointer_width {
+            PointerWidth::Bits64 => true,
+            PointerWidth::Bits32 => false,
+            PointerWidth::Unknown => bytes.len() >= md::DSO_DEBUG_64::size_with(&endian),
+        };
+
+        let (dso_debug, map_rva, dso_count) = if is_64bit {
+            let raw: md::DSO_DEBUG_64 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let dso_debug = MinidumpLinuxDsoDebug {
+                version: raw.version,
+                brk: raw.brk,
+                ldbase: raw.ldbase,
+                dynamic: raw.dynamic,
+                link_map: vec![],
+                is_64bit,
+            };
+            (dso_debug, raw.map, raw.dso_count)
+        } else {
+            let raw: md::DSO_DEBUG_32 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let dso_debug = MinidumpLinuxDsoDebug {
+                version: raw.version,
+                brk: raw.brk as u64,
+                ldbase: raw.ldbase as u64,
+                dynamic: raw.dynamic as u64,
+                link_map: vec![],
+                is_64bit,
+            };
+            (dso_debug, raw.map, raw.dso_count)
+        };
+
+        let entry_size = if is_64bit {
+            md::LINK_MAP_64::size_with(&endian)
+        } else {
+            md::LINK_MAP_32::size_with(&endian)
+        };
+        let (count, _) =
+            ensure_count_in_bound(all, dso_count as usize, entry_size, map_rva as usize)?;
+
+        let mut offset = map_rva as usize;
+        let mut link_map = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (addr, name_rva, ld) = if is_64bit {
+                let raw: md::LINK_MAP_64 = all
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw.addr, raw.name, raw.ld)
+            } else {
+                let raw: md::LINK_MAP_32 = all
+                    .gread_with(&mut offset, endian)
+                    .or(Err(Error::StreamReadFailure))?;
+                (raw.addr as u64, raw.name, raw.ld as u64)
+            };
+            let name = if name_rva == 0 {
+                String::new()
+            } else {
+                read_string_utf16(&mut (name_rva as usize), all, endian).unwrap_or_default()
+            };
+            link_map.push(MinidumpLinkMapEntry { addr, name, ld });
+        }
+
+        Ok(MinidumpLinuxDsoDebug {
+            link_map,
+            ..dso_debug
+        })
+    }
+}
+
+impl MinidumpLinuxDsoDebug {
+    /// The maximum number of `link_map` entries we'll follow, in case the chain is corrupt.
+    const MAX_LINK_MAP_ENTRIES: usize = 4096;
+
+    /// Find the address of the process's `r_debug` from the `DT_DEBUG` entry
+    /// of the main executable's dynamic section.
+    fn r_debug_address(&self, memory: &UnifiedMemoryList) -> Option<u64> {
+        const DT_NULL: u64 = 0;
+        const DT_DEBUG: u64 = 21;
+
+        let word_size = if self.is_64bit { 8 } else { 4 };
+        let mut address = self.dynamic;
+        loop {
+            let tag = read_memory_pointer(memory, address, self.is_64bit)?;
+            let value =
+                read_memory_pointer(memory, address.checked_add(word_size)?, self.is_64bit)?;
+            match tag {
+                DT_NULL => return None,
+                DT_DEBUG => return Some(value).filter(|&r_debug| r_debug != 0),
+                _ => address = address.checked_add(word_size * 2)?,
+            }
+        }
+    }
+
+    /// Walk the live `link_map` chain of the dynamic linker in the process's memory.
+    ///
+    /// This starts from the `r_debug` structure referenced by the main executable's
+    /// dynamic section and follows the `l_next` pointers, so it requires the relevant
+    /// memory to have been captured in the minidump. Returns `None` if the chain
+    /// couldn't be found at all, otherwise every entry that could be read.
+    ///
+    /// Names that weren't captured in the minidump are left empty.
+    pub fn walk_link_map(&self, memory: &UnifiedMemoryList) -> Option<Vec<MinidumpLinkMapEntry>> {
+        let word_size = if self.is_64bit { 8 } else { 4 };
+        let r_debug = self.r_debug_address(memory)?;
+        // `r_map` follows the `int r_version` field, aligned to the pointer size.
+        let mut next = read_memory_pointer(memory, r_debug.checked_add(word_size)?, self.is_64bit)?;
+
+        let mut entries = vec![];
+        let mut seen = HashSet::new();
+        while next != 0 && entries.len() < Self::MAX_LINK_MAP_ENTRIES && seen.insert(next) {
+            let read_field = |index: u64| {
+                let address = next.checked_add(index.checked_mul(word_size)?)?;
+                read_memory_pointer(memory, address, self.is_64bit)
+            };
+            let (addr, name_address, ld, l_next) =
+                match (read_field(0), read_field(1), read_field(2), read_field(3)) {
+                    (Some(addr), Some(name_address), Some(ld), Some(l_next)) => {
+                        (addr, name_address, ld, l_next)
+                    }
+                    _ => break,
+                };
+            let name = read_memory_cstring(memory, name_address).unwrap_or_default();
+            entries.push(MinidumpLinkMapEntry { addr, name, ld });
+            next = l_next;
+        }
+        Some(entries)
+    }
+
+    /// Write a human-readable description of this `MinidumpLinuxDsoDebug` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpLinuxDsoDebug
+  version   = {}
+  brk       = {:#x}
+  ldbase    = {:#x}
+  dynamic   = {:#x}
+  dso_count = {}
+
+",
+            self.version,
+            self.brk,
+            self.ldbase,
+            self.dynamic,
+            self.link_map.len(),
+        )?;
+        for (i, entry) in self.link_map.iter().enumerate() {
+            write!(
+                f,
+                "link_map[{}]
+  addr = {:#x}
+  name = \"{}\"
+  ld   = {:#x}
+
+",
+                i, entry.addr, entry.name, entry.ld
+            )?;
+        }
+        Ok(())
+    }
+}
+
 fn systemtime_from_timestamp(timestamp: u64) -> Option<SystemTime> {
     SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(timestamp))
 }
@@ -4197,13 +6075,313 @@ impl MinidumpMiscInfo {
                     } else {
                         write!(f, "    feature {i:2} - (unknown)           : ")?;
                     }
-                    writeln!(f, " offset {:4}, size {:4}", feature.offset, feature.size)?;
+                    writeln!(f, " offset {:4}, size {:4}", feature.offset, feature.size)?;
+                }
+            }
+            None => writeln!(f, "(invalid)")?,
+        }
+
+        write_simple_field!(f, process_cookie);
+        writeln!(f)?;
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpSystemMemoryInfo {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpSystemMemoryInfo, Error> {
+        let raw: md::MINIDUMP_SYSTEM_MEMORY_INFO_1 = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        Ok(MinidumpSystemMemoryInfo { raw })
+    }
+}
+
+impl MinidumpSystemMemoryInfo {
+    fn has_flag(&self, flag: md::SystemMemoryInfoFlags) -> bool {
+        md::SystemMemoryInfoFlags::from_bits_truncate(self.raw.flags).contains(flag)
+    }
+
+    fn pages_to_bytes(&self, pages: u64) -> u64 {
+        pages.saturating_mul(self.raw.basic_info.page_size as u64)
+    }
+
+    /// The size of a page, in bytes.
+    pub fn page_size(&self) -> u64 {
+        self.raw.basic_info.page_size as u64
+    }
+
+    /// The amount of physical memory in the system, in bytes.
+    pub fn total_physical_memory(&self) -> u64 {
+        self.pages_to_bytes(self.raw.basic_info.number_of_physical_pages as u64)
+    }
+
+    /// The amount of physical memory available for use, in bytes.
+    pub fn available_physical_memory(&self) -> u64 {
+        if self.has_flag(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF) {
+            self.pages_to_bytes(self.raw.basic_perf_info.available_pages)
+        } else {
+            self.pages_to_bytes(self.raw.perf_info.available_pages as u64)
+        }
+    }
+
+    /// The system-wide commit charge, in bytes.
+    pub fn commit_charge(&self) -> u64 {
+        if self.has_flag(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF) {
+            self.pages_to_bytes(self.raw.basic_perf_info.committed_pages)
+        } else {
+            self.pages_to_bytes(self.raw.perf_info.committed_pages as u64)
+        }
+    }
+
+    /// The system-wide commit limit, in bytes.
+    ///
+    /// This is the amount of memory that can be committed before the page file
+    /// has to grow. Allocations fail once the commit charge hits this limit and
+    /// the page file can't grow anymore.
+    pub fn commit_limit(&self) -> u64 {
+        if self.has_flag(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF) {
+            self.pages_to_bytes(self.raw.basic_perf_info.commit_limit)
+        } else {
+            self.pages_to_bytes(self.raw.perf_info.commit_limit as u64)
+        }
+    }
+
+    /// The peak system-wide commit charge, in bytes.
+    pub fn peak_commit_charge(&self) -> u64 {
+        if self.has_flag(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF) {
+            self.pages_to_bytes(self.raw.basic_perf_info.peak_commitment)
+        } else {
+            self.pages_to_bytes(self.raw.perf_info.peak_commitment as u64)
+        }
+    }
+
+    /// Write a human-readable description of this `MinidumpSystemMemoryInfo` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let raw = &self.raw;
+        write!(
+            f,
+            "MINIDUMP_SYSTEM_MEMORY_INFO_1
+  revision                      = {}
+  flags                         = {:#x}
+  basic_info.page_size          = {:#x}
+  basic_info.number_of_physical_pages = {:#x}
+  basic_info.allocation_granularity = {:#x}
+  basic_info.number_of_processors = {}
+  file_cache_info.current_size  = {:#x}
+  file_cache_info.peak_size     = {:#x}
+",
+            raw.revision,
+            raw.flags,
+            raw.basic_info.page_size,
+            raw.basic_info.number_of_physical_pages,
+            raw.basic_info.allocation_granularity,
+            raw.basic_info.number_of_processors,
+            raw.file_cache_info.current_size,
+            raw.file_cache_info.peak_size,
+        )?;
+        if self.has_flag(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF) {
+            write!(
+                f,
+                "  basic_perf_info.available_pages = {:#x}
+  basic_perf_info.committed_pages = {:#x}
+  basic_perf_info.commit_limit  = {:#x}
+  basic_perf_info.peak_commitment = {:#x}
+",
+                raw.basic_perf_info.available_pages,
+                raw.basic_perf_info.committed_pages,
+                raw.basic_perf_info.commit_limit,
+                raw.basic_perf_info.peak_commitment,
+            )?;
+        }
+        write!(
+            f,
+            "  perf_info.available_pages     = {:#x}
+  perf_info.committed_pages     = {:#x}
+  perf_info.commit_limit        = {:#x}
+  perf_info.peak_commitment     = {:#x}
+  perf_info.page_fault_count    = {}
+  perf_info.paged_pool_pages    = {:#x}
+  perf_info.non_paged_pool_pages = {:#x}
+  total_physical_memory (bytes) = {}
+  available_physical_memory (bytes) = {}
+  commit_charge (bytes)         = {}
+  commit_limit (bytes)          = {}
+  peak_commit_charge (bytes)    = {}
+
+",
+            raw.perf_info.available_pages,
+            raw.perf_info.committed_pages,
+            raw.perf_info.commit_limit,
+            raw.perf_info.peak_commitment,
+            raw.perf_info.page_fault_count,
+            raw.perf_info.paged_pool_pages,
+            raw.perf_info.non_paged_pool_pages,
+            self.total_physical_memory(),
+            self.available_physical_memory(),
+            self.commit_charge(),
+            self.commit_limit(),
+            self.peak_commit_charge(),
+        )?;
+        Ok(())
+    }
+}
+
+// Generates an accessor for a PROCESS_VM_COUNTERS field with the syntax:
+//
+// * VERSION_NUMBER: FIELD_NAME if FLAG -> FIELD_TYPE
+//
+// With the following definitions:
+//
+// * VERSION_NUMBER: The PROCESS_VM_COUNTERS version this field was introduced in
+// * FIELD_NAME: The name of the field to read
+// * FLAG: A ProcessVmCountersFlags that defines if this field contains valid
+//   data (version 1 has no flags, so all of its fields are always valid)
+// * FIELD_TYPE: The type of the field
+macro_rules! vm_counters_accessors {
+    () => {};
+    (1: $name:ident if $flag:ident -> $t:ty, $($rest:tt)*) => {
+        pub fn $name(&self) -> Option<&$t> {
+            match self {
+                RawProcessVmCounters::V1(ref raw) => Some(&raw.$name),
+                RawProcessVmCounters::V2(ref raw) => md::ProcessVmCountersFlags::from_bits_truncate(raw.flags)
+                    .contains(md::ProcessVmCountersFlags::$flag)
+                    .then_some(&raw.$name),
+            }
+        }
+        vm_counters_accessors!($($rest)*);
+    };
+    (2: $name:ident if $flag:ident -> $t:ty, $($rest:tt)*) => {
+        pub fn $name(&self) -> Option<&$t> {
+            match self {
+                RawProcessVmCounters::V1(_) => None,
+                RawProcessVmCounters::V2(ref raw) => md::ProcessVmCountersFlags::from_bits_truncate(raw.flags)
+                    .contains(md::ProcessVmCountersFlags::$flag)
+                    .then_some(&raw.$name),
+            }
+        }
+        vm_counters_accessors!($($rest)*);
+    };
+}
+
+impl RawProcessVmCounters {
+    pub fn revision(&self) -> u16 {
+        match self {
+            RawProcessVmCounters::V1(ref raw) => raw.revision,
+            RawProcessVmCounters::V2(ref raw) => raw.revision,
+        }
+    }
+
+    // Fields are grouped by the flag that guards them.
+    vm_counters_accessors!(
+        1: page_fault_count if MINIDUMP_PROCESS_VM_COUNTERS -> u32,
+        1: peak_working_set_size if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: working_set_size if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: quota_peak_paged_pool_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: quota_paged_pool_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: quota_peak_non_paged_pool_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: quota_non_paged_pool_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: pagefile_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+        1: peak_pagefile_usage if MINIDUMP_PROCESS_VM_COUNTERS -> u64,
+
+        2: peak_virtual_size if MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE -> u64,
+        2: virtual_size if MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE -> u64,
+
+        1: private_usage if MINIDUMP_PROCESS_VM_COUNTERS_EX -> u64,
+
+        2: private_working_set_size if MINIDUMP_PROCESS_VM_COUNTERS_EX2 -> u64,
+        2: shared_commit_usage if MINIDUMP_PROCESS_VM_COUNTERS_EX2 -> u64,
+
+        2: job_shared_commit_usage if MINIDUMP_PROCESS_VM_COUNTERS_JOB -> u64,
+        2: job_private_commit_usage if MINIDUMP_PROCESS_VM_COUNTERS_JOB -> u64,
+        2: job_peak_private_commit_usage if MINIDUMP_PROCESS_VM_COUNTERS_JOB -> u64,
+        2: job_private_commit_limit if MINIDUMP_PROCESS_VM_COUNTERS_JOB -> u64,
+        2: job_total_commit_limit if MINIDUMP_PROCESS_VM_COUNTERS_JOB -> u64,
+    );
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpProcessVmCounters {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::ProcessVmCountersStream as u32;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpProcessVmCounters, Error> {
+        // Both revisions start with the revision number, and the second one is
+        // a strict superset of the first one (with some flags to say what's valid).
+        let revision: u16 = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let raw = if revision >= 2 {
+            RawProcessVmCounters::V2(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        } else {
+            RawProcessVmCounters::V1(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        };
+        Ok(MinidumpProcessVmCounters { raw })
+    }
+}
+
+impl MinidumpProcessVmCounters {
+    /// Write a human-readable description of this `MinidumpProcessVmCounters` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        macro_rules! write_simple_field {
+            ($stream:ident, $field:ident, $format:literal) => {
+                write!(f, "  {:31}= ", stringify!($field))?;
+                match self.raw.$field() {
+                    Some($field) => {
+                        writeln!(f, $format, $field)?;
+                    }
+                    None => writeln!(f, "(invalid)")?,
                 }
-            }
-            None => writeln!(f, "(invalid)")?,
+            };
+            ($stream:ident, $field:ident) => {
+                write_simple_field!($stream, $field, "{:#x}");
+            };
         }
-
-        write_simple_field!(f, process_cookie);
+        writeln!(f, "MINIDUMP_PROCESS_VM_COUNTERS_{}", self.raw.revision())?;
+        if let RawProcessVmCounters::V2(ref raw) = self.raw {
+            writeln!(f, "  {:31}= {:#x}", "flags", raw.flags)?;
+        }
+
+        write_simple_field!(f, page_fault_count, "{}");
+        write_simple_field!(f, peak_working_set_size);
+        write_simple_field!(f, working_set_size);
+        write_simple_field!(f, quota_peak_paged_pool_usage);
+        write_simple_field!(f, quota_paged_pool_usage);
+        write_simple_field!(f, quota_peak_non_paged_pool_usage);
+        write_simple_field!(f, quota_non_paged_pool_usage);
+        write_simple_field!(f, pagefile_usage);
+        write_simple_field!(f, peak_pagefile_usage);
+        write_simple_field!(f, peak_virtual_size);
+        write_simple_field!(f, virtual_size);
+        write_simple_field!(f, private_usage);
+        write_simple_field!(f, private_working_set_size);
+        write_simple_field!(f, shared_commit_usage);
+        write_simple_field!(f, job_shared_commit_usage);
+        write_simple_field!(f, job_private_commit_usage);
+        write_simple_field!(f, job_peak_private_commit_usage);
+        write_simple_field!(f, job_private_commit_limit);
+        write_simple_field!(f, job_total_commit_limit);
         writeln!(f)?;
         Ok(())
     }
@@ -5039,6 +7217,82 @@ impl<'a> MinidumpStream<'a> for MinidumpAssertion {
     }
 }
 
+/// Strip a comment stream down to the text before its first nul terminator.
+fn comment_bytes(bytes: &[u8], char_size: usize) -> &[u8] {
+    let len = bytes
+        .chunks_exact(char_size)
+        .position(|c| c.iter().all(|&b| b == 0))
+        .map_or(bytes.len() - bytes.len() % char_size, |i| i * char_size);
+    &bytes[..len]
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpCommentA {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::CommentStreamA as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        _endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpCommentA, Error> {
+        // There's no way to know which code page the writer used, so assume
+        // the most common one (which is also a superset of ASCII).
+        let (comment, _) =
+            encoding_rs::WINDOWS_1252.decode_without_bom_handling(comment_bytes(bytes, 1));
+        Ok(MinidumpCommentA {
+            comment: comment.into_owned(),
+        })
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpCommentW {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::CommentStreamW as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpCommentW, Error> {
+        let encoding = match endian {
+            scroll::Endian::Little => encoding_rs::UTF_16LE,
+            scroll::Endian::Big => encoding_rs::UTF_16BE,
+        };
+        let (comment, _) = encoding.decode_without_bom_handling(comment_bytes(bytes, 2));
+        Ok(MinidumpCommentW {
+            comment: comment.into_owned(),
+        })
+    }
+}
+
+impl MinidumpComment {
+    /// The text of the comment.
+    pub fn comment(&self) -> &str {
+        match self {
+            MinidumpComment::Ansi(comment) => &comment.comment,
+            MinidumpComment::Unicode(comment) => &comment.comment,
+        }
+    }
+
+    /// Write a human-readable description of this `MinidumpComment` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let stream_type = match self {
+            MinidumpComment::Ansi(_) => MINIDUMP_STREAM_TYPE::CommentStreamA,
+            MinidumpComment::Unicode(_) => MINIDUMP_STREAM_TYPE::CommentStreamW,
+        };
+        write!(
+            f,
+            "MinidumpComment
+  stream_type = {:?}
+  comment     = {}
+
+",
+            stream_type,
+            self.comment(),
+        )
+    }
+}
+
 fn utf16_to_string(data: &[u16]) -> Option<String> {
     use std::slice;
 
@@ -5375,6 +7629,64 @@ impl<'a> Minidump<'a, Mmap> {
     }
 }
 
+impl<'a> Minidump<'a, LazyData> {
+    /// Read a `Minidump` from a [`Read`][] + [`Seek`][std::io::Seek] source, without
+    /// loading the whole file into memory.
+    ///
+    /// Only the header and the stream directory are read up front. The contents of each stream
+    /// are read the first time it's requested, together with the part of the file that streams
+    /// can refer to, which for full-memory dumps leaves out the block of memory contents at the
+    /// end of the file. The memory regions of a [`MinidumpMemory64List`][] are then read one at
+    /// a time, the first time they're looked up.
+    ///
+    /// ```
+    /// use minidump::*;
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let file = File::open("../testdata/test.dmp").or(Err(Error::FileNotFound))?;
+    ///     let dump = Minidump::read_lazy(BufReader::new(file))?;
+    ///
+    ///     let system_info = dump.get_stream::<MinidumpSystemInfo>()?;
+    ///     let memory = dump.get_memory().unwrap_or_default();
+    ///     // ...
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn read_lazy<R>(mut reader: R) -> Result<Minidump<'a, LazyData>, Error>
+    where
+        R: Read + io::Seek + Send + 'static,
+    {
+        let header_size = md::MINIDUMP_HEADER::size_with(&LE) as u64;
+        let header_bytes = read_at(&mut reader, 0, header_size).or(Err(Error::IoError))?;
+        let (header, endian) = read_header(&header_bytes)?;
+
+        let count = header.stream_count as usize;
+        let directory_size =
+            header.stream_count as u64 * <md::MINIDUMP_DIRECTORY>::size_with(&endian) as u64;
+        let directory_bytes = read_at(
+            &mut reader,
+            header.stream_directory_rva as u64,
+            directory_size,
+        )
+        .or(Err(Error::IoError))?;
+        let (directory, streams) = read_directory(&directory_bytes, 0, count, endian)?;
+
+        let data = LazyData::new(reader, &directory, endian)?;
+        Ok(Minidump {
+            data: data.clone(),
+            header,
+            directory,
+            streams,
+            endian,
+            system_info: OnceLock::new(),
+            lazy: Some(data),
+            _phantom: PhantomData,
+        })
+    }
+}
+
 /// A stream in the minidump that this implementation can interpret,
 #[derive(Debug)]
 pub struct MinidumpImplementedStream {
@@ -5409,88 +7721,22 @@ where
     /// Typically this will be a `Vec<u8>` or `&[u8]` with the full contents of the minidump,
     /// but you can also use something like `memmap::Mmap`.
     pub fn read(data: T) -> Result<Minidump<'a, T>, Error> {
-        let mut offset = 0;
-        let mut endian = LE;
-        let mut header: md::MINIDUMP_HEADER = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::MissingHeader))?;
-        if header.signature != md::MINIDUMP_SIGNATURE {
-            if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-            // Try again with big-endian.
-            endian = BE;
-            offset = 0;
-            header = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingHeader))?;
-            if header.signature != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-        }
-        if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
-            return Err(Error::VersionMismatch);
-        }
-
-        offset = header.stream_directory_rva as usize;
-
-        let (count, _) = ensure_count_in_bound(
+        let (header, endian) = read_header(&data)?;
+        let (directory, streams) = read_directory(
             &data,
+            header.stream_directory_rva as usize,
             header.stream_count as usize,
-            <md::MINIDUMP_DIRECTORY>::size_with(&endian),
-            offset,
+            endian,
         )?;
 
-        let mut streams = HashMap::with_capacity(count);
-        for i in 0..header.stream_count {
-            let dir: md::MINIDUMP_DIRECTORY = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingDirectory))?;
-            if let Some((old_idx, old_dir)) = streams.insert(dir.stream_type, (i, dir.clone())) {
-                if let Some(known_stream_type) = MINIDUMP_STREAM_TYPE::from_u32(dir.stream_type) {
-                    if !(known_stream_type == MINIDUMP_STREAM_TYPE::UnusedStream
-                        && old_dir.location.data_size == 0
-                        && dir.location.data_size == 0)
-                    {
-                        warn!("Minidump contains multiple streams of type {} ({:?}) at indices {} ({} bytes) and {} ({} bytes) (using {})",
-                            dir.stream_type,
-                            known_stream_type,
-                            old_idx,
-                            old_dir.location.data_size,
-                            i,
-                            dir.location.data_size,
-                            i,
-                        );
-                    }
-                } else {
-                    warn!("Minidump contains multiple streams of unknown type {} at indices {} ({} bytes) and {} ({} bytes) (using {})",
-                        dir.stream_type,
-                        old_idx,
-                        old_dir.location.data_size,
-                        i,
-                        dir.location.data_size,
-                        i,
-                    );
-                }
-            }
-        }
-        let system_info = streams
-            .get(&MinidumpSystemInfo::STREAM_TYPE)
-            .and_then(|(_, dir)| {
-                location_slice(data.deref(), &dir.location)
-                    .ok()
-                    .and_then(|bytes| {
-                        let all_bytes = data.deref();
-                        MinidumpSystemInfo::read(bytes, all_bytes, endian, None).ok()
-                    })
-            });
-
         Ok(Minidump {
             data,
             header,
+            directory,
             streams,
             endian,
-            system_info,
+            system_info: OnceLock::new(),
+            lazy: None,
             _phantom: PhantomData,
         })
     }
@@ -5553,9 +7799,16 @@ where
     ///
     /// * [`MinidumpAssertion`][]
     /// * [`MinidumpBreakpadInfo`][]
+    /// * [`MinidumpCommentA`][]
+    /// * [`MinidumpCommentW`][]
     /// * [`MinidumpCrashpadInfo`][]
     /// * [`MinidumpException`][]
+    /// * [`MinidumpFunctionTableList`][]
+    /// * [`MinidumpHandleOperationList`][]
+    /// * [`MinidumpLinuxAuxv`][]
+    /// * [`MinidumpLinuxCmdLine`][]
     /// * [`MinidumpLinuxCpuInfo`][]
+    /// * [`MinidumpLinuxDsoDebug`][]
     /// * [`MinidumpLinuxEnviron`][]
     /// * [`MinidumpLinuxLsbRelease`][]
     /// * [`MinidumpLinuxMaps`][]
@@ -5567,23 +7820,29 @@ where
     /// * [`MinidumpMemoryInfoList`][]
     /// * [`MinidumpMiscInfo`][]
     /// * [`MinidumpModuleList`][]
+    /// * [`MinidumpProcessVmCounters`][]
     /// * [`MinidumpSystemInfo`][]
+    /// * [`MinidumpSystemMemoryInfo`][]
+    /// * [`MinidumpThreadInfoList`][]
     /// * [`MinidumpThreadList`][]
     /// * [`MinidumpThreadNames`][]
+    /// * [`MinidumpTokenList`][]
     /// * [`MinidumpUnloadedModuleList`][]
     /// * [`MinidumpHandleDataStream`][]
     ///
+    /// If there are several streams of type `S` (which should not happen for well-formed
+    /// Minidumps), the last one in the stream directory is used. Use
+    /// [`Minidump::get_streams`][] to get all of them.
     pub fn get_stream<S>(&'a self) -> Result<S, Error>
     where
         S: MinidumpStream<'a>,
     {
-        match self.get_raw_stream(S::STREAM_TYPE) {
-            Err(e) => Err(e),
-            Ok(bytes) => {
-                let all_bytes = self.data.deref();
-                S::read(bytes, all_bytes, self.endian, self.system_info.as_ref())
-            }
-        }
+        let index = self
+            .streams
+            .get(&S::STREAM_TYPE)
+            .and_then(|indices| indices.last())
+            .ok_or(Error::StreamNotFound)?;
+        self.read_stream(*index)
     }
 
     /// Get a stream of raw data from the minidump.
@@ -5595,21 +7854,102 @@ where
     /// Note that the lifetime of the returned stream is bound to the lifetime of the this
     /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
     /// This is a consequence of how this struct relies on [Deref] to access the data.
+    ///
+    /// If there are several streams of the type, the last one in the stream directory is
+    /// used, like [`Minidump::get_stream`][].
     pub fn get_raw_stream(&'a self, stream_type: u32) -> Result<&'a [u8], Error> {
-        match self.streams.get(&stream_type) {
-            None => Err(Error::StreamNotFound),
-            Some((_, dir)) => {
-                let bytes = self.data.deref();
-                location_slice(bytes, &dir.location)
+        self.get_raw_streams(stream_type)
+            .last()
+            .unwrap_or(Err(Error::StreamNotFound))
+    }
+
+    /// Read and parse every copy of the specified [`MinidumpStream`][] `S` from the Minidump,
+    /// in the order of the stream directory.
+    ///
+    /// Well-formed Minidumps have at most one stream of each type, but some writers emit
+    /// several streams of the same (usually vendor-specific) type. Each copy is parsed on
+    /// its own, so one of them being corrupt doesn't affect the others.
+    pub fn get_streams<S>(&'a self) -> impl Iterator<Item = Result<S, Error>> + 'a
+    where
+        S: MinidumpStream<'a>,
+    {
+        let indices = self
+            .streams
+            .get(&S::STREAM_TYPE)
+            .map(|indices| &indices[..])
+            .unwrap_or_default();
+        indices.iter().map(move |&i| self.read_stream(i))
+    }
+
+    /// Get every stream of raw data of the given type from the minidump, in the order of the
+    /// stream directory.
+    ///
+    /// See [`Minidump::get_raw_stream`][] and [`Minidump::get_streams`][].
+    pub fn get_raw_streams(
+        &'a self,
+        stream_type: u32,
+    ) -> impl Iterator<Item = Result<&'a [u8], Error>> + 'a {
+        let indices = self
+            .streams
+            .get(&stream_type)
+            .map(|indices| &indices[..])
+            .unwrap_or_default();
+        indices.iter().map(move |&i| self.read_raw_stream(i))
+    }
+
+    /// Read and parse the stream at `index` in the stream directory as `S`.
+    fn read_stream<S>(&'a self, index: usize) -> Result<S, Error>
+    where
+        S: MinidumpStream<'a>,
+    {
+        self.read_stream_with(index, self.system_info())
+    }
+
+    fn read_stream_with<S>(
+        &'a self,
+        index: usize,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<S, Error>
+    where
+        S: MinidumpStream<'a>,
+    {
+        let location = self.directory[index].location;
+        match &self.lazy {
+            Some(lazy) => S::read_lazy(lazy.stream(index, location), self.endian, system_info),
+            None => {
+                let bytes = location_slice(&self.data, &location)?;
+                S::read(bytes, &self.data, self.endian, system_info)
             }
         }
     }
 
+    /// Get the raw contents of the stream at `index` in the stream directory.
+    fn read_raw_stream(&'a self, index: usize) -> Result<&'a [u8], Error> {
+        let location = self.directory[index].location;
+        match &self.lazy {
+            Some(lazy) => lazy.stream(index, location).bytes(),
+            None => location_slice(&self.data, &location),
+        }
+    }
+
+    /// The last `SystemInfoStream` in the minidump, which many streams need to be parsed.
+    fn system_info(&'a self) -> Option<&'a MinidumpSystemInfo> {
+        self.system_info
+            .get_or_init(|| {
+                let index = self.streams.get(&MinidumpSystemInfo::STREAM_TYPE)?.last()?;
+                self.read_stream_with(*index, None).ok()
+            })
+            .as_ref()
+    }
+
     /// Get whichever of the two MemoryLists are available in the minidump,
     /// preferring [`MinidumpMemory64List`][].
+    ///
+    /// For minidumps read with [`Minidump::read_lazy`], this returns a
+    /// [`MinidumpLazyMemory64List`][] instead of a [`MinidumpMemory64List`][], which only reads
+    /// the contents of a region when it's used.
     pub fn get_memory(&'a self) -> Option<UnifiedMemoryList<'a>> {
-        self.get_stream::<MinidumpMemory64List>()
-            .map(UnifiedMemoryList::Memory64)
+        self.get_memory64()
             .or_else(|_| {
                 self.get_stream::<MinidumpMemoryList>()
                     .map(UnifiedMemoryList::Memory)
@@ -5617,13 +7957,42 @@ where
             .ok()
     }
 
+    fn get_memory64(&'a self) -> Result<UnifiedMemoryList<'a>, Error> {
+        let Some(lazy) = &self.lazy else {
+            return self
+                .get_stream::<MinidumpMemory64List>()
+                .map(UnifiedMemoryList::Memory64);
+        };
+        let index = *self
+            .streams
+            .get(&MinidumpMemory64List::STREAM_TYPE)
+            .and_then(|indices| indices.last())
+            .ok_or(Error::StreamNotFound)?;
+        let stream = lazy.stream(index, self.directory[index].location);
+        MinidumpLazyMemory64List::read(stream, self.endian).map(UnifiedMemoryList::LazyMemory64)
+    }
+
+    /// Get whichever of the two comment streams are available in the minidump,
+    /// preferring [`MinidumpCommentW`][].
+    pub fn get_comment(&'a self) -> Option<MinidumpComment> {
+        self.get_stream::<MinidumpCommentW>()
+            .map(MinidumpComment::Unicode)
+            .or_else(|_| {
+                self.get_stream::<MinidumpCommentA>()
+                    .map(MinidumpComment::Ansi)
+            })
+            .ok()
+    }
+
     /// A listing of all the streams in the Minidump that this library is *aware* of,
     /// but has no further analysis for.
     ///
-    /// If there are multiple copies of the same stream type (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    /// The streams are yielded in the order of the stream directory, including every copy
+    /// of a stream type that appears multiple times (which should not happen for
+    /// well-formed Minidumps). The exception is empty `UnusedStream`s, which writers use to
+    /// reserve entries in the directory: only the last of those is yielded.
     pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
-        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 31] = [
+        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 20] = [
             // Presumably will never have an implementation:
             MINIDUMP_STREAM_TYPE::UnusedStream,
             MINIDUMP_STREAM_TYPE::ReservedStream0,
@@ -5631,15 +8000,7 @@ where
             MINIDUMP_STREAM_TYPE::LastReservedStream,
             // Presumably should be implemented:
             MINIDUMP_STREAM_TYPE::ThreadExListStream,
-            MINIDUMP_STREAM_TYPE::CommentStreamA,
-            MINIDUMP_STREAM_TYPE::CommentStreamW,
-            MINIDUMP_STREAM_TYPE::FunctionTable,
-            MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
-            MINIDUMP_STREAM_TYPE::HandleOperationListStream,
-            MINIDUMP_STREAM_TYPE::TokenStream,
             MINIDUMP_STREAM_TYPE::JavaScriptDataStream,
-            MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream,
-            MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
             MINIDUMP_STREAM_TYPE::IptTraceStream,
             // Windows CE streams, very unlikely to be found in the wild.
             // Their contents are documented here: https://docs.microsoft.com/en-us/previous-versions/windows/embedded/ms939618(v=msdn.10)
@@ -5656,12 +8017,17 @@ where
             MINIDUMP_STREAM_TYPE::ceStreamBucketParameters,
             MINIDUMP_STREAM_TYPE::ceStreamProcessModuleMap,
             MINIDUMP_STREAM_TYPE::ceStreamDiagnosisList,
-            // non-standard streams (should also be implemented):
-            MINIDUMP_STREAM_TYPE::LinuxCmdLine,
-            MINIDUMP_STREAM_TYPE::LinuxAuxv,
-            MINIDUMP_STREAM_TYPE::LinuxDsoDebug,
         ];
-        self.streams.iter().filter_map(|(_, (_, stream))| {
+        let is_placeholder = |stream: &md::MINIDUMP_DIRECTORY| {
+            stream.stream_type == MINIDUMP_STREAM_TYPE::UnusedStream as u32
+                && stream.location.data_size == 0
+        };
+        let last_placeholder = self.directory.iter().rposition(is_placeholder);
+        let streams = self.directory.iter().enumerate();
+        streams.filter_map(move |(i, stream)| {
+            if is_placeholder(stream) && Some(i) != last_placeholder {
+                return None;
+            }
             MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).and_then(|stream_type| {
                 if UNIMPLEMENTED_STREAMS.contains(&stream_type) {
                     return Some(MinidumpUnimplementedStream {
@@ -5677,10 +8043,10 @@ where
 
     /// A listing of all the streams in the Minidump that this library has no knowledge of.
     ///
-    /// If there are multiple copies of the same stream (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    /// The streams are yielded in the order of the stream directory, including every copy
+    /// of a stream type that appears multiple times.
     pub fn unknown_streams(&self) -> impl Iterator<Item = MinidumpUnknownStream> + '_ {
-        self.streams.iter().filter_map(|(_, (_, stream))| {
+        self.directory.iter().filter_map(|stream| {
             if MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).is_none() {
                 return Some(MinidumpUnknownStream {
                     stream_type: stream.stream_type,
@@ -5694,10 +8060,44 @@ where
 
     /// A listing of all the streams in the Minidump.
     ///
-    /// If there are multiple copies of the same stream (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    /// The streams are yielded in the order of the stream directory, including every copy
+    /// of a stream type that appears multiple times (which should not happen for
+    /// well-formed Minidumps).
     pub fn all_streams(&self) -> impl Iterator<Item = &md::MINIDUMP_DIRECTORY> + '_ {
-        self.streams.iter().map(|(_, (_, stream))| stream)
+        self.directory.iter()
+    }
+
+    /// The contents of the minidump file.
+    ///
+    /// For minidumps read with [`Minidump::read_lazy`], this reads the whole file into
+    /// memory, including the memory block of full-memory dumps which is otherwise only read
+    /// a region at a time. Prefer [`Minidump::image`] where the memory isn't needed.
+    pub(crate) fn data(&self) -> &[u8] {
+        match &self.lazy {
+            Some(lazy) => lazy.contents(),
+            None => &self.data,
+        }
+    }
+
+    /// The part of the minidump file that the location descriptors of streams refer to.
+    ///
+    /// For minidumps read with [`Minidump::read_lazy`], this leaves out the memory block at
+    /// the end of full-memory dumps, see [`LazyData`].
+    pub(crate) fn image(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The length of the whole minidump file.
+    pub(crate) fn file_len(&self) -> u64 {
+        match &self.lazy {
+            Some(lazy) => lazy.file_len(),
+            None => self.data.len() as u64,
+        }
+    }
+
+    /// The stream directory, in the order the streams appear in the file.
+    pub(crate) fn directory(&self) -> &[md::MINIDUMP_DIRECTORY] {
+        &self.directory
     }
 
     /// Write a verbose description of the `Minidump` to `f`.
@@ -5731,9 +8131,7 @@ where
             format_time_t(self.header.time_date_stamp),
             self.header.flags,
         )?;
-        let mut streams = self.streams.iter().collect::<Vec<_>>();
-        streams.sort_by(|&(&_, &(a, _)), &(&_, &(b, _))| a.cmp(&b));
-        for &(_, &(i, ref stream)) in streams.iter() {
+        for (i, stream) in self.directory.iter().enumerate() {
             write!(
                 f,
                 r#"mDirectory[{}]
@@ -5751,8 +8149,9 @@ MDRawDirectory
             )?;
         }
         writeln!(f, "Streams:")?;
-        streams.sort_by(|&(&a, &(_, _)), &(&b, &(_, _))| a.cmp(&b));
-        for (_, &(i, ref stream)) in streams {
+        let mut streams = self.directory.iter().enumerate().collect::<Vec<_>>();
+        streams.sort_by_key(|&(i, stream)| (stream.stream_type, i));
+        for (i, stream) in streams {
             writeln!(
                 f,
                 "  stream type {:#x} ({}) at index {}",
@@ -5784,12 +8183,16 @@ mod test {
     use md::GUID;
     use minidump_common::format::{PlatformId, ProcessorArchitecture};
     use minidump_synth::{
-        self, AnnotationValue, CrashpadInfo, DumpString, Exception,
-        HandleDescriptor as SynthHandleDescriptor, Memory, MemoryInfo as SynthMemoryInfo,
-        MiscFieldsBuildString, MiscFieldsPowerInfo, MiscFieldsProcessTimes, MiscFieldsTimeZone,
-        MiscInfo5Fields, MiscStream, Module as SynthModule, ModuleCrashpadInfo, SimpleStream,
-        SynthMinidump, SystemInfo, Thread, ThreadName, UnloadedModule as SynthUnloadedModule,
-        STOCK_VERSION_INFO,
+        self, AnnotationValue, CrashpadInfo, DumpSection, DumpString, Exception,
+        FunctionTable as SynthFunctionTable, FunctionTableList as SynthFunctionTableList,
+        HandleDescriptor as SynthHandleDescriptor, HandleOperation as SynthHandleOperation, Memory,
+        MemoryInfo as SynthMemoryInfo, MiscFieldsBuildString, MiscFieldsPowerInfo,
+        MiscFieldsProcessTimes, MiscFieldsTimeZone, MiscInfo5Fields, MiscStream,
+        Module as SynthModule, ModuleCrashpadInfo, ProcessVmCounters, Sid, SimpleStream,
+        SynthMinidump, SystemInfo, SystemMemoryFieldsCommit, SystemMemoryInfo, Thread,
+        ThreadInfo as SynthThreadInfo, ThreadName, TokenAccessInformation, TokenList,
+        UnloadedModule as SynthUnloadedModule, VmCountersFields, VmCountersFieldsJob,
+        VmCountersFieldsVirtualSize, STOCK_VERSION_INFO,
     };
     use std::mem;
     use test_assembler::*;
@@ -5838,6 +8241,71 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_duplicate_streams() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let stream = |stream_type: u32, bytes: &[u8]| SimpleStream {
+            stream_type,
+            section: Section::new().append_bytes(bytes),
+        };
+        let cmd_line = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(STREAM_TYPE, b"first"))
+            .add_stream(stream(cmd_line, b"app\0--first\0"))
+            .add_stream(stream(STREAM_TYPE, b"second"))
+            .add_stream(stream(cmd_line, b"app\0--second\0"));
+        let dump = read_synth_dump(dump).unwrap();
+
+        // The last copy is the one that's used by default.
+        assert_eq!(dump.get_raw_stream(STREAM_TYPE).unwrap(), b"second");
+        let raw: Vec<_> = dump
+            .get_raw_streams(STREAM_TYPE)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(raw, vec![&b"first"[..], &b"second"[..]]);
+        assert_eq!(dump.get_raw_streams(0xaabbccdd).count(), 0);
+
+        let cmd_line_stream = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+        assert_eq!(&cmd_line_stream.raw_bytes()[..], b"app\0--second\0");
+        let cmd_lines: Vec<_> = dump
+            .get_streams::<MinidumpLinuxCmdLine>()
+            .map(|cmd_line| cmd_line.unwrap().raw_bytes())
+            .collect();
+        assert_eq!(
+            cmd_lines,
+            vec![&b"app\0--first\0"[..], &b"app\0--second\0"[..]]
+        );
+
+        let all: Vec<_> = dump
+            .all_streams()
+            .map(|stream| stream.stream_type)
+            .collect();
+        assert_eq!(all, vec![STREAM_TYPE, cmd_line, STREAM_TYPE, cmd_line]);
+        let unknown: Vec<_> = dump
+            .unknown_streams()
+            .map(|stream| stream.location.data_size)
+            .collect();
+        assert_eq!(unknown, vec![5, 6]);
+
+        // Of the empty entries that reserve room in the directory, only the last one is
+        // reported as unimplemented.
+        let unused = MINIDUMP_STREAM_TYPE::UnusedStream as u32;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(unused, b""))
+            .add_stream(stream(unused, b"data"))
+            .add_stream(stream(unused, b""));
+        let dump = read_synth_dump(dump).unwrap();
+        let locations: Vec<_> = dump
+            .all_streams()
+            .map(|stream| (stream.location.rva, stream.location.data_size))
+            .collect();
+        let unimplemented: Vec<_> = dump
+            .unimplemented_streams()
+            .map(|stream| (stream.location.rva, stream.location.data_size))
+            .collect();
+        assert_eq!(unimplemented, locations[1..]);
+    }
+
     #[test]
     fn test_thread_names() {
         let good_thread_id = 17;
@@ -6547,9 +9015,152 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(regions[0].size, CONTENTS0.len() as u64);
         assert_eq!(&regions[0].bytes, &CONTENTS0);
 
-        assert_eq!(regions[1].base_address, 0x1234);
-        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
-        assert_eq!(&regions[1].bytes, &CONTENTS1);
+        assert_eq!(regions[1].base_address, 0x1234);
+        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
+        assert_eq!(&regions[1].bytes, &CONTENTS1);
+    }
+
+    /// A reader that records which ranges of the file are read.
+    struct RecordingReader {
+        inner: io::Cursor<Vec<u8>>,
+        reads: std::sync::Arc<std::sync::Mutex<Vec<std::ops::Range<u64>>>>,
+    }
+
+    impl Read for RecordingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let start = self.inner.position();
+            let len = self.inner.read(buf)?;
+            let end = start + len as u64;
+            self.reads.lock().unwrap().push(start..end);
+            Ok(len)
+        }
+    }
+
+    impl io::Seek for RecordingReader {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_read_lazy() {
+        const CONTENTS0: &[u8] = b"memory_contents";
+        const CONTENTS1: &[u8] = b"another_block";
+        let memory0 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS0),
+            0x1000,
+        );
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS1),
+            0x2000,
+        );
+        let name = DumpString::new("single module", Endian::Little);
+        let module = SynthModule::new(
+            Endian::Little,
+            0x1000,
+            0x2000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name)
+            .add_memory64(memory0)
+            .add_memory64(memory1);
+        let bytes = dump.finish().unwrap();
+        let eager = Minidump::read(bytes.clone()).unwrap();
+        let dump = Minidump::read_lazy(io::Cursor::new(bytes)).unwrap();
+
+        assert_eq!(dump.header.stream_count, eager.header.stream_count);
+        assert_eq!(
+            dump.get_raw_stream(MinidumpModuleList::STREAM_TYPE)
+                .unwrap(),
+            eager
+                .get_raw_stream(MinidumpModuleList::STREAM_TYPE)
+                .unwrap()
+        );
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].code_file(), "single module");
+
+        let memory = dump.get_memory().unwrap();
+        assert!(matches!(memory, UnifiedMemoryList::LazyMemory64(_)));
+        let region = memory.memory_at_address(0x2004).unwrap();
+        assert_eq!(region.base_address(), 0x2000);
+        assert_eq!(region.size(), CONTENTS1.len() as u64);
+        assert_eq!(region.bytes(), CONTENTS1);
+        assert_eq!(region.get_memory_at_address::<u8>(0x2001), Some(b'n'));
+        assert_eq!(memory.by_addr().count(), 2);
+
+        let memory_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
+        let regions = memory_list
+            .iter()
+            .map(|region| region.bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(regions, [CONTENTS0, CONTENTS1]);
+
+        assert_eq!(
+            Minidump::read_lazy(io::Cursor::new(Vec::new())).unwrap_err(),
+            Error::MissingHeader
+        );
+    }
+
+    #[test]
+    fn test_read_lazy_only_reads_used_memory() {
+        const CONTENTS0: &[u8] = b"memory_contents";
+        const CONTENTS1: &[u8] = b"another_block";
+        let memory0 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS0),
+            0x1000,
+        );
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS1),
+            0x2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(SystemInfo::new(Endian::Little))
+            .add_memory64(memory0)
+            .add_memory64(memory1);
+        let bytes = dump.finish().unwrap();
+        let find = |contents: &[u8]| {
+            let start = bytes
+                .windows(contents.len())
+                .position(|window| window == contents)
+                .unwrap() as u64;
+            start..start + contents.len() as u64
+        };
+        let (offset0, offset1) = (find(CONTENTS0), find(CONTENTS1));
+
+        let reads = std::sync::Arc::default();
+        let reader = RecordingReader {
+            inner: io::Cursor::new(bytes.clone()),
+            reads: std::sync::Arc::clone(&reads),
+        };
+        let dump = Minidump::read_lazy(reader).unwrap();
+        let was_read = |range: &std::ops::Range<u64>| {
+            let reads = reads.lock().unwrap();
+            reads
+                .iter()
+                .any(|read| read.start < range.end && range.start < read.end)
+        };
+
+        dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let memory = dump.get_memory().unwrap();
+        assert!(!was_read(&offset0));
+        assert!(!was_read(&offset1));
+
+        // Validation only checks the bounds of the memory.
+        assert!(dump.validate().is_empty());
+        assert!(!was_read(&offset0));
+        assert!(!was_read(&offset1));
+
+        let region = memory.memory_at_address(0x1000).unwrap();
+        assert_eq!(region.bytes(), CONTENTS0);
+        assert!(was_read(&offset0));
+        assert!(!was_read(&offset1));
     }
 
     #[test]
@@ -6569,6 +9180,84 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(mem_slices[0], CONTENTS);
     }
 
+    #[test]
+    fn test_address_space() {
+        let regions: &[(u64, &[u8])] = &[
+            (0x1000, &[0x88, 0x77, 0x66, 0x55]),
+            (0x1004, &[0x44, 0x33, 0x22, 0x11]),
+            (0x2000, b"hello, "),
+            (0x2007, b"world\0"),
+            (0x3000, &[b'h', 0, b'i']),
+            (0x3003, &[0, 0, 0]),
+        ];
+        let dump = regions.iter().fold(
+            SynthMinidump::with_endian(Endian::Little),
+            |dump, &(base, bytes)| {
+                dump.add_memory64(Memory::with_section(
+                    Section::with_endian(Endian::Little).append_bytes(bytes),
+                    base,
+                ))
+            },
+        );
+        let bytes = dump.finish().unwrap();
+        let eager = Minidump::read(bytes.clone()).unwrap();
+        let lazy = Minidump::read_lazy(io::Cursor::new(bytes)).unwrap();
+
+        for memory_list in [eager.get_memory().unwrap(), lazy.get_memory().unwrap()] {
+            let memory = MinidumpAddressSpace::from(&memory_list);
+
+            assert_eq!(
+                memory.memory_at_address(0x1005).unwrap().base_address(),
+                0x1004
+            );
+            assert!(matches!(
+                memory.read_bytes(0x1000, 4),
+                Some(Cow::Borrowed(_))
+            ));
+            assert_eq!(memory.read_u64(0x1000), Some(0x1122334455667788));
+            assert_eq!(memory.read_u32(0x1002), Some(0x33445566));
+            assert_eq!(memory.read_u32(0x1006), None);
+            assert_eq!(memory.read_bytes(0x1006, 4), None);
+            assert_eq!(
+                memory.read_bytes_up_to(0x1006, 4).as_deref(),
+                Some(&[0x22, 0x11][..])
+            );
+            assert_eq!(memory.read_bytes_up_to(0x1008, 4), None);
+            assert_eq!(
+                memory.memory_range_at_address(0x1005),
+                Some(Range::new(0x1000, 0x1007))
+            );
+            assert_eq!(memory.memory_range_at_address(0x1008), None);
+
+            assert_eq!(
+                memory.read_c_string(0x2000, 64).as_deref(),
+                Some(&b"hello, world"[..])
+            );
+            assert_eq!(
+                memory.read_c_string(0x2008, 64).as_deref(),
+                Some(&b"orld"[..])
+            );
+            assert_eq!(memory.read_c_string(0x2000, 12), None);
+            assert_eq!(memory.read_c_string(0x1000, 64), None);
+
+            assert_eq!(memory.read_utf16_string(0x3000, 8).as_deref(), Some("hi"));
+            assert_eq!(memory.read_utf16_string(0x3000, 2), None);
+
+            // Earlier sources take precedence over later ones.
+            let zeroes = MinidumpMemory {
+                desc: Default::default(),
+                base_address: 0x1000,
+                size: 4,
+                bytes: &[0; 4],
+                endian: scroll::LE,
+            };
+            let memory = MinidumpAddressSpace::new()
+                .with_region(UnifiedMemory::Memory(&zeroes))
+                .with_memory_list(&memory_list);
+            assert_eq!(memory.read_u64(0x1000), Some(0x1122334400000000));
+        }
+    }
+
     #[test]
     fn test_memory_overflow() {
         let memory1 = Memory::with_section(
@@ -6907,6 +9596,97 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(xstate_iter.next(), None);
     }
 
+    #[test]
+    fn test_system_memory_info() {
+        let mut info = SystemMemoryInfo::new(Endian::Little);
+        info.number_of_physical_pages = 0x40000;
+        info.perf_info = SystemMemoryFieldsCommit {
+            available_pages: 0x100,
+            committed_pages: 0x200,
+            commit_limit: 0x300,
+            peak_commitment: 0x280,
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(info);
+        let dump = read_synth_dump(dump).unwrap();
+        let info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(info.raw.revision, 1);
+        assert_eq!(info.page_size(), 0x1000);
+        assert_eq!(info.total_physical_memory(), 0x4000_0000);
+        assert_eq!(info.available_physical_memory(), 0x10_0000);
+        assert_eq!(info.commit_charge(), 0x20_0000);
+        assert_eq!(info.commit_limit(), 0x30_0000);
+        assert_eq!(info.peak_commit_charge(), 0x28_0000);
+
+        // The 64-bit counters take precedence when they're valid.
+        let mut info = SystemMemoryInfo::new(Endian::Big);
+        info.perf_info.committed_pages = 0x200;
+        info.basic_perf_info = Some(SystemMemoryFieldsCommit {
+            available_pages: 0x1_0000_0000,
+            committed_pages: 0x2_0000_0000,
+            commit_limit: 0x3_0000_0000,
+            peak_commitment: 0x2_8000_0000,
+        });
+        let dump = SynthMinidump::with_endian(Endian::Big).add_stream(info);
+        let dump = read_synth_dump(dump).unwrap();
+        let info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(info.commit_charge(), 0x2_0000_0000 * 0x1000);
+        assert_eq!(info.commit_limit(), 0x3_0000_0000 * 0x1000);
+    }
+
+    #[test]
+    fn test_process_vm_counters_1() {
+        let mut counters = ProcessVmCounters::new(Endian::Little);
+        counters.counters = Some(VmCountersFields {
+            page_fault_count: 12,
+            working_set_size: 0x1000,
+            pagefile_usage: 0x2000,
+            peak_pagefile_usage: 0x3000,
+            ..Default::default()
+        });
+        counters.private_usage = Some(0x4000);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(counters);
+        let dump = read_synth_dump(dump).unwrap();
+        let counters = dump.get_stream::<MinidumpProcessVmCounters>().unwrap();
+        assert!(matches!(counters.raw, RawProcessVmCounters::V1(_)));
+        assert_eq!(counters.raw.revision(), 1);
+        assert_eq!(counters.raw.page_fault_count(), Some(&12));
+        assert_eq!(counters.raw.working_set_size(), Some(&0x1000));
+        assert_eq!(counters.raw.pagefile_usage(), Some(&0x2000));
+        assert_eq!(counters.raw.peak_pagefile_usage(), Some(&0x3000));
+        assert_eq!(counters.raw.private_usage(), Some(&0x4000));
+        assert_eq!(counters.raw.virtual_size(), None);
+        assert_eq!(counters.raw.job_total_commit_limit(), None);
+    }
+
+    #[test]
+    fn test_process_vm_counters_2() {
+        let mut counters = ProcessVmCounters::new(Endian::Big);
+        counters.counters = Some(VmCountersFields {
+            pagefile_usage: 0x2000,
+            ..Default::default()
+        });
+        counters.virtual_size = Some(VmCountersFieldsVirtualSize {
+            peak_virtual_size: 0x20_0000,
+            virtual_size: 0x10_0000,
+        });
+        counters.job = Some(VmCountersFieldsJob {
+            job_total_commit_limit: 0x8000,
+            ..Default::default()
+        });
+        let dump = SynthMinidump::with_endian(Endian::Big).add_stream(counters);
+        let dump = read_synth_dump(dump).unwrap();
+        let counters = dump.get_stream::<MinidumpProcessVmCounters>().unwrap();
+        assert!(matches!(counters.raw, RawProcessVmCounters::V2(_)));
+        assert_eq!(counters.raw.revision(), 2);
+        assert_eq!(counters.raw.pagefile_usage(), Some(&0x2000));
+        assert_eq!(counters.raw.virtual_size(), Some(&0x10_0000));
+        assert_eq!(counters.raw.peak_virtual_size(), Some(&0x20_0000));
+        assert_eq!(counters.raw.job_total_commit_limit(), Some(&0x8000));
+        // Not flagged as valid
+        assert_eq!(counters.raw.private_usage(), None);
+        assert_eq!(counters.raw.shared_commit_usage(), None);
+    }
+
     #[test]
     fn test_elf_build_id() {
         // Add a module with a long ELF build id
@@ -7178,6 +9958,84 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(stack.size, 0x1000);
     }
 
+    #[test]
+    fn test_thread_context_xstate_amd64() {
+        let (context, xstate_data) = minidump_synth::amd64_context_with_xstate(
+            Endian::Little,
+            0x1234abcd1234abcd,
+            0x1000000010000000,
+            0x7ff0_1234_5000,
+        );
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.misc_5 = Some(MiscInfo5Fields {
+            xstate_data,
+            process_cookie: None,
+        });
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info)
+            .add_stream(misc);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+
+        // Without the layout of the XSAVE area, there's nothing to read.
+        let context = thread_list.threads[0]
+            .context(&system_info, None)
+            .expect("Should have a thread context");
+        assert!(context.xstate.is_none());
+
+        let context = thread_list.threads[0]
+            .context(&system_info, Some(&misc_info))
+            .expect("Should have a thread context");
+        match &context.raw {
+            MinidumpRawContext::Amd64(raw) => assert_eq!(raw.rip, 0x1234abcd1234abcd),
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let xstate = context.xstate.as_ref().expect("Should have xstate");
+
+        assert_eq!(xstate.ymm.len(), 16);
+        assert_eq!(xstate.ymm[0][..16], [0x10; 16]);
+        assert_eq!(xstate.ymm[0][16..], [0x20; 16]);
+        assert_eq!(xstate.ymm[15][..16], [0x1f; 16]);
+        assert_eq!(xstate.ymm[15][16..], [0x2f; 16]);
+
+        assert_eq!(xstate.kmask, (0..8).collect::<Vec<u64>>());
+
+        assert_eq!(xstate.zmm.len(), 32);
+        assert_eq!(xstate.zmm[3][..32], xstate.ymm[3]);
+        assert_eq!(xstate.zmm[3][32..], [0x33; 32]);
+        assert_eq!(xstate.zmm[16], [0x40; 64]);
+        assert_eq!(xstate.zmm[31], [0x4f; 64]);
+
+        assert_eq!(xstate.cet_control, Some(1));
+        assert_eq!(xstate.shadow_stack_pointer, Some(0x7ff0_1234_5000));
+
+        let registers = xstate.registers().collect::<HashMap<_, _>>();
+        assert_eq!(
+            registers["ymm1"],
+            format!("0x{}{}", "21".repeat(16), "11".repeat(16))
+        );
+        assert_eq!(registers["k7"], "0x0000000000000007");
+        assert_eq!(registers["ssp"], "0x00007ff012345000");
+
+        let mut output = vec![];
+        context.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("XSTATE"));
+        assert!(output.contains("zmm31"));
+    }
+
     #[test]
     fn test_thread_list_amd64() {
         let context =
@@ -7216,6 +10074,80 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(stack.size, 0x1000);
     }
 
+    #[test]
+    fn test_thread_list_mips64() {
+        let context =
+            minidump_synth::mips64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_MIPS64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(system_info.cpu, Cpu::Mips64);
+        let mut thread = thread_list.threads.pop().unwrap();
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd1234abcd);
+        assert_eq!(context.get_stack_pointer(), 0x1000000010000000);
+        assert!(matches!(context.raw, MinidumpRawContext::Mips(_)));
+        let stack = thread.stack.take().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000000010000000);
+    }
+
+    #[test]
+    fn test_thread_list_riscv64() {
+        let context =
+            minidump_synth::riscv64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_RISCV64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(system_info.cpu, Cpu::Riscv64);
+        assert_eq!(thread_list.threads.len(), 1);
+        let mut thread = thread_list.threads.pop().unwrap();
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        assert_eq!(context.valid, MinidumpContextValidity::All);
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd1234abcd);
+        assert_eq!(context.get_stack_pointer(), 0x1000000010000000);
+        match &context.raw {
+            MinidumpRawContext::Riscv64(raw) => {
+                assert_eq!(raw.pc, 0x1234abcd1234abcd);
+                assert_eq!(raw.iregs[1], 0x1000000010000000);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let stack = thread.stack.take().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000000010000000);
+        assert_eq!(stack.size, 0x1000);
+    }
+
     #[test]
     fn test_crashpad_info_missing() {
         let dump = SynthMinidump::with_endian(Endian::Little);
@@ -7443,4 +10375,464 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
             OBJECT_NAME
         );
     }
+
+    #[test]
+    fn test_handle_operation_list() {
+        let open = SynthHandleOperation::new(
+            Endian::Little,
+            0x44,
+            0x1234,
+            md::eHANDLE_TRACE_OPERATIONS::OperationDbOPEN,
+            &[0x7ff0_1000, 0x7ff0_2000, 0x7ff0_3000],
+        );
+        let close = SynthHandleOperation::new(
+            Endian::Little,
+            0x44,
+            0x5678,
+            md::eHANDLE_TRACE_OPERATIONS::OperationDbCLOSE,
+            &[0x7ff0_4000],
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_handle_operation(open)
+            .add_handle_operation(close);
+        let dump = read_synth_dump(dump).unwrap();
+        let operations = dump.get_stream::<MinidumpHandleOperationList>().unwrap();
+        let operations = operations.iter().collect::<Vec<_>>();
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].raw.handle, 0x44);
+        assert_eq!(operations[0].raw.thread_id, 0x1234);
+        assert_eq!(
+            operations[0].operation,
+            Some(md::eHANDLE_TRACE_OPERATIONS::OperationDbOPEN)
+        );
+        assert_eq!(
+            operations[0].backtrace(),
+            &[0x7ff0_1000, 0x7ff0_2000, 0x7ff0_3000]
+        );
+        assert_eq!(
+            operations[1].operation,
+            Some(md::eHANDLE_TRACE_OPERATIONS::OperationDbCLOSE)
+        );
+        assert_eq!(operations[1].backtrace(), &[0x7ff0_4000]);
+    }
+
+    #[test]
+    fn test_function_table_list() {
+        let tables = SynthFunctionTableList::new(Endian::Little)
+            .add_table(SynthFunctionTable {
+                minimum_address: 0x7ff0_0000_1000,
+                maximum_address: 0x7ff0_0000_2000,
+                base_address: 0x7ff0_0000_0000,
+                // Deliberately out of order
+                functions: vec![(0x1800, 0x2000, 0x40), (0x1000, 0x1100, 0x20)],
+            })
+            .add_table(SynthFunctionTable {
+                minimum_address: 0x7ff1_0000_0000,
+                maximum_address: 0x7ff1_0000_0100,
+                base_address: 0x7ff1_0000_0000,
+                functions: vec![(0, 0x100, 0x200)],
+            });
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(tables);
+        let dump = read_synth_dump(dump).unwrap();
+        let tables = dump.get_stream::<MinidumpFunctionTableList>().unwrap();
+        assert_eq!(tables.tables.len(), 2);
+        assert_eq!(tables.tables[0].native_descriptor.len(), 88);
+        assert_eq!(tables.tables[0].raw_entries.len(), 24);
+        assert_eq!(tables.tables[0].functions[0].begin_address, 0x1000);
+        assert_eq!(tables.tables[0].functions[1].begin_address, 0x1800);
+
+        let (table, function) = tables.function_at_address(0x7ff0_0000_1050).unwrap();
+        assert_eq!(table.raw.base_address, 0x7ff0_0000_0000);
+        assert_eq!(function.unwind_info_address, 0x20);
+        let (_, function) = tables.function_at_address(0x7ff0_0000_1fff).unwrap();
+        assert_eq!(function.unwind_info_address, 0x40);
+        let (table, function) = tables.function_at_address(0x7ff1_0000_0000).unwrap();
+        assert_eq!(table.raw.base_address, 0x7ff1_0000_0000);
+        assert_eq!(function.unwind_info_address, 0x200);
+        // In a table, but between functions
+        assert!(tables.function_at_address(0x7ff0_0000_1100).is_none());
+        // Not in any table
+        assert!(tables.function_at_address(0x1000).is_none());
+    }
+
+    #[test]
+    fn test_token_list() {
+        let local_system = Sid {
+            identifier_authority: 5,
+            sub_authorities: vec![18],
+        };
+        let administrators = Sid {
+            identifier_authority: 5,
+            sub_authorities: vec![32, 544],
+        };
+        let mut info = TokenAccessInformation::new(Endian::Little);
+        info.base = 0x2_0000_1000;
+        info.authentication_id = 0x3e7;
+        info.sids = vec![(local_system, 0), (administrators, 0xe)];
+        info.privileges = vec![(20, 0x3), (23, 0x3), (0x1234, 0)];
+
+        let mut system_info = SystemInfo::new(Endian::Little);
+        system_info.processor_architecture =
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let tokens = TokenList::new(Endian::Little)
+            .add_token(0x1000, 0x80, info)
+            .add_token(0x1004, 0x84, Section::new().append_bytes(b"garbage"));
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_stream(tokens);
+        let dump = read_synth_dump(dump).unwrap();
+        let tokens = dump.get_stream::<MinidumpTokenList>().unwrap();
+        let tokens = tokens.iter().collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].raw.token_id, 0x1000);
+        assert_eq!(tokens[0].raw.token_handle, 0x80);
+
+        let info = tokens[0].access_info.as_ref().unwrap();
+        assert_eq!(info.authentication_id, 0x3e7);
+        assert_eq!(info.token_type, 1);
+        let user = info.user.as_ref().unwrap();
+        assert_eq!(user.sid.to_string(), "S-1-5-18");
+        assert_eq!(info.groups.len(), 1);
+        assert_eq!(info.groups[0].sid.to_string(), "S-1-5-32-544");
+        assert_eq!(info.groups[0].attributes, 0xe);
+        let privileges = info
+            .privileges
+            .iter()
+            .map(|privilege| (privilege.name(), privilege.is_enabled()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            privileges,
+            vec![
+                (Some("SeDebugPrivilege"), true),
+                (Some("SeChangeNotifyPrivilege"), true),
+                (None, false)
+            ]
+        );
+
+        // Data that can't be decoded is still available.
+        assert_eq!(tokens[1].raw.token_id, 0x1004);
+        assert_eq!(tokens[1].data, b"garbage");
+        assert!(tokens[1].access_info.is_none());
+    }
+
+    #[test]
+    fn test_thread_info_list() {
+        // 2021-01-01T00:00:00Z as a FILETIME.
+        const CREATE_TIME: u64 = 132_539_328_000_000_000;
+        let exited = md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_EXITED_THREAD.bits();
+        let info1 = SynthThreadInfo::new(
+            Endian::Little,
+            0x1234,
+            0,
+            CREATE_TIME,
+            0,
+            15_000_000,
+            25_000_000,
+            0x7fff_0000_1000,
+        );
+        let info2 = SynthThreadInfo::new(
+            Endian::Little,
+            0x5678,
+            exited,
+            CREATE_TIME,
+            CREATE_TIME + 10_000_000,
+            0,
+            0,
+            0x7fff_0000_2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_info(info1)
+            .add_thread_info(info2);
+        let dump = read_synth_dump(dump).unwrap();
+        let info_list = dump.get_stream::<MinidumpThreadInfoList>().unwrap();
+        assert_eq!(info_list.iter().count(), 2);
+
+        let info = info_list.get_thread_info(0x1234).unwrap();
+        assert_eq!(info.raw.start_address, 0x7fff_0000_1000);
+        assert!(!info.has_exited());
+        assert_eq!(
+            info.create_time(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_609_459_200))
+        );
+        assert_eq!(info.exit_time(), None);
+        assert_eq!(info.kernel_time(), Duration::from_millis(1500));
+        assert_eq!(info.user_time(), Duration::from_millis(2500));
+
+        let info = info_list.get_thread_info(0x5678).unwrap();
+        assert!(info.has_exited());
+        assert_eq!(
+            info.exit_time(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_609_459_201))
+        );
+
+        assert!(info_list.get_thread_info(0x9999).is_none());
+    }
+
+    #[test]
+    fn test_comment_streams() {
+        // "café" in Windows-1252, followed by a terminator and some garbage.
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::CommentStreamA as u32,
+            section: Section::new().append_bytes(b"caf\xe9\0junk"),
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let comment = dump.get_stream::<MinidumpCommentA>().unwrap();
+        assert_eq!(comment.comment, "café");
+        let comment = dump.get_comment().unwrap();
+        assert!(matches!(comment, MinidumpComment::Ansi(_)));
+        assert_eq!(comment.comment(), "café");
+
+        for endian in [Endian::Little, Endian::Big] {
+            let comment_w = DumpString::new("crash in ☃\0", endian);
+            // Skip the length prefix, comment streams are just the raw characters.
+            let section = Section::with_endian(endian).append_section(comment_w);
+            let bytes = section.get_contents().unwrap();
+            let dump = SynthMinidump::with_endian(endian)
+                .add_stream(SimpleStream {
+                    stream_type: MINIDUMP_STREAM_TYPE::CommentStreamA as u32,
+                    section: Section::new().append_bytes(b"ansi\0"),
+                })
+                .add_stream(SimpleStream {
+                    stream_type: MINIDUMP_STREAM_TYPE::CommentStreamW as u32,
+                    section: Section::new().append_bytes(&bytes[4..]),
+                });
+            let dump = read_synth_dump(dump).unwrap();
+            let comment = dump.get_comment().unwrap();
+            assert!(matches!(comment, MinidumpComment::Unicode(_)));
+            assert_eq!(comment.comment(), "crash in ☃");
+        }
+    }
+
+    #[test]
+    fn test_linux_auxv_64bit() {
+        let strings = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_bytes(b"x86_64\0/usr/bin/app\0")
+                .append_repeated(0xab, 16),
+            0x7ffd_0000_0000,
+        );
+        let auxv = Section::with_endian(Endian::Little)
+            .D64(md::AuxvType::AT_SYSINFO_EHDR as u64)
+            .D64(0x7ffd_1234_0000)
+            .D64(md::AuxvType::AT_HWCAP as u64)
+            .D64(0x178bfbff)
+            .D64(md::AuxvType::AT_BASE as u64)
+            .D64(0x7f00_0000_0000)
+            .D64(md::AuxvType::AT_RANDOM as u64)
+            .D64(0x7ffd_0000_0014)
+            .D64(md::AuxvType::AT_HWCAP2 as u64)
+            .D64(0x2)
+            .D64(0x1234) // Unknown type
+            .D64(0x5678)
+            .D64(md::AuxvType::AT_EXECFN as u64)
+            .D64(0x7ffd_0000_0007)
+            .D64(md::AuxvType::AT_PLATFORM as u64)
+            .D64(0x7ffd_0000_0000)
+            .D64(md::AuxvType::AT_NULL as u64)
+            .D64(0)
+            // Trailing garbage after AT_NULL should be ignored.
+            .D64(md::AuxvType::AT_HWCAP as u64)
+            .D64(0xffff);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_memory(strings)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+                section: auxv,
+            });
+        let dump = read_synth_dump(dump).unwrap();
+        let memory = dump.get_memory().unwrap();
+        let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+        assert_eq!(auxv.iter().count(), 8);
+        assert_eq!(auxv.iter().nth(5).unwrap().auxv_type(), None);
+        assert_eq!(auxv.hwcap(), Some(0x178bfbff));
+        assert_eq!(auxv.hwcap2(), Some(0x2));
+        assert_eq!(auxv.sysinfo_ehdr(), Some(0x7ffd_1234_0000));
+        assert_eq!(auxv.base(), Some(0x7f00_0000_0000));
+        assert_eq!(auxv.random_bytes(&memory), Some([0xab; 16]));
+        assert_eq!(auxv.platform(&memory).as_deref(), Some("x86_64"));
+        assert_eq!(auxv.execfn(&memory).as_deref(), Some("/usr/bin/app"));
+        assert_eq!(auxv.execfn(&UnifiedMemoryList::default()), None);
+    }
+
+    #[test]
+    fn test_linux_auxv_32bit() {
+        let auxv = Section::with_endian(Endian::Big)
+            .D32(md::AuxvType::AT_HWCAP as u32)
+            .D32(0x1234)
+            .D32(md::AuxvType::AT_SYSINFO_EHDR as u32)
+            .D32(0xb7f0_0000);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM as u16;
+        let system_info = SystemInfo::new(Endian::Big).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Big)
+            .add_system_info(system_info)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+                section: auxv,
+            });
+        let dump = read_synth_dump(dump).unwrap();
+        let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+        assert_eq!(auxv.iter().count(), 2);
+        assert_eq!(auxv.hwcap(), Some(0x1234));
+        assert_eq!(auxv.sysinfo_ehdr(), Some(0xb7f0_0000));
+        assert_eq!(auxv.hwcap2(), None);
+    }
+
+    #[test]
+    fn test_linux_cmd_line() {
+        let input = b"/opt/app/app\0--type=renderer\0\0--lang=en\0";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cmd_line(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let cmd_line = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+        let args = cmd_line
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(args, ["/opt/app/app", "--type=renderer", "", "--lang=en"]);
+        assert_eq!(&cmd_line.raw_bytes()[..], &input[..]);
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cmd_line(b"");
+        let dump = read_synth_dump(dump).unwrap();
+        let cmd_line = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+        assert_eq!(cmd_line.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_linux_dso_debug() {
+        const BASE: u64 = 0x1000_0000;
+        const LIB: &str = "/usr/lib/libplugin.so";
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+
+        // The copy of the link_map that the minidump writer made.
+        let name = DumpString::new(LIB, Endian::Little);
+        let link_map = Section::with_endian(Endian::Little)
+            .D64(0)
+            .D32(0)
+            .D64(0x5555_0000_3000)
+            .D64(0x7f00_0000_0000)
+            .D32(name.file_offset())
+            .D64(0x7f00_0000_2000);
+        let dso_debug = Section::with_endian(Endian::Little)
+            .D32(1) // version
+            .D32(link_map.file_offset())
+            .D32(2) // dso_count
+            .D64(0x7f10_0000_1000) // brk
+            .D64(0x7f10_0000_0000) // ldbase
+            .D64(BASE); // dynamic
+
+        // The live linker state in memory, starting with the main executable's
+        // dynamic section.
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                // _DYNAMIC: DT_NEEDED, DT_DEBUG, DT_NULL
+                .D64(1)
+                .D64(0)
+                .D64(21)
+                .D64(BASE + 0x30)
+                .D64(0)
+                .D64(0)
+                // r_debug
+                .D32(1)
+                .D32(0)
+                .D64(BASE + 0x60) // r_map
+                .D64(0x7f10_0000_1000)
+                .D64(0)
+                .D64(0x7f10_0000_0000)
+                .D64(0) // padding
+                // link_map for the main executable
+                .D64(0)
+                .D64(BASE + 0xb0)
+                .D64(0x5555_0000_3000)
+                .D64(BASE + 0x88)
+                .D64(0)
+                // link_map for the library
+                .D64(0x7f00_0000_0000)
+                .D64(BASE + 0xb1)
+                .D64(0x7f00_0000_2000)
+                .D64(0)
+                .D64(BASE + 0x60)
+                // strings
+                .D8(0)
+                .append_bytes(LIB.as_bytes())
+                .D8(0),
+            BASE,
+        );
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_memory(memory)
+            .add(link_map)
+            .add(name)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+                section: dso_debug,
+            });
+        let dump = read_synth_dump(dump).unwrap();
+        let dso_debug = dump.get_stream::<MinidumpLinuxDsoDebug>().unwrap();
+        assert_eq!(dso_debug.version, 1);
+        assert_eq!(dso_debug.brk, 0x7f10_0000_1000);
+        assert_eq!(dso_debug.ldbase, 0x7f10_0000_0000);
+        assert_eq!(dso_debug.dynamic, BASE);
+        let expected = vec![
+            MinidumpLinkMapEntry {
+                addr: 0,
+                name: String::new(),
+                ld: 0x5555_0000_3000,
+            },
+            MinidumpLinkMapEntry {
+                addr: 0x7f00_0000_0000,
+                name: String::from(LIB),
+                ld: 0x7f00_0000_2000,
+            },
+        ];
+        assert_eq!(dso_debug.link_map, expected);
+
+        let memory = dump.get_memory().unwrap();
+        assert_eq!(dso_debug.walk_link_map(&memory), Some(expected));
+        assert_eq!(dso_debug.walk_link_map(&UnifiedMemoryList::default()), None);
+    }
+
+    #[test]
+    fn test_linux_dso_debug_overflow() {
+        const BASE: u64 = 0x1000_0000;
+        let walk = |dynamic: Section| {
+            let bytes = dynamic.get_contents().unwrap();
+            let memory =
+                UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(vec![MinidumpMemory {
+                    desc: Default::default(),
+                    base_address: BASE,
+                    size: bytes.len() as u64,
+                    bytes: &bytes,
+                    endian: scroll::LE,
+                }]));
+            let dso_debug = MinidumpLinuxDsoDebug {
+                version: 1,
+                brk: 0,
+                ldbase: 0,
+                dynamic: BASE,
+                link_map: vec![],
+                is_64bit: true,
+            };
+            dso_debug.walk_link_map(&memory)
+        };
+
+        // DT_DEBUG points at the very end of the address space.
+        let dynamic = Section::with_endian(Endian::Little)
+            .D64(21)
+            .D64(u64::MAX - 3);
+        assert_eq!(walk(dynamic), None);
+
+        // r_map points at the very end of the address space.
+        let dynamic = Section::with_endian(Endian::Little)
+            .D64(21)
+            .D64(BASE + 0x10)
+            // r_debug
+            .D32(1)
+            .D32(0)
+            .D64(u64::MAX - 7);
+        assert_eq!(walk(dynamic), Some(vec![]));
+    }
 }
@@ -0,0 +1,1010 @@
+//! Scrubbing personal data from minidumps.
+//!
+//! [`redact`] seeds a [`MinidumpBuilder`] with the contents of a minidump and removes the
+//! parts of it that are likely to contain personal data:
+//!
+//! * Memory outside of thread stacks and module images is zeroed. The regions are kept
+//!   (so the memory layout of the process is still visible), but their contents are gone.
+//! * Environment variables (`LinuxEnviron`) and Crashpad annotations whose keys match one of
+//!   the [key patterns][RedactionOptions::key_patterns] are dropped, along with the Crashpad
+//!   list annotations, which have no keys to match.
+//! * The command line (`LinuxCmdLine`) is dropped.
+//! * The comments (`CommentStreamA` and `CommentStreamW`), which are free-form text, are
+//!   dropped.
+//! * The access tokens (`TokenStream`), which have the SIDs of the user and their groups,
+//!   are dropped.
+//! * User names in paths (`C:\Users\alice\...`, `/home/alice/...`) are replaced with
+//!   `<redacted>`. This covers the names of modules and unloaded modules, the PDB paths in
+//!   the CodeView records of modules, the object names of handles, the names of threads,
+//!   the `LinuxMaps` stream and the names of the `LinuxDsoDebug` link map.
+//!
+//! Everything that was removed is listed in a [`RedactionReport`]:
+//!
+//! ```
+//! use minidump::redact::{redact, RedactionOptions};
+//! use minidump::Minidump;
+//!
+//! # fn foo() -> Result<(), minidump::Error> {
+//! let dump = Minidump::read_path("../testdata/test.dmp")?;
+//! let (builder, report) = redact(&dump, &RedactionOptions::default());
+//!
+//! report.print(&mut std::io::stderr()).or(Err(minidump::Error::IoError))?;
+//! std::fs::write("redacted.dmp", builder.build()?).or(Err(minidump::Error::IoError))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Redaction is best-effort: stacks and streams this crate doesn't know about can still
+//! contain personal data.
+
+use num_traits::FromPrimitive;
+use scroll::{Endian, Pread, Pwrite};
+use std::io;
+use std::io::prelude::*;
+use std::ops::{Deref, Range};
+
+use crate::minidump::{
+    read_string_utf16, read_string_utf8_unterminated, Minidump, MinidumpLinuxEnviron,
+    MinidumpModuleList, MinidumpThreadList,
+};
+use crate::writer::{
+    entries, ex_list_entries, list_entries, MinidumpBuilder, ReferenceTarget, StreamData,
+    CRASHPAD_ANNOTATION_SIZE, CRASHPAD_DICTIONARY_ENTRY_SIZE,
+    CRASHPAD_MODULE_ANNOTATION_OBJECTS_OFFSET, CRASHPAD_MODULE_LINK_SIZE,
+    CRASHPAD_MODULE_LIST_ANNOTATIONS_OFFSET, CRASHPAD_MODULE_LIST_OFFSET,
+    CRASHPAD_MODULE_SIMPLE_ANNOTATIONS_OFFSET, CRASHPAD_SIMPLE_ANNOTATIONS_OFFSET,
+    DSO_DEBUG_MAP_OFFSET, HANDLE_DESCRIPTOR_SIZE, HANDLE_OBJECT_NAME_OFFSET,
+    MODULE_CV_RECORD_OFFSET, MODULE_NAME_OFFSET, MODULE_SIZE, THREAD_NAME_OFFSET, THREAD_NAME_SIZE,
+};
+use minidump_common::format::{CvSignature, MINIDUMP_STREAM_TYPE};
+use minidump_common::traits::Module;
+
+/// The key patterns used by [`RedactionOptions::default`].
+///
+/// These cover the usual places for user names, paths, and credentials. They're a starting
+/// point: products with their own annotations should extend them.
+pub const DEFAULT_KEY_PATTERNS: &[&str] = &[
+    "USER",
+    "USERNAME",
+    "LOGNAME",
+    "HOME",
+    "MAIL",
+    "HOSTNAME",
+    "*PWD",
+    "*PATH*",
+    "*DIR",
+    "*TOKEN*",
+    "*SECRET*",
+    "*PASSWORD*",
+    "*PASSWD*",
+    "*KEY*",
+    "*AUTH*",
+    "*COOKIE*",
+    "*SESSION*",
+    "*CREDENTIAL*",
+    "*EMAIL*",
+    "*URL*",
+];
+
+/// What [`redact`] removes.
+#[derive(Debug, Clone)]
+pub struct RedactionOptions {
+    /// Environment variables and Crashpad annotations with keys matching any of these
+    /// patterns are dropped.
+    ///
+    /// Patterns are matched against the whole key, ignoring ASCII case. `*` matches any
+    /// number of characters and `?` matches a single one.
+    pub key_patterns: Vec<String>,
+    /// Zero memory outside of thread stacks and module images.
+    pub zero_memory: bool,
+    /// Drop the `LinuxCmdLine` stream.
+    pub remove_command_line: bool,
+    /// Drop the `CommentStreamA` and `CommentStreamW` streams.
+    pub remove_comments: bool,
+    /// Drop the `TokenStream`.
+    pub remove_tokens: bool,
+    /// Drop the Crashpad list annotations, which are values without keys.
+    pub remove_list_annotations: bool,
+    /// Replace user names in the paths of modules, unloaded modules, PDBs, handles,
+    /// threads, memory mappings (`LinuxMaps`) and loaded libraries (`LinuxDsoDebug`).
+    pub scrub_paths: bool,
+}
+
+impl Default for RedactionOptions {
+    fn default() -> Self {
+        RedactionOptions {
+            key_patterns: DEFAULT_KEY_PATTERNS.iter().map(|s| s.to_string()).collect(),
+            zero_memory: true,
+            remove_command_line: true,
+            remove_comments: true,
+            remove_tokens: true,
+            remove_list_annotations: true,
+            scrub_paths: true,
+        }
+    }
+}
+
+impl RedactionOptions {
+    /// Whether `key` matches any of the [key patterns][Self::key_patterns].
+    pub fn matches_key(&self, key: &[u8]) -> bool {
+        self.key_patterns
+            .iter()
+            .any(|pattern| glob_match(pattern.as_bytes(), key))
+    }
+}
+
+/// Everything [`redact`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionReport {
+    /// The number of memory regions that had bytes zeroed.
+    pub zeroed_regions: usize,
+    /// The total number of bytes of memory zeroed.
+    pub zeroed_bytes: u64,
+    /// The names of the environment variables that were dropped.
+    pub environment_variables: Vec<String>,
+    /// Whether the command line was dropped.
+    pub command_line: bool,
+    /// Whether the comments were dropped.
+    pub comments: bool,
+    /// Whether the access tokens were dropped.
+    pub tokens: bool,
+    /// The keys of the Crashpad simple annotations and annotation objects that were dropped.
+    pub annotations: Vec<String>,
+    /// The number of Crashpad list annotations that were dropped.
+    pub list_annotations: usize,
+    /// The modules whose paths were scrubbed, as `(original, scrubbed)`.
+    pub module_names: Vec<(String, String)>,
+    /// The PDB paths in the CodeView records of modules that were scrubbed, as
+    /// `(original, scrubbed)`.
+    pub debug_files: Vec<(String, String)>,
+    /// The object names of handles that were scrubbed, as `(original, scrubbed)`.
+    pub handle_names: Vec<(String, String)>,
+    /// The names of threads that were scrubbed, as `(original, scrubbed)`.
+    pub thread_names: Vec<(String, String)>,
+    /// The lines of the `LinuxMaps` stream whose paths were scrubbed, as
+    /// `(original, scrubbed)`.
+    pub linux_maps: Vec<(String, String)>,
+    /// The names in the `LinuxDsoDebug` link map that were scrubbed, as
+    /// `(original, scrubbed)`.
+    pub link_map_names: Vec<(String, String)>,
+}
+
+impl RedactionReport {
+    /// Whether nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        *self == RedactionReport::default()
+    }
+
+    /// Write a human-readable description of the report to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        writeln!(
+            f,
+            "zeroed {} bytes of memory in {} regions",
+            self.zeroed_bytes, self.zeroed_regions
+        )?;
+        if self.command_line {
+            writeln!(f, "removed the command line")?;
+        }
+        if self.comments {
+            writeln!(f, "removed the comments")?;
+        }
+        if self.tokens {
+            writeln!(f, "removed the access tokens")?;
+        }
+        for name in &self.environment_variables {
+            writeln!(f, "removed environment variable {name}")?;
+        }
+        for key in &self.annotations {
+            writeln!(f, "removed annotation {key}")?;
+        }
+        if self.list_annotations > 0 {
+            writeln!(f, "removed {} list annotations", self.list_annotations)?;
+        }
+        for (original, scrubbed) in &self.module_names {
+            writeln!(f, "scrubbed module name {original} -> {scrubbed}")?;
+        }
+        for (original, scrubbed) in &self.debug_files {
+            writeln!(f, "scrubbed debug file {original} -> {scrubbed}")?;
+        }
+        for (original, scrubbed) in &self.handle_names {
+            writeln!(f, "scrubbed handle name {original} -> {scrubbed}")?;
+        }
+        for (original, scrubbed) in &self.thread_names {
+            writeln!(f, "scrubbed thread name {original} -> {scrubbed}")?;
+        }
+        for (original, scrubbed) in &self.linux_maps {
+            writeln!(f, "scrubbed mapping {original} -> {scrubbed}")?;
+        }
+        for (original, scrubbed) in &self.link_map_names {
+            writeln!(f, "scrubbed link map name {original} -> {scrubbed}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Seed a [`MinidumpBuilder`] with `dump`, minus the personal data `options` asks to remove.
+///
+/// See the [module documentation][self] for what is removed.
+pub fn redact<'a, T>(
+    dump: &'a Minidump<'a, T>,
+    options: &RedactionOptions,
+) -> (MinidumpBuilder<'a>, RedactionReport)
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let mut builder = MinidumpBuilder::from_minidump(dump);
+    let mut report = RedactionReport::default();
+    let endian = builder.endian();
+
+    if options.zero_memory {
+        zero_memory(dump, &mut builder, &mut report);
+    }
+
+    if let Ok(environ) = dump.get_stream::<MinidumpLinuxEnviron>() {
+        let raw = environ.raw_bytes();
+        let mut kept = Vec::with_capacity(raw.len());
+        for entry in raw.split_inclusive(|&byte| byte == b'\0' || byte == b'\n') {
+            let line = entry.strip_suffix(b"\0").unwrap_or(entry);
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            let key = line.split(|&byte| byte == b'=').next().unwrap_or(line);
+            let key = key.trim_ascii();
+            if !key.is_empty() && options.matches_key(key) {
+                let key = String::from_utf8_lossy(key).into_owned();
+                report.environment_variables.push(key);
+            } else {
+                kept.extend_from_slice(entry);
+            }
+        }
+        if !report.environment_variables.is_empty() {
+            builder.set_stream(MINIDUMP_STREAM_TYPE::LinuxEnviron, StreamData::new(kept));
+        }
+    }
+
+    if options.remove_command_line {
+        report.command_line = builder
+            .remove_stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine)
+            .is_some();
+    }
+
+    if options.remove_comments {
+        let ansi = builder.remove_stream(MINIDUMP_STREAM_TYPE::CommentStreamA);
+        let unicode = builder.remove_stream(MINIDUMP_STREAM_TYPE::CommentStreamW);
+        report.comments = ansi.is_some() || unicode.is_some();
+    }
+
+    if options.remove_tokens {
+        report.tokens = builder
+            .remove_stream(MINIDUMP_STREAM_TYPE::TokenStream)
+            .is_some();
+    }
+
+    if let Some(crashpad) = builder.stream_mut(MINIDUMP_STREAM_TYPE::CrashpadInfoStream) {
+        redact_crashpad(crashpad, endian, options, &mut report);
+    }
+
+    if options.scrub_paths {
+        if let Some(modules) = builder.stream_mut(MINIDUMP_STREAM_TYPE::ModuleListStream) {
+            let entries = list_entries(modules.bytes(), MODULE_SIZE, endian);
+            let names = entries
+                .iter()
+                .map(|entry| entry + MODULE_NAME_OFFSET)
+                .collect();
+            scrub_strings(modules, names, endian, &mut report.module_names);
+            for entry in entries {
+                let offset = entry + MODULE_CV_RECORD_OFFSET;
+                scrub_cv_record(modules, offset, endian, &mut report.debug_files);
+            }
+        }
+        if let Some(modules) = builder.stream_mut(MINIDUMP_STREAM_TYPE::UnloadedModuleListStream) {
+            // The names are the only references of unloaded modules.
+            let names = modules.references().map(|(offset, ..)| offset).collect();
+            scrub_strings(modules, names, endian, &mut report.module_names);
+        }
+        if let Some(handles) = builder.stream_mut(MINIDUMP_STREAM_TYPE::HandleDataStream) {
+            let names = ex_list_entries(handles.bytes(), HANDLE_DESCRIPTOR_SIZE, endian)
+                .into_iter()
+                .map(|entry| entry + HANDLE_OBJECT_NAME_OFFSET)
+                .collect();
+            scrub_strings(handles, names, endian, &mut report.handle_names);
+        }
+        if let Some(threads) = builder.stream_mut(MINIDUMP_STREAM_TYPE::ThreadNamesStream) {
+            let names = list_entries(threads.bytes(), THREAD_NAME_SIZE, endian)
+                .into_iter()
+                .map(|entry| entry + THREAD_NAME_OFFSET)
+                .collect();
+            scrub_strings(threads, names, endian, &mut report.thread_names);
+        }
+        if let Some(dso_debug) = builder.stream_mut(MINIDUMP_STREAM_TYPE::LinuxDsoDebug) {
+            if let Some(ReferenceTarget::Data(map)) = dso_debug.reference_mut(DSO_DEBUG_MAP_OFFSET)
+            {
+                // The names are the only references of link map entries.
+                let names = map.references().map(|(offset, ..)| offset).collect();
+                scrub_strings(map, names, endian, &mut report.link_map_names);
+            }
+        }
+        if let Some(maps) = builder.stream_mut(MINIDUMP_STREAM_TYPE::LinuxMaps) {
+            let mut kept = Vec::with_capacity(maps.bytes().len());
+            for line in maps.bytes().split_inclusive(|&byte| byte == b'\n') {
+                match std::str::from_utf8(line).ok().and_then(scrub_path) {
+                    Some(scrubbed) => {
+                        kept.extend_from_slice(scrubbed.as_bytes());
+                        let original = String::from_utf8_lossy(line).trim_end().to_owned();
+                        let scrubbed = scrubbed.trim_end().to_owned();
+                        report.linux_maps.push((original, scrubbed));
+                    }
+                    None => kept.extend_from_slice(line),
+                }
+            }
+            if !report.linux_maps.is_empty() {
+                *maps = StreamData::new(kept);
+            }
+        }
+    }
+
+    (builder, report)
+}
+
+/// Zero the builder's memory outside of the thread stacks and module images of `dump`.
+fn zero_memory<'a, T>(
+    dump: &'a Minidump<'a, T>,
+    builder: &mut MinidumpBuilder<'a>,
+    report: &mut RedactionReport,
+) where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let memory = dump.get_memory().unwrap_or_default();
+    let mut keep: Vec<Range<u64>> = vec![];
+    if let Ok(threads) = dump.get_stream::<MinidumpThreadList>() {
+        for thread in &threads.threads {
+            let stack = &thread.raw.stack;
+            let start = stack.start_of_memory_range;
+            keep.push(start..start.saturating_add(stack.memory.data_size as u64));
+            if let Some(stack) = thread.stack_memory(&memory) {
+                let start = stack.base_address();
+                keep.push(start..start.saturating_add(stack.size()));
+            }
+        }
+    }
+    if let Ok(modules) = dump.get_stream::<MinidumpModuleList>() {
+        for module in modules.iter() {
+            let start = module.base_address();
+            keep.push(start..start.saturating_add(module.size()));
+        }
+    }
+
+    let regions: Vec<(u64, usize)> = builder
+        .memory()
+        .map(|(base, bytes)| (base, bytes.len()))
+        .collect();
+    for (base, len) in regions {
+        let end = base.saturating_add(len as u64);
+        let mut zeroed: Vec<Range<u64>> = std::iter::once(base..end).collect();
+        for kept in &keep {
+            zeroed = zeroed
+                .into_iter()
+                .flat_map(|range| {
+                    let before = range.start..range.end.min(kept.start);
+                    let after = range.start.max(kept.end)..range.end;
+                    [before, after]
+                })
+                .filter(|range| !range.is_empty())
+                .collect();
+        }
+        if zeroed.is_empty() {
+            continue;
+        }
+        let bytes = match builder.memory_mut(base) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+        for range in zeroed {
+            let range = (range.start - base) as usize..(range.end - base) as usize;
+            bytes[range.clone()].fill(0);
+            report.zeroed_bytes += range.len() as u64;
+        }
+        report.zeroed_regions += 1;
+    }
+}
+
+fn redact_crashpad(
+    crashpad: &mut StreamData,
+    endian: Endian,
+    options: &RedactionOptions,
+    report: &mut RedactionReport,
+) {
+    redact_annotations(
+        crashpad,
+        CRASHPAD_SIMPLE_ANNOTATIONS_OFFSET,
+        CRASHPAD_DICTIONARY_ENTRY_SIZE,
+        endian,
+        options,
+        report,
+    );
+    let modules = match crashpad.reference_mut(CRASHPAD_MODULE_LIST_OFFSET) {
+        Some(ReferenceTarget::Data(modules)) => modules,
+        _ => return,
+    };
+    let count = modules.bytes().pread_with::<u32>(0, endian).unwrap_or(0) as usize;
+    for entry in entries(modules.bytes(), 4, count, CRASHPAD_MODULE_LINK_SIZE) {
+        let info = match modules.reference_mut(entry + 4) {
+            Some(ReferenceTarget::Data(info)) => info,
+            _ => continue,
+        };
+        if options.remove_list_annotations {
+            let offset = CRASHPAD_MODULE_LIST_ANNOTATIONS_OFFSET;
+            if let Some(ReferenceTarget::Data(list)) = info.remove_reference(offset) {
+                let count = list.bytes().pread_with::<u32>(0, endian).unwrap_or(0);
+                report.list_annotations += count as usize;
+            }
+        }
+        redact_annotations(
+            info,
+            CRASHPAD_MODULE_SIMPLE_ANNOTATIONS_OFFSET,
+            CRASHPAD_DICTIONARY_ENTRY_SIZE,
+            endian,
+            options,
+            report,
+        );
+        redact_annotations(
+            info,
+            CRASHPAD_MODULE_ANNOTATION_OBJECTS_OFFSET,
+            CRASHPAD_ANNOTATION_SIZE,
+            endian,
+            options,
+            report,
+        );
+    }
+}
+
+/// Drop the entries with matching keys from the list of annotations `data` refers to at
+/// `offset`.
+///
+/// Both simple annotations and annotation objects are a 32-bit count followed by entries
+/// that start with the RVA of their key.
+fn redact_annotations(
+    data: &mut StreamData,
+    offset: usize,
+    entry_size: usize,
+    endian: Endian,
+    options: &RedactionOptions,
+    report: &mut RedactionReport,
+) {
+    let list = match data.reference_mut(offset) {
+        Some(ReferenceTarget::Data(list)) => list,
+        _ => return,
+    };
+    let count = list.bytes().pread_with::<u32>(0, endian).unwrap_or(0) as usize;
+    let mut bytes = vec![0; 4];
+    let mut references = vec![];
+    let mut kept_count = 0u32;
+    for entry in entries(list.bytes(), 4, count, entry_size) {
+        let key = match list.reference(entry) {
+            Some(ReferenceTarget::Data(key)) => {
+                read_string_utf8_unterminated(&mut 0, key.bytes(), endian)
+            }
+            _ => None,
+        };
+        if let Some(key) = key.filter(|key| options.matches_key(key.as_bytes())) {
+            report.annotations.push(key.to_owned());
+            continue;
+        }
+        let entry_range = entry..entry + entry_size;
+        for (offset, kind, target) in list.references() {
+            if entry_range.contains(&offset) {
+                references.push((offset - entry + bytes.len(), kind, target.clone()));
+            }
+        }
+        bytes.extend_from_slice(&list.bytes()[entry_range]);
+        kept_count += 1;
+    }
+    if kept_count as usize == count {
+        return;
+    }
+    // The count fits, `bytes` started out with four bytes for it.
+    let _ = bytes.pwrite_with(kept_count, 0, endian);
+    let mut kept = StreamData::new(bytes);
+    for (offset, kind, target) in references {
+        kept.add_reference(offset, kind, target);
+    }
+    *list = kept;
+}
+
+/// Scrub the paths of the `MINIDUMP_STRING`s `data` refers to at `offsets`.
+fn scrub_strings(
+    data: &mut StreamData,
+    offsets: Vec<usize>,
+    endian: Endian,
+    scrubbed_paths: &mut Vec<(String, String)>,
+) {
+    for offset in offsets {
+        let name = match data.reference_mut(offset) {
+            Some(ReferenceTarget::Data(name)) => name,
+            _ => continue,
+        };
+        let original = match read_string_utf16(&mut 0, name.bytes(), endian) {
+            Some(original) => original,
+            None => continue,
+        };
+        if let Some(scrubbed) = scrub_path(&original) {
+            *name = StreamData::string(&scrubbed, endian);
+            scrubbed_paths.push((original, scrubbed));
+        }
+    }
+}
+
+/// Scrub the PDB path of the CodeView record `modules` refers to at `offset`.
+fn scrub_cv_record(
+    modules: &mut StreamData,
+    offset: usize,
+    endian: Endian,
+    scrubbed_paths: &mut Vec<(String, String)>,
+) {
+    let cv_record = match modules.reference_mut(offset) {
+        Some(ReferenceTarget::Data(cv_record)) => cv_record,
+        _ => return,
+    };
+    let signature = cv_record.bytes().pread_with::<u32>(0, endian).unwrap_or(0);
+    // The PDB path is the last field of the record, after the signature, the guid or
+    // offset and signature, and the age.
+    let name_offset = match CvSignature::from_u32(signature) {
+        Some(CvSignature::Pdb70) => 24,
+        Some(CvSignature::Pdb20) => 16,
+        _ => return,
+    };
+    let name = match cv_record.bytes().get(name_offset..) {
+        Some(name) => name.split(|&byte| byte == 0).next().unwrap_or(name),
+        None => return,
+    };
+    let original = match std::str::from_utf8(name) {
+        Ok(original) => original,
+        Err(_) => return,
+    };
+    if let Some(scrubbed) = scrub_path(original) {
+        let mut bytes = cv_record.bytes()[..name_offset].to_vec();
+        bytes.extend_from_slice(scrubbed.as_bytes());
+        bytes.push(0);
+        scrubbed_paths.push((original.to_owned(), scrubbed));
+        *cv_record = StreamData::new(bytes);
+    }
+}
+
+/// The directories that contain a directory per user.
+const USER_DIRECTORIES: &[&str] = &[
+    "\\users\\",
+    "\\documents and settings\\",
+    "/users/",
+    "/home/",
+];
+
+/// Replace the user names in `path` with `<redacted>`, or `None` if it doesn't have any.
+pub fn scrub_path(path: &str) -> Option<String> {
+    let lower = path.to_ascii_lowercase();
+    let mut scrubbed = String::with_capacity(path.len());
+    let mut rest = 0;
+    for (start, dir) in USER_DIRECTORIES
+        .iter()
+        .flat_map(|dir| lower.match_indices(dir))
+        .collect::<std::collections::BTreeMap<_, _>>()
+    {
+        let user_start = start + dir.len();
+        if user_start <= rest {
+            continue;
+        }
+        let user_end = path[user_start..]
+            .find(['/', '\\'])
+            .map_or(path.len(), |end| user_start + end);
+        if user_end == user_start {
+            continue;
+        }
+        scrubbed.push_str(&path[rest..user_start]);
+        scrubbed.push_str("<redacted>");
+        rest = user_end;
+    }
+    if rest == 0 {
+        return None;
+    }
+    scrubbed.push_str(&path[rest..]);
+    Some(scrubbed)
+}
+
+/// Whether `text` matches the glob `pattern`, ignoring ASCII case.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*`, if a later part of the pattern fails to match.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c.eq_ignore_ascii_case(&text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::minidump::{
+        MinidumpCommentA, MinidumpCommentW, MinidumpCrashpadInfo, MinidumpHandleDataStream,
+        MinidumpLinuxCmdLine, MinidumpLinuxDsoDebug, MinidumpThreadNames, MinidumpTokenList,
+        MinidumpUnloadedModuleList,
+    };
+    use crate::MinidumpMemoryList;
+    use minidump_common::format as md;
+    use minidump_synth::{
+        AnnotationValue, CrashpadInfo, DumpSection, DumpString, HandleDescriptor, Memory,
+        Module as SynthModule, ModuleCrashpadInfo, SimpleStream, SynthMinidump, SystemInfo, Thread,
+        ThreadName, TokenList, UnloadedModule,
+    };
+    use test_assembler::{Endian as AsmEndian, Section};
+
+    const STACK_BASE: u64 = 0x7fff_0000;
+    const MODULE_BASE: u64 = 0x4000_0000;
+    const HEAP_BASE: u64 = 0x1_0000;
+    const MAPS: &[u8] = b"\
+40000000-40002000 r-xp 00000000 08:01 1234 /home/alice/bin/app
+50000000-50001000 r-xp 00000000 08:01 5678 /usr/lib/libc.so.6
+";
+
+    fn synth_dump() -> Vec<u8> {
+        let endian = AsmEndian::Little;
+        let context = minidump_synth::amd64_context(endian, MODULE_BASE + 0x10, STACK_BASE);
+        let stack = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0xab, 0x100),
+            STACK_BASE,
+        );
+        let thread = Thread::new(endian, 1, &stack, &context);
+        // Half of the code is in the module image.
+        let code = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0xcc, 0x20),
+            MODULE_BASE + 0x1ff0,
+        );
+        let heap = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0xcd, 0x20),
+            HEAP_BASE,
+        );
+        let module_name = DumpString::new("/home/alice/bin/app", endian);
+        let cv_record = Section::with_endian(endian)
+            .D32(CvSignature::Pdb70 as u32)
+            .append_repeated(0x11, 16) // guid
+            .D32(1) // age
+            .append_bytes(b"C:\\Users\\alice\\src\\app.pdb\0");
+        let module = SynthModule::new(endian, MODULE_BASE, 0x2000, &module_name, 0, 0, None)
+            .cv_record(&cv_record);
+        let unloaded_name = DumpString::new("C:\\Users\\alice\\AppData\\gone.dll", endian);
+        let unloaded = UnloadedModule::new(endian, 0x6000_0000, 0x1000, &unloaded_name, 0, 0);
+        let system_name = DumpString::new("/usr/lib/libc.so.6", endian);
+        let system = SynthModule::new(endian, 0x5000_0000, 0x1000, &system_name, 0, 0, None);
+        let link_map_name = DumpString::new("/home/alice/lib/libplugin.so", endian);
+        let link_map = Section::with_endian(endian)
+            .D64(0x7f00_0000_0000)
+            .D32(link_map_name.file_offset())
+            .D64(0x7f00_0000_2000);
+        let dso_debug = Section::with_endian(endian)
+            .D32(1) // version
+            .D32(link_map.file_offset())
+            .D32(1) // dso_count
+            .D64(0) // brk
+            .D64(0) // ldbase
+            .D64(0); // dynamic
+        let handle_type = DumpString::new("File", endian);
+        let handle_name = DumpString::new("C:\\Users\\alice\\Documents\\notes.txt", endian);
+        let handle = HandleDescriptor::new(
+            endian,
+            0x80,
+            Some(&handle_type),
+            Some(&handle_name),
+            0,
+            0,
+            1,
+            1,
+        );
+        let thread_name = DumpString::new("loader /home/alice/bin/app", endian);
+        let tokens = TokenList::new(endian).add_token(
+            0x1000,
+            0x84,
+            Section::with_endian(endian).append_bytes(b"S-1-5-21-alice"),
+        );
+        let system_info = SystemInfo::new(endian).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        let crashpad_info = CrashpadInfo::new(endian)
+            .add_simple_annotation("prod", "app")
+            .add_simple_annotation("user_email", "alice@example.com")
+            .add_simple_annotation("ver", "1.0")
+            .add_module(
+                ModuleCrashpadInfo::new(0, endian)
+                    .add_list_annotation("alice was here")
+                    .add_simple_annotation("channel", "beta")
+                    .add_simple_annotation("auth_token", "hunter2")
+                    .add_annotation_object(
+                        "last_url",
+                        AnnotationValue::String("https://example.com/alice".to_owned()),
+                    )
+                    .add_annotation_object("phase", AnnotationValue::String("startup".to_owned())),
+            );
+        SynthMinidump::with_endian(endian)
+            .add_system_info(system_info)
+            .add_thread(thread)
+            .add_thread_name(ThreadName::new(endian, 1, Some(&thread_name)))
+            .add(thread_name)
+            .add_handle_descriptor(handle)
+            .add(handle_type)
+            .add(handle_name)
+            .add_stream(tokens)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::CommentStreamA as u32,
+                section: Section::new().append_bytes(b"alice's crash\0"),
+            })
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::CommentStreamW as u32,
+                section: Section::with_endian(endian).D16(b'a' as u16).D16(0),
+            })
+            .add(context)
+            .add_memory(stack)
+            .add_memory(code)
+            .add_memory(heap)
+            .add_module(module)
+            .add(module_name)
+            .add(cv_record)
+            .add_module(system)
+            .add(system_name)
+            .add_unloaded_module(unloaded)
+            .add(unloaded_name)
+            .add_crashpad_info(crashpad_info)
+            .add(link_map)
+            .add(link_map_name)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+                section: dso_debug,
+            })
+            .set_linux_maps(MAPS)
+            .set_linux_environ(b"HOME=/home/alice\0LANG=en_US.UTF-8\0AUTH_TOKEN=hunter2\0")
+            .set_linux_cmd_line(b"/home/alice/bin/app\0--password\0hunter2\0")
+            .finish()
+            .unwrap()
+    }
+
+    fn redacted(options: &RedactionOptions) -> (Minidump<'static, Vec<u8>>, RedactionReport) {
+        let dump = Minidump::read(synth_dump()).unwrap();
+        let (builder, report) = redact(&dump, options);
+        (Minidump::read(builder.build().unwrap()).unwrap(), report)
+    }
+
+    #[test]
+    fn test_redact() {
+        let (dump, report) = redacted(&RedactionOptions::default());
+
+        // Memory
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        let stack = memory.memory_at_address(STACK_BASE).unwrap();
+        assert_eq!(stack.bytes, &[0xab; 0x100][..]);
+        let code = memory.memory_at_address(MODULE_BASE + 0x1ff0).unwrap();
+        assert_eq!(&code.bytes[..0x10], &[0xcc; 0x10][..]);
+        assert_eq!(&code.bytes[0x10..], &[0; 0x10][..]);
+        let heap = memory.memory_at_address(HEAP_BASE).unwrap();
+        assert_eq!(heap.bytes, &[0; 0x20][..]);
+        assert_eq!(report.zeroed_regions, 2);
+        assert_eq!(report.zeroed_bytes, 0x30);
+
+        // Linux streams
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        assert_eq!(&environ.raw_bytes()[..], b"LANG=en_US.UTF-8\0");
+        assert_eq!(report.environment_variables, vec!["HOME", "AUTH_TOKEN"]);
+        assert!(dump.get_stream::<MinidumpLinuxCmdLine>().is_err());
+        assert!(report.command_line);
+
+        // Crashpad
+        let crashpad = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        let keys: Vec<_> = crashpad.simple_annotations.keys().collect();
+        assert_eq!(keys, vec!["prod", "ver"]);
+        assert_eq!(crashpad.simple_annotations["ver"], "1.0");
+        let module = &crashpad.module_list[0];
+        assert!(module.list_annotations.is_empty());
+        let keys: Vec<_> = module.simple_annotations.keys().collect();
+        assert_eq!(keys, vec!["channel"]);
+        assert_eq!(module.simple_annotations["channel"], "beta");
+        let keys: Vec<_> = module.annotation_objects.keys().collect();
+        assert_eq!(keys, vec!["phase"]);
+        assert_eq!(
+            report.annotations,
+            vec!["user_email", "auth_token", "last_url"]
+        );
+        assert_eq!(report.list_annotations, 1);
+
+        // Module names
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let names: Vec<_> = modules.iter().map(|module| module.code_file()).collect();
+        assert_eq!(
+            names,
+            vec!["/home/<redacted>/bin/app", "/usr/lib/libc.so.6"]
+        );
+        let unloaded = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        let names: Vec<_> = unloaded.iter().map(|module| module.code_file()).collect();
+        assert_eq!(names, vec!["C:\\Users\\<redacted>\\AppData\\gone.dll"]);
+        assert_eq!(report.module_names.len(), 2);
+        assert_eq!(report.module_names[0].0, "/home/alice/bin/app");
+        assert_eq!(
+            modules.iter().next().unwrap().debug_file().unwrap(),
+            "C:\\Users\\<redacted>\\src\\app.pdb"
+        );
+        assert_eq!(
+            report.debug_files,
+            vec![(
+                "C:\\Users\\alice\\src\\app.pdb".to_owned(),
+                "C:\\Users\\<redacted>\\src\\app.pdb".to_owned()
+            )]
+        );
+
+        // Paths in Linux streams
+        let maps = dump
+            .get_raw_stream(MINIDUMP_STREAM_TYPE::LinuxMaps as u32)
+            .unwrap();
+        assert_eq!(
+            maps,
+            &b"\
+40000000-40002000 r-xp 00000000 08:01 1234 /home/<redacted>/bin/app
+50000000-50001000 r-xp 00000000 08:01 5678 /usr/lib/libc.so.6
+"[..]
+        );
+        assert_eq!(report.linux_maps.len(), 1);
+        assert_eq!(
+            report.linux_maps[0].0,
+            "40000000-40002000 r-xp 00000000 08:01 1234 /home/alice/bin/app"
+        );
+        let dso_debug = dump.get_stream::<MinidumpLinuxDsoDebug>().unwrap();
+        assert_eq!(
+            dso_debug.link_map[0].name,
+            "/home/<redacted>/lib/libplugin.so"
+        );
+        assert_eq!(
+            report.link_map_names,
+            vec![(
+                "/home/alice/lib/libplugin.so".to_owned(),
+                "/home/<redacted>/lib/libplugin.so".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_redact_nothing() {
+        let options = RedactionOptions {
+            key_patterns: vec![],
+            zero_memory: false,
+            remove_command_line: false,
+            remove_comments: false,
+            remove_tokens: false,
+            remove_list_annotations: false,
+            scrub_paths: false,
+        };
+        let (dump, report) = redacted(&options);
+        assert!(report.is_empty());
+
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        let heap = memory.memory_at_address(HEAP_BASE).unwrap();
+        assert_eq!(heap.bytes, &[0xcd; 0x20][..]);
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        assert_eq!(
+            &environ.raw_bytes()[..],
+            b"HOME=/home/alice\0LANG=en_US.UTF-8\0AUTH_TOKEN=hunter2\0"
+        );
+        assert!(dump.get_stream::<MinidumpLinuxCmdLine>().is_ok());
+        let crashpad = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        assert_eq!(crashpad.simple_annotations.len(), 3);
+        assert_eq!(crashpad.module_list[0].list_annotations.len(), 1);
+        assert_eq!(crashpad.module_list[0].annotation_objects.len(), 2);
+        assert!(dump.get_stream::<MinidumpCommentA>().is_ok());
+        assert!(dump.get_stream::<MinidumpCommentW>().is_ok());
+        assert!(dump.get_stream::<MinidumpTokenList>().is_ok());
+        let handles = dump.get_stream::<MinidumpHandleDataStream>().unwrap();
+        assert_eq!(
+            handles.handles[0].object_name.as_deref(),
+            Some("C:\\Users\\alice\\Documents\\notes.txt")
+        );
+        let names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(names.get_name(1).unwrap(), "loader /home/alice/bin/app");
+    }
+
+    #[test]
+    fn test_redact_handle_names() {
+        let (dump, report) = redacted(&RedactionOptions::default());
+        let handles = dump.get_stream::<MinidumpHandleDataStream>().unwrap();
+        assert_eq!(handles.handles[0].type_name.as_deref(), Some("File"));
+        assert_eq!(
+            handles.handles[0].object_name.as_deref(),
+            Some("C:\\Users\\<redacted>\\Documents\\notes.txt")
+        );
+        assert_eq!(
+            report.handle_names,
+            vec![(
+                "C:\\Users\\alice\\Documents\\notes.txt".to_owned(),
+                "C:\\Users\\<redacted>\\Documents\\notes.txt".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_redact_thread_names() {
+        let (dump, report) = redacted(&RedactionOptions::default());
+        let names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(
+            names.get_name(1).unwrap(),
+            "loader /home/<redacted>/bin/app"
+        );
+        assert_eq!(
+            report.thread_names,
+            vec![(
+                "loader /home/alice/bin/app".to_owned(),
+                "loader /home/<redacted>/bin/app".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_redact_tokens() {
+        let (dump, report) = redacted(&RedactionOptions::default());
+        assert!(dump.get_stream::<MinidumpTokenList>().is_err());
+        assert!(report.tokens);
+    }
+
+    #[test]
+    fn test_redact_comments() {
+        let (dump, report) = redacted(&RedactionOptions::default());
+        assert!(dump.get_stream::<MinidumpCommentA>().is_err());
+        assert!(dump.get_stream::<MinidumpCommentW>().is_err());
+        assert!(report.comments);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*TOKEN*", b"auth_token"));
+        assert!(glob_match(b"*TOKEN*", b"TOKEN"));
+        assert!(glob_match(b"HOME", b"home"));
+        assert!(!glob_match(b"HOME", b"HOMEBREW"));
+        assert!(glob_match(b"?OME*", b"HOMEBREW"));
+        assert!(glob_match(b"*_*_*", b"a_b_c"));
+        assert!(!glob_match(b"*_*_*", b"a_bc"));
+        assert!(glob_match(b"*", b""));
+        assert!(!glob_match(b"", b"a"));
+    }
+
+    #[test]
+    fn test_scrub_path() {
+        assert_eq!(
+            scrub_path("C:\\Users\\alice\\AppData\\Local\\app.dll").as_deref(),
+            Some("C:\\Users\\<redacted>\\AppData\\Local\\app.dll")
+        );
+        assert_eq!(
+            scrub_path("c:\\documents and settings\\bob\\app.exe").as_deref(),
+            Some("c:\\documents and settings\\<redacted>\\app.exe")
+        );
+        assert_eq!(
+            scrub_path("/Users/carol/Library/app.dylib").as_deref(),
+            Some("/Users/<redacted>/Library/app.dylib")
+        );
+        assert_eq!(
+            scrub_path("/home/dave").as_deref(),
+            Some("/home/<redacted>")
+        );
+        assert_eq!(
+            scrub_path("/home/dave/mnt/home/erin/lib.so").as_deref(),
+            Some("/home/<redacted>/mnt/home/<redacted>/lib.so")
+        );
+        assert_eq!(scrub_path("/usr/lib/libc.so.6"), None);
+        assert_eq!(scrub_path("/home/"), None);
+    }
+}
@@ -0,0 +1,804 @@
+//! Checking minidumps for spec violations.
+//!
+//! [`Minidump::read`] and the streams are deliberately forgiving: when there are several
+//! streams of a type one of them is picked, out-of-bounds data is ignored, and corrupt
+//! entries are skipped. That's what you want when processing a crash, but it hides bugs in
+//! the programs that write minidumps.
+//!
+//! [`Minidump::validate`] instead reads the raw structures of the file and reports every
+//! violation it finds as a [`Finding`]:
+//!
+//! ```
+//! use minidump::validate::Severity;
+//! use minidump::Minidump;
+//!
+//! # fn foo() -> Result<(), minidump::Error> {
+//! let dump = Minidump::read_path("../testdata/test.dmp")?;
+//! for finding in dump.validate() {
+//!     println!("{finding}");
+//!     if finding.severity() == Severity::Error {
+//!         // ...
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use scroll::ctx::SizeWith;
+use scroll::{Endian, Pread};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::context::{ContextError, MinidumpContext};
+use crate::minidump::{Minidump, MinidumpMiscInfo, MinidumpSystemInfo};
+use crate::writer::{entries, THREAD_EX_SIZE, THREAD_SIZE};
+use minidump_common::format::{self as md, MINIDUMP_STREAM_TYPE};
+use num_traits::FromPrimitive;
+
+/// The offset of the backing store of a `MINIDUMP_THREAD_EX`.
+const THREAD_EX_BACKING_STORE_OFFSET: usize = 48;
+
+/// How bad a [`Finding`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The minidump is unusual, but everything in it can still be read.
+    Warning,
+    /// Part of the minidump can't be read.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The kinds of problems [`Minidump::validate`] finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// A stream in the directory extends past the end of the file.
+    StreamOutOfBounds,
+    /// There are several streams of the same type. Only one of them is used.
+    DuplicateStream,
+    /// A stream has a type this crate doesn't know about.
+    UnknownStream,
+    /// A list stream is too short for the number of entries it claims to have.
+    TruncatedList,
+    /// A list stream is longer than its entries (and padding) account for.
+    ListSizeMismatch,
+    /// A stream refers to data that is out of bounds of the file.
+    BadRva,
+    /// Memory regions overlap each other.
+    OverlappingMemory,
+    /// Modules overlap each other.
+    OverlappingModules,
+    /// A thread or exception context doesn't match the CPU in the system info.
+    ContextCpuMismatch,
+}
+
+impl FindingKind {
+    /// The name of the kind, for display purposes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FindingKind::StreamOutOfBounds => "StreamOutOfBounds",
+            FindingKind::DuplicateStream => "DuplicateStream",
+            FindingKind::UnknownStream => "UnknownStream",
+            FindingKind::TruncatedList => "TruncatedList",
+            FindingKind::ListSizeMismatch => "ListSizeMismatch",
+            FindingKind::BadRva => "BadRva",
+            FindingKind::OverlappingMemory => "OverlappingMemory",
+            FindingKind::OverlappingModules => "OverlappingModules",
+            FindingKind::ContextCpuMismatch => "ContextCpuMismatch",
+        }
+    }
+
+    /// How bad findings of this kind are.
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::DuplicateStream
+            | FindingKind::UnknownStream
+            | FindingKind::OverlappingMemory
+            | FindingKind::OverlappingModules => Severity::Warning,
+            FindingKind::StreamOutOfBounds
+            | FindingKind::TruncatedList
+            | FindingKind::ListSizeMismatch
+            | FindingKind::BadRva
+            | FindingKind::ContextCpuMismatch => Severity::Error,
+        }
+    }
+}
+
+/// A problem found by [`Minidump::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// What kind of problem this is.
+    pub kind: FindingKind,
+    /// The index in the stream directory of the stream with the problem, if the problem
+    /// is with a single stream.
+    pub stream_index: Option<u32>,
+    /// The type of that stream.
+    pub stream_type: Option<u32>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Finding {
+    /// How bad the problem is.
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: ", self.severity(), self.kind.name())?;
+        if let (Some(index), Some(stream_type)) = (self.stream_index, self.stream_type) {
+            write!(f, "stream {index} ")?;
+            match MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
+                Some(known) => write!(f, "({known:?}): ")?,
+                None => write!(f, "(0x{stream_type:08x}): ")?,
+            }
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl<'a, T> Minidump<'a, T>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    /// Check the minidump for spec violations.
+    ///
+    /// See the [`validate`][crate::validate] module for details. For minidumps read with
+    /// [`Minidump::read_lazy`], the contents of the memory regions of full-memory dumps
+    /// aren't read, only their bounds are checked.
+    pub fn validate(&'a self) -> Vec<Finding> {
+        let system_info = self.get_stream::<MinidumpSystemInfo>().ok();
+        let misc = self.get_stream::<MinidumpMiscInfo>().ok();
+        let mut validator = Validator {
+            all: self.image(),
+            file_len: self.file_len(),
+            endian: self.endian,
+            system_info: system_info.as_ref(),
+            misc: misc.as_ref(),
+            stream: None,
+            findings: vec![],
+            memory: vec![],
+            modules: vec![],
+        };
+        validator.directory(self.directory());
+        validator.overlaps();
+        validator.findings
+    }
+}
+
+struct Validator<'a, 'b> {
+    /// The part of the file that location descriptors refer to.
+    all: &'a [u8],
+    /// The length of the whole file, which is where the memory of a `Memory64ListStream`
+    /// can extend to.
+    file_len: u64,
+    endian: Endian,
+    system_info: Option<&'b MinidumpSystemInfo>,
+    misc: Option<&'b MinidumpMiscInfo>,
+    /// The index and type of the stream being validated.
+    stream: Option<(u32, u32)>,
+    findings: Vec<Finding>,
+    /// The base address and size of every memory region.
+    memory: Vec<(u64, u64)>,
+    /// The base address, size and name of every module.
+    modules: Vec<(u64, u64, String)>,
+}
+
+impl<'a, 'b> Validator<'a, 'b> {
+    fn report(&mut self, kind: FindingKind, message: String) {
+        self.findings.push(Finding {
+            kind,
+            stream_index: self.stream.map(|(index, _)| index),
+            stream_type: self.stream.map(|(_, stream_type)| stream_type),
+            message,
+        });
+    }
+
+    fn directory(&mut self, directory: &[md::MINIDUMP_DIRECTORY]) {
+        // Reading the minidump already checked that the directory fits in the file.
+        let mut seen = HashMap::new();
+        for (index, entry) in (0..).zip(directory) {
+            let stream_type = entry.stream_type;
+            let known = MINIDUMP_STREAM_TYPE::from_u32(stream_type);
+            // Writers reserve directory entries and leave the unused ones empty.
+            if known == Some(MINIDUMP_STREAM_TYPE::UnusedStream) && entry.location.data_size == 0 {
+                continue;
+            }
+            self.stream = Some((index, stream_type));
+
+            if let Some(first) = seen.insert(stream_type, index) {
+                let message = format!("stream {first} has the same type");
+                self.report(FindingKind::DuplicateStream, message);
+            }
+            if known.is_none() {
+                let message = "unknown stream type".to_owned();
+                self.report(FindingKind::UnknownStream, message);
+            }
+            let location = &entry.location;
+            let start = location.rva as usize;
+            let bytes = start
+                .checked_add(location.data_size as usize)
+                .and_then(|end| self.all.get(start..end));
+            match (bytes, known) {
+                (Some(bytes), Some(known)) => self.stream(known, bytes),
+                (Some(_), None) => {}
+                (None, _) => {
+                    let message = format!(
+                        "the stream (rva 0x{:x}, {} bytes) is out of bounds (the file is {} bytes)",
+                        location.rva,
+                        location.data_size,
+                        self.all.len()
+                    );
+                    self.report(FindingKind::StreamOutOfBounds, message);
+                }
+            }
+        }
+        self.stream = None;
+    }
+
+    fn stream(&mut self, stream_type: MINIDUMP_STREAM_TYPE, bytes: &'a [u8]) {
+        use MINIDUMP_STREAM_TYPE::*;
+
+        match stream_type {
+            ThreadListStream | ThreadExListStream => {
+                let thread_size = if stream_type == ThreadListStream {
+                    THREAD_SIZE
+                } else {
+                    THREAD_EX_SIZE
+                };
+                for (i, entry) in self.list(bytes, thread_size).into_iter().enumerate() {
+                    let thread: md::MINIDUMP_THREAD = match bytes.pread_with(entry, self.endian) {
+                        Ok(thread) => thread,
+                        Err(_) => continue,
+                    };
+                    let what = format!("the stack of thread {i} (id {})", thread.thread_id);
+                    self.location(&what, &thread.stack.memory);
+                    if stream_type == ThreadExListStream {
+                        let backing_store = bytes.pread_with::<md::MINIDUMP_MEMORY_DESCRIPTOR>(
+                            entry + THREAD_EX_BACKING_STORE_OFFSET,
+                            self.endian,
+                        );
+                        if let Ok(backing_store) = backing_store {
+                            let what = format!(
+                                "the backing store of thread {i} (id {})",
+                                thread.thread_id
+                            );
+                            self.location(&what, &backing_store.memory);
+                        }
+                    }
+                    let what = format!("the context of thread {i} (id {})", thread.thread_id);
+                    self.context(&what, &thread.thread_context);
+                }
+            }
+            ModuleListStream => {
+                let module_size = md::MINIDUMP_MODULE::size_with(&self.endian);
+                for (i, entry) in self.list(bytes, module_size).into_iter().enumerate() {
+                    let module: md::MINIDUMP_MODULE = match bytes.pread_with(entry, self.endian) {
+                        Ok(module) => module,
+                        Err(_) => continue,
+                    };
+                    let name =
+                        self.string(&format!("the name of module {i}"), module.module_name_rva);
+                    let name = name.unwrap_or_else(|| format!("module {i}"));
+                    self.optional_location(
+                        &format!("the CodeView record of {name}"),
+                        &module.cv_record,
+                    );
+                    self.optional_location(
+                        &format!("the misc record of {name}"),
+                        &module.misc_record,
+                    );
+                    self.modules
+                        .push((module.base_of_image, module.size_of_image as u64, name));
+                }
+            }
+            UnloadedModuleListStream => {
+                let module_size = md::MINIDUMP_UNLOADED_MODULE::size_with(&self.endian);
+                for (i, entry) in self.ex_list(bytes, module_size).into_iter().enumerate() {
+                    let module: md::MINIDUMP_UNLOADED_MODULE =
+                        match bytes.pread_with(entry, self.endian) {
+                            Ok(module) => module,
+                            Err(_) => continue,
+                        };
+                    let what = format!("the name of unloaded module {i}");
+                    self.string(&what, module.module_name_rva);
+                }
+            }
+            MemoryListStream => {
+                let descriptor_size = md::MINIDUMP_MEMORY_DESCRIPTOR::size_with(&self.endian);
+                for (i, entry) in self.list(bytes, descriptor_size).into_iter().enumerate() {
+                    let descriptor: md::MINIDUMP_MEMORY_DESCRIPTOR =
+                        match bytes.pread_with(entry, self.endian) {
+                            Ok(descriptor) => descriptor,
+                            Err(_) => continue,
+                        };
+                    let what = format!(
+                        "memory region {i} (0x{:x})",
+                        descriptor.start_of_memory_range
+                    );
+                    if self.location(&what, &descriptor.memory).is_some() {
+                        self.memory.push((
+                            descriptor.start_of_memory_range,
+                            descriptor.memory.data_size as u64,
+                        ));
+                    }
+                }
+            }
+            Memory64ListStream => self.memory64(bytes),
+            ExceptionStream => {
+                if let Ok(exception) =
+                    bytes.pread_with::<md::MINIDUMP_EXCEPTION_STREAM>(0, self.endian)
+                {
+                    self.context("the exception context", &exception.thread_context);
+                } else {
+                    let message = format!("the stream is too short ({} bytes)", bytes.len());
+                    self.report(FindingKind::TruncatedList, message);
+                }
+            }
+            SystemInfoStream => {
+                if let Ok(system_info) =
+                    bytes.pread_with::<md::MINIDUMP_SYSTEM_INFO>(0, self.endian)
+                {
+                    if system_info.csd_version_rva != 0 {
+                        self.string("the CSD version", system_info.csd_version_rva);
+                    }
+                }
+            }
+            ThreadNamesStream => {
+                let name_size = md::MINIDUMP_THREAD_NAME::size_with(&self.endian);
+                for entry in self.list(bytes, name_size) {
+                    let name: md::MINIDUMP_THREAD_NAME = match bytes.pread_with(entry, self.endian)
+                    {
+                        Ok(name) => name,
+                        Err(_) => continue,
+                    };
+                    let what = format!("the name of thread id {}", name.thread_id);
+                    match u32::try_from(name.thread_name_rva) {
+                        Ok(rva) => {
+                            self.string(&what, rva);
+                        }
+                        Err(_) => {
+                            let message = format!(
+                                "{what} (rva 0x{:x}) is out of bounds",
+                                name.thread_name_rva
+                            );
+                            self.report(FindingKind::BadRva, message);
+                        }
+                    }
+                }
+            }
+            MemoryInfoListStream => {
+                let info_size = md::MINIDUMP_MEMORY_INFO::size_with(&self.endian);
+                self.ex_list(bytes, info_size);
+            }
+            ThreadInfoListStream => {
+                let info_size = md::MINIDUMP_THREAD_INFO::size_with(&self.endian);
+                self.ex_list(bytes, info_size);
+            }
+            HandleDataStream => {
+                let descriptor_size = md::MINIDUMP_HANDLE_DESCRIPTOR::size_with(&self.endian);
+                self.ex_list(bytes, descriptor_size);
+            }
+            _ => {}
+        }
+    }
+
+    /// The offsets of the entries of a list: a 32-bit count (which may be padded to 8 bytes)
+    /// followed by the entries.
+    fn list(&mut self, bytes: &[u8], entry_size: usize) -> Vec<usize> {
+        let count = match bytes.pread_with::<u32>(0, self.endian) {
+            Ok(count) => count as usize,
+            Err(_) => {
+                let message = format!("the stream is too short ({} bytes)", bytes.len());
+                self.report(FindingKind::TruncatedList, message);
+                return vec![];
+            }
+        };
+        let size = count.saturating_mul(entry_size).saturating_add(4);
+        if size > bytes.len() {
+            let message = format!(
+                "{count} entries of {entry_size} bytes need {size} bytes, but the stream is {} bytes",
+                bytes.len()
+            );
+            self.report(FindingKind::TruncatedList, message);
+        } else if bytes.len() != size && bytes.len() != size + 4 {
+            let message = format!(
+                "{count} entries of {entry_size} bytes need {size} bytes, but the stream is {} bytes",
+                bytes.len()
+            );
+            self.report(FindingKind::ListSizeMismatch, message);
+        }
+        let start = if bytes.len() == size + 4 { 8 } else { 4 };
+        entries(bytes, start, count, entry_size)
+    }
+
+    /// The offsets of the entries of a list with a `size_of_header`, `size_of_entry` and
+    /// `number_of_entries` header.
+    fn ex_list(&mut self, bytes: &[u8], min_entry_size: usize) -> Vec<usize> {
+        let header = (
+            bytes.pread_with::<u32>(0, self.endian),
+            bytes.pread_with::<u32>(4, self.endian),
+            bytes.pread_with::<u32>(8, self.endian),
+        );
+        let (header_size, entry_size, count) = match header {
+            (Ok(header_size), Ok(entry_size), Ok(count)) => {
+                (header_size as usize, entry_size as usize, count as usize)
+            }
+            _ => {
+                let message = format!("the stream is too short ({} bytes)", bytes.len());
+                self.report(FindingKind::TruncatedList, message);
+                return vec![];
+            }
+        };
+        if entry_size < min_entry_size {
+            let message =
+                format!("entries are {entry_size} bytes, but must be at least {min_entry_size}");
+            self.report(FindingKind::ListSizeMismatch, message);
+            return vec![];
+        }
+        let size = count.saturating_mul(entry_size).saturating_add(header_size);
+        if size > bytes.len() {
+            let message = format!(
+                "{count} entries of {entry_size} bytes after a {header_size} byte header need {size} bytes, but the stream is {} bytes",
+                bytes.len()
+            );
+            self.report(FindingKind::TruncatedList, message);
+        }
+        entries(bytes, header_size, count, entry_size)
+    }
+
+    fn memory64(&mut self, bytes: &[u8]) {
+        let header = (
+            bytes.pread_with::<u64>(0, self.endian),
+            bytes.pread_with::<u64>(8, self.endian),
+        );
+        let (count, base_rva) = match header {
+            (Ok(count), Ok(base_rva)) => (count, base_rva),
+            _ => {
+                let message = format!("the stream is too short ({} bytes)", bytes.len());
+                self.report(FindingKind::TruncatedList, message);
+                return;
+            }
+        };
+        let descriptor_size = md::MINIDUMP_MEMORY_DESCRIPTOR64::size_with(&self.endian);
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+        let size = count.saturating_mul(descriptor_size).saturating_add(16);
+        if size > bytes.len() {
+            let message = format!(
+                "{count} entries of {descriptor_size} bytes need {size} bytes, but the stream is {} bytes",
+                bytes.len()
+            );
+            self.report(FindingKind::TruncatedList, message);
+        } else if size < bytes.len() {
+            let message = format!(
+                "{count} entries of {descriptor_size} bytes need {size} bytes, but the stream is {} bytes",
+                bytes.len()
+            );
+            self.report(FindingKind::ListSizeMismatch, message);
+        }
+        let mut rva = base_rva;
+        for (i, entry) in entries(bytes, 16, count, descriptor_size)
+            .into_iter()
+            .enumerate()
+        {
+            let descriptor: md::MINIDUMP_MEMORY_DESCRIPTOR64 =
+                match bytes.pread_with(entry, self.endian) {
+                    Ok(descriptor) => descriptor,
+                    Err(_) => continue,
+                };
+            let end = rva.checked_add(descriptor.data_size);
+            if end.is_none_or(|end| end > self.file_len) {
+                let message = format!(
+                    "memory region {i} (0x{:x}, rva 0x{rva:x}, {} bytes) is out of bounds (the file is {} bytes)",
+                    descriptor.start_of_memory_range,
+                    descriptor.data_size,
+                    self.file_len
+                );
+                self.report(FindingKind::BadRva, message);
+                // Everything after this is out of bounds too.
+                return;
+            }
+            self.memory
+                .push((descriptor.start_of_memory_range, descriptor.data_size));
+            rva = end.unwrap();
+        }
+    }
+
+    /// The data at `location`, or report it as `what` being out of bounds.
+    fn location(
+        &mut self,
+        what: &str,
+        location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    ) -> Option<&'a [u8]> {
+        let start = location.rva as usize;
+        let bytes = start
+            .checked_add(location.data_size as usize)
+            .and_then(|end| self.all.get(start..end));
+        if bytes.is_none() {
+            let message = format!(
+                "{what} (rva 0x{:x}, {} bytes) is out of bounds (the file is {} bytes)",
+                location.rva,
+                location.data_size,
+                self.all.len()
+            );
+            self.report(FindingKind::BadRva, message);
+        }
+        bytes
+    }
+
+    /// Like [`Self::location`], but a null location is fine.
+    fn optional_location(&mut self, what: &str, location: &md::MINIDUMP_LOCATION_DESCRIPTOR) {
+        if location.rva != 0 || location.data_size != 0 {
+            self.location(what, location);
+        }
+    }
+
+    /// The `MINIDUMP_STRING` at `rva`, or report it as `what` being out of bounds.
+    fn string(&mut self, what: &str, rva: u32) -> Option<String> {
+        let mut offset = rva as usize;
+        let string = crate::minidump::read_string_utf16(&mut offset, self.all, self.endian);
+        if string.is_none() {
+            let message =
+                format!("{what} (rva 0x{rva:x}) is out of bounds or isn't a valid string");
+            self.report(FindingKind::BadRva, message);
+        }
+        string
+    }
+
+    /// Check that the context at `location` can be read as a context of the CPU in the
+    /// system info.
+    fn context(&mut self, what: &str, location: &md::MINIDUMP_LOCATION_DESCRIPTOR) {
+        let bytes = match self.location(what, location) {
+            Some(bytes) => bytes,
+            None => return,
+        };
+        let system_info = match self.system_info {
+            Some(system_info) => system_info,
+            None => return,
+        };
+        if let Err(ContextError::ReadFailure) =
+            MinidumpContext::read(bytes, self.endian, system_info, self.misc)
+        {
+            let message = format!(
+                "{what} ({} bytes) isn't a valid {} context",
+                bytes.len(),
+                system_info.cpu
+            );
+            self.report(FindingKind::ContextCpuMismatch, message);
+        }
+    }
+
+    fn overlaps(&mut self) {
+        let mut memory = std::mem::take(&mut self.memory);
+        memory.sort_unstable();
+        // The region that extends the furthest so far, which later regions may overlap.
+        let mut furthest: Option<(u64, u64)> = None;
+        for (base, size) in memory {
+            let end = base.saturating_add(size);
+            if let Some((furthest_base, furthest_end)) = furthest {
+                if furthest_end > base {
+                    let message = format!(
+                        "memory regions 0x{furthest_base:x}-0x{furthest_end:x} and 0x{base:x}-0x{end:x} overlap"
+                    );
+                    self.report(FindingKind::OverlappingMemory, message);
+                }
+            }
+            if furthest.is_none_or(|(_, furthest_end)| end > furthest_end) {
+                furthest = Some((base, end));
+            }
+        }
+        let mut modules = std::mem::take(&mut self.modules);
+        modules.sort_unstable();
+        for pair in modules.windows(2) {
+            let ((base, size, name), (next_base, _, next_name)) = (&pair[0], &pair[1]);
+            if base.saturating_add(*size) > *next_base {
+                let message = format!(
+                    "modules {name} (0x{base:x}-0x{:x}) and {next_name} (0x{next_base:x}) overlap",
+                    base.saturating_add(*size)
+                );
+                self.report(FindingKind::OverlappingModules, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use minidump_synth::{
+        DumpString, Memory, Module as SynthModule, SimpleStream, SynthMinidump,
+        SystemInfo as SynthSystemInfo, Thread,
+    };
+    use scroll::Pwrite;
+    use test_assembler::{Endian as AsmEndian, Section};
+
+    const CUSTOM_STREAM: u32 = 0x4b6b_0001;
+
+    fn synth_dump() -> SynthMinidump {
+        let endian = AsmEndian::Little;
+        let context = minidump_synth::amd64_context(endian, 0x4000_1000, 0x7fff_0010);
+        let stack = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0, 0x100),
+            0x7fff_0000,
+        );
+        let thread = Thread::new(endian, 1, &stack, &context);
+        let module_name = DumpString::new("/usr/bin/app", endian);
+        let module = SynthModule::new(endian, 0x4000_0000, 0x2000, &module_name, 0, 0, None);
+        let system_info = SynthSystemInfo::new(endian).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        SynthMinidump::with_endian(endian)
+            .add_system_info(system_info)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_module(module)
+            .add(module_name)
+    }
+
+    fn validate(dump: &[u8]) -> Vec<(FindingKind, Option<u32>)> {
+        let dump = Minidump::read(dump).unwrap();
+        dump.validate()
+            .into_iter()
+            .map(|finding| (finding.kind, finding.stream_type))
+            .collect()
+    }
+
+    fn stream(stream_type: MINIDUMP_STREAM_TYPE, bytes: Vec<u8>) -> SimpleStream {
+        SimpleStream {
+            stream_type: stream_type as u32,
+            section: Section::with_endian(AsmEndian::Little).append_bytes(&bytes),
+        }
+    }
+
+    #[test]
+    fn test_valid() {
+        let dump = synth_dump().finish().unwrap();
+        assert_eq!(validate(&dump), vec![]);
+    }
+
+    #[test]
+    fn test_duplicate_and_unknown_streams() {
+        let dump = synth_dump()
+            .add_stream(stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine, b"a".to_vec()))
+            .add_stream(stream(MINIDUMP_STREAM_TYPE::LinuxCmdLine, b"b".to_vec()))
+            .add_stream(SimpleStream {
+                stream_type: CUSTOM_STREAM,
+                section: Section::new().append_bytes(b"custom"),
+            })
+            .finish()
+            .unwrap();
+        let cmd_line = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
+        assert_eq!(
+            validate(&dump),
+            vec![
+                (FindingKind::DuplicateStream, Some(cmd_line)),
+                (FindingKind::UnknownStream, Some(CUSTOM_STREAM)),
+            ]
+        );
+
+        let dump = Minidump::read(dump).unwrap();
+        let findings = dump.validate();
+        assert_eq!(findings[0].severity(), Severity::Warning);
+        assert_eq!(
+            findings[1].to_string(),
+            "warning: UnknownStream: stream 2 (0x4b6b0001): unknown stream type"
+        );
+    }
+
+    #[test]
+    fn test_stream_out_of_bounds() {
+        let mut dump = synth_dump().finish().unwrap();
+        let directory = dump.pread_with::<u32>(12, scroll::LE).unwrap() as usize;
+        // The data size of the first stream.
+        dump.pwrite_with(0x10_0000u32, directory + 4, scroll::LE)
+            .unwrap();
+        let findings = validate(&dump);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].0, FindingKind::StreamOutOfBounds);
+    }
+
+    #[test]
+    fn test_lists() {
+        let module_list = MINIDUMP_STREAM_TYPE::ModuleListStream;
+        let module = md::MINIDUMP_MODULE {
+            base_of_image: 0x5000_0000,
+            size_of_image: 0x1000,
+            module_name_rva: 0xffff_0000,
+            ..Default::default()
+        };
+        let module_size = md::MINIDUMP_MODULE::size_with(&scroll::LE);
+        let mut bytes = vec![0; 4 + module_size];
+        bytes.pwrite_with(1u32, 0, scroll::LE).unwrap();
+        bytes.pwrite_with(module, 4, scroll::LE).unwrap();
+        let bad_rva = bytes.clone();
+        // Claim a second module that isn't there.
+        bytes.pwrite_with(2u32, 0, scroll::LE).unwrap();
+        let truncated = bytes.clone();
+        // Or have too many bytes for one module.
+        bytes.pwrite_with(1u32, 0, scroll::LE).unwrap();
+        bytes.extend_from_slice(&[0; 12]);
+        let mismatched = bytes;
+
+        let module_list_type = Some(module_list as u32);
+        for (bytes, kinds) in [
+            (bad_rva, vec![FindingKind::BadRva]),
+            (
+                truncated,
+                vec![FindingKind::TruncatedList, FindingKind::BadRva],
+            ),
+            (
+                mismatched,
+                vec![FindingKind::ListSizeMismatch, FindingKind::BadRva],
+            ),
+        ] {
+            let dump = SynthMinidump::with_endian(AsmEndian::Little)
+                .add_stream(stream(module_list, bytes))
+                .finish()
+                .unwrap();
+            let expected: Vec<_> = kinds
+                .into_iter()
+                .map(|kind| (kind, module_list_type))
+                .collect();
+            assert_eq!(validate(&dump), expected);
+        }
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let endian = AsmEndian::Little;
+        let memory = |base| {
+            Memory::with_section(Section::with_endian(endian).append_repeated(0, 0x100), base)
+        };
+        let name = DumpString::new("/usr/lib/libfoo.so", endian);
+        let module = SynthModule::new(endian, 0x4000_1000, 0x1000, &name, 0, 0, None);
+        let dump = synth_dump()
+            .add_memory(memory(0x1000))
+            .add_memory(memory(0x1080))
+            .add_memory(memory(0x2000))
+            .add_module(module)
+            .add(name)
+            .finish()
+            .unwrap();
+        assert_eq!(
+            validate(&dump),
+            vec![
+                (FindingKind::OverlappingMemory, None),
+                (FindingKind::OverlappingModules, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_cpu_mismatch() {
+        let endian = AsmEndian::Little;
+        let context = minidump_synth::x86_context(endian, 0x1000, 0x2000);
+        let stack = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0, 0x100),
+            0x2000,
+        );
+        let thread = Thread::new(endian, 2, &stack, &context);
+        let dump = synth_dump()
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .finish()
+            .unwrap();
+        let thread_list = MINIDUMP_STREAM_TYPE::ThreadListStream as u32;
+        assert_eq!(
+            validate(&dump),
+            vec![(FindingKind::ContextCpuMismatch, Some(thread_list))]
+        );
+    }
+}
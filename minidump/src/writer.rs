@@ -0,0 +1,1430 @@
+//! Writing minidumps.
+//!
+//! [`MinidumpBuilder`] serializes a list of streams and a set of memory regions into a
+//! minidump file. It can start out empty, or be seeded with the contents of a parsed
+//! [`Minidump`] with [`MinidumpBuilder::from_minidump`], after which streams and memory
+//! can be added, removed or replaced before [`MinidumpBuilder::build`] writes the result:
+//!
+//! ```
+//! use minidump::format::MINIDUMP_STREAM_TYPE;
+//! use minidump::writer::{MinidumpBuilder, StreamData};
+//! use minidump::Minidump;
+//!
+//! # fn foo() -> Result<(), minidump::Error> {
+//! let dump = Minidump::read_path("../testdata/test.dmp")?;
+//! let mut builder = MinidumpBuilder::from_minidump(&dump);
+//!
+//! // Attach some metadata in a custom stream...
+//! builder.add_stream(0x4b6b0001u32, StreamData::new(&b"processed-by=server"[..]));
+//! // ...drop the comment...
+//! builder.remove_stream(MINIDUMP_STREAM_TYPE::CommentStreamW);
+//! // ...and only keep the first 4k of each memory region.
+//! builder.retain_memory(|_, bytes| bytes.len() <= 0x1000);
+//!
+//! std::fs::write("trimmed.dmp", builder.build()?).or(Err(minidump::Error::IoError))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Many streams refer to data elsewhere in the file: the name of a module, the context
+//! and stack of a thread, and so on. The file offsets (RVAs) of that data change whenever
+//! a minidump is rewritten, so a [`StreamData`] holds the bytes of a stream along with
+//! [references][StreamData::add_reference] to the data it refers to. The referenced data
+//! is laid out when the minidump is written, and the references are patched with its
+//! location.
+//!
+//! When seeding from a minidump, the references of all of the streams this crate can read
+//! are recovered. Other streams are copied as-is, so any RVAs they contain will be wrong
+//! in the new file.
+//!
+//! The memory list is generated from the builder's memory regions, rather than being a
+//! stream of its own. Thread stacks that were captured in the memory list refer to it
+//! with [`ReferenceTarget::Memory`], so they aren't duplicated (and removing the memory
+//! region removes the stack).
+
+use scroll::ctx::{SizeWith, TryIntoCtx};
+use scroll::{Endian, Pread, Pwrite};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::ops::Deref;
+use tracing::warn;
+
+use crate::minidump::{location_slice, Error, Minidump, MinidumpSystemInfo};
+use crate::system_info::PointerWidth;
+use minidump_common::format::{self as md, MINIDUMP_STREAM_TYPE};
+use num_traits::FromPrimitive;
+
+// Offsets of the RVAs in the raw structs of the streams that have them.
+pub(crate) const THREAD_SIZE: usize = 48;
+pub(crate) const THREAD_EX_SIZE: usize = 64;
+pub(crate) const THREAD_STACK_OFFSET: usize = 24;
+pub(crate) const THREAD_CONTEXT_OFFSET: usize = 40;
+const THREAD_EX_BACKING_STORE_OFFSET: usize = 48;
+pub(crate) const MODULE_SIZE: usize = 108;
+pub(crate) const MODULE_NAME_OFFSET: usize = 20;
+pub(crate) const MODULE_CV_RECORD_OFFSET: usize = 76;
+const MODULE_MISC_RECORD_OFFSET: usize = 84;
+const UNLOADED_MODULE_NAME_OFFSET: usize = 20;
+pub(crate) const EXCEPTION_CONTEXT_OFFSET: usize = 160;
+pub(crate) const SYSTEM_INFO_CSD_VERSION_OFFSET: usize = 24;
+pub(crate) const THREAD_NAME_SIZE: usize = 12;
+pub(crate) const THREAD_NAME_OFFSET: usize = 4;
+pub(crate) const HANDLE_DESCRIPTOR_SIZE: usize = 32;
+const HANDLE_DESCRIPTOR_2_SIZE: usize = 40;
+const HANDLE_TYPE_NAME_OFFSET: usize = 8;
+pub(crate) const HANDLE_OBJECT_NAME_OFFSET: usize = 12;
+const HANDLE_OBJECT_INFO_OFFSET: usize = 32;
+pub(crate) const CRASHPAD_SIMPLE_ANNOTATIONS_OFFSET: usize = 36;
+pub(crate) const CRASHPAD_MODULE_LIST_OFFSET: usize = 44;
+pub(crate) const CRASHPAD_MODULE_LINK_SIZE: usize = 12;
+pub(crate) const CRASHPAD_MODULE_LIST_ANNOTATIONS_OFFSET: usize = 4;
+pub(crate) const CRASHPAD_MODULE_SIMPLE_ANNOTATIONS_OFFSET: usize = 12;
+pub(crate) const CRASHPAD_MODULE_ANNOTATION_OBJECTS_OFFSET: usize = 20;
+pub(crate) const CRASHPAD_DICTIONARY_ENTRY_SIZE: usize = 8;
+pub(crate) const CRASHPAD_ANNOTATION_SIZE: usize = 12;
+const CRASHPAD_ANNOTATION_VALUE_OFFSET: usize = 8;
+const MAC_CRASH_INFO_RECORDS_OFFSET: usize = 12;
+const MAC_BOOTARGS_OFFSET: usize = 4;
+pub(crate) const DSO_DEBUG_MAP_OFFSET: usize = 4;
+const DSO_DEBUG_COUNT_OFFSET: usize = 8;
+const LINK_MAP_32_SIZE: usize = 12;
+const LINK_MAP_32_NAME_OFFSET: usize = 4;
+const LINK_MAP_64_SIZE: usize = 20;
+const LINK_MAP_64_NAME_OFFSET: usize = 8;
+
+/// The maximum number of chained handle object informations we'll follow, in case
+/// the chain is corrupt.
+const MAX_HANDLE_OBJECT_INFOS: usize = 256;
+
+/// How a [`StreamData`] refers to other data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A [`MINIDUMP_LOCATION_DESCRIPTOR`][md::MINIDUMP_LOCATION_DESCRIPTOR]: the 32-bit size
+    /// of the data followed by its 32-bit RVA.
+    Location,
+    /// The 32-bit RVA of the data.
+    Rva,
+    /// The 64-bit RVA of the data.
+    Rva64,
+}
+
+impl ReferenceKind {
+    /// The size of the reference, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            ReferenceKind::Location | ReferenceKind::Rva64 => 8,
+            ReferenceKind::Rva => 4,
+        }
+    }
+}
+
+/// The data a [`StreamData`] refers to.
+#[derive(Debug, Clone)]
+pub enum ReferenceTarget<'a> {
+    /// Data of its own, which is written to the file along with the stream.
+    Data(StreamData<'a>),
+    /// `size` bytes of process memory at `address`, from the builder's memory regions.
+    ///
+    /// If none of the regions contain all of that memory when the minidump is written,
+    /// the reference is written as zeroes.
+    Memory { address: u64, size: u64 },
+}
+
+#[derive(Debug, Clone)]
+struct Reference<'a> {
+    offset: usize,
+    kind: ReferenceKind,
+    target: ReferenceTarget<'a>,
+}
+
+/// The contents of a stream, or of some data a stream refers to.
+///
+/// The bytes are written to the file as-is, except for the [references][Self::add_reference]
+/// to other data, which are filled in with the location of that data.
+#[derive(Debug, Clone, Default)]
+pub struct StreamData<'a> {
+    bytes: Cow<'a, [u8]>,
+    references: Vec<Reference<'a>>,
+}
+
+impl<'a> StreamData<'a> {
+    /// Data consisting of just `bytes`, without any references.
+    pub fn new<B>(bytes: B) -> StreamData<'a>
+    where
+        B: Into<Cow<'a, [u8]>>,
+    {
+        StreamData {
+            bytes: bytes.into(),
+            references: vec![],
+        }
+    }
+
+    /// A length-prefixed, nul-terminated UTF-16 string (a `MINIDUMP_STRING`), as used for
+    /// the names of modules and threads.
+    pub fn string(s: &str, endian: Endian) -> StreamData<'a> {
+        let chars = s.encode_utf16().collect::<Vec<_>>();
+        let mut bytes = vec![0; 4 + chars.len() * 2 + 2];
+        let mut offset = 0;
+        // Writing to a buffer of the right size can't fail.
+        let _ = bytes.gwrite_with((chars.len() * 2) as u32, &mut offset, endian);
+        for c in chars {
+            let _ = bytes.gwrite_with(c, &mut offset, endian);
+        }
+        StreamData::new(bytes)
+    }
+
+    /// A length-prefixed, nul-terminated UTF-8 string (a `MINIDUMP_UTF8_STRING`), as used
+    /// by Crashpad.
+    pub fn utf8_string(s: &str, endian: Endian) -> StreamData<'a> {
+        let mut bytes = vec![0; 4];
+        let _ = bytes.pwrite_with(s.len() as u32, 0, endian);
+        bytes.extend_from_slice(s.as_bytes());
+        bytes.push(0);
+        StreamData::new(bytes)
+    }
+
+    /// The bytes of a single raw struct.
+    pub(crate) fn from_value<S>(val: S, endian: Endian) -> Result<StreamData<'a>, Error>
+    where
+        S: SizeWith<Endian> + TryIntoCtx<Endian, Error = scroll::Error>,
+    {
+        let mut bytes = vec![0; S::size_with(&endian)];
+        bytes
+            .pwrite_with(val, 0, endian)
+            .or(Err(Error::DataError))?;
+        Ok(StreamData::new(bytes))
+    }
+
+    /// The bytes of a list stream: a 32-bit count followed by the items.
+    pub(crate) fn from_list<S>(items: Vec<S>, endian: Endian) -> Result<StreamData<'a>, Error>
+    where
+        S: SizeWith<Endian> + TryIntoCtx<Endian, Error = scroll::Error>,
+    {
+        let mut bytes = vec![0; 4 + items.len() * S::size_with(&endian)];
+        let mut offset = 0;
+        bytes
+            .gwrite_with(items.len() as u32, &mut offset, endian)
+            .or(Err(Error::DataError))?;
+        for item in items {
+            bytes
+                .gwrite_with(item, &mut offset, endian)
+                .or(Err(Error::DataError))?;
+        }
+        Ok(StreamData::new(bytes))
+    }
+
+    /// The bytes of the data, with the references as they were when the data was created.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Mutable access to the bytes of the data.
+    ///
+    /// The references will be overwritten when the data is written.
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        self.bytes.to_mut()
+    }
+
+    /// Refer to `target` from `offset` in the data, replacing any reference already there.
+    ///
+    /// # Panics
+    ///
+    /// If the reference doesn't fit in the data.
+    pub fn add_reference(
+        &mut self,
+        offset: usize,
+        kind: ReferenceKind,
+        target: ReferenceTarget<'a>,
+    ) {
+        assert!(
+            offset + kind.size() <= self.bytes.len(),
+            "reference at {offset} doesn't fit in {} bytes",
+            self.bytes.len()
+        );
+        self.remove_reference(offset);
+        self.references.push(Reference {
+            offset,
+            kind,
+            target,
+        });
+    }
+
+    /// Like [`StreamData::add_reference`], but consumes and returns the data.
+    pub fn with_reference(
+        mut self,
+        offset: usize,
+        kind: ReferenceKind,
+        target: ReferenceTarget<'a>,
+    ) -> StreamData<'a> {
+        self.add_reference(offset, kind, target);
+        self
+    }
+
+    /// Remove the reference at `offset`, zeroing it in the data.
+    pub fn remove_reference(&mut self, offset: usize) -> Option<ReferenceTarget<'a>> {
+        let index = self
+            .references
+            .iter()
+            .position(|reference| reference.offset == offset)?;
+        let reference = self.references.remove(index);
+        self.bytes.to_mut()[offset..offset + reference.kind.size()].fill(0);
+        Some(reference.target)
+    }
+
+    /// The target of the reference at `offset`.
+    pub fn reference(&self, offset: usize) -> Option<&ReferenceTarget<'a>> {
+        self.references
+            .iter()
+            .find(|reference| reference.offset == offset)
+            .map(|reference| &reference.target)
+    }
+
+    /// Mutable access to the target of the reference at `offset`.
+    pub fn reference_mut(&mut self, offset: usize) -> Option<&mut ReferenceTarget<'a>> {
+        self.references
+            .iter_mut()
+            .find(|reference| reference.offset == offset)
+            .map(|reference| &mut reference.target)
+    }
+
+    /// All of the references in the data, as `(offset, kind, target)`.
+    pub fn references(
+        &self,
+    ) -> impl Iterator<Item = (usize, ReferenceKind, &ReferenceTarget<'a>)> + '_ {
+        self.references
+            .iter()
+            .map(|reference| (reference.offset, reference.kind, &reference.target))
+    }
+
+    /// Zero the reference field at `offset`, without recording a reference.
+    fn clear(&mut self, offset: usize, kind: ReferenceKind) {
+        if let Some(field) = self.bytes.to_mut().get_mut(offset..offset + kind.size()) {
+            field.fill(0);
+        }
+    }
+}
+
+/// Builds a minidump file out of streams and memory.
+///
+/// See the [module-level documentation][self] for an overview.
+#[derive(Debug, Clone)]
+pub struct MinidumpBuilder<'a> {
+    endian: Endian,
+    version: u32,
+    /// The `time_date_stamp` of the minidump header.
+    pub time_date_stamp: u32,
+    /// The `flags` of the minidump header (a [`MINIDUMP_TYPE`][md::MINIDUMP_TYPE]).
+    pub flags: u64,
+    streams: Vec<(u32, StreamData<'a>)>,
+    memory: BTreeMap<u64, Cow<'a, [u8]>>,
+    memory64: bool,
+}
+
+impl<'a> MinidumpBuilder<'a> {
+    /// An empty minidump, to be written with the given endianness.
+    pub fn new(endian: Endian) -> MinidumpBuilder<'a> {
+        MinidumpBuilder {
+            endian,
+            version: md::MINIDUMP_VERSION,
+            time_date_stamp: 0,
+            flags: 0,
+            streams: vec![],
+            memory: BTreeMap::new(),
+            memory64: false,
+        }
+    }
+
+    /// A builder with the contents of `dump`.
+    ///
+    /// The streams are kept in the order of the stream directory, and the contents of
+    /// the memory lists become the builder's memory regions. If `dump` has a
+    /// `Memory64ListStream`, the memory is written as one too.
+    ///
+    /// Nothing is copied until it is modified, and streams that are out of the bounds of
+    /// the file are dropped. For minidumps read with [`Minidump::read_lazy`], this reads the
+    /// whole file.
+    pub fn from_minidump<T>(dump: &'a Minidump<'a, T>) -> MinidumpBuilder<'a>
+    where
+        T: Deref<Target = [u8]> + 'a,
+    {
+        let all = dump.data();
+        let endian = dump.endian;
+        let mut builder = MinidumpBuilder {
+            endian,
+            version: dump.header.version,
+            time_date_stamp: dump.header.time_date_stamp,
+            flags: dump.header.flags,
+            streams: vec![],
+            memory: BTreeMap::new(),
+            memory64: false,
+        };
+
+        let mut streams = vec![];
+        for entry in dump.directory() {
+            let bytes = match location_slice(all, &entry.location) {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    warn!(
+                        "Dropping stream of type {} that is out of bounds",
+                        entry.stream_type
+                    );
+                    continue;
+                }
+            };
+            match MINIDUMP_STREAM_TYPE::from_u32(entry.stream_type) {
+                Some(MINIDUMP_STREAM_TYPE::MemoryListStream) => {
+                    builder.read_memory_list(bytes, all);
+                }
+                Some(MINIDUMP_STREAM_TYPE::Memory64ListStream) => {
+                    builder.memory64 = true;
+                    builder.read_memory64_list(bytes, all);
+                }
+                _ => streams.push((entry.stream_type, bytes)),
+            }
+        }
+
+        let pointer_width = dump
+            .get_stream::<MinidumpSystemInfo>()
+            .map(|info| info.cpu.pointer_width())
+            .unwrap_or(PointerWidth::Unknown);
+        let seeder = Seeder {
+            all,
+            endian,
+            memory: &builder.memory,
+            pointer_width,
+        };
+        let streams = streams
+            .into_iter()
+            .map(|(stream_type, bytes)| (stream_type, seeder.stream(stream_type, bytes)))
+            .collect();
+        builder.streams = streams;
+        builder
+    }
+
+    fn read_memory_list(&mut self, bytes: &'a [u8], all: &'a [u8]) {
+        let descriptor_size = md::MINIDUMP_MEMORY_DESCRIPTOR::size_with(&self.endian);
+        for offset in list_entries(bytes, descriptor_size, self.endian) {
+            let descriptor: md::MINIDUMP_MEMORY_DESCRIPTOR =
+                match bytes.pread_with(offset, self.endian) {
+                    Ok(descriptor) => descriptor,
+                    Err(_) => break,
+                };
+            match location_slice(all, &descriptor.memory) {
+                Ok(memory) => {
+                    self.memory
+                        .entry(descriptor.start_of_memory_range)
+                        .or_insert(Cow::Borrowed(memory));
+                }
+                Err(_) => warn!(
+                    "Dropping memory at {:#x} that is out of bounds",
+                    descriptor.start_of_memory_range
+                ),
+            }
+        }
+    }
+
+    fn read_memory64_list(&mut self, bytes: &'a [u8], all: &'a [u8]) {
+        let mut offset = 0;
+        let (count, base_rva) = match (
+            bytes.gread_with::<u64>(&mut offset, self.endian),
+            bytes.gread_with::<u64>(&mut offset, self.endian),
+        ) {
+            (Ok(count), Ok(base_rva)) => (count, base_rva),
+            _ => return,
+        };
+        let mut rva = base_rva;
+        for _ in 0..count {
+            let descriptor: md::MINIDUMP_MEMORY_DESCRIPTOR64 =
+                match bytes.gread_with(&mut offset, self.endian) {
+                    Ok(descriptor) => descriptor,
+                    Err(_) => break,
+                };
+            let memory = rva
+                .checked_add(descriptor.data_size)
+                .and_then(|end| all.get(rva.try_into().ok()?..end.try_into().ok()?));
+            match memory {
+                Some(memory) => {
+                    self.memory
+                        .entry(descriptor.start_of_memory_range)
+                        .or_insert(Cow::Borrowed(memory));
+                }
+                None => {
+                    warn!(
+                        "Dropping memory at {:#x} that is out of bounds",
+                        descriptor.start_of_memory_range
+                    );
+                    break;
+                }
+            }
+            rva += descriptor.data_size;
+        }
+    }
+
+    /// The endianness the minidump will be written with.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// All of the streams, in the order they will be written, as `(stream_type, data)`.
+    ///
+    /// This doesn't include the memory list.
+    pub fn streams(&self) -> impl Iterator<Item = (u32, &StreamData<'a>)> + '_ {
+        self.streams
+            .iter()
+            .map(|(stream_type, data)| (*stream_type, data))
+    }
+
+    /// The first stream of type `stream_type`.
+    pub fn stream<S: Into<u32>>(&self, stream_type: S) -> Option<&StreamData<'a>> {
+        let stream_type = stream_type.into();
+        self.streams
+            .iter()
+            .find(|(ty, _)| *ty == stream_type)
+            .map(|(_, data)| data)
+    }
+
+    /// Mutable access to the first stream of type `stream_type`.
+    pub fn stream_mut<S: Into<u32>>(&mut self, stream_type: S) -> Option<&mut StreamData<'a>> {
+        let stream_type = stream_type.into();
+        self.streams
+            .iter_mut()
+            .find(|(ty, _)| *ty == stream_type)
+            .map(|(_, data)| data)
+    }
+
+    /// Add a stream after all of the others, even if there already is one of the same type.
+    ///
+    /// The memory list is generated from the builder's memory regions, so use
+    /// [`MinidumpBuilder::add_memory`] for memory rather than adding a memory list here.
+    pub fn add_stream<S: Into<u32>>(&mut self, stream_type: S, data: StreamData<'a>) {
+        self.streams.push((stream_type.into(), data));
+    }
+
+    /// Replace the stream of type `stream_type`, returning the old one.
+    ///
+    /// The new stream takes the place of the first stream of the type, and any other
+    /// streams of the type are removed. If there are none, the stream is added after all
+    /// of the others.
+    pub fn set_stream<S: Into<u32>>(
+        &mut self,
+        stream_type: S,
+        data: StreamData<'a>,
+    ) -> Option<StreamData<'a>> {
+        let stream_type = stream_type.into();
+        match self.streams.iter().position(|(ty, _)| *ty == stream_type) {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.streams[index].1, data);
+                let mut i = 0;
+                self.streams.retain(|(ty, _)| {
+                    i += 1;
+                    *ty != stream_type || i - 1 == index
+                });
+                Some(old)
+            }
+            None => {
+                self.streams.push((stream_type, data));
+                None
+            }
+        }
+    }
+
+    /// Remove all of the streams of type `stream_type`, returning the first one.
+    pub fn remove_stream<S: Into<u32>>(&mut self, stream_type: S) -> Option<StreamData<'a>> {
+        let stream_type = stream_type.into();
+        let mut removed = None;
+        let mut i = 0;
+        while i < self.streams.len() {
+            if self.streams[i].0 == stream_type {
+                let (_, data) = self.streams.remove(i);
+                removed.get_or_insert(data);
+            } else {
+                i += 1;
+            }
+        }
+        removed
+    }
+
+    /// All of the memory regions, in order of address, as `(base_address, bytes)`.
+    pub fn memory(&self) -> impl Iterator<Item = (u64, &[u8])> + '_ {
+        self.memory.iter().map(|(base, bytes)| (*base, &bytes[..]))
+    }
+
+    /// Mutable access to the contents of the memory region at `base_address`.
+    pub fn memory_mut(&mut self, base_address: u64) -> Option<&mut [u8]> {
+        self.memory
+            .get_mut(&base_address)
+            .map(|bytes| &mut bytes.to_mut()[..])
+    }
+
+    /// Add a memory region, returning the contents of the region it replaces, if any.
+    pub fn add_memory<B>(&mut self, base_address: u64, bytes: B) -> Option<Cow<'a, [u8]>>
+    where
+        B: Into<Cow<'a, [u8]>>,
+    {
+        self.memory.insert(base_address, bytes.into())
+    }
+
+    /// Remove the memory region at `base_address`, returning its contents.
+    pub fn remove_memory(&mut self, base_address: u64) -> Option<Cow<'a, [u8]>> {
+        self.memory.remove(&base_address)
+    }
+
+    /// Only keep the memory regions for which `f(base_address, bytes)` returns true.
+    pub fn retain_memory<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u64, &[u8]) -> bool,
+    {
+        self.memory.retain(|base, bytes| f(*base, bytes));
+    }
+
+    /// Whether the memory is written as a `Memory64ListStream` rather than a
+    /// `MemoryListStream`.
+    ///
+    /// A `Memory64ListStream` is more compact, and can hold more than 4GB of memory.
+    pub fn set_memory64(&mut self, memory64: bool) {
+        self.memory64 = memory64;
+    }
+
+    /// Write the minidump, returning the contents of the file.
+    ///
+    /// Everything but the memory is laid out in the order of the streams, with the data each
+    /// stream refers to after it. The memory list and the memory itself come last.
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        let endian = self.endian;
+        let header_size = md::MINIDUMP_HEADER::size_with(&endian);
+        let entry_size = md::MINIDUMP_DIRECTORY::size_with(&endian);
+        let stream_count = self.streams.len() + usize::from(!self.memory.is_empty());
+        let mut writer = DumpWriter {
+            data: vec![0; header_size + stream_count * entry_size],
+            endian,
+            memory_references: vec![],
+        };
+
+        let mut directory = Vec::with_capacity(stream_count);
+        for (stream_type, data) in &self.streams {
+            directory.push(md::MINIDUMP_DIRECTORY {
+                stream_type: *stream_type,
+                location: writer.write(data)?,
+            });
+        }
+        if !self.memory.is_empty() {
+            let (stream_type, location) = writer.write_memory(&self.memory, self.memory64)?;
+            directory.push(md::MINIDUMP_DIRECTORY {
+                stream_type: stream_type as u32,
+                location,
+            });
+        }
+
+        let mut offset = header_size;
+        for entry in directory {
+            writer
+                .data
+                .gwrite_with(entry, &mut offset, endian)
+                .or(Err(Error::DataError))?;
+        }
+        let header = md::MINIDUMP_HEADER {
+            signature: md::MINIDUMP_SIGNATURE,
+            version: (self.version & 0xffff0000) | md::MINIDUMP_VERSION,
+            stream_count: stream_count as u32,
+            stream_directory_rva: header_size as u32,
+            checksum: 0,
+            time_date_stamp: self.time_date_stamp,
+            flags: self.flags,
+        };
+        writer
+            .data
+            .pwrite_with(header, 0, endian)
+            .or(Err(Error::DataError))?;
+        Ok(writer.data)
+    }
+}
+
+/// The offsets of the entries of a list stream: a 32-bit count followed by the entries.
+pub(crate) fn list_entries(bytes: &[u8], entry_size: usize, endian: Endian) -> Vec<usize> {
+    let count = match bytes.pread_with::<u32>(0, endian) {
+        Ok(count) => count as usize,
+        Err(_) => return vec![],
+    };
+    // Some writers pad the count to 8 bytes.
+    let padded_size = count
+        .checked_mul(entry_size)
+        .and_then(|size| size.checked_add(8));
+    let start = if padded_size == Some(bytes.len()) {
+        8
+    } else {
+        4
+    };
+    entries(bytes, start, count, entry_size)
+}
+
+/// The offsets of up to `count` entries of `entry_size` bytes from `start`, as far as
+/// they fit in `bytes`.
+pub(crate) fn entries(bytes: &[u8], start: usize, count: usize, entry_size: usize) -> Vec<usize> {
+    if entry_size == 0 || start > bytes.len() {
+        return vec![];
+    }
+    let count = count.min((bytes.len() - start) / entry_size);
+    (0..count).map(|i| start + i * entry_size).collect()
+}
+
+/// The offsets of the entries of a list stream with a header that has its size, the size
+/// of the entries and their count, if the entries are at least `min_entry_size` bytes.
+pub(crate) fn ex_list_entries(bytes: &[u8], min_entry_size: usize, endian: Endian) -> Vec<usize> {
+    let header = (
+        bytes.pread_with::<u32>(0, endian),
+        bytes.pread_with::<u32>(4, endian),
+        bytes.pread_with::<u32>(8, endian),
+    );
+    match header {
+        (Ok(header_size), Ok(entry_size), Ok(count)) if entry_size as usize >= min_entry_size => {
+            entries(
+                bytes,
+                header_size as usize,
+                count as usize,
+                entry_size as usize,
+            )
+        }
+        _ => vec![],
+    }
+}
+
+/// Recovers the references of the streams of a minidump.
+struct Seeder<'a, 'b> {
+    all: &'a [u8],
+    endian: Endian,
+    memory: &'b BTreeMap<u64, Cow<'a, [u8]>>,
+    pointer_width: PointerWidth,
+}
+
+impl<'a, 'b> Seeder<'a, 'b> {
+    fn stream(&self, stream_type: u32, bytes: &'a [u8]) -> StreamData<'a> {
+        use MINIDUMP_STREAM_TYPE::*;
+
+        let mut data = StreamData::new(bytes);
+        let stream_type = match MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
+            Some(stream_type) => stream_type,
+            None => return data,
+        };
+        match stream_type {
+            ThreadListStream | ThreadExListStream => {
+                let thread_size = if stream_type == ThreadListStream {
+                    THREAD_SIZE
+                } else {
+                    THREAD_EX_SIZE
+                };
+                for entry in list_entries(bytes, thread_size, self.endian) {
+                    self.memory_descriptor(&mut data, entry + THREAD_STACK_OFFSET);
+                    self.location(&mut data, entry + THREAD_CONTEXT_OFFSET);
+                    if stream_type == ThreadExListStream {
+                        self.memory_descriptor(&mut data, entry + THREAD_EX_BACKING_STORE_OFFSET);
+                    }
+                }
+            }
+            ModuleListStream => {
+                for entry in list_entries(bytes, MODULE_SIZE, self.endian) {
+                    self.string(&mut data, entry + MODULE_NAME_OFFSET, ReferenceKind::Rva);
+                    self.location(&mut data, entry + MODULE_CV_RECORD_OFFSET);
+                    self.location(&mut data, entry + MODULE_MISC_RECORD_OFFSET);
+                }
+            }
+            UnloadedModuleListStream => {
+                for entry in ex_list_entries(bytes, UNLOADED_MODULE_NAME_OFFSET + 4, self.endian) {
+                    let offset = entry + UNLOADED_MODULE_NAME_OFFSET;
+                    self.string(&mut data, offset, ReferenceKind::Rva);
+                }
+            }
+            ExceptionStream => self.location(&mut data, EXCEPTION_CONTEXT_OFFSET),
+            SystemInfoStream => {
+                let offset = SYSTEM_INFO_CSD_VERSION_OFFSET;
+                self.string(&mut data, offset, ReferenceKind::Rva);
+            }
+            ThreadNamesStream => {
+                for entry in list_entries(bytes, THREAD_NAME_SIZE, self.endian) {
+                    let offset = entry + THREAD_NAME_OFFSET;
+                    self.string(&mut data, offset, ReferenceKind::Rva64);
+                }
+            }
+            HandleDataStream => self.handles(&mut data),
+            CrashpadInfoStream => {
+                self.reference(
+                    &mut data,
+                    CRASHPAD_SIMPLE_ANNOTATIONS_OFFSET,
+                    ReferenceKind::Location,
+                    |bytes| Some(self.dictionary(bytes)),
+                );
+                self.reference(
+                    &mut data,
+                    CRASHPAD_MODULE_LIST_OFFSET,
+                    ReferenceKind::Location,
+                    |bytes| Some(self.crashpad_modules(bytes)),
+                );
+            }
+            MozMacosCrashInfoStream => {
+                let count = bytes.pread_with::<u32>(4, self.endian).unwrap_or(0) as usize;
+                let count = count.min(md::MAC_CRASH_INFOS_MAX);
+                for entry in entries(bytes, MAC_CRASH_INFO_RECORDS_OFFSET, count, 8) {
+                    self.location(&mut data, entry);
+                }
+            }
+            MozMacosBootargsStream => {
+                self.string(&mut data, MAC_BOOTARGS_OFFSET, ReferenceKind::Rva64);
+            }
+            LinuxDsoDebug => self.dso_debug(&mut data),
+            _ => {}
+        }
+        data
+    }
+
+    /// Refer to the data `read` finds at the reference at `offset` in `data`.
+    ///
+    /// `read` is passed the data of a location, or everything from an RVA on. If the
+    /// reference is null or `read` fails, it is zeroed instead.
+    fn reference<F>(&self, data: &mut StreamData<'a>, offset: usize, kind: ReferenceKind, read: F)
+    where
+        F: FnOnce(&'a [u8]) -> Option<StreamData<'a>>,
+    {
+        let bytes = match kind {
+            ReferenceKind::Location => data
+                .bytes
+                .pread_with::<md::MINIDUMP_LOCATION_DESCRIPTOR>(offset, self.endian)
+                .ok()
+                .filter(|location| location.rva != 0 && location.data_size != 0)
+                .map(|location| location_slice(self.all, &location).ok()),
+            ReferenceKind::Rva => data
+                .bytes
+                .pread_with::<u32>(offset, self.endian)
+                .ok()
+                .filter(|&rva| rva != 0)
+                .map(|rva| self.all.get(rva as usize..)),
+            ReferenceKind::Rva64 => data
+                .bytes
+                .pread_with::<u64>(offset, self.endian)
+                .ok()
+                .filter(|&rva| rva != 0)
+                .map(|rva| self.all.get(rva.try_into().ok()?..)),
+        };
+        match bytes.flatten().and_then(read) {
+            Some(target) => data.add_reference(offset, kind, ReferenceTarget::Data(target)),
+            None => data.clear(offset, kind),
+        }
+    }
+
+    /// Refer to the data of the location descriptor at `offset`.
+    fn location(&self, data: &mut StreamData<'a>, offset: usize) {
+        self.reference(data, offset, ReferenceKind::Location, |bytes| {
+            Some(StreamData::new(bytes))
+        });
+    }
+
+    /// Refer to the memory of the memory descriptor at `offset`, preferring the copy in
+    /// the memory list.
+    fn memory_descriptor(&self, data: &mut StreamData<'a>, offset: usize) {
+        let address = match data.bytes.pread_with::<u64>(offset, self.endian) {
+            Ok(address) => address,
+            Err(_) => return,
+        };
+        let location_offset = offset + 8;
+        let location = data
+            .bytes
+            .pread_with::<md::MINIDUMP_LOCATION_DESCRIPTOR>(location_offset, self.endian);
+        let bytes = match location
+            .ok()
+            .map(|location| location_slice(self.all, &location))
+        {
+            Some(Ok(bytes)) if !bytes.is_empty() => bytes,
+            _ => return data.clear(location_offset, ReferenceKind::Location),
+        };
+        let size = bytes.len() as u64;
+        let target = match memory_range(self.memory, address, size) {
+            Some((_, memory)) if memory == bytes => ReferenceTarget::Memory { address, size },
+            _ => ReferenceTarget::Data(StreamData::new(bytes)),
+        };
+        data.add_reference(location_offset, ReferenceKind::Location, target);
+    }
+
+    /// Refer to the `MINIDUMP_STRING` at `offset`.
+    fn string(&self, data: &mut StreamData<'a>, offset: usize, kind: ReferenceKind) {
+        self.reference(data, offset, kind, |bytes| {
+            let length = bytes.pread_with::<u32>(0, self.endian).ok()? as usize;
+            let end = length.checked_add(4)?;
+            // Include the nul terminator, if there is one.
+            let terminated = end.saturating_add(2).min(bytes.len());
+            Some(StreamData::new(bytes.get(..end.max(terminated))?))
+        });
+    }
+
+    /// Refer to the `MINIDUMP_UTF8_STRING` at `offset`.
+    fn utf8_string(&self, data: &mut StreamData<'a>, offset: usize) {
+        self.reference(data, offset, ReferenceKind::Rva, |bytes| {
+            let length = bytes.pread_with::<u32>(0, self.endian).ok()? as usize;
+            let end = length.checked_add(5)?;
+            Some(StreamData::new(bytes.get(..end)?))
+        });
+    }
+
+    /// The offsets of the entries of a list with a `size_of_header`, `size_of_entry` and
+    /// `number_of_entries` header, if the entries are at least `min_entry_size` bytes.
+    fn handles(&self, data: &mut StreamData<'a>) {
+        let bytes = data.bytes.clone();
+        for entry in ex_list_entries(&bytes, HANDLE_DESCRIPTOR_SIZE, self.endian) {
+            let offset = entry + HANDLE_TYPE_NAME_OFFSET;
+            self.string(data, offset, ReferenceKind::Rva);
+            let offset = entry + HANDLE_OBJECT_NAME_OFFSET;
+            self.string(data, offset, ReferenceKind::Rva);
+        }
+        for entry in ex_list_entries(&bytes, HANDLE_DESCRIPTOR_2_SIZE, self.endian) {
+            let offset = entry + HANDLE_OBJECT_INFO_OFFSET;
+            self.reference(data, offset, ReferenceKind::Rva, |bytes| {
+                self.handle_object_info(bytes, 0)
+            });
+        }
+    }
+
+    /// A `MINIDUMP_HANDLE_OBJECT_INFORMATION` and the ones chained after it.
+    fn handle_object_info(&self, bytes: &'a [u8], depth: usize) -> Option<StreamData<'a>> {
+        let header_size = md::MINIDUMP_HANDLE_OBJECT_INFORMATION::size_with(&self.endian);
+        let size = bytes.pread_with::<u32>(8, self.endian).ok()? as usize;
+        let mut data = StreamData::new(bytes.get(..size.max(header_size))?);
+        if depth < MAX_HANDLE_OBJECT_INFOS {
+            self.reference(&mut data, 0, ReferenceKind::Rva, |bytes| {
+                self.handle_object_info(bytes, depth + 1)
+            });
+        } else {
+            data.clear(0, ReferenceKind::Rva);
+        }
+        Some(data)
+    }
+
+    /// A `MINIDUMP_SIMPLE_STRING_DICTIONARY`.
+    fn dictionary(&self, bytes: &'a [u8]) -> StreamData<'a> {
+        let mut data = StreamData::new(bytes);
+        let count = bytes.pread_with::<u32>(0, self.endian).unwrap_or(0) as usize;
+        for entry in entries(bytes, 4, count, CRASHPAD_DICTIONARY_ENTRY_SIZE) {
+            self.utf8_string(&mut data, entry);
+            self.utf8_string(&mut data, entry + 4);
+        }
+        data
+    }
+
+    /// A `MINIDUMP_MODULE_CRASHPAD_INFO_LIST`.
+    fn crashpad_modules(&self, bytes: &'a [u8]) -> StreamData<'a> {
+        let mut data = StreamData::new(bytes);
+        let count = bytes.pread_with::<u32>(0, self.endian).unwrap_or(0) as usize;
+        for entry in entries(bytes, 4, count, CRASHPAD_MODULE_LINK_SIZE) {
+            self.reference(&mut data, entry + 4, ReferenceKind::Location, |bytes| {
+                let mut info = StreamData::new(bytes);
+                self.reference(
+                    &mut info,
+                    CRASHPAD_MODULE_LIST_ANNOTATIONS_OFFSET,
+                    ReferenceKind::Location,
+                    |bytes| Some(self.string_list(bytes)),
+                );
+                self.reference(
+                    &mut info,
+                    CRASHPAD_MODULE_SIMPLE_ANNOTATIONS_OFFSET,
+                    ReferenceKind::Location,
+                    |bytes| Some(self.dictionary(bytes)),
+                );
+                self.reference(
+                    &mut info,
+                    CRASHPAD_MODULE_ANNOTATION_OBJECTS_OFFSET,
+                    ReferenceKind::Location,
+                    |bytes| Some(self.annotation_objects(bytes)),
+                );
+                Some(info)
+            });
+        }
+        data
+    }
+
+    /// A `MINIDUMP_RVA_LIST` of `MINIDUMP_UTF8_STRING`s.
+    fn string_list(&self, bytes: &'a [u8]) -> StreamData<'a> {
+        let mut data = StreamData::new(bytes);
+        let count = bytes.pread_with::<u32>(0, self.endian).unwrap_or(0) as usize;
+        for entry in entries(bytes, 4, count, 4) {
+            self.utf8_string(&mut data, entry);
+        }
+        data
+    }
+
+    /// A `MINIDUMP_ANNOTATION_LIST`.
+    fn annotation_objects(&self, bytes: &'a [u8]) -> StreamData<'a> {
+        let mut data = StreamData::new(bytes);
+        let count = bytes.pread_with::<u32>(0, self.endian).unwrap_or(0) as usize;
+        for entry in entries(bytes, 4, count, CRASHPAD_ANNOTATION_SIZE) {
+            self.utf8_string(&mut data, entry);
+            // The value is a `MinidumpByteArray`: a 32-bit length followed by the bytes.
+            let offset = entry + CRASHPAD_ANNOTATION_VALUE_OFFSET;
+            self.reference(&mut data, offset, ReferenceKind::Rva, |bytes| {
+                let length = bytes.pread_with::<u32>(0, self.endian).ok()? as usize;
+                Some(StreamData::new(bytes.get(..length.checked_add(4)?)?))
+            });
+        }
+        data
+    }
+
+    fn dso_debug(&self, data: &mut StreamData<'a>) {
+        let is_64bit = match self.pointer_width {
+            PointerWidth::Bits64 => true,
+            PointerWidth::Bits32 => false,
+            PointerWidth::Unknown => data.bytes.len() >= md::DSO_DEBUG_64::size_with(&self.endian),
+        };
+        let (entry_size, name_offset) = if is_64bit {
+            (LINK_MAP_64_SIZE, LINK_MAP_64_NAME_OFFSET)
+        } else {
+            (LINK_MAP_32_SIZE, LINK_MAP_32_NAME_OFFSET)
+        };
+        let count = data
+            .bytes
+            .pread_with::<u32>(DSO_DEBUG_COUNT_OFFSET, self.endian)
+            .unwrap_or(0) as usize;
+        self.reference(data, DSO_DEBUG_MAP_OFFSET, ReferenceKind::Rva, |bytes| {
+            let mut map = StreamData::new(bytes.get(..count.checked_mul(entry_size)?)?);
+            for entry in entries(bytes, 0, count, entry_size) {
+                self.string(&mut map, entry + name_offset, ReferenceKind::Rva);
+            }
+            Some(map)
+        });
+    }
+}
+
+/// The `size` bytes of memory at `address`, if a single region contains all of them,
+/// along with the base address of that region.
+fn memory_range<'m>(
+    memory: &'m BTreeMap<u64, Cow<[u8]>>,
+    address: u64,
+    size: u64,
+) -> Option<(u64, &'m [u8])> {
+    let (&base, bytes) = memory.range(..=address).next_back()?;
+    let start: usize = (address - base).try_into().ok()?;
+    let end = start.checked_add(size.try_into().ok()?)?;
+    Some((base, bytes.get(start..end)?))
+}
+
+/// Lays out the contents of a minidump.
+struct DumpWriter {
+    data: Vec<u8>,
+    endian: Endian,
+    /// References to memory, which are patched once the memory has been written.
+    memory_references: Vec<(usize, ReferenceKind, u64, u64)>,
+}
+
+impl DumpWriter {
+    fn rva(&self) -> Result<md::RVA, Error> {
+        self.data.len().try_into().or(Err(Error::DataError))
+    }
+
+    fn align(&mut self) {
+        let padding = (8 - self.data.len() % 8) % 8;
+        self.data.resize(self.data.len() + padding, 0);
+    }
+
+    fn append<S>(&mut self, val: S) -> Result<(), Error>
+    where
+        S: SizeWith<Endian> + TryIntoCtx<Endian, Error = scroll::Error>,
+    {
+        let offset = self.data.len();
+        self.data.resize(offset + S::size_with(&self.endian), 0);
+        self.data
+            .pwrite_with(val, offset, self.endian)
+            .or(Err(Error::DataError))?;
+        Ok(())
+    }
+
+    /// Write `data` and everything it refers to.
+    fn write(&mut self, data: &StreamData) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        self.align();
+        let location = md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: data.bytes.len().try_into().or(Err(Error::DataError))?,
+            rva: self.rva()?,
+        };
+        self.data.extend_from_slice(&data.bytes);
+        for reference in &data.references {
+            let offset = location.rva as usize + reference.offset;
+            match &reference.target {
+                ReferenceTarget::Data(target) => {
+                    let target = self.write(target)?;
+                    self.patch(offset, reference.kind, target.data_size, target.rva as u64)?;
+                }
+                ReferenceTarget::Memory { address, size } => {
+                    self.memory_references
+                        .push((offset, reference.kind, *address, *size));
+                }
+            }
+        }
+        Ok(location)
+    }
+
+    /// Fill in the reference at `offset` with the location of some data.
+    fn patch(
+        &mut self,
+        offset: usize,
+        kind: ReferenceKind,
+        size: u32,
+        rva: u64,
+    ) -> Result<(), Error> {
+        let endian = self.endian;
+        let result = match kind {
+            ReferenceKind::Location => {
+                let location = md::MINIDUMP_LOCATION_DESCRIPTOR {
+                    data_size: size,
+                    rva: rva.try_into().or(Err(Error::DataError))?,
+                };
+                self.data.pwrite_with(location, offset, endian)
+            }
+            ReferenceKind::Rva => {
+                let rva: u32 = rva.try_into().or(Err(Error::DataError))?;
+                self.data.pwrite_with(rva, offset, endian)
+            }
+            ReferenceKind::Rva64 => self.data.pwrite_with(rva, offset, endian),
+        };
+        result.map(|_| ()).or(Err(Error::DataError))
+    }
+
+    /// Write the memory list and the memory, and fill in the references to it.
+    fn write_memory(
+        &mut self,
+        memory: &BTreeMap<u64, Cow<[u8]>>,
+        memory64: bool,
+    ) -> Result<(MINIDUMP_STREAM_TYPE, md::MINIDUMP_LOCATION_DESCRIPTOR), Error> {
+        self.align();
+        let list_rva = self.data.len();
+        let (stream_type, list_size) = if memory64 {
+            let size =
+                16 + memory.len() * md::MINIDUMP_MEMORY_DESCRIPTOR64::size_with(&self.endian);
+            (MINIDUMP_STREAM_TYPE::Memory64ListStream, size)
+        } else {
+            let size = 4 + memory.len() * md::MINIDUMP_MEMORY_DESCRIPTOR::size_with(&self.endian);
+            (MINIDUMP_STREAM_TYPE::MemoryListStream, size)
+        };
+        let base_rva = (list_rva + list_size) as u64;
+
+        if memory64 {
+            self.append(memory.len() as u64)?;
+            self.append(base_rva)?;
+            for (&base, bytes) in memory {
+                self.append(md::MINIDUMP_MEMORY_DESCRIPTOR64 {
+                    start_of_memory_range: base,
+                    data_size: bytes.len() as u64,
+                })?;
+            }
+        } else {
+            self.append(memory.len() as u32)?;
+            let mut rva = base_rva;
+            for (&base, bytes) in memory {
+                self.append(md::MINIDUMP_MEMORY_DESCRIPTOR {
+                    start_of_memory_range: base,
+                    memory: md::MINIDUMP_LOCATION_DESCRIPTOR {
+                        data_size: bytes.len().try_into().or(Err(Error::DataError))?,
+                        rva: rva.try_into().or(Err(Error::DataError))?,
+                    },
+                })?;
+                rva += bytes.len() as u64;
+            }
+        }
+        debug_assert_eq!(self.data.len() as u64, base_rva);
+
+        let mut rvas = BTreeMap::new();
+        for (&base, bytes) in memory {
+            rvas.insert(base, self.data.len() as u64);
+            self.data.extend_from_slice(bytes);
+        }
+        for (offset, kind, address, size) in std::mem::take(&mut self.memory_references) {
+            let location = memory_range(memory, address, size).and_then(|(base, _)| {
+                let rva: u32 = (rvas[&base] + (address - base)).try_into().ok()?;
+                Some((size.try_into().ok()?, rva))
+            });
+            match location {
+                Some((size, rva)) => self.patch(offset, kind, size, rva as u64)?,
+                // The memory was removed, or is too far into the file to refer to.
+                None => self.patch(offset, kind, 0, 0)?,
+            }
+        }
+
+        let location = md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: list_size.try_into().or(Err(Error::DataError))?,
+            rva: list_rva.try_into().or(Err(Error::DataError))?,
+        };
+        Ok((stream_type, location))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        MinidumpAnnotation, MinidumpCrashpadInfo, MinidumpMemory64List, MinidumpMemoryList,
+        MinidumpModuleList, MinidumpThreadList, MinidumpThreadNames, MinidumpUnloadedModuleList,
+        Module, UnifiedMemoryList,
+    };
+    use minidump_synth::{
+        amd64_context, AnnotationValue, CrashpadInfo, DumpString, Memory, Module as SynthModule,
+        ModuleCrashpadInfo, SimpleStream, SynthMinidump, SystemInfo as SynthSystemInfo, Thread,
+        ThreadName, UnloadedModule,
+    };
+    use test_assembler::{Endian as AsmEndian, Section};
+
+    const CUSTOM_STREAM: u32 = 0x4b6b0001;
+    const OTHER_STREAM: u32 = 0x4b6b0002;
+    const THREAD_ID: u32 = 1234;
+    const STACK_BASE: u64 = 0x7ffe0000;
+    const HEAP_BASE: u64 = 0x50000000;
+
+    fn synth_dump(endian: AsmEndian, memory64: bool) -> Vec<u8> {
+        let context = amd64_context(endian, 0x40001000, STACK_BASE + 0x10);
+        let stack = || {
+            Memory::with_section(
+                Section::with_endian(endian).append_repeated(0xab, 0x100),
+                STACK_BASE,
+            )
+        };
+        let thread_stack = stack();
+        let thread = Thread::new(endian, THREAD_ID, &thread_stack, &context);
+        let heap = Memory::with_section(
+            Section::with_endian(endian).append_repeated(0xcd, 0x20),
+            HEAP_BASE,
+        );
+        let module_name = DumpString::new("/usr/lib/libfoo.so", endian);
+        let module = SynthModule::new(endian, 0x40000000, 0x3000, &module_name, 0, 0, None);
+        let unloaded_name = DumpString::new("/usr/lib/libgone.so", endian);
+        let unloaded = UnloadedModule::new(endian, 0x60000000, 0x1000, &unloaded_name, 0, 0);
+        let thread_name = DumpString::new("main", endian);
+        let crashpad_info = CrashpadInfo::new(endian)
+            .add_module(
+                ModuleCrashpadInfo::new(0, endian)
+                    .add_list_annotation("list")
+                    .add_simple_annotation("module-key", "module-value")
+                    .add_annotation_object("object", AnnotationValue::String("value".to_owned())),
+            )
+            .add_simple_annotation("key", "value");
+        let system_info = SynthSystemInfo::new(endian)
+            .set_processor_architecture(
+                md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+            )
+            .set_platform_id(md::PlatformId::Linux as u32);
+        let dump = SynthMinidump::with_endian(endian)
+            .add_system_info(system_info)
+            .add_thread(thread)
+            .add(context)
+            .add_module(module)
+            .add(module_name)
+            .add_unloaded_module(unloaded)
+            .add(unloaded_name)
+            .add_thread_name(ThreadName::new(endian, THREAD_ID, Some(&thread_name)))
+            .add(thread_name)
+            .add_crashpad_info(crashpad_info)
+            .add_stream(SimpleStream {
+                stream_type: CUSTOM_STREAM,
+                section: Section::with_endian(endian).append_bytes(b"custom"),
+            });
+        // The labels of memory64 regions can't be cited, so the stack is in there twice.
+        let dump = if memory64 {
+            dump.add(thread_stack)
+                .add_memory64(stack())
+                .add_memory64(heap)
+        } else {
+            dump.add_memory(thread_stack).add_memory(heap)
+        };
+        dump.finish().unwrap()
+    }
+
+    fn rewrite(dump: &[u8], edit: impl FnOnce(&mut MinidumpBuilder)) -> Minidump<'static, Vec<u8>> {
+        let dump = Minidump::read(dump).unwrap();
+        let mut builder = MinidumpBuilder::from_minidump(&dump);
+        edit(&mut builder);
+        Minidump::read(builder.build().unwrap()).unwrap()
+    }
+
+    fn check_streams(dump: &Minidump<Vec<u8>>) {
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let memory = dump.get_memory().unwrap();
+
+        let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+        assert_eq!(threads.threads.len(), 1);
+        let thread = &threads.threads[0];
+        assert_eq!(thread.raw.thread_id, THREAD_ID);
+        let context = thread.context(&system_info, None).unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0x40001000);
+        let stack = thread.stack_memory(&memory).unwrap();
+        assert_eq!(stack.base_address(), STACK_BASE);
+        assert_eq!(stack.bytes(), &[0xab; 0x100][..]);
+
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = modules.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].code_file(), "/usr/lib/libfoo.so");
+        let unloaded = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        let unloaded = unloaded.iter().collect::<Vec<_>>();
+        assert_eq!(unloaded[0].code_file(), "/usr/lib/libgone.so");
+        let names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(names.get_name(THREAD_ID).as_deref(), Some("main"));
+
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().unwrap();
+        assert_eq!(crashpad_info.simple_annotations["key"], "value");
+        let module = &crashpad_info.module_list[0];
+        assert_eq!(module.list_annotations, vec!["list".to_owned()]);
+        assert_eq!(module.simple_annotations["module-key"], "module-value");
+        assert_eq!(
+            module.annotation_objects["object"],
+            MinidumpAnnotation::String("value".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for endian in [AsmEndian::Little, AsmEndian::Big] {
+            let dump = rewrite(&synth_dump(endian, false), |_| {});
+            check_streams(&dump);
+            assert_eq!(dump.get_raw_stream(CUSTOM_STREAM).unwrap(), b"custom");
+
+            // The stack refers to the copy of it in the memory list.
+            let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+            let regions = memory
+                .iter()
+                .map(|region| (region.base_address, region.size))
+                .collect::<Vec<_>>();
+            assert_eq!(regions, vec![(HEAP_BASE, 0x20), (STACK_BASE, 0x100)]);
+            let stack_region = memory.memory_at_address(STACK_BASE).unwrap();
+            let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+            assert_eq!(
+                threads.threads[0].raw.stack.memory.rva,
+                stack_region.desc.memory.rva
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip_memory64() {
+        let dump = rewrite(&synth_dump(AsmEndian::Little, true), |_| {});
+        check_streams(&dump);
+        assert!(dump.get_stream::<MinidumpMemoryList>().is_err());
+        let memory = dump.get_stream::<MinidumpMemory64List>().unwrap();
+        let regions = memory
+            .iter()
+            .map(|region| (region.base_address, region.bytes.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            regions,
+            vec![
+                (HEAP_BASE, vec![0xcd; 0x20]),
+                (STACK_BASE, vec![0xab; 0x100])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_windows() {
+        let original = Minidump::read(std::fs::read("../testdata/test.dmp").unwrap()).unwrap();
+        let dump =
+            Minidump::read(MinidumpBuilder::from_minidump(&original).build().unwrap()).unwrap();
+
+        let original_streams = original.directory().len();
+        assert_eq!(dump.directory().len(), original_streams);
+        let modules = |dump: &Minidump<_>| {
+            let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+            modules
+                .iter()
+                .map(|module| (module.code_file().into_owned(), module.debug_identifier()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(modules(&dump), modules(&original));
+        let threads = |dump: &Minidump<_>| {
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let memory = dump.get_memory().unwrap();
+            let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+            threads
+                .threads
+                .iter()
+                .map(|thread| {
+                    let context = thread.context(&system_info, None).unwrap();
+                    let stack = thread.stack_memory(&memory).unwrap();
+                    (context.get_instruction_pointer(), stack.bytes().to_vec())
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(threads(&dump), threads(&original));
+    }
+
+    #[test]
+    fn test_edit() {
+        let dump = rewrite(&synth_dump(AsmEndian::Little, false), |builder| {
+            builder.add_stream(OTHER_STREAM, StreamData::new(&b"metadata"[..]));
+            let old = builder.set_stream(CUSTOM_STREAM, StreamData::new(&b"replaced"[..]));
+            assert_eq!(old.unwrap().bytes(), b"custom");
+            assert!(builder
+                .remove_stream(MINIDUMP_STREAM_TYPE::CrashpadInfoStream)
+                .is_some());
+            assert!(builder.remove_memory(STACK_BASE).is_some());
+            builder.add_memory(0x1000, vec![1, 2, 3]);
+            builder.memory_mut(HEAP_BASE).unwrap()[0] = 0;
+        });
+
+        assert_eq!(dump.get_raw_stream(CUSTOM_STREAM).unwrap(), b"replaced");
+        assert_eq!(dump.get_raw_stream(OTHER_STREAM).unwrap(), b"metadata");
+        assert!(dump.get_stream::<MinidumpCrashpadInfo>().is_err());
+
+        let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+        let regions = memory
+            .iter()
+            .map(|region| (region.base_address, region.bytes.to_vec()))
+            .collect::<Vec<_>>();
+        let mut heap = vec![0xcd; 0x20];
+        heap[0] = 0;
+        assert_eq!(regions, vec![(0x1000, vec![1, 2, 3]), (HEAP_BASE, heap)]);
+
+        // The stack went away with its memory.
+        let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+        let thread = &threads.threads[0];
+        assert_eq!(thread.raw.stack.memory.data_size, 0);
+        let memory = UnifiedMemoryList::Memory(memory);
+        assert!(thread.stack_memory(&memory).is_none());
+    }
+
+    #[test]
+    fn test_new() {
+        let endian = Endian::Little;
+        let mut builder = MinidumpBuilder::new(endian);
+        builder.time_date_stamp = 0x12345678;
+        let thread = md::MINIDUMP_THREAD {
+            thread_id: THREAD_ID,
+            suspend_count: 0,
+            priority_class: 0,
+            priority: 0,
+            teb: 0,
+            stack: md::MINIDUMP_MEMORY_DESCRIPTOR {
+                start_of_memory_range: STACK_BASE + 0x10,
+                memory: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+            },
+            thread_context: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+        };
+        let threads = StreamData::from_list(vec![thread], endian)
+            .unwrap()
+            .with_reference(
+                4 + THREAD_STACK_OFFSET + 8,
+                ReferenceKind::Location,
+                ReferenceTarget::Memory {
+                    address: STACK_BASE + 0x10,
+                    size: 0x20,
+                },
+            );
+        builder.add_stream(MINIDUMP_STREAM_TYPE::ThreadListStream, threads);
+        builder.add_memory(STACK_BASE, (0..0x40).collect::<Vec<u8>>());
+
+        let dump = Minidump::read(builder.build().unwrap()).unwrap();
+        assert_eq!(dump.header.time_date_stamp, 0x12345678);
+        let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+        let memory = dump.get_memory().unwrap();
+        let stack = threads.threads[0].stack_memory(&memory).unwrap();
+        assert_eq!(stack.base_address(), STACK_BASE + 0x10);
+        assert_eq!(stack.bytes(), &(0x10..0x30).collect::<Vec<u8>>()[..]);
+    }
+
+    #[test]
+    fn test_strings() {
+        let string = StreamData::string("hé", Endian::Little);
+        assert_eq!(string.bytes(), b"\x04\0\0\0h\0\xe9\0\0\0");
+        let string = StreamData::utf8_string("hé", Endian::Big);
+        assert_eq!(string.bytes(), b"\0\0\0\x03h\xc3\xa9\0");
+    }
+}
@@ -235,6 +235,7 @@ fn test_exception() {
         if let MinidumpContext {
             raw: MinidumpRawContext::X86(ref raw),
             ref valid,
+            ..
         } = *ctx
         {
             assert_eq!(raw.eip, 0x40429e);
@@ -270,6 +271,7 @@ fn test_thread_list() {
         if let MinidumpContext {
             raw: MinidumpRawContext::X86(ref raw),
             ref valid,
+            ..
         } = *ctx
         {
             assert_eq!(raw.eip, 0x7c90eb94);